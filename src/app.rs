@@ -1,21 +1,34 @@
 // src/app.rs
 // Main application structure and logic
 
-use crate::database::Database;
+use crate::backup::Integrity;
+use crate::database::{Database, DbResult, EntryKind, EntryTemplate};
+use crate::journal::{get_journal_path, Journal};
+use crate::links::TicketLinker;
 use crate::models::*;
+use crate::settings::{Settings, StartupBackup, Theme};
 use crate::ui;
 use eframe::egui;
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use std::time::Duration;
 
 /// Main application struct
 pub struct WorkTrackerApp {
     db: Database,
+    journal: Journal,
     current_view: AppView,
     dialog_state: DialogState,
     previous_dialog_state: Option<DialogState>,
     date_state: DateState,
     cache: CachedData,
     filter_state: FilterState,
+    report_state: ReportState,
+    timesheet_state: TimesheetState,
+    settings_view: SettingsViewState,
+    settings: Settings,
 
     // Form data
     project_form: ProjectForm,
@@ -24,42 +37,137 @@ pub struct WorkTrackerApp {
 
     // Messages
     messages: Vec<UserMessage>,
+
+    /// Results of the periodic backup checksum check
+    backup_checks: Option<Receiver<HashMap<PathBuf, Integrity>>>,
 }
 
+/// How often backups are checked against their checksums
+const BACKUP_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
 impl WorkTrackerApp {
     /// Create a new application instance
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
         // Configure fonts for better appearance
         configure_fonts(&cc.egui_ctx);
 
+        let mut messages = Vec::new();
+        let mut settings = Settings::load().unwrap_or_else(|e| {
+            messages.push(UserMessage::error(format!(
+                "Failed to load settings from {}: {}. Using defaults.",
+                crate::settings::get_settings_path().display(),
+                e
+            )));
+            Settings::default()
+        });
+        apply_theme(&cc.egui_ctx, settings.display.theme);
+
         // Determine database path
-        let db_path = get_database_path();
+        let db_path = settings
+            .storage
+            .database_path
+            .clone()
+            .unwrap_or_else(get_database_path);
 
         // Create database connection
-        let db = match Database::new(&db_path) {
-            Ok(db) => {
-                println!("Database opened at: {:?}", db_path);
-                db
-            }
+        let mut db = match Database::new(&db_path) {
+            Ok(db) => db,
             Err(e) => {
-                eprintln!("Failed to open database: {}. Using in-memory database.", e);
+                messages.push(UserMessage::persistent_error(format!(
+                    "Failed to open the database {}: {}. Working in memory; nothing will be saved.",
+                    db_path.display(),
+                    e
+                )));
                 Database::new_in_memory().expect("Failed to create in-memory database")
             }
         };
 
+        // Days off used to live in the settings file
+        if !settings.calendar.legacy_days_off.is_empty() {
+            match db.import_days_off(&settings.calendar.legacy_days_off) {
+                Ok(_) => {
+                    settings.calendar.legacy_days_off.clear();
+                    if let Err(e) = settings.save() {
+                        messages.push(UserMessage::error(format!(
+                            "Failed to save settings: {}",
+                            e
+                        )));
+                    }
+                }
+                Err(e) => messages.push(UserMessage::error(format!(
+                    "Could not move the days off from the settings into the database: {}",
+                    e
+                ))),
+            }
+        }
+
+        // Restore point for reverting days to how they looked this morning
+        db.set_snapshot_limit(settings.backups.keep);
+        db.set_lock_after_days(settings.entries.lock_after_days);
+        if let Some(dir) = db.backup_dir() {
+            let today = chrono::Local::now().date_naive();
+            let snapshots = crate::backup::list_snapshots(&dir);
+            let reason = match settings.backups.on_startup {
+                StartupBackup::Off => None,
+                StartupBackup::Daily => {
+                    (!crate::backup::taken_on(&snapshots, crate::backup::DAILY, today))
+                        .then_some(crate::backup::DAILY)
+                }
+                StartupBackup::EveryStart => Some(crate::backup::STARTUP),
+            };
+            if let Some(reason) = reason {
+                if let Err(e) = db.snapshot(reason) {
+                    messages.push(UserMessage::error(format!(
+                        "Failed to back up the database: {}",
+                        e
+                    )));
+                }
+            }
+        }
+
+        // Retry entries a previous run failed to write
+        let mut journal = Journal::open(&get_journal_path());
+        if journal.unreadable > 0 {
+            messages.push(UserMessage::error(format!(
+                "Skipped {} unreadable lines of the entry journal",
+                journal.unreadable
+            )));
+        }
+        if journal.pending_count() > 0 {
+            let outcome = journal.flush(&db);
+            if outcome.written > 0 {
+                messages.push(UserMessage::info(format!(
+                    "Saved {} entries left over from the last session",
+                    outcome.written
+                )));
+            }
+            messages.extend(ui::flush_messages(outcome));
+        }
+
         let mut app = Self {
             db,
+            journal,
             current_view: AppView::default(),
             dialog_state: DialogState::default(),
             previous_dialog_state: None,
             date_state: DateState::default(),
             cache: CachedData::new(),
             filter_state: FilterState::new(),
+            report_state: ReportState::default(),
+            timesheet_state: TimesheetState::default(),
+            settings_view: SettingsViewState::default(),
+            settings,
             project_form: ProjectForm::new(),
             activity_form: ActivityForm::new(),
             entry_form: TimeEntryForm::new(),
-            messages: Vec::new(),
+            messages,
+            backup_checks: None,
         };
+        app.entry_form.clear(app.settings.entries.default_minutes);
+        app.backup_checks = app
+            .db
+            .backup_dir()
+            .map(|dir| crate::backup::spawn_verifier(dir, BACKUP_CHECK_INTERVAL));
 
         // Initial data load
         app.refresh_cache();
@@ -73,68 +181,161 @@ impl WorkTrackerApp {
         app
     }
 
-    /// Refresh cached data from database
+    /// Reload what the current view needs from the database
     fn refresh_cache(&mut self) {
-        // Load projects
-        self.cache.projects = self.db.get_all_projects(false).unwrap_or_default();
+        let view = self.current_view;
+
+        // Compile ticket link rules
+        self.cache.ticket_linker = TicketLinker::new(&self.settings.links.rules);
+        self.cache.duration_style = self.settings.display.duration_style;
+        self.cache.week_start = self.settings.calendar.week_start;
+        self.settings.calendar.day_types = self.db.get_day_types().unwrap_or_default();
+        self.cache.day_types = self.settings.calendar.day_types.clone();
+
+        // The summary view names activities itself; everything else picks from the catalog
+        if view != AppView::DailySummary {
+            self.load_catalog();
+        }
 
-        // Load all activities
-        self.cache.all_activities = self.db.get_all_activity_types(false).unwrap_or_default();
+        match view {
+            AppView::TimeTracking => {
+                self.load_date_navigation();
+                self.load_selected_day();
+                self.load_snapshots();
+                self.cache.entry_templates = self.db.get_entry_templates().unwrap_or_default();
+            }
+            AppView::DailySummary => {
+                self.load_date_navigation();
+                self.cache.daily_summary = self
+                    .db
+                    .get_daily_summary(self.date_state.selected_date)
+                    .unwrap_or_default();
+                self.cache.summary_date = Some(self.date_state.selected_date);
+            }
+            AppView::MonthlyReport => {
+                // The report reloads its month on next draw
+                self.cache.monthly_month = None;
+            }
+            AppView::Timesheet => self.load_timesheet(),
+            AppView::ManageProjects => {
+                let today = chrono::Local::now().date_naive();
+                self.cache.project_usage =
+                    Arc::new(self.db.get_project_usage(today).unwrap_or_default());
+                self.cache.project_links = self.db.get_all_project_links().unwrap_or_default();
+            }
+            AppView::ManageActivities => {
+                let today = chrono::Local::now().date_naive();
+                self.cache.activity_usage =
+                    Arc::new(self.db.get_activity_usage(today).unwrap_or_default());
+            }
+            AppView::Reports => {
+                self.cache.export_log = self.db.get_export_log(50).unwrap_or_default();
+                // Reports reload their range and search on next draw
+                self.cache.report_range = None;
+                self.cache.search_query = None;
+            }
+            AppView::Settings => {
+                self.load_snapshots();
+                self.cache.entry_templates = self.db.get_entry_templates().unwrap_or_default();
+            }
+        }
+
+        self.cache.loaded_view = Some(view);
+        self.cache.needs_refresh = false;
+    }
 
-        // Load entries for current date
-        self.cache.current_date_entries = self
+    /// Projects and activities, for pickers, names and dialogs
+    fn load_catalog(&mut self) {
+        self.cache.projects = self.db.get_all_projects(false).unwrap_or_default().into();
+        self.cache.all_activities = self
             .db
-            .get_time_entries_for_date(self.date_state.selected_date)
-            .unwrap_or_default();
+            .get_all_activity_types(false)
+            .unwrap_or_default()
+            .into();
+        self.cache.last_activity_by_project =
+            self.db.get_last_activity_per_project().unwrap_or_default();
+    }
 
-        // Load daily summary for current date
-        self.cache.daily_summary = self
+    /// Day totals shown around the date selector and in the recent days panel
+    fn load_date_navigation(&mut self) {
+        self.cache.recent_entry_dates = self.db.get_recent_entry_dates(7).unwrap_or_default();
+        let today = chrono::Local::now().date_naive();
+        let first = week_start_on(
+            self.date_state.selected_date,
+            self.settings.calendar.week_start.weekday(),
+        );
+        self.cache.week_day_totals = self
             .db
-            .get_daily_summary(self.date_state.selected_date)
+            .get_daily_totals(first, first + chrono::Duration::days(6))
             .unwrap_or_default();
-        self.cache.summary_date = Some(self.date_state.selected_date);
+        self.cache.week_billable = billable_split(
+            &self
+                .db
+                .get_time_entries_for_range(first, first + chrono::Duration::days(6))
+                .unwrap_or_default(),
+        );
+        self.cache.recent_day_totals = self
+            .db
+            .get_daily_totals(today - chrono::Duration::days(13), today)
+            .unwrap_or_default();
+        let (start, end) = month_range(self.date_state.calendar_month);
+        self.cache.calendar_day_totals = self.db.get_daily_totals(start, end).unwrap_or_default();
+    }
 
-        self.cache.needs_refresh = false;
+    /// Entries of the selected date's week, for the timesheet grid
+    fn load_timesheet(&mut self) {
+        let first = week_start_on(
+            self.date_state.selected_date,
+            self.settings.calendar.week_start.weekday(),
+        );
+        let last = first + chrono::Duration::days(6);
+        self.cache.timesheet_entries = self
+            .db
+            .get_time_entries_for_range(first, last)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|e| e.kind != EntryKind::Break)
+            .collect();
+        self.cache.timesheet_locked_days = first
+            .iter_days()
+            .take(7)
+            .filter(|day| self.db.is_date_locked(*day).unwrap_or(false))
+            .collect();
     }
 
-    /// Create example data for first run
-    fn create_example_data(&mut self) {
-        // Create example project
-        if let Ok(project_id) = self
+    /// Entries and status of the selected date
+    fn load_selected_day(&mut self) {
+        let date = self.date_state.selected_date;
+        self.cache.current_date_entries =
+            self.db.get_time_entries_for_date(date).unwrap_or_default();
+        self.cache.current_date_trips = self.db.get_trips_between(date, date).unwrap_or_default();
+        self.cache.current_date_expenses =
+            self.db.get_expenses_between(date, date).unwrap_or_default();
+        self.cache.current_date_closed = self.db.is_day_closed(date).unwrap_or(false);
+        self.cache.current_date_locked = self.db.is_date_locked(date).unwrap_or(false);
+        self.cache.month_activity_totals = self
             .db
-            .create_project("33 - IT-Support", "IT Support activities across locations")
-        {
-            // Create example activities
-            let _ = self
-                .db
-                .create_activity_type(project_id, "IT-Support - Trollhättan");
-            let _ = self
-                .db
-                .create_activity_type(project_id, "IT-Support - Göteborg");
-            let _ = self
-                .db
-                .create_activity_type(project_id, "IT-Support - Västerås");
-            let _ = self
-                .db
-                .create_activity_type(project_id, "IT-Support - Östersund");
-        }
+            .get_activity_totals_for_month(date)
+            .unwrap_or_default();
+    }
 
-        // Create another example project
-        if let Ok(project_id) = self
+    fn load_snapshots(&mut self) {
+        self.cache.snapshots = self
             .db
-            .create_project("40 - Development", "Software development tasks")
-        {
-            let _ = self
-                .db
-                .create_activity_type(project_id, "Development - Feature work");
-            let _ = self
-                .db
-                .create_activity_type(project_id, "Development - Bug fixes");
-            let _ = self
-                .db
-                .create_activity_type(project_id, "Development - Code review");
-        }
+            .backup_dir()
+            .map(|dir| crate::backup::list_snapshots(&dir))
+            .unwrap_or_default();
+    }
 
+    /// Create example data for first run
+    fn create_example_data(&mut self) {
+        if let Err(e) = self.db.create_sample_data() {
+            self.add_message(UserMessage::error(format!(
+                "Failed to create example data: {}",
+                e
+            )));
+            return;
+        }
         self.add_message(UserMessage::info("Created example projects and activities"));
     }
 
@@ -143,13 +344,481 @@ impl WorkTrackerApp {
         self.messages.push(msg);
     }
 
+    /// Take the latest checksum results and report newly damaged backups
+    fn receive_backup_checks(&mut self) {
+        let Some(receiver) = &self.backup_checks else {
+            return;
+        };
+        while let Ok(results) = receiver.try_recv() {
+            let newly_corrupted = results
+                .iter()
+                .filter(|(path, integrity)| {
+                    **integrity == Integrity::Corrupted
+                        && self.cache.snapshot_integrity.get(*path) != Some(&Integrity::Corrupted)
+                })
+                .count();
+            if newly_corrupted > 0 {
+                self.messages.push(UserMessage::error(format!(
+                    "{} backups do not match their checksums, see Settings → Backups",
+                    newly_corrupted
+                )));
+            }
+            self.cache.snapshot_integrity = results;
+        }
+    }
+
+    /// Run the actions queued while drawing, recording how to undo them
+    fn run_actions(&mut self) {
+        for action in std::mem::take(&mut self.cache.actions) {
+            let (action, replay) = match action {
+                AppAction::Undo => match self.cache.undo_stack.pop() {
+                    Some(step) => (step.inverse, Replay::Undo(step.label)),
+                    None => continue,
+                },
+                AppAction::Redo => match self.cache.redo_stack.pop() {
+                    Some(step) => (step.inverse, Replay::Redo(step.label)),
+                    None => continue,
+                },
+                action => (action, Replay::None),
+            };
+            let label = action.describe();
+
+            match (self.execute(&action), replay) {
+                (Ok(inverse), Replay::Undo(undone)) => {
+                    self.messages
+                        .push(UserMessage::info(format!("Undone: {}", undone)));
+                    if let Some(inverse) = inverse {
+                        push_step(&mut self.cache.redo_stack, undone, inverse);
+                    }
+                }
+                (Ok(inverse), Replay::Redo(redone)) => {
+                    self.messages
+                        .push(UserMessage::info(format!("Redone: {}", redone)));
+                    if let Some(inverse) = inverse {
+                        push_step(&mut self.cache.undo_stack, redone, inverse);
+                    }
+                }
+                (Ok(inverse), Replay::None) => {
+                    if let Some(inverse) = inverse {
+                        push_step(&mut self.cache.undo_stack, label, inverse);
+                        self.cache.redo_stack.clear();
+                    }
+                }
+                (Err(e), _) => self
+                    .messages
+                    .push(UserMessage::error(format!("{} failed: {}", label, e))),
+            }
+            self.cache.mark_dirty();
+        }
+    }
+
+    /// Apply one action to the database. Returns the action reverting it, if any.
+    fn execute(&mut self, action: &AppAction) -> DbResult<Option<AppAction>> {
+        let inverse = match action {
+            AppAction::CloseDay(date) => {
+                self.db.close_day(*date)?;
+                Some(AppAction::ReopenDay(*date))
+            }
+            AppAction::ReopenDay(date) => {
+                self.db.reopen_day(*date)?;
+                Some(AppAction::CloseDay(*date))
+            }
+            AppAction::LockMonth(date) => {
+                self.db.lock_month(*date)?;
+                Some(AppAction::UnlockMonth(*date))
+            }
+            AppAction::UnlockMonth(date) => {
+                self.db.unlock_month(*date)?;
+                Some(AppAction::LockMonth(*date))
+            }
+            AppAction::DeactivateActivity(id) => {
+                self.db.deactivate_activity_type(*id)?;
+                Some(AppAction::ReactivateActivity(*id))
+            }
+            AppAction::ReactivateActivity(id) => {
+                self.db.reactivate_activity_type(*id)?;
+                Some(AppAction::DeactivateActivity(*id))
+            }
+            AppAction::CreateEntryTemplate {
+                weekday,
+                activity_type_id,
+                minutes,
+                comment,
+            } => {
+                let id = self.db.create_entry_template(
+                    *weekday,
+                    *activity_type_id,
+                    *minutes,
+                    comment,
+                )?;
+                Some(AppAction::DeleteEntryTemplate(EntryTemplate {
+                    id,
+                    weekday: *weekday,
+                    activity_type_id: *activity_type_id,
+                    minutes: *minutes,
+                    comment: comment.clone(),
+                }))
+            }
+            AppAction::DeleteEntryTemplate(template) => {
+                self.db.delete_entry_template(template.id)?;
+                Some(AppAction::CreateEntryTemplate {
+                    weekday: template.weekday,
+                    activity_type_id: template.activity_type_id,
+                    minutes: template.minutes,
+                    comment: template.comment.clone(),
+                })
+            }
+            AppAction::RestoreBackup(path) => {
+                // Saving the current state first makes the restore reversible
+                let before = self.db.snapshot("before-restore")?;
+                self.db.restore(path)?;
+                // Earlier steps refer to rows of the replaced database
+                self.cache.undo_stack.clear();
+                self.cache.redo_stack.clear();
+                self.messages.push(UserMessage::info(format!(
+                    "Restored backup {}",
+                    path.display()
+                )));
+                before.map(AppAction::RestoreBackup)
+            }
+            AppAction::RemoveSampleData => {
+                // Safety copy only; undo restores just the removed items
+                self.db.snapshot("delete")?;
+                let (projects, activities) = self.db.remove_sample_data()?;
+                self.messages.push(UserMessage::info(format!(
+                    "Removed {} example projects and {} example activities; those with entries were kept",
+                    projects.len(),
+                    activities.len()
+                )));
+                (!projects.is_empty() || !activities.is_empty()).then_some(
+                    AppAction::RestoreSampleData {
+                        projects,
+                        activities,
+                    },
+                )
+            }
+            AppAction::RestoreSampleData {
+                projects,
+                activities,
+            } => {
+                // Projects first, so their activities have a parent again
+                for record in projects {
+                    self.db.restore_project(record)?;
+                }
+                for activity in activities {
+                    self.db.restore_activity_type(activity)?;
+                }
+                Some(AppAction::RemoveSampleData)
+            }
+            AppAction::AddProject(record) => {
+                let id = self.db.create_project_record(record)?;
+                Some(AppAction::DeleteProjects(vec![(
+                    id,
+                    record.project.name.clone(),
+                )]))
+            }
+            AppAction::UpdateProject(record) => {
+                let before = self.db.get_project_record(record.project.id)?;
+                self.db.update_project_record(record)?;
+                Some(AppAction::UpdateProject(before))
+            }
+            AppAction::DeleteProjects(items) => {
+                // Projects take their links with them: back up first
+                self.db.snapshot("delete")?;
+                let mut deleted = Vec::new();
+                let mut failures = Vec::new();
+                for (id, name) in items {
+                    match self.db.take_project(*id) {
+                        Ok(record) => deleted.push(record),
+                        Err(e) => failures.push(format!("{}: {}", name, e)),
+                    }
+                }
+                self.report_kept("projects", &failures);
+                (!deleted.is_empty()).then_some(AppAction::RestoreProjects(deleted))
+            }
+            AppAction::RestoreProjects(records) => {
+                for record in records {
+                    self.db.restore_project(record)?;
+                }
+                Some(AppAction::DeleteProjects(
+                    records
+                        .iter()
+                        .map(|r| (r.project.id, r.project.name.clone()))
+                        .collect(),
+                ))
+            }
+            AppAction::AddActivity {
+                project_id,
+                name,
+                icon,
+            } => {
+                let id = self.db.create_activity_type(*project_id, name, icon)?;
+                Some(AppAction::DeleteActivities(vec![(id, name.clone())]))
+            }
+            AppAction::UpdateActivity(activity) => {
+                let before = self.db.get_activity_type(activity.id)?;
+                self.db.update_activity_record(activity)?;
+                Some(AppAction::UpdateActivity(before))
+            }
+            AppAction::DeleteActivities(items) => {
+                self.db.snapshot("delete")?;
+                let mut deleted = Vec::new();
+                let mut failures = Vec::new();
+                for (id, name) in items {
+                    match self.db.take_activity_type(*id) {
+                        Ok(activity) => deleted.push(activity),
+                        Err(e) => failures.push(format!("{}: {}", name, e)),
+                    }
+                }
+                self.report_kept("activities", &failures);
+                (!deleted.is_empty()).then_some(AppAction::RestoreActivities(deleted))
+            }
+            AppAction::RestoreActivities(deleted) => {
+                for activity in deleted {
+                    self.db.restore_activity_type(activity)?;
+                }
+                Some(AppAction::DeleteActivities(
+                    deleted
+                        .iter()
+                        .map(|d| (d.activity.id, d.activity.name.clone()))
+                        .collect(),
+                ))
+            }
+            AppAction::SetProjectActive {
+                id,
+                active,
+                include_activities,
+                ..
+            } => self.change_states(|| {
+                if *active {
+                    self.db.reactivate_project(*id)?;
+                } else {
+                    self.db.deactivate_project(*id)?;
+                }
+                if *include_activities {
+                    self.db.set_project_activities_active(*id, *active)?;
+                }
+                Ok(())
+            })?,
+            AppAction::SetProjectsState { items, action } => {
+                let inverse = self.change_states(|| {
+                    for (id, _) in items {
+                        match action {
+                            BulkAction::Activate => self.db.reactivate_project(*id),
+                            BulkAction::Deactivate => self.db.deactivate_project(*id),
+                            BulkAction::Archive => self.db.archive_project(*id),
+                            BulkAction::Delete => self.db.delete_project(*id),
+                        }?;
+                    }
+                    Ok(())
+                })?;
+                self.messages.push(UserMessage::info(format!(
+                    "{} {} projects",
+                    action.done(),
+                    items.len()
+                )));
+                inverse
+            }
+            AppAction::SetActivitiesState { items, action } => {
+                let inverse = self.change_states(|| {
+                    for (id, _) in items {
+                        match action {
+                            BulkAction::Activate => self.db.reactivate_activity_type(*id),
+                            BulkAction::Deactivate => self.db.deactivate_activity_type(*id),
+                            BulkAction::Archive => self.db.archive_activity_type(*id),
+                            BulkAction::Delete => self.db.delete_activity_type(*id),
+                        }?;
+                    }
+                    Ok(())
+                })?;
+                self.messages.push(UserMessage::info(format!(
+                    "{} {} activities",
+                    action.done(),
+                    items.len()
+                )));
+                inverse
+            }
+            AppAction::DeactivateActivitiesOfInactiveProjects => {
+                let inverse = self.change_states(|| {
+                    self.db.deactivate_activities_of_inactive_projects()?;
+                    Ok(())
+                })?;
+                if let Some(AppAction::RestoreStates { activities, .. }) = &inverse {
+                    self.messages.push(UserMessage::info(format!(
+                        "Deactivated {} activities of inactive projects",
+                        activities.len()
+                    )));
+                }
+                inverse
+            }
+            AppAction::RestoreStates {
+                projects,
+                activities,
+            } => self.change_states(|| self.db.set_states(projects, activities))?,
+            AppAction::ImportCatalog(changes) => {
+                // Adds and deactivations are reverted from the backup, not by undo
+                self.db.snapshot("catalog-import")?;
+                let applied = self.db.apply_catalog_changes(changes)?;
+                self.messages.push(UserMessage::info(format!(
+                    "Applied {} catalog changes",
+                    applied
+                )));
+                None
+            }
+            AppAction::UpdateTimeEntry {
+                id,
+                minutes,
+                comment,
+                reason,
+                is_billable,
+            } => {
+                let before = self.db.get_time_entry(*id)?;
+                self.db.update_time_entry(*id, *minutes, comment, reason)?;
+                self.db.set_entry_billable(*id, *is_billable)?;
+                self.messages.extend(ui::budget_warning(
+                    &self.db,
+                    &self.cache,
+                    before.activity_type_id,
+                    minutes - before.minutes,
+                ));
+                Some(AppAction::UpdateTimeEntry {
+                    id: *id,
+                    minutes: before.minutes,
+                    comment: before.comment,
+                    reason: before.reason,
+                    is_billable: before.is_billable,
+                })
+            }
+            AppAction::DeleteTimeEntries(ids) => {
+                let entries = self.db.take_time_entries(ids)?;
+                Some(AppAction::RestoreTimeEntries(entries))
+            }
+            AppAction::RestoreTimeEntries(entries) => {
+                self.db.restore_time_entries(entries)?;
+                Some(AppAction::DeleteTimeEntries(
+                    entries.iter().map(|e| e.id).collect(),
+                ))
+            }
+            AppAction::AddEntries {
+                date,
+                entries,
+                comment,
+            } => {
+                let ids = self.db.create_time_entries(*date, entries, comment)?;
+                for (activity_type_id, minutes) in entries {
+                    self.messages.extend(ui::budget_warning(
+                        &self.db,
+                        &self.cache,
+                        *activity_type_id,
+                        *minutes,
+                    ));
+                }
+                Some(AppAction::DeleteTimeEntries(ids))
+            }
+            AppAction::CopyDay {
+                from,
+                to,
+                clear_comments,
+            } => {
+                let ids = self.db.copy_day(*from, *to, *clear_comments)?;
+                self.messages.push(UserMessage::info(format!(
+                    "Copied {} entries from {}",
+                    ids.len(),
+                    from
+                )));
+                (!ids.is_empty()).then_some(AppAction::DeleteTimeEntries(ids))
+            }
+            AppAction::RevertDay { date, snapshot } => {
+                self.db.snapshot("revert-day")?;
+                let before = self.db.get_time_entries_for_date(*date)?;
+                let restored = self.db.revert_day(snapshot, *date)?;
+                self.messages.push(UserMessage::info(format!(
+                    "Restored {} entries of {}",
+                    restored, date
+                )));
+                Some(AppAction::ReplaceDayEntries {
+                    date: *date,
+                    entries: before,
+                })
+            }
+            AppAction::ReplaceDayEntries { date, entries } => {
+                let before = self.db.replace_day_entries(*date, entries)?;
+                Some(AppAction::ReplaceDayEntries {
+                    date: *date,
+                    entries: before,
+                })
+            }
+            AppAction::AddExpense(expense) => {
+                let id = self.db.create_expense(expense)?;
+                Some(AppAction::DeleteExpense(id))
+            }
+            AppAction::DeleteExpense(id) => {
+                Some(AppAction::RestoreExpense(self.db.take_expense(*id)?))
+            }
+            AppAction::RestoreExpense(expense) => {
+                self.db.restore_expense(expense)?;
+                Some(AppAction::DeleteExpense(expense.id))
+            }
+            AppAction::UnlockDay(date) => {
+                self.db.unlock_day(*date);
+                Some(AppAction::RelockDay(*date))
+            }
+            AppAction::RelockDay(date) => {
+                self.db.relock_day(*date);
+                Some(AppAction::UnlockDay(*date))
+            }
+            AppAction::SetDayType { date, day_type } => {
+                let before = self.db.get_day_types()?.get(date).copied();
+                self.db.set_day_type(*date, *day_type)?;
+                Some(AppAction::SetDayType {
+                    date: *date,
+                    day_type: before,
+                })
+            }
+            AppAction::Undo | AppAction::Redo => None,
+        };
+        Ok(inverse)
+    }
+
+    /// Run a change of project and activity states in one transaction. Returns the
+    /// action putting back the states it changed, or None when nothing changed.
+    fn change_states(&self, change: impl FnOnce() -> DbResult<()>) -> DbResult<Option<AppAction>> {
+        self.db.transaction(|| {
+            let projects = self.db.get_project_states()?;
+            let activities = self.db.get_activity_states()?;
+            change()?;
+            let changed = |before: Vec<_>, after: Vec<_>| -> Vec<_> {
+                before.into_iter().filter(|s| !after.contains(s)).collect()
+            };
+            let projects = changed(projects, self.db.get_project_states()?);
+            let activities = changed(activities, self.db.get_activity_states()?);
+            Ok((!projects.is_empty() || !activities.is_empty()).then_some(
+                AppAction::RestoreStates {
+                    projects,
+                    activities,
+                },
+            ))
+        })
+    }
+
+    /// Tell the user which items of a bulk delete were kept, and why
+    fn report_kept(&mut self, noun: &str, failures: &[String]) {
+        if !failures.is_empty() {
+            self.messages.push(UserMessage::error(format!(
+                "Some {} could not be deleted:\n{}",
+                noun,
+                failures.join("\n")
+            )));
+        }
+    }
+
     /// Clean up expired messages
     fn cleanup_messages(&mut self) {
         self.messages.retain(|m| !m.is_expired());
     }
 
     /// Prepare form data when opening dialogs (only on dialog state change)
-    fn prepare_dialog_forms_if_changed(&mut self) {
+    fn prepare_dialog_forms_if_changed(&mut self, ctx: &egui::Context) {
         // Check if dialog state has changed
         let dialog_changed = match (&self.previous_dialog_state, &self.dialog_state) {
             (None, DialogState::None) => false,
@@ -168,7 +837,10 @@ impl WorkTrackerApp {
         // Prepare forms based on new dialog state
         match &self.dialog_state {
             DialogState::EditProject(project) => {
-                self.project_form = ProjectForm::from_project(project);
+                self.project_form = ProjectForm::from_project(
+                    project,
+                    &self.cache.get_links_for_project(project.id),
+                );
             }
             DialogState::EditActivity(activity) => {
                 self.activity_form = ActivityForm::from_activity(activity);
@@ -186,6 +858,11 @@ impl WorkTrackerApp {
             _ => {}
         }
 
+        // Start typing in the dialog's first field
+        if !matches!(self.dialog_state, DialogState::None) {
+            ctx.memory_mut(|m| m.request_focus(ui::dialog_first_field_id()));
+        }
+
         // Update previous state
         self.previous_dialog_state = Some(self.dialog_state.clone());
     }
@@ -201,13 +878,43 @@ impl eframe::App for WorkTrackerApp {
         // Clean up old messages
         self.cleanup_messages();
 
+        self.receive_backup_checks();
+
         // Prepare form data when dialog state changes (before drawing)
-        self.prepare_dialog_forms_if_changed();
+        self.prepare_dialog_forms_if_changed(ctx);
+
+        // Status bar
+        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            ui::draw_status_bar(
+                ui,
+                &mut self.journal,
+                &mut self.cache,
+                &mut self.messages,
+                &self.db,
+            );
+        });
+
+        // Recent days, next to the views that work on a selected date
+        if self.settings.display.show_recent_days
+            && matches!(
+                self.current_view,
+                AppView::TimeTracking | AppView::DailySummary
+            )
+        {
+            egui::SidePanel::left("recent_days")
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui::draw_recent_days(ui, &mut self.date_state, &mut self.cache, &self.settings);
+                });
+        }
 
         // Draw main panel
         egui::CentralPanel::default().show(ctx, |ui| {
-            // Navigation bar
+            // Navigation bar; a new view loads its data before it is drawn
             ui::draw_nav_bar(ui, &mut self.current_view);
+            if self.cache.loaded_view != Some(self.current_view) {
+                self.refresh_cache();
+            }
 
             // Messages area
             if !self.messages.is_empty() {
@@ -231,23 +938,57 @@ impl eframe::App for WorkTrackerApp {
                         &mut self.cache,
                         &mut self.dialog_state,
                         &mut self.entry_form,
+                        &mut self.journal,
+                        &mut self.messages,
+                        &self.settings,
                         &self.db,
                     );
                 }
                 AppView::DailySummary => {
-                    ui::draw_daily_summary_view(
+                    if ui::draw_daily_summary_view(
                         ui,
                         &mut self.date_state,
                         &mut self.cache,
+                        &mut self.dialog_state,
+                        &mut self.settings,
+                        &self.db,
+                    ) {
+                        if let Err(e) = self.settings.save() {
+                            self.messages.push(UserMessage::error(format!(
+                                "Failed to save settings: {}",
+                                e
+                            )));
+                        }
+                    }
+                }
+                AppView::MonthlyReport => {
+                    ui::draw_monthly_report_view(
+                        ui,
+                        &mut self.date_state,
+                        &mut self.cache,
+                        &mut self.dialog_state,
+                        &self.settings,
                         &self.db,
                     );
                 }
+                AppView::Timesheet => {
+                    ui::draw_timesheet_view(
+                        ui,
+                        &mut self.date_state,
+                        &mut self.timesheet_state,
+                        &mut self.cache,
+                        &mut self.messages,
+                        &self.settings,
+                    );
+                }
                 AppView::ManageProjects => {
                     ui::draw_projects_view(
                         ui,
                         &mut self.cache,
                         &mut self.dialog_state,
                         &mut self.filter_state,
+                        &mut self.settings,
+                        &mut self.messages,
                         &self.db,
                     );
                 }
@@ -257,9 +998,52 @@ impl eframe::App for WorkTrackerApp {
                         &mut self.cache,
                         &mut self.dialog_state,
                         &mut self.filter_state,
-                        &self.db,
                     );
                 }
+                AppView::Reports => {
+                    if ui::draw_reports_view(
+                        ui,
+                        &mut self.report_state,
+                        &mut self.cache,
+                        &mut self.dialog_state,
+                        &mut self.settings,
+                        &mut self.messages,
+                        &self.db,
+                    ) {
+                        if let Err(e) = self.settings.save() {
+                            self.messages.push(UserMessage::error(format!(
+                                "Failed to save settings: {}",
+                                e
+                            )));
+                        }
+                    }
+                    if let Some(date) = self.report_state.open_date.take() {
+                        self.date_state.selected_date = date;
+                        self.current_view = AppView::TimeTracking;
+                        self.cache.mark_dirty();
+                    }
+                }
+                AppView::Settings => {
+                    if ui::draw_settings_view(
+                        ui,
+                        &mut self.settings,
+                        &mut self.settings_view,
+                        &mut self.cache,
+                        &mut self.messages,
+                    ) {
+                        self.cache.mark_dirty();
+                        apply_theme(ctx, self.settings.display.theme);
+                        self.db.set_snapshot_limit(self.settings.backups.keep);
+                        self.db
+                            .set_lock_after_days(self.settings.entries.lock_after_days);
+                        if let Err(e) = self.settings.save() {
+                            self.messages.push(UserMessage::error(format!(
+                                "Failed to save settings: {}",
+                                e
+                            )));
+                        }
+                    }
+                }
             }
         });
 
@@ -271,11 +1055,39 @@ impl eframe::App for WorkTrackerApp {
             &mut self.activity_form,
             &mut self.entry_form,
             &mut self.cache,
+            &mut self.settings,
+            &mut self.messages,
             &self.db,
         );
+
+        if !ctx.wants_keyboard_input()
+            && ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::Z))
+        {
+            self.cache.queue(AppAction::Undo);
+        }
+        if !ctx.wants_keyboard_input()
+            && ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::Y))
+        {
+            self.cache.queue(AppAction::Redo);
+        }
+        self.run_actions();
     }
 }
 
+/// Whether an action is new or replayed from the undo or redo stack
+enum Replay {
+    None,
+    Undo(String),
+    Redo(String),
+}
+
+/// Record how to revert an action, keeping at most `UNDO_LIMIT` steps
+fn push_step(stack: &mut Vec<UndoStep>, label: String, inverse: AppAction) {
+    stack.push(UndoStep { label, inverse });
+    let excess = stack.len().saturating_sub(UNDO_LIMIT);
+    stack.drain(..excess);
+}
+
 /// Configure egui fonts
 fn configure_fonts(ctx: &egui::Context) {
     let mut style = (*ctx.style()).clone();
@@ -300,6 +1112,15 @@ fn configure_fonts(ctx: &egui::Context) {
     ctx.set_style(style);
 }
 
+/// Switch egui's colours to the theme chosen in Settings
+fn apply_theme(ctx: &egui::Context, theme: Theme) {
+    ctx.set_theme(match theme {
+        Theme::System => egui::ThemePreference::System,
+        Theme::Light => egui::ThemePreference::Light,
+        Theme::Dark => egui::ThemePreference::Dark,
+    });
+}
+
 /// Get the database file path
 fn get_database_path() -> PathBuf {
     // Try to use user's data directory
@@ -0,0 +1,376 @@
+// src/backup.rs
+// Database snapshots taken before destructive operations
+
+use chrono::{NaiveDate, NaiveDateTime};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Default number of snapshots kept in the backup folder; older ones are removed
+pub const KEEP_SNAPSHOTS: usize = 20;
+
+const TIME_FORMAT: &str = "%Y%m%d-%H%M%S%.3f";
+
+/// Reason of the restore point taken on the first start of each day
+pub const DAILY: &str = "daily";
+
+/// Reason of the snapshot taken on every start, when enabled in Settings
+pub const STARTUP: &str = "startup";
+
+/// A copy of the database in the backup folder
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub path: PathBuf,
+    pub taken_at: NaiveDateTime,
+    /// Operation the snapshot was taken before, e.g. `delete` or `migration`
+    pub reason: String,
+}
+
+/// File name for a snapshot, e.g. `20241003-091500.123_delete.db`
+pub fn snapshot_file_name(reason: &str, taken_at: NaiveDateTime) -> String {
+    format!("{}_{}.db", taken_at.format(TIME_FORMAT), reason)
+}
+
+/// Read the time and reason back from a snapshot's file name
+pub fn parse_snapshot(path: &Path) -> Option<Snapshot> {
+    if path.extension()? != "db" {
+        return None;
+    }
+    let (time, reason) = path.file_stem()?.to_str()?.split_once('_')?;
+    Some(Snapshot {
+        path: path.to_path_buf(),
+        taken_at: NaiveDateTime::parse_from_str(time, TIME_FORMAT).ok()?,
+        reason: reason.to_string(),
+    })
+}
+
+/// Snapshots in `dir`, newest first. A missing folder has none.
+pub fn list_snapshots(dir: &Path) -> Vec<Snapshot> {
+    let mut snapshots: Vec<Snapshot> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| parse_snapshot(&entry.ok()?.path()))
+                .collect()
+        })
+        .unwrap_or_default();
+    snapshots.sort_by_key(|s| std::cmp::Reverse(s.taken_at));
+    snapshots
+}
+
+/// Whether a snapshot with `reason` was taken on `date`
+pub fn taken_on(snapshots: &[Snapshot], reason: &str, date: NaiveDate) -> bool {
+    snapshots
+        .iter()
+        .any(|s| s.reason == reason && s.taken_at.date() == date)
+}
+
+/// Remove all but the newest `keep` snapshots of each reason, so a run of
+/// deletions can't push out the daily restore points. Returns the number removed.
+pub fn prune_snapshots(dir: &Path, keep: usize) -> std::io::Result<usize> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let old = list_snapshots(dir).into_iter().filter(|s| {
+        let count = seen.entry(s.reason.clone()).or_default();
+        *count += 1;
+        *count > keep
+    });
+    let mut removed = 0;
+    for snapshot in old {
+        std::fs::remove_file(&snapshot.path)?;
+        std::fs::remove_file(checksum_path(&snapshot.path)).ok();
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+// ==================== Checksums ====================
+
+/// Result of checking a snapshot against its stored checksum
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Integrity {
+    Verified,
+    /// Taken before checksums were stored
+    Missing,
+    /// Unreadable, or the contents no longer match the checksum
+    Corrupted,
+}
+
+/// Checksum file stored next to a snapshot, e.g. `…_delete.db.sha256`
+pub fn checksum_path(snapshot: &Path) -> PathBuf {
+    let mut name = snapshot.as_os_str().to_os_string();
+    name.push(".sha256");
+    PathBuf::from(name)
+}
+
+/// Store the SHA-256 of a snapshot in `sha256sum` format
+pub fn write_checksum(snapshot: &Path) -> std::io::Result<()> {
+    let digest = to_hex(&sha256_file(snapshot)?);
+    let name = snapshot
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    std::fs::write(checksum_path(snapshot), format!("{}  {}\n", digest, name))
+}
+
+/// Compare a snapshot with its stored checksum
+pub fn verify(snapshot: &Path) -> Integrity {
+    let Ok(stored) = std::fs::read_to_string(checksum_path(snapshot)) else {
+        return Integrity::Missing;
+    };
+    let expected = stored.split_whitespace().next().unwrap_or_default();
+    match sha256_file(snapshot) {
+        Ok(digest) if to_hex(&digest).eq_ignore_ascii_case(expected) => Integrity::Verified,
+        _ => Integrity::Corrupted,
+    }
+}
+
+/// Check every snapshot in `dir`
+pub fn verify_all(dir: &Path) -> HashMap<PathBuf, Integrity> {
+    list_snapshots(dir)
+        .into_iter()
+        .map(|s| {
+            let integrity = verify(&s.path);
+            (s.path, integrity)
+        })
+        .collect()
+}
+
+/// Check the snapshots in `dir` now and then every `interval` on a background
+/// thread. The thread ends once the receiver is dropped.
+pub fn spawn_verifier(
+    dir: PathBuf,
+    interval: Duration,
+) -> mpsc::Receiver<HashMap<PathBuf, Integrity>> {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        while sender.send(verify_all(&dir)).is_ok() {
+            std::thread::sleep(interval);
+        }
+    });
+    receiver
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// Digest of a file, read in chunks so large backups aren't loaded into memory
+fn sha256_file(path: &Path) -> std::io::Result<[u8; 32]> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut hasher = Sha256::new();
+    loop {
+        let chunk = reader.fill_buf()?;
+        if chunk.is_empty() {
+            return Ok(hasher.finish());
+        }
+        hasher.update(chunk);
+        let len = chunk.len();
+        reader.consume(len);
+    }
+}
+
+/// Incremental SHA-256 (FIPS 180-4), enough to detect damaged backups. The
+/// `sha2` crate isn't available to the offline build.
+struct Sha256 {
+    state: [u32; 8],
+    block: [u8; 64],
+    filled: usize,
+    length: u64,
+}
+
+impl Sha256 {
+    fn new() -> Self {
+        Self {
+            state: [
+                0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+                0x5be0cd19,
+            ],
+            block: [0; 64],
+            filled: 0,
+            length: 0,
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        self.length += data.len() as u64;
+        while !data.is_empty() {
+            let take = (64 - self.filled).min(data.len());
+            self.block[self.filled..self.filled + take].copy_from_slice(&data[..take]);
+            self.filled += take;
+            data = &data[take..];
+            if self.filled == 64 {
+                self.compress();
+                self.filled = 0;
+            }
+        }
+    }
+
+    fn finish(mut self) -> [u8; 32] {
+        let bits = self.length * 8;
+        self.update(&[0x80]);
+        while self.filled != 56 {
+            self.update(&[0]);
+        }
+        self.update(&bits.to_be_bytes());
+
+        let mut digest = [0u8; 32];
+        for (chunk, word) in digest.chunks_exact_mut(4).zip(self.state) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+
+    fn compress(&mut self) {
+        let mut w = [0u32; 64];
+        for (i, word) in self.block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut hh] = self.state;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = hh
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            hh = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (state, value) in self.state.iter_mut().zip([a, b, c, d, e, f, g, hh]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_names() {
+        let taken_at = NaiveDate::from_ymd_opt(2024, 10, 3)
+            .unwrap()
+            .and_hms_milli_opt(9, 15, 0, 42)
+            .unwrap();
+        let name = snapshot_file_name("catalog-import", taken_at);
+        assert_eq!(name, "20241003-091500.042_catalog-import.db");
+
+        let snapshot = parse_snapshot(&Path::new("backups").join(&name)).unwrap();
+        assert_eq!(snapshot.taken_at, taken_at);
+        assert_eq!(snapshot.reason, "catalog-import");
+        assert!(parse_snapshot(Path::new("notes.txt")).is_none());
+        assert!(parse_snapshot(Path::new("chronos_log.db")).is_none());
+
+        let snapshots = [snapshot];
+        assert!(taken_on(&snapshots, "catalog-import", taken_at.date()));
+        assert!(!taken_on(&snapshots, DAILY, taken_at.date()));
+        assert!(!taken_on(
+            &snapshots,
+            "catalog-import",
+            taken_at.date().succ_opt().unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_prune_per_reason() {
+        let dir = std::env::temp_dir().join("chronos-log-test-prune");
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::create_dir_all(&dir).unwrap();
+        let day = NaiveDate::from_ymd_opt(2024, 10, 3).unwrap();
+        for (minute, reason) in [(0, DAILY), (1, "delete"), (2, "delete"), (3, "delete")] {
+            let taken_at = day.and_hms_opt(9, minute, 0).unwrap();
+            std::fs::write(dir.join(snapshot_file_name(reason, taken_at)), b"").unwrap();
+        }
+
+        assert_eq!(prune_snapshots(&dir, 2).unwrap(), 1);
+        let reasons: Vec<String> = list_snapshots(&dir).into_iter().map(|s| s.reason).collect();
+        assert_eq!(reasons, ["delete", "delete", DAILY]);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    fn sha256(data: &[u8]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(data);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_sha256() {
+        assert_eq!(
+            to_hex(&sha256(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            to_hex(&sha256(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        // Two blocks of padding
+        assert_eq!(
+            to_hex(&sha256(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+        // Fed in pieces that straddle block boundaries
+        let data: Vec<u8> = (0..1000u32).map(|i| i as u8).collect();
+        let mut hasher = Sha256::new();
+        for piece in data.chunks(37) {
+            hasher.update(piece);
+        }
+        assert_eq!(hasher.finish(), sha256(&data));
+        assert_eq!(
+            to_hex(&sha256(&[b'a'; 64])),
+            "ffe054fe7ae0cb6dc65c3af9b61d5209f439851db43d0ba5997337df154668eb"
+        );
+    }
+
+    #[test]
+    fn test_verify_checksum() {
+        let dir = std::env::temp_dir().join("chronos-log-test-checksum");
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("20241003-091500.000_delete.db");
+        std::fs::write(&path, b"snapshot").unwrap();
+        assert_eq!(verify(&path), Integrity::Missing);
+
+        write_checksum(&path).unwrap();
+        assert_eq!(verify(&path), Integrity::Verified);
+        assert_eq!(verify_all(&dir)[&path], Integrity::Verified);
+
+        std::fs::write(&path, b"snapshoT").unwrap();
+        assert_eq!(verify(&path), Integrity::Corrupted);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,606 @@
+// src/catalog.rs
+// Import of the company project/activity catalog (CSV or JSON)
+
+use crate::database::{ActivityType, Project};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::fmt;
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum CatalogError {
+    #[error("Could not read catalog: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid JSON catalog: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Line {line}: {message}")]
+    Csv { line: usize, message: String },
+}
+
+/// One row of the catalog. A row without an activity code describes the project itself.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CatalogRow {
+    pub project_code: String,
+    #[serde(default)]
+    pub project_name: String,
+    #[serde(default)]
+    pub activity_code: String,
+    #[serde(default)]
+    pub activity_name: String,
+    #[serde(default)]
+    pub valid_from: Option<NaiveDate>,
+    #[serde(default)]
+    pub valid_to: Option<NaiveDate>,
+    /// Identity of the project in the database it was exported from
+    #[serde(default)]
+    pub project_uuid: String,
+    #[serde(default)]
+    pub activity_uuid: String,
+}
+
+impl CatalogRow {
+    /// Whether the code is valid on the given date (bounds are inclusive)
+    pub fn is_valid_on(&self, date: NaiveDate) -> bool {
+        self.valid_from.is_none_or(|from| from <= date) && self.valid_to.is_none_or(|to| date <= to)
+    }
+}
+
+/// A single change needed to bring local data in line with the catalog
+#[derive(Debug, Clone, PartialEq)]
+pub enum CatalogChange {
+    /// `uuid` is kept from an exported structure, empty for company catalogs
+    AddProject {
+        code: String,
+        name: String,
+        uuid: String,
+    },
+    AddActivity {
+        project_code: String,
+        code: String,
+        name: String,
+        uuid: String,
+    },
+    /// Attach a catalog code to an existing project matched by name
+    LinkProject {
+        id: i64,
+        name: String,
+        code: String,
+    },
+    LinkActivity {
+        id: i64,
+        name: String,
+        code: String,
+    },
+    RenameProject {
+        id: i64,
+        from: String,
+        to: String,
+    },
+    RenameActivity {
+        id: i64,
+        from: String,
+        to: String,
+    },
+    DeactivateProject {
+        id: i64,
+        name: String,
+    },
+    DeactivateActivity {
+        id: i64,
+        name: String,
+    },
+}
+
+impl fmt::Display for CatalogChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AddProject { code, name, .. } => write!(f, "New project {} ({})", name, code),
+            Self::AddActivity {
+                project_code,
+                code,
+                name,
+                ..
+            } => write!(f, "New activity {} ({}) in {}", name, code, project_code),
+            Self::LinkProject { name, code, .. } => {
+                write!(f, "Project {} gets code {}", name, code)
+            }
+            Self::LinkActivity { name, code, .. } => {
+                write!(f, "Activity {} gets code {}", name, code)
+            }
+            Self::RenameProject { from, to, .. } => write!(f, "Rename project {} to {}", from, to),
+            Self::RenameActivity { from, to, .. } => {
+                write!(f, "Rename activity {} to {}", from, to)
+            }
+            Self::DeactivateProject { name, .. } => {
+                write!(f, "Deactivate expired project {}", name)
+            }
+            Self::DeactivateActivity { name, .. } => {
+                write!(f, "Deactivate expired activity {}", name)
+            }
+        }
+    }
+}
+
+/// Load a catalog file. Files ending in `.json` are read as JSON, anything else as CSV.
+pub fn load_catalog(path: &Path) -> Result<Vec<CatalogRow>, CatalogError> {
+    let text = std::fs::read_to_string(path)?;
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    if is_json {
+        Ok(serde_json::from_str(&text)?)
+    } else {
+        parse_csv(&text)
+    }
+}
+
+/// Split a CSV line into fields, honouring double quotes
+fn split_csv_line(line: &str, separator: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == separator && !in_quotes => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Parse a CSV catalog with a header row naming the columns.
+/// Both `;` and `,` separated files are accepted.
+pub fn parse_csv(text: &str) -> Result<Vec<CatalogRow>, CatalogError> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+
+    let Some((_, header)) = lines.next() else {
+        return Ok(Vec::new());
+    };
+    let separator = if header.contains(';') { ';' } else { ',' };
+    let columns: Vec<String> = split_csv_line(header, separator)
+        .iter()
+        .map(|c| c.trim().to_lowercase())
+        .collect();
+    let column = |name: &str| columns.iter().position(|c| c == name);
+    let Some(project_code_col) = column("project_code") else {
+        return Err(CatalogError::Csv {
+            line: 1,
+            message: "missing project_code column".to_string(),
+        });
+    };
+
+    let mut rows = Vec::new();
+    for (index, line) in lines {
+        let fields = split_csv_line(line, separator);
+        let get = |col: Option<usize>| {
+            col.and_then(|i| fields.get(i))
+                .map(|f| f.trim().to_string())
+                .unwrap_or_default()
+        };
+        let date = |name: &str| {
+            let value = get(column(name));
+            if value.is_empty() {
+                return Ok(None);
+            }
+            NaiveDate::parse_from_str(&value, "%Y-%m-%d")
+                .map(Some)
+                .map_err(|_| CatalogError::Csv {
+                    line: index + 1,
+                    message: format!("invalid {} date '{}'", name, value),
+                })
+        };
+
+        let row = CatalogRow {
+            project_code: get(Some(project_code_col)),
+            project_name: get(column("project_name")),
+            activity_code: get(column("activity_code")),
+            activity_name: get(column("activity_name")),
+            valid_from: date("valid_from")?,
+            valid_to: date("valid_to")?,
+            project_uuid: get(column("project_uuid")),
+            activity_uuid: get(column("activity_uuid")),
+        };
+        if row.project_code.is_empty() {
+            return Err(CatalogError::Csv {
+                line: index + 1,
+                message: "empty project code".to_string(),
+            });
+        }
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+/// Name to use for a catalog entry, falling back to its code
+fn name_or_code<'a>(name: &'a str, code: &'a str) -> &'a str {
+    if name.is_empty() {
+        code
+    } else {
+        name
+    }
+}
+
+/// Compare the catalog against existing projects and activities.
+///
+/// Existing entries are matched by the uuid of an exported structure, then by
+/// code, or by name when they have no code yet.
+/// Codes not valid on `today` are deactivated locally and never added.
+/// Local entries missing from the catalog are left untouched.
+pub fn plan_import(
+    rows: &[CatalogRow],
+    projects: &[Project],
+    activities: &[ActivityType],
+    today: NaiveDate,
+) -> Vec<CatalogChange> {
+    let mut project_changes = Vec::new();
+    let mut activity_changes = Vec::new();
+
+    // Group rows by project, keeping catalog order
+    let mut project_codes: Vec<&str> = Vec::new();
+    for row in rows {
+        if !project_codes.contains(&row.project_code.as_str()) {
+            project_codes.push(&row.project_code);
+        }
+    }
+
+    for code in project_codes {
+        let project_rows: Vec<&CatalogRow> =
+            rows.iter().filter(|r| r.project_code == code).collect();
+        let project_row = project_rows.iter().find(|r| r.activity_code.is_empty());
+        let catalog_name = project_rows
+            .iter()
+            .map(|r| r.project_name.as_str())
+            .find(|n| !n.is_empty());
+        let name = catalog_name.unwrap_or(code);
+        // A project row decides validity; otherwise any valid activity keeps it alive
+        let project_valid = match project_row {
+            Some(row) => row.is_valid_on(today),
+            None => project_rows.iter().any(|r| r.is_valid_on(today)),
+        };
+
+        let uuid = project_rows
+            .iter()
+            .map(|r| r.project_uuid.as_str())
+            .find(|u| !u.is_empty())
+            .unwrap_or_default();
+        let existing = projects
+            .iter()
+            .find(|p| !uuid.is_empty() && p.uuid == uuid)
+            .or_else(|| projects.iter().find(|p| p.code == code))
+            .or_else(|| {
+                projects
+                    .iter()
+                    .find(|p| p.code.is_empty() && p.name == name)
+            });
+
+        match existing {
+            Some(project) => {
+                if project.code != code {
+                    project_changes.push(CatalogChange::LinkProject {
+                        id: project.id,
+                        name: project.name.clone(),
+                        code: code.to_string(),
+                    });
+                }
+                if catalog_name.is_some_and(|n| n != project.name) {
+                    project_changes.push(CatalogChange::RenameProject {
+                        id: project.id,
+                        from: project.name.clone(),
+                        to: name.to_string(),
+                    });
+                }
+                if !project_valid && project.is_active {
+                    project_changes.push(CatalogChange::DeactivateProject {
+                        id: project.id,
+                        name: project.name.clone(),
+                    });
+                }
+            }
+            None if project_valid => project_changes.push(CatalogChange::AddProject {
+                code: code.to_string(),
+                name: name.to_string(),
+                uuid: uuid.to_string(),
+            }),
+            // Expired before we ever saw it
+            None => continue,
+        }
+
+        let project_activities: Vec<&ActivityType> = existing
+            .map(|p| activities.iter().filter(|a| a.project_id == p.id).collect())
+            .unwrap_or_default();
+
+        let mut seen_codes: Vec<&str> = Vec::new();
+        for row in project_rows.iter().filter(|r| !r.activity_code.is_empty()) {
+            // Duplicate rows are reported in the preview; the first one wins
+            if seen_codes.contains(&row.activity_code.as_str()) {
+                continue;
+            }
+            seen_codes.push(&row.activity_code);
+
+            let name = name_or_code(&row.activity_name, &row.activity_code);
+            let valid = project_valid && row.is_valid_on(today);
+            let existing = project_activities
+                .iter()
+                .find(|a| !row.activity_uuid.is_empty() && a.uuid == row.activity_uuid)
+                .or_else(|| {
+                    project_activities
+                        .iter()
+                        .find(|a| a.code == row.activity_code)
+                })
+                .or_else(|| {
+                    project_activities
+                        .iter()
+                        .find(|a| a.code.is_empty() && a.name == name)
+                });
+
+            match existing {
+                Some(activity) => {
+                    if activity.code != row.activity_code {
+                        activity_changes.push(CatalogChange::LinkActivity {
+                            id: activity.id,
+                            name: activity.name.clone(),
+                            code: row.activity_code.clone(),
+                        });
+                    }
+                    if !row.activity_name.is_empty() && activity.name != name {
+                        activity_changes.push(CatalogChange::RenameActivity {
+                            id: activity.id,
+                            from: activity.name.clone(),
+                            to: name.to_string(),
+                        });
+                    }
+                    if !valid && activity.is_active {
+                        activity_changes.push(CatalogChange::DeactivateActivity {
+                            id: activity.id,
+                            name: activity.name.clone(),
+                        });
+                    }
+                }
+                None if valid => activity_changes.push(CatalogChange::AddActivity {
+                    project_code: code.to_string(),
+                    code: row.activity_code.clone(),
+                    name: name.to_string(),
+                    uuid: row.activity_uuid.clone(),
+                }),
+                None => {}
+            }
+        }
+    }
+
+    // Projects first, so new activities can find their project by code
+    project_changes.extend(activity_changes);
+    project_changes
+}
+
+/// Catalog rows describing the local projects and activities, for sharing the
+/// project structure. Entries without a catalog code use their name as code;
+/// inactive ones are exported as expired yesterday so an import deactivates them.
+/// Uuids let another database recognise the entries after renames.
+pub fn catalog_rows(
+    projects: &[Project],
+    activities: &[ActivityType],
+    today: NaiveDate,
+) -> Vec<CatalogRow> {
+    let expired = |is_active: bool| (!is_active).then(|| today.pred_opt()).flatten();
+    let mut rows = Vec::new();
+    for project in projects {
+        let project_code = name_or_code(&project.code, &project.name);
+        rows.push(CatalogRow {
+            project_code: project_code.to_string(),
+            project_name: project.name.clone(),
+            activity_code: String::new(),
+            activity_name: String::new(),
+            valid_from: None,
+            valid_to: expired(project.is_active),
+            project_uuid: project.uuid.clone(),
+            activity_uuid: String::new(),
+        });
+        for activity in activities.iter().filter(|a| a.project_id == project.id) {
+            rows.push(CatalogRow {
+                project_code: project_code.to_string(),
+                project_name: String::new(),
+                activity_code: name_or_code(&activity.code, &activity.name).to_string(),
+                activity_name: activity.name.clone(),
+                valid_from: None,
+                valid_to: expired(activity.is_active),
+                project_uuid: project.uuid.clone(),
+                activity_uuid: activity.uuid.clone(),
+            });
+        }
+    }
+    rows
+}
+
+/// Codes (`PROJECT` or `PROJECT/ACTIVITY`) that appear on more than one row
+pub fn duplicate_codes(rows: &[CatalogRow]) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    let mut duplicates = Vec::new();
+    for row in rows {
+        let code = if row.activity_code.is_empty() {
+            row.project_code.clone()
+        } else {
+            format!("{}/{}", row.project_code, row.activity_code)
+        };
+        if !seen.insert(code.clone()) && !duplicates.contains(&code) {
+            duplicates.push(code);
+        }
+    }
+    duplicates
+}
+
+/// Keep the changes ticked in the preview. New activities are dropped along with
+/// their new project when the project itself was left out.
+pub fn select_changes(changes: &[CatalogChange], included: &[bool]) -> Vec<CatalogChange> {
+    let skipped_projects: Vec<&str> = changes
+        .iter()
+        .zip(included)
+        .filter_map(|(change, include)| match change {
+            CatalogChange::AddProject { code, .. } if !include => Some(code.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    changes
+        .iter()
+        .zip(included)
+        .filter(|(change, include)| {
+            **include
+                && !matches!(change, CatalogChange::AddActivity { project_code, .. }
+                    if skipped_projects.contains(&project_code.as_str()))
+        })
+        .map(|(change, _)| change.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let rows = parse_csv(
+            "project_code;project_name;activity_code;activity_name;valid_from;valid_to\n\
+             P100;\"Platform; core\";;;;\n\
+             P100;;A1;Development;2024-01-01;\n",
+        )
+        .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].project_name, "Platform; core");
+        assert_eq!(rows[1].activity_code, "A1");
+        assert_eq!(rows[1].valid_from, Some(date("2024-01-01")));
+        assert_eq!(rows[1].valid_to, None);
+
+        assert!(parse_csv("name,code\nx,y\n").is_err());
+        assert!(parse_csv("project_code,valid_to\nP1,31.12.2024\n").is_err());
+    }
+
+    #[test]
+    fn test_duplicates_and_selection() {
+        let rows = parse_csv(
+            "project_code,activity_code,activity_name\n\
+             P1,,\n\
+             P1,A1,Dev\n\
+             P1,A1,Development\n",
+        )
+        .unwrap();
+        assert_eq!(duplicate_codes(&rows), vec!["P1/A1".to_string()]);
+
+        let changes = plan_import(&rows, &[], &[], date("2024-06-01"));
+        assert_eq!(changes.len(), 2);
+        assert!(select_changes(&changes, &[true, true]).len() == 2);
+        assert!(select_changes(&changes, &[false, true]).is_empty());
+    }
+
+    #[test]
+    fn test_plan_import() {
+        let project = |id, name: &str, code: &str| Project {
+            id,
+            name: name.to_string(),
+            description: String::new(),
+            is_active: true,
+            icon: String::new(),
+            notes: String::new(),
+            code: code.to_string(),
+            uuid: String::new(),
+            archived: false,
+            hourly_rate_cents: None,
+            budget_minutes: None,
+        };
+        let activity = |id, project_id, name: &str, code: &str| ActivityType {
+            archived: false,
+            is_billable: false,
+            hourly_rate_cents: None,
+            monthly_cap_minutes: None,
+            cost_center: String::new(),
+            valid_from: None,
+            valid_to: None,
+            id,
+            project_id,
+            name: name.to_string(),
+            is_active: true,
+            icon: String::new(),
+            code: code.to_string(),
+            uuid: String::new(),
+        };
+        let row = |project_code: &str, activity_code: &str, name: &str, valid_to: Option<&str>| {
+            CatalogRow {
+                project_code: project_code.to_string(),
+                project_name: String::new(),
+                activity_code: activity_code.to_string(),
+                activity_name: name.to_string(),
+                valid_from: None,
+                valid_to: valid_to.map(date),
+                project_uuid: String::new(),
+                activity_uuid: String::new(),
+            }
+        };
+
+        let projects = vec![project(1, "Platform", ""), project(2, "Legacy", "P200")];
+        let activities = vec![
+            activity(10, 1, "Development", ""),
+            activity(11, 1, "Support", "A2"),
+            activity(20, 2, "Maintenance", "B1"),
+        ];
+        let mut rows = vec![
+            row("P100", "", "", None),
+            row("P100", "A1", "Development", None),
+            row("P100", "A2", "2nd line support", None),
+            row("P100", "A3", "Testing", None),
+            row("P100", "A4", "Old", Some("2023-12-31")),
+            row("P200", "B1", "Maintenance", Some("2023-12-31")),
+        ];
+        rows[0].project_name = "Platform".to_string();
+
+        let changes = plan_import(&rows, &projects, &activities, date("2024-06-01"));
+        assert_eq!(
+            changes,
+            vec![
+                CatalogChange::LinkProject {
+                    id: 1,
+                    name: "Platform".to_string(),
+                    code: "P100".to_string(),
+                },
+                CatalogChange::DeactivateProject {
+                    id: 2,
+                    name: "Legacy".to_string(),
+                },
+                CatalogChange::LinkActivity {
+                    id: 10,
+                    name: "Development".to_string(),
+                    code: "A1".to_string(),
+                },
+                CatalogChange::RenameActivity {
+                    id: 11,
+                    from: "Support".to_string(),
+                    to: "2nd line support".to_string(),
+                },
+                CatalogChange::AddActivity {
+                    project_code: "P100".to_string(),
+                    code: "A3".to_string(),
+                    name: "Testing".to_string(),
+                    uuid: String::new(),
+                },
+                CatalogChange::DeactivateActivity {
+                    id: 20,
+                    name: "Maintenance".to_string(),
+                },
+            ]
+        );
+    }
+}
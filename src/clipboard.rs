@@ -0,0 +1,147 @@
+// src/clipboard.rs
+// Text formatting for clipboard copies
+
+use crate::database::ActivitySummary;
+use crate::formatting::{format_hours_minutes, format_minutes_to_decimal, DurationStyle};
+
+/// Placeholders available in summary line templates
+pub const SUMMARY_PLACEHOLDERS: &str = "{project} {activity} {time} {hours} {comments}";
+
+/// Placeholders available in entry line templates
+pub const ENTRY_PLACEHOLDERS: &str = "{time} {hours} {project} {activity} {comment}";
+
+/// Format one entry (or an activity's total with its comments) using a line template.
+/// A literal `\t` in the template is expanded to a tab.
+pub fn format_entry_line(
+    line_template: &str,
+    style: DurationStyle,
+    minutes: i32,
+    project: &str,
+    activity: &str,
+    comment: &str,
+) -> String {
+    line_template
+        .replace("\\t", "\t")
+        .replace("{time}", &style.format(minutes))
+        .replace("{hours}", &format_minutes_to_decimal(minutes))
+        .replace("{project}", project)
+        .replace("{activity}", activity)
+        .replace("{comment}", comment.trim())
+}
+
+/// An activity's day total as a Jira worklog: time spent on the first line,
+/// followed by the entry comments as the work description
+pub fn format_jira_worklog(summary: &ActivitySummary) -> String {
+    // Jira's "Time spent" field expects e.g. `1h 30m`, whatever the display style
+    let mut lines = vec![format_hours_minutes(summary.total_minutes.max(0))];
+    lines.extend(
+        summary
+            .entries
+            .iter()
+            .map(|e| e.comment.trim())
+            .filter(|c| !c.is_empty())
+            .map(str::to_string),
+    );
+    lines.join("\n")
+}
+
+/// Format a day's activity summaries, one line per activity, using a line template.
+/// A literal `\t` in the template is expanded to a tab.
+pub fn format_daily_summary(
+    summaries: &[ActivitySummary],
+    line_template: &str,
+    style: DurationStyle,
+) -> String {
+    summaries
+        .iter()
+        .map(|summary| {
+            let comments: Vec<&str> = summary
+                .entries
+                .iter()
+                .map(|e| e.comment.trim())
+                .filter(|c| !c.is_empty())
+                .collect();
+            line_template
+                .replace("\\t", "\t")
+                .replace("{project}", &summary.project_name)
+                .replace("{activity}", &summary.activity_name)
+                .replace("{time}", &style.format(summary.total_minutes))
+                .replace("{hours}", &format_minutes_to_decimal(summary.total_minutes))
+                .replace("{comments}", &comments.join("; "))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::TimeEntry;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_format_daily_summary() {
+        let date = NaiveDate::from_ymd_opt(2024, 10, 3).unwrap();
+        let entry = |minutes, comment: &str| TimeEntry::for_test(1, date, minutes, comment);
+        let summaries = vec![ActivitySummary {
+            activity_type_id: 1,
+            activity_name: "Bug fixes".to_string(),
+            project_name: "40 - Development".to_string(),
+            total_minutes: 90,
+            entries: vec![entry(60, "Login crash"), entry(30, " ")],
+        }];
+
+        assert_eq!(
+            format_daily_summary(
+                &summaries,
+                "{project}\\t{activity}\\t{hours}",
+                DurationStyle::Clock
+            ),
+            "40 - Development\tBug fixes\t1,50"
+        );
+        assert_eq!(
+            format_daily_summary(&summaries, "{time} {comments}", DurationStyle::HoursMinutes),
+            "1h 30m Login crash"
+        );
+    }
+
+    #[test]
+    fn test_format_jira_worklog() {
+        let date = NaiveDate::from_ymd_opt(2024, 10, 3).unwrap();
+        let entry = |minutes, comment: &str| TimeEntry::for_test(1, date, minutes, comment);
+        let summary = ActivitySummary {
+            activity_type_id: 1,
+            activity_name: "Bug fixes".to_string(),
+            project_name: "40 - Development".to_string(),
+            total_minutes: 90,
+            entries: vec![entry(60, "Login crash"), entry(20, ""), entry(10, "Review")],
+        };
+        assert_eq!(format_jira_worklog(&summary), "1h 30m\nLogin crash\nReview");
+    }
+
+    #[test]
+    fn test_format_entry_line() {
+        assert_eq!(
+            format_entry_line(
+                "{time} – {project} – {activity} – {comment}",
+                DurationStyle::Clock,
+                90,
+                "40 - Development",
+                "Bug fixes",
+                " PROJ-12 login crash ",
+            ),
+            "01:30 – 40 - Development – Bug fixes – PROJ-12 login crash"
+        );
+        assert_eq!(
+            format_entry_line(
+                "{hours}\\t{comment}",
+                DurationStyle::Clock,
+                45,
+                "",
+                "",
+                "Review"
+            ),
+            "0,75\tReview"
+        );
+    }
+}
@@ -1,9 +1,13 @@
 // src/database.rs
 // All database operations for the work tracker application
 
+use crate::catalog::CatalogChange;
+use crate::journal::PendingEntry;
 use chrono::NaiveDate;
-use rusqlite::{params, Connection};
-use std::path::Path;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -14,43 +18,393 @@ pub enum DatabaseError {
     ProjectNotFound(i64),
     #[error("Activity not found: {0}")]
     ActivityNotFound(i64),
+    #[error("Time entry not found: {0}")]
+    EntryNotFound(i64),
     #[error("Invalid time format")]
     InvalidTimeFormat,
     #[error("Activity has time entries and cannot be deleted")]
     ActivityHasEntries,
     #[error("Project has activities and cannot be deleted")]
     ProjectHasActivities,
+    #[error("{0} is locked")]
+    MonthLocked(String),
+    #[error("{0} is older than {1} days and locked")]
+    DayTooOld(String, i64),
+    #[error("File error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("The database is only kept in memory")]
+    InMemory,
+    #[error("Backup does not match its checksum: {0}")]
+    CorruptedBackup(String),
+}
+
+impl DatabaseError {
+    /// Whether the same write may succeed later, e.g. once the file is no longer busy.
+    /// Validation failures such as locked days or deleted activities never will.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            DatabaseError::Sqlite(rusqlite::Error::SqliteFailure(e, _)) => matches!(
+                e.code,
+                rusqlite::ErrorCode::DatabaseBusy
+                    | rusqlite::ErrorCode::DatabaseLocked
+                    | rusqlite::ErrorCode::SystemIoFailure
+                    | rusqlite::ErrorCode::DiskFull
+                    | rusqlite::ErrorCode::CannotOpen
+                    | rusqlite::ErrorCode::ReadOnly
+            ),
+            DatabaseError::Io(_) => true,
+            _ => false,
+        }
+    }
 }
 
 pub type DbResult<T> = Result<T, DatabaseError>;
 
 /// Represents a project in the database
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Project {
     pub id: i64,
     pub name: String,
     pub description: String,
     pub is_active: bool,
+    pub icon: String,
+    pub notes: String,
+    /// Code from the company catalog, empty if not imported
+    pub code: String,
+    /// Identifies the project across databases; carried by catalog exports
+    pub uuid: String,
+    /// Archived projects are inactive and hidden from the management views
+    pub archived: bool,
+    /// Hourly rate in cents of the report currency, for activities without their own
+    pub hourly_rate_cents: Option<i64>,
+    /// Hours budgeted for the whole project, in minutes
+    pub budget_minutes: Option<i32>,
 }
 
-/// Represents an activity type linked to a project
+/// A project with its reference links, as needed to put it back after an edit or delete
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectRecord {
+    pub project: Project,
+    /// (label, url) pairs
+    pub links: Vec<(String, String)>,
+}
+
+/// Whether a project or activity is active or archived, as needed to undo a change of state
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActiveState {
+    pub id: i64,
+    pub is_active: bool,
+    pub archived: bool,
+    /// Activities only: switched off together with their project
+    pub with_project: bool,
+}
+
+/// A reference link (wiki, repository, ticket board) attached to a project
 #[derive(Debug, Clone)]
+pub struct ProjectLink {
+    pub project_id: i64,
+    pub label: String,
+    pub url: String,
+}
+
+/// Represents an activity type linked to a project
+#[derive(Debug, Clone, PartialEq)]
 pub struct ActivityType {
     pub id: i64,
     pub project_id: i64,
     pub name: String,
     pub is_active: bool,
+    pub icon: String,
+    /// Code from the company catalog, empty if not imported
+    pub code: String,
+    /// Identifies the activity across databases; carried by catalog exports
+    pub uuid: String,
+    /// Most time that may be booked on the activity per month
+    pub monthly_cap_minutes: Option<i32>,
+    /// Cost center in the ERP, booked alongside the project code
+    pub cost_center: String,
+    /// First day the activity may be used for new entries
+    pub valid_from: Option<NaiveDate>,
+    /// Last day the activity may be used for new entries
+    pub valid_to: Option<NaiveDate>,
+    /// Archived activities are inactive and hidden from the management views
+    pub archived: bool,
+    /// Whether new entries on the activity are billable by default
+    pub is_billable: bool,
+    /// Hourly rate in cents of the report currency; the project's applies when unset
+    pub hourly_rate_cents: Option<i64>,
+}
+
+impl Project {
+    /// Name prefixed with the project's icon, if any
+    pub fn display_name(&self) -> String {
+        with_icon(&self.icon, &self.name)
+    }
+
+    /// Whether going from `before` to `after` logged minutes takes the
+    /// project over its budget
+    pub fn crosses_budget(&self, before: i32, after: i32) -> bool {
+        self.budget_minutes
+            .is_some_and(|budget| before <= budget && after > budget)
+    }
+}
+
+impl ActivityType {
+    /// Name prefixed with the activity's icon, if any
+    pub fn display_name(&self) -> String {
+        with_icon(&self.icon, &self.name)
+    }
+
+    /// Whether the activity's assignment covers `date`
+    pub fn is_valid_on(&self, date: NaiveDate) -> bool {
+        self.valid_from.is_none_or(|from| from <= date) && self.valid_to.is_none_or(|to| date <= to)
+    }
+
+    /// Whether `month_total` minutes go over the activity's monthly cap
+    pub fn exceeds_cap(&self, month_total: i32) -> bool {
+        self.monthly_cap_minutes
+            .is_some_and(|cap| month_total > cap)
+    }
+}
+
+fn with_icon(icon: &str, name: &str) -> String {
+    if icon.is_empty() {
+        name.to_string()
+    } else {
+        format!("{} {}", icon, name)
+    }
+}
+
+/// Columns selected for a `Project`, in the order `project_from_row` expects
+const PROJECT_COLUMNS: &str = "id, name, description, is_active, icon, notes, code, uuid, \
+     archived, hourly_rate_cents, budget_minutes";
+
+fn project_from_row(row: &rusqlite::Row) -> rusqlite::Result<Project> {
+    Ok(Project {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        is_active: row.get::<_, i32>(3)? == 1,
+        icon: row.get(4)?,
+        notes: row.get(5)?,
+        code: row.get(6)?,
+        uuid: row.get(7)?,
+        archived: row.get::<_, i32>(8)? == 1,
+        hourly_rate_cents: row.get(9)?,
+        budget_minutes: row.get(10)?,
+    })
+}
+
+/// Columns selected for an `ActivityType`, in the order `activity_from_row` expects
+const ACTIVITY_COLUMNS: &str = "id, project_id, name, is_active, icon, code, uuid, \
+     monthly_cap_minutes, cost_center, valid_from, valid_to, archived, is_billable, \
+     hourly_rate_cents";
+
+fn activity_from_row(row: &rusqlite::Row) -> rusqlite::Result<ActivityType> {
+    Ok(ActivityType {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        name: row.get(2)?,
+        is_active: row.get::<_, i32>(3)? == 1,
+        icon: row.get(4)?,
+        code: row.get(5)?,
+        uuid: row.get(6)?,
+        monthly_cap_minutes: row.get(7)?,
+        cost_center: row.get(8)?,
+        valid_from: parse_optional_date(row.get(9)?),
+        valid_to: parse_optional_date(row.get(10)?),
+        archived: row.get::<_, i32>(11)? == 1,
+        is_billable: row.get::<_, i32>(12)? == 1,
+        hourly_rate_cents: row.get(13)?,
+    })
+}
+
+/// Parse a nullable `YYYY-MM-DD` column
+fn parse_optional_date(value: Option<String>) -> Option<NaiveDate> {
+    value.and_then(|v| NaiveDate::parse_from_str(&v, "%Y-%m-%d").ok())
+}
+
+/// Kind of time entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryKind {
+    /// Regular logged work
+    #[default]
+    Work,
+    /// Correction to an already-locked or exported period; may be negative
+    Adjustment,
+    /// Pause during the day; left out of work totals, reports and exports
+    Break,
+    /// Time spent travelling; counts as work and may have trip details
+    Travel,
+}
+
+impl EntryKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Work => "work",
+            Self::Adjustment => "adjustment",
+            Self::Break => "break",
+            Self::Travel => "travel",
+        }
+    }
+
+    /// Parse the stored kind, treating unknown values as work
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "adjustment" => Self::Adjustment,
+            "break" => Self::Break,
+            "travel" => Self::Travel,
+            _ => Self::Work,
+        }
+    }
+}
+
+/// Kind of day registered on a date, overriding the working weekdays
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DayType {
+    Workday,
+    Vacation,
+    SickLeave,
+    PublicHoliday,
+}
+
+impl DayType {
+    pub const ALL: [DayType; 4] = [
+        DayType::Workday,
+        DayType::Vacation,
+        DayType::SickLeave,
+        DayType::PublicHoliday,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DayType::Workday => "workday",
+            DayType::Vacation => "vacation",
+            DayType::SickLeave => "sick",
+            DayType::PublicHoliday => "holiday",
+        }
+    }
+
+    /// Parse the stored type, `None` for unknown values
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.as_str() == value)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DayType::Workday => "Workday",
+            DayType::Vacation => "Vacation",
+            DayType::SickLeave => "Sick leave",
+            DayType::PublicHoliday => "Public holiday",
+        }
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            DayType::Workday => "💼",
+            DayType::Vacation => "🏖",
+            DayType::SickLeave => "🤒",
+            DayType::PublicHoliday => "🎉",
+        }
+    }
 }
 
 /// Represents a time entry for an activity
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct TimeEntry {
     pub id: i64,
     pub activity_type_id: i64,
-    #[allow(dead_code)]
     pub date: NaiveDate,
     pub minutes: i32,
     pub comment: String,
+    pub kind: EntryKind,
+    /// Why an adjustment was made (empty for work entries)
+    pub reason: String,
+    /// Whether the time is invoiced to the customer
+    pub is_billable: bool,
+    /// Identifies the entry across databases; kept when an entry is restored
+    pub uuid: String,
+}
+
+#[cfg(test)]
+impl TimeEntry {
+    /// Work entry with the other fields left at their defaults
+    pub fn for_test(activity_type_id: i64, date: NaiveDate, minutes: i32, comment: &str) -> Self {
+        TimeEntry {
+            id: 0,
+            activity_type_id,
+            date,
+            minutes,
+            comment: comment.to_string(),
+            kind: EntryKind::Work,
+            reason: String::new(),
+            is_billable: false,
+            uuid: String::new(),
+        }
+    }
+}
+
+/// Columns selected for a `TimeEntry`, in the order `entry_from_row` expects
+const TIME_ENTRY_COLUMNS: &str =
+    "id, activity_type_id, date, minutes, comment, kind, reason, is_billable, uuid";
+
+/// `TIME_ENTRY_COLUMNS` qualified with the `te` alias, for joins
+const JOINED_TIME_ENTRY_COLUMNS: &str = "te.id, te.activity_type_id, te.date, te.minutes, \
+     te.comment, te.kind, te.reason, te.is_billable, te.uuid";
+
+/// Condition keeping entries of active activities in active projects unless
+/// parameter ?3 (include inactive) is set
+const ACTIVE_ENTRIES: &str = "(?3 OR activity_type_id IN (
+    SELECT at.id FROM activity_types at JOIN projects p ON at.project_id = p.id
+    WHERE at.is_active = 1 AND p.is_active = 1))";
+
+/// Values for entry columns that snapshots of older schemas lack, matching
+/// the defaults of the migrations that added them
+const ENTRY_COLUMN_DEFAULTS: &[(&str, &str)] = &[
+    ("comment", "''"),
+    ("kind", "'work'"),
+    ("reason", "''"),
+    ("is_billable", "0"),
+];
+
+/// `TIME_ENTRY_COLUMNS` for reading a snapshot's entries, with the default
+/// in place of each column the snapshot's schema doesn't have yet
+fn snapshot_entry_columns(conn: &Connection, schema: &str) -> DbResult<String> {
+    let mut stmt = conn.prepare("SELECT name FROM pragma_table_info('time_entries', ?1)")?;
+    let existing = stmt
+        .query_map(params![schema], |row| row.get::<_, String>(0))?
+        .collect::<Result<HashSet<_>, _>>()?;
+    let columns: Vec<&str> = TIME_ENTRY_COLUMNS
+        .split(", ")
+        .map(|column| {
+            if existing.contains(column) {
+                column
+            } else {
+                ENTRY_COLUMN_DEFAULTS
+                    .iter()
+                    .find(|(name, _)| *name == column)
+                    .map_or("NULL", |(_, default)| default)
+            }
+        })
+        .collect();
+    Ok(columns.join(", "))
+}
+
+fn entry_from_row(row: &rusqlite::Row) -> rusqlite::Result<TimeEntry> {
+    let date_str: String = row.get(2)?;
+    Ok(TimeEntry {
+        id: row.get(0)?,
+        activity_type_id: row.get(1)?,
+        date: NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
+            .unwrap_or_else(|_| NaiveDate::from_ymd_opt(2000, 1, 1).unwrap()),
+        minutes: row.get(3)?,
+        comment: row.get(4)?,
+        kind: EntryKind::parse(&row.get::<_, String>(5)?),
+        reason: row.get(6)?,
+        is_billable: row.get::<_, i32>(7)? == 1,
+        uuid: row.get::<_, Option<String>>(8)?.unwrap_or_default(),
+    })
 }
 
 /// Summary of time spent on an activity type for a specific day
@@ -63,16 +417,441 @@ pub struct ActivitySummary {
     pub entries: Vec<TimeEntry>,
 }
 
+/// Example project created on first run
+pub struct SampleProject {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub icon: &'static str,
+    pub activities: &'static [&'static str],
+}
+
+pub const SAMPLE_PROJECTS: &[SampleProject] = &[
+    SampleProject {
+        name: "33 - IT-Support",
+        description: "IT Support activities across locations",
+        icon: "🛠",
+        activities: &[
+            "IT-Support - Trollhättan",
+            "IT-Support - Göteborg",
+            "IT-Support - Västerås",
+            "IT-Support - Östersund",
+        ],
+    },
+    SampleProject {
+        name: "40 - Development",
+        description: "Software development tasks",
+        icon: "💻",
+        activities: &[
+            "Development - Feature work",
+            "Development - Bug fixes",
+            "Development - Code review",
+        ],
+    },
+];
+
+/// Whether a project is one of the examples created on first run
+pub fn is_sample_project(name: &str) -> bool {
+    SAMPLE_PROJECTS.iter().any(|s| s.name == name)
+}
+
+/// Minutes logged on one project in a date range
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectMinutes {
+    pub project_id: i64,
+    pub project_name: String,
+    pub minutes: i32,
+}
+
+/// Minutes logged on one activity in a date range
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActivityMinutes {
+    pub activity_type_id: i64,
+    pub activity_name: String,
+    pub project_id: i64,
+    pub project_name: String,
+    pub minutes: i32,
+    /// Number of days with entries on the activity
+    pub days: i32,
+}
+
+/// Time logged on one activity in a date range and what it earns
+#[derive(Debug, Clone, PartialEq)]
+pub struct Earning {
+    pub project_name: String,
+    pub activity_name: String,
+    pub minutes: i32,
+    /// The activity's hourly rate, else its project's
+    pub rate_cents: Option<i64>,
+}
+
+impl Earning {
+    /// Rate times decimal hours, `None` without a rate
+    pub fn amount_cents(&self) -> Option<i64> {
+        self.rate_cents
+            .map(|rate| (rate as f64 * self.minutes as f64 / 60.0).round() as i64)
+    }
+}
+
+/// Minutes logged on one day of the week in a date range
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeekdayMinutes {
+    pub weekday: chrono::Weekday,
+    pub minutes: i32,
+    /// Number of those weekdays with entries
+    pub days: i32,
+}
+
+impl WeekdayMinutes {
+    /// Average over the days with entries
+    pub fn average(&self) -> i32 {
+        if self.days == 0 {
+            0
+        } else {
+            self.minutes / self.days
+        }
+    }
+}
+
+/// Aggregates of a date range, computed in SQL
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RangeStatistics {
+    pub total_minutes: i32,
+    pub logged_days: i32,
+    /// Most time first
+    pub per_project: Vec<ProjectMinutes>,
+    /// Monday first; weekdays without entries are left out
+    pub per_weekday: Vec<WeekdayMinutes>,
+}
+
+impl RangeStatistics {
+    /// Average per day with entries
+    pub fn average_per_logged_day(&self) -> i32 {
+        if self.logged_days == 0 {
+            0
+        } else {
+            self.total_minutes / self.logged_days
+        }
+    }
+}
+
+/// How much a project or activity has been used
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Usage {
+    pub total_minutes: i32,
+    /// Minutes in the month the usage was queried for
+    pub month_minutes: i32,
+    /// Date of the latest entry
+    pub last_used: Option<NaiveDate>,
+}
+
+/// A time entry together with its activity and project names (for reports and exports)
+#[derive(Debug, Clone)]
+pub struct EntryDetail {
+    pub entry: TimeEntry,
+    pub project_name: String,
+    pub activity_name: String,
+    pub project_code: String,
+    pub cost_center: String,
+}
+
+/// Where a travel entry went, for expense claims
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Trip {
+    pub origin: String,
+    pub destination: String,
+    pub kilometers: Option<f64>,
+}
+
+/// A travel entry with its names and trip details (for the travel report)
+#[derive(Debug, Clone)]
+pub struct TravelEntry {
+    pub detail: EntryDetail,
+    pub trip: Trip,
+}
+
+/// A work-related expense paid on a day, e.g. a train ticket or a meal
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expense {
+    pub id: i64,
+    pub date: NaiveDate,
+    /// Amount in hundredths of the currency unit
+    pub amount_cents: i64,
+    /// Currency code such as `EUR`
+    pub currency: String,
+    pub description: String,
+    /// Scanned receipt or invoice; empty when there is none
+    pub receipt_path: String,
+}
+
+fn expense_from_row(row: &rusqlite::Row) -> rusqlite::Result<Expense> {
+    Ok(Expense {
+        id: row.get(0)?,
+        date: NaiveDate::parse_from_str(&row.get::<_, String>(1)?, "%Y-%m-%d")
+            .unwrap_or_else(|_| NaiveDate::from_ymd_opt(2000, 1, 1).unwrap()),
+        amount_cents: row.get(2)?,
+        currency: row.get(3)?,
+        description: row.get(4)?,
+        receipt_path: row.get(5)?,
+    })
+}
+
+/// SQL for a random version 4 UUID string
+macro_rules! uuid_v4_sql {
+    () => {
+        "lower(hex(randomblob(4)) || '-' || hex(randomblob(2)) || '-4' || \
+         substr(hex(randomblob(2)), 2) || '-' || \
+         substr('89ab', 1 + (abs(random()) % 4), 1) || substr(hex(randomblob(2)), 2) || '-' || \
+         hex(randomblob(6)))"
+    };
+}
+
+/// Add a `uuid` column to a table: backfill existing rows, index it, and
+/// fill it in on every insert so all write paths get one
+macro_rules! add_uuid_column {
+    ($table:literal) => {
+        concat!(
+            "ALTER TABLE ",
+            $table,
+            " ADD COLUMN uuid TEXT;\n",
+            "UPDATE ",
+            $table,
+            " SET uuid = ",
+            uuid_v4_sql!(),
+            ";\n",
+            "CREATE UNIQUE INDEX idx_",
+            $table,
+            "_uuid ON ",
+            $table,
+            "(uuid);\n",
+            "CREATE TRIGGER ",
+            $table,
+            "_uuid AFTER INSERT ON ",
+            $table,
+            " WHEN NEW.uuid IS NULL BEGIN UPDATE ",
+            $table,
+            " SET uuid = ",
+            uuid_v4_sql!(),
+            " WHERE id = NEW.id; END;\n",
+        )
+    };
+}
+
+/// A recorded file export
+#[derive(Debug, Clone)]
+pub struct ExportRecord {
+    /// Local time, "YYYY-MM-DD HH:MM:SS"
+    pub exported_at: String,
+    /// What was exported, e.g. "tickets" or "catalog"
+    pub kind: String,
+    pub start_date: Option<NaiveDate>,
+    pub end_date: Option<NaiveDate>,
+    pub path: String,
+    pub row_count: i64,
+}
+
+/// A deleted activity with the templates removed alongside it
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeletedActivity {
+    pub activity: ActivityType,
+    pub templates: Vec<EntryTemplate>,
+}
+
+/// An entry suggested for every empty day falling on a weekday
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntryTemplate {
+    pub id: i64,
+    /// 0 = Monday .. 6 = Sunday
+    pub weekday: u32,
+    pub activity_type_id: i64,
+    pub minutes: i32,
+    pub comment: String,
+}
+
+/// Schema migrations, applied in order. The database's `user_version` records how many
+/// have run. Never edit or reorder existing entries; append new ones.
+const MIGRATIONS: &[&str] = &[
+    // 1: Icons for projects and activities
+    r#"
+    ALTER TABLE projects ADD COLUMN icon TEXT NOT NULL DEFAULT '';
+    ALTER TABLE activity_types ADD COLUMN icon TEXT NOT NULL DEFAULT '';
+    "#,
+    // 2: Project notes and reference links
+    r#"
+    ALTER TABLE projects ADD COLUMN notes TEXT NOT NULL DEFAULT '';
+    CREATE TABLE project_links (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        project_id INTEGER NOT NULL,
+        label TEXT NOT NULL DEFAULT '',
+        url TEXT NOT NULL,
+        FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+    );
+    CREATE INDEX idx_project_links_project ON project_links(project_id);
+    "#,
+    // 3: Company catalog codes
+    r#"
+    ALTER TABLE projects ADD COLUMN code TEXT NOT NULL DEFAULT '';
+    ALTER TABLE activity_types ADD COLUMN code TEXT NOT NULL DEFAULT '';
+    "#,
+    // 4: Stable UUIDs for matching entities across databases (sync, merge)
+    concat!(
+        add_uuid_column!("projects"),
+        add_uuid_column!("activity_types"),
+        add_uuid_column!("time_entries"),
+    ),
+    // 5: Adjustment entries
+    r#"
+    ALTER TABLE time_entries ADD COLUMN kind TEXT NOT NULL DEFAULT 'work';
+    ALTER TABLE time_entries ADD COLUMN reason TEXT NOT NULL DEFAULT '';
+    "#,
+    // 6: Export history
+    r#"
+    CREATE TABLE export_log (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        exported_at TEXT NOT NULL DEFAULT (datetime('now', 'localtime')),
+        kind TEXT NOT NULL,
+        start_date TEXT,
+        end_date TEXT,
+        path TEXT NOT NULL,
+        row_count INTEGER NOT NULL
+    );
+    "#,
+    // 7: Weekday entry templates
+    r#"
+    CREATE TABLE entry_templates (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        weekday INTEGER NOT NULL,
+        activity_type_id INTEGER NOT NULL,
+        minutes INTEGER NOT NULL,
+        comment TEXT NOT NULL DEFAULT '',
+        FOREIGN KEY (activity_type_id) REFERENCES activity_types(id) ON DELETE CASCADE
+    );
+    "#,
+    // 8: Months locked by the month-end closing
+    r#"
+    CREATE TABLE locked_months (
+        month TEXT PRIMARY KEY,
+        locked_at TEXT NOT NULL DEFAULT (datetime('now', 'localtime'))
+    );
+    "#,
+    // 9: Monthly caps on activities
+    r#"
+    ALTER TABLE activity_types ADD COLUMN monthly_cap_minutes INTEGER;
+    "#,
+    // 10: Cost centers on activities
+    r#"
+    ALTER TABLE activity_types ADD COLUMN cost_center TEXT NOT NULL DEFAULT '';
+    "#,
+    // 11: Assignment validity periods on activities
+    r#"
+    ALTER TABLE activity_types ADD COLUMN valid_from TEXT;
+    ALTER TABLE activity_types ADD COLUMN valid_to TEXT;
+    "#,
+    // 12: Archived projects and activities
+    r#"
+    ALTER TABLE projects ADD COLUMN archived INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE activity_types ADD COLUMN archived INTEGER NOT NULL DEFAULT 0;
+    "#,
+    // 13: Full-text index on entry comments, kept in sync by triggers
+    r#"
+    CREATE VIRTUAL TABLE time_entries_fts USING fts5(
+        comment,
+        content = 'time_entries',
+        content_rowid = 'id'
+    );
+    INSERT INTO time_entries_fts(time_entries_fts) VALUES ('rebuild');
+    CREATE TRIGGER time_entries_fts_insert AFTER INSERT ON time_entries BEGIN
+        INSERT INTO time_entries_fts(rowid, comment) VALUES (new.id, new.comment);
+    END;
+    CREATE TRIGGER time_entries_fts_delete AFTER DELETE ON time_entries BEGIN
+        INSERT INTO time_entries_fts(time_entries_fts, rowid, comment)
+        VALUES ('delete', old.id, old.comment);
+    END;
+    CREATE TRIGGER time_entries_fts_update AFTER UPDATE OF comment ON time_entries BEGIN
+        INSERT INTO time_entries_fts(time_entries_fts, rowid, comment)
+        VALUES ('delete', old.id, old.comment);
+        INSERT INTO time_entries_fts(rowid, comment) VALUES (new.id, new.comment);
+    END;
+    "#,
+    // 14: Trip details of travel entries. No foreign key: a trip outlives its
+    // entry so undoing a delete brings it back (entry ids are never reused)
+    r#"
+    CREATE TABLE trips (
+        entry_id INTEGER PRIMARY KEY,
+        origin TEXT NOT NULL DEFAULT '',
+        destination TEXT NOT NULL DEFAULT '',
+        kilometers REAL
+    );
+    "#,
+    // 15: Expenses recorded on days
+    r#"
+    CREATE TABLE expenses (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        date TEXT NOT NULL,
+        amount_cents INTEGER NOT NULL,
+        currency TEXT NOT NULL,
+        description TEXT NOT NULL DEFAULT '',
+        receipt_path TEXT NOT NULL DEFAULT ''
+    );
+    CREATE INDEX idx_expenses_date ON expenses(date);
+    "#,
+    // 16: Billable flag on entries, with a default per activity
+    r#"
+    ALTER TABLE activity_types ADD COLUMN is_billable INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE time_entries ADD COLUMN is_billable INTEGER NOT NULL DEFAULT 0;
+    "#,
+    // 17: Hourly rates on projects and activities
+    r#"
+    ALTER TABLE projects ADD COLUMN hourly_rate_cents INTEGER;
+    ALTER TABLE activity_types ADD COLUMN hourly_rate_cents INTEGER;
+    "#,
+    // 18: Hour budgets on projects
+    r#"
+    ALTER TABLE projects ADD COLUMN budget_minutes INTEGER;
+    "#,
+    // 19: Day types (vacation, sick leave, ...) registered on dates
+    r#"
+    CREATE TABLE day_types (
+        date TEXT PRIMARY KEY,
+        day_type TEXT NOT NULL
+    );
+    "#,
+    // 20: Activities deactivated together with their project, reactivated with it
+    r#"
+    ALTER TABLE activity_types ADD COLUMN deactivated_with_project INTEGER NOT NULL DEFAULT 0;
+    "#,
+];
+
 /// Database manager handling all database operations
 pub struct Database {
     conn: Connection,
+    /// File the database is stored in, `None` when in memory
+    path: Option<PathBuf>,
+    /// Snapshots kept in the backup folder
+    keep_snapshots: usize,
+    /// Entries older than this many days are read-only, `None` when off
+    lock_after_days: Option<i64>,
+    /// Old days unlocked for this session
+    unlocked_days: BTreeSet<NaiveDate>,
 }
 
 impl Database {
-    /// Create a new database connection and initialize tables
+    /// Create a new database connection and initialize tables.
+    /// An existing database is backed up before its schema is migrated.
     pub fn new<P: AsRef<Path>>(path: P) -> DbResult<Self> {
-        let conn = Connection::open(path)?;
-        let db = Database { conn };
+        let existed = path.as_ref().exists();
+        let conn = Connection::open(&path)?;
+        let db = Database {
+            conn,
+            path: Some(path.as_ref().to_path_buf()),
+            keep_snapshots: crate::backup::KEEP_SNAPSHOTS,
+            lock_after_days: None,
+            unlocked_days: BTreeSet::new(),
+        };
+        if existed && db.has_pending_migrations()? {
+            if let Err(e) = db.snapshot("migration") {
+                eprintln!("Failed to back up the database before migrating: {}", e);
+            }
+        }
         db.initialize_tables()?;
         Ok(db)
     }
@@ -81,11 +860,141 @@ impl Database {
     #[allow(dead_code)]
     pub fn new_in_memory() -> DbResult<Self> {
         let conn = Connection::open_in_memory()?;
-        let db = Database { conn };
+        let db = Database {
+            conn,
+            path: None,
+            keep_snapshots: crate::backup::KEEP_SNAPSHOTS,
+            lock_after_days: None,
+            unlocked_days: BTreeSet::new(),
+        };
         db.initialize_tables()?;
         Ok(db)
     }
 
+    /// Whether the database lives in memory only, so nothing is saved
+    pub fn is_in_memory(&self) -> bool {
+        self.path.is_none()
+    }
+
+    /// Run `f` in one transaction: its writes are kept together or not at all.
+    /// Inside another transaction it joins that one, so operations combine.
+    pub fn transaction<T>(&self, f: impl FnOnce() -> DbResult<T>) -> DbResult<T> {
+        if !self.conn.is_autocommit() {
+            return f();
+        }
+        let tx = self.conn.unchecked_transaction()?;
+        let result = f()?;
+        tx.commit()?;
+        Ok(result)
+    }
+
+    // ==================== Backups ====================
+
+    /// Number of snapshots to keep; older ones are pruned on the next snapshot
+    pub fn set_snapshot_limit(&mut self, keep: usize) {
+        self.keep_snapshots = keep.max(1);
+    }
+
+    /// Folder snapshots are written to, next to the database file
+    pub fn backup_dir(&self) -> Option<PathBuf> {
+        let path = self.path.as_ref()?;
+        Some(
+            path.parent()
+                .unwrap_or_else(|| Path::new("."))
+                .join("backups"),
+        )
+    }
+
+    /// Write a copy of the database to the backup folder before a destructive
+    /// operation. Returns the snapshot's path, or `None` for an in-memory database.
+    pub fn snapshot(&self, reason: &str) -> DbResult<Option<PathBuf>> {
+        let Some(dir) = self.backup_dir() else {
+            return Ok(None);
+        };
+        std::fs::create_dir_all(&dir)?;
+        let now = chrono::Local::now().naive_local();
+        let path = dir.join(crate::backup::snapshot_file_name(reason, now));
+        self.conn.execute(
+            "VACUUM INTO ?1",
+            params![path.to_string_lossy().to_string()],
+        )?;
+        crate::backup::write_checksum(&path)?;
+        crate::backup::prune_snapshots(&dir, self.keep_snapshots)?;
+        Ok(Some(path))
+    }
+
+    /// Replace the database with a snapshot. The connection is closed while
+    /// the file is copied and reopened afterwards.
+    pub fn restore(&mut self, snapshot: &Path) -> DbResult<()> {
+        let path = self.path.clone().ok_or(DatabaseError::InMemory)?;
+        ensure_intact(snapshot)?;
+        // Replacing the connection closes the database file
+        self.conn = Connection::open_in_memory()?;
+        let copied = std::fs::copy(snapshot, &path);
+        self.conn = Connection::open(&path)?;
+        copied?;
+        // Older snapshots may predate schema migrations
+        self.initialize_tables()
+    }
+
+    /// Entries of `date` as they were when the snapshot was taken
+    pub fn get_snapshot_entries(
+        &self,
+        snapshot: &Path,
+        date: NaiveDate,
+    ) -> DbResult<Vec<TimeEntry>> {
+        let conn =
+            Connection::open_with_flags(snapshot, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM time_entries WHERE date = ?1 ORDER BY id",
+            snapshot_entry_columns(&conn, "main")?
+        ))?;
+        let entries = stmt
+            .query_map(params![date.to_string()], entry_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+
+    /// Replace the entries of `date` with those in the snapshot, leaving every
+    /// other day untouched. Entries of activities deleted since are skipped.
+    /// Returns the number of entries restored.
+    pub fn revert_day(&self, snapshot: &Path, date: NaiveDate) -> DbResult<usize> {
+        self.ensure_date_unlocked(date)?;
+        ensure_intact(snapshot)?;
+        self.conn.execute(
+            "ATTACH DATABASE ?1 AS snapshot",
+            params![snapshot.to_string_lossy().to_string()],
+        )?;
+        let result = (|| -> DbResult<usize> {
+            let tx = self.conn.unchecked_transaction()?;
+            tx.execute(
+                "DELETE FROM main.time_entries WHERE date = ?1",
+                params![date.to_string()],
+            )?;
+            let restored = tx.execute(
+                &format!(
+                    "INSERT INTO main.time_entries ({})
+                     SELECT {} FROM snapshot.time_entries
+                     WHERE date = ?1 AND activity_type_id IN (SELECT id FROM main.activity_types)",
+                    TIME_ENTRY_COLUMNS,
+                    snapshot_entry_columns(&tx, "snapshot")?
+                ),
+                params![date.to_string()],
+            )?;
+            tx.commit()?;
+            Ok(restored)
+        })();
+        self.conn.execute("DETACH DATABASE snapshot", [])?;
+        result
+    }
+
+    fn has_pending_migrations(&self) -> DbResult<bool> {
+        let version: usize = self
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        Ok(version < MIGRATIONS.len())
+    }
+
     /// Initialize all database tables
     fn initialize_tables(&self) -> DbResult<()> {
         self.conn.execute_batch(
@@ -121,43 +1030,75 @@ impl Database {
                 FOREIGN KEY (activity_type_id) REFERENCES activity_types(id) ON DELETE CASCADE
             );
 
+            -- Days marked as done via the close-out checklist
+            CREATE TABLE IF NOT EXISTS closed_days (
+                date TEXT PRIMARY KEY,
+                closed_at TEXT DEFAULT CURRENT_TIMESTAMP
+            );
+
             -- Index for faster date queries
             CREATE INDEX IF NOT EXISTS idx_time_entries_date ON time_entries(date);
             CREATE INDEX IF NOT EXISTS idx_time_entries_activity ON time_entries(activity_type_id);
             "#,
         )?;
+        self.run_migrations()?;
+        Ok(())
+    }
+
+    /// Apply schema migrations newer than the database's `user_version`
+    fn run_migrations(&self) -> DbResult<()> {
+        let version: usize = self
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+        for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            let tx = self.conn.unchecked_transaction()?;
+            tx.execute_batch(migration)?;
+            tx.pragma_update(None, "user_version", i + 1)?;
+            tx.commit()?;
+        }
         Ok(())
     }
 
     // ==================== Project Operations ====================
 
     /// Create a new project
-    pub fn create_project(&self, name: &str, description: &str) -> DbResult<i64> {
+    pub fn create_project(&self, name: &str, description: &str, icon: &str) -> DbResult<i64> {
         self.conn.execute(
-            "INSERT INTO projects (name, description) VALUES (?1, ?2)",
-            params![name, description],
+            "INSERT INTO projects (name, description, icon) VALUES (?1, ?2, ?3)",
+            params![name, description, icon],
         )?;
         Ok(self.conn.last_insert_rowid())
     }
 
+    /// Create a project with its notes, rate, budget and links. Returns the new id.
+    pub fn create_project_record(&self, record: &ProjectRecord) -> DbResult<i64> {
+        let p = &record.project;
+        self.transaction(|| {
+            let id = self.create_project(&p.name, &p.description, &p.icon)?;
+            self.update_project_record(&ProjectRecord {
+                project: Project { id, ..p.clone() },
+                links: record.links.clone(),
+            })?;
+            Ok(id)
+        })
+    }
+
     /// Get all projects (optionally only active ones)
     pub fn get_all_projects(&self, only_active: bool) -> DbResult<Vec<Project>> {
-        let sql = if only_active {
-            "SELECT id, name, description, is_active FROM projects WHERE is_active = 1 ORDER BY name"
+        let filter = if only_active {
+            "WHERE is_active = 1"
         } else {
-            "SELECT id, name, description, is_active FROM projects ORDER BY name"
+            ""
         };
+        let sql = format!(
+            "SELECT {} FROM projects {} ORDER BY name",
+            PROJECT_COLUMNS, filter
+        );
 
-        let mut stmt = self.conn.prepare(sql)?;
+        let mut stmt = self.conn.prepare(&sql)?;
         let projects = stmt
-            .query_map([], |row| {
-                Ok(Project {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    description: row.get(2)?,
-                    is_active: row.get::<_, i32>(3)? == 1,
-                })
-            })?
+            .query_map([], project_from_row)?
             .collect::<Result<Vec<_>, _>>()?;
         Ok(projects)
     }
@@ -167,25 +1108,24 @@ impl Database {
     pub fn get_project(&self, id: i64) -> DbResult<Project> {
         self.conn
             .query_row(
-                "SELECT id, name, description, is_active FROM projects WHERE id = ?1",
+                &format!("SELECT {} FROM projects WHERE id = ?1", PROJECT_COLUMNS),
                 params![id],
-                |row| {
-                    Ok(Project {
-                        id: row.get(0)?,
-                        name: row.get(1)?,
-                        description: row.get(2)?,
-                        is_active: row.get::<_, i32>(3)? == 1,
-                    })
-                },
+                project_from_row,
             )
             .map_err(|_| DatabaseError::ProjectNotFound(id))
     }
 
     /// Update a project
-    pub fn update_project(&self, id: i64, name: &str, description: &str) -> DbResult<()> {
+    pub fn update_project(
+        &self,
+        id: i64,
+        name: &str,
+        description: &str,
+        icon: &str,
+    ) -> DbResult<()> {
         let rows = self.conn.execute(
-            "UPDATE projects SET name = ?1, description = ?2 WHERE id = ?3",
-            params![name, description, id],
+            "UPDATE projects SET name = ?1, description = ?2, icon = ?3 WHERE id = ?4",
+            params![name, description, icon, id],
         )?;
         if rows == 0 {
             return Err(DatabaseError::ProjectNotFound(id));
@@ -193,11 +1133,11 @@ impl Database {
         Ok(())
     }
 
-    /// Deactivate a project (soft delete)
-    pub fn deactivate_project(&self, id: i64) -> DbResult<()> {
+    /// Update a project's free-form notes
+    pub fn update_project_notes(&self, id: i64, notes: &str) -> DbResult<()> {
         let rows = self.conn.execute(
-            "UPDATE projects SET is_active = 0 WHERE id = ?1",
-            params![id],
+            "UPDATE projects SET notes = ?1 WHERE id = ?2",
+            params![notes, id],
         )?;
         if rows == 0 {
             return Err(DatabaseError::ProjectNotFound(id));
@@ -205,11 +1145,38 @@ impl Database {
         Ok(())
     }
 
-    /// Reactivate a project
-    pub fn reactivate_project(&self, id: i64) -> DbResult<()> {
+    /// A project and its links
+    pub fn get_project_record(&self, id: i64) -> DbResult<ProjectRecord> {
+        let project = self.get_project(id)?;
+        let mut stmt = self
+            .conn
+            .prepare("SELECT label, url FROM project_links WHERE project_id = ?1 ORDER BY id")?;
+        let links = stmt
+            .query_map(params![id], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ProjectRecord { project, links })
+    }
+
+    /// Write back name, description, icon, notes, hourly rate, budget and links of a project
+    pub fn update_project_record(&self, record: &ProjectRecord) -> DbResult<()> {
+        let project = &record.project;
+        self.update_project(
+            project.id,
+            &project.name,
+            &project.description,
+            &project.icon,
+        )?;
+        self.update_project_notes(project.id, &project.notes)?;
+        self.set_project_hourly_rate(project.id, project.hourly_rate_cents)?;
+        self.set_project_budget(project.id, project.budget_minutes)?;
+        self.set_project_links(project.id, &record.links)
+    }
+
+    /// Set a project's budget in minutes (`None` for no budget)
+    pub fn set_project_budget(&self, id: i64, budget_minutes: Option<i32>) -> DbResult<()> {
         let rows = self.conn.execute(
-            "UPDATE projects SET is_active = 1 WHERE id = ?1",
-            params![id],
+            "UPDATE projects SET budget_minutes = ?1 WHERE id = ?2",
+            params![budget_minutes, id],
         )?;
         if rows == 0 {
             return Err(DatabaseError::ProjectNotFound(id));
@@ -217,87 +1184,313 @@ impl Database {
         Ok(())
     }
 
-    /// Delete a project permanently (only if no activities exist)
-    pub fn delete_project(&self, id: i64) -> DbResult<()> {
-        // Check if project has any activities
-        let count: i32 = self.conn.query_row(
-            "SELECT COUNT(*) FROM activity_types WHERE project_id = ?1",
-            params![id],
+    /// Minutes logged on a project over its whole lifetime, breaks left out
+    pub fn get_project_total_minutes(&self, project_id: i64) -> DbResult<i32> {
+        let total = self.conn.query_row(
+            "SELECT COALESCE(SUM(te.minutes), 0)
+             FROM time_entries te
+             JOIN activity_types at ON te.activity_type_id = at.id
+             WHERE at.project_id = ?1 AND te.kind != 'break'",
+            params![project_id],
             |row| row.get(0),
         )?;
+        Ok(total)
+    }
 
-        if count > 0 {
-            return Err(DatabaseError::ProjectHasActivities);
-        }
-
-        let rows = self
-            .conn
-            .execute("DELETE FROM projects WHERE id = ?1", params![id])?;
+    /// Set a project's hourly rate in cents (`None` for no rate)
+    pub fn set_project_hourly_rate(&self, id: i64, rate_cents: Option<i64>) -> DbResult<()> {
+        let rows = self.conn.execute(
+            "UPDATE projects SET hourly_rate_cents = ?1 WHERE id = ?2",
+            params![rate_cents, id],
+        )?;
         if rows == 0 {
             return Err(DatabaseError::ProjectNotFound(id));
         }
         Ok(())
     }
 
-    // ==================== Activity Type Operations ====================
-
-    /// Create a new activity type for a project
-    pub fn create_activity_type(&self, project_id: i64, name: &str) -> DbResult<i64> {
+    /// Put a deleted project back under its old id
+    pub fn restore_project(&self, record: &ProjectRecord) -> DbResult<()> {
+        let p = &record.project;
         self.conn.execute(
-            "INSERT INTO activity_types (project_id, name) VALUES (?1, ?2)",
-            params![project_id, name],
+            &format!(
+                "INSERT INTO projects ({})
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                PROJECT_COLUMNS
+            ),
+            params![
+                p.id,
+                p.name,
+                p.description,
+                p.is_active as i32,
+                p.icon,
+                p.notes,
+                p.code,
+                p.uuid,
+                p.archived as i32,
+                p.hourly_rate_cents,
+                p.budget_minutes
+            ],
         )?;
-        Ok(self.conn.last_insert_rowid())
+        self.set_project_links(p.id, &record.links)
     }
 
-    /// Get all activity types for a project
-    #[allow(dead_code)]
-    pub fn get_activity_types_for_project(
-        &self,
-        project_id: i64,
-        only_active: bool,
-    ) -> DbResult<Vec<ActivityType>> {
-        let sql = if only_active {
-            "SELECT id, project_id, name, is_active FROM activity_types
-             WHERE project_id = ?1 AND is_active = 1 ORDER BY name"
-        } else {
-            "SELECT id, project_id, name, is_active FROM activity_types
-             WHERE project_id = ?1 ORDER BY name"
-        };
-
-        let mut stmt = self.conn.prepare(sql)?;
-        let activities = stmt
-            .query_map(params![project_id], |row| {
-                Ok(ActivityType {
+    /// Get the reference links of all projects
+    pub fn get_all_project_links(&self) -> DbResult<Vec<ProjectLink>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT project_id, label, url FROM project_links ORDER BY project_id, id")?;
+        let links = stmt
+            .query_map([], |row| {
+                Ok(ProjectLink {
+                    project_id: row.get(0)?,
+                    label: row.get(1)?,
+                    url: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(links)
+    }
+
+    /// Replace a project's reference links with `links` (label, url pairs)
+    pub fn set_project_links(&self, project_id: i64, links: &[(String, String)]) -> DbResult<()> {
+        self.transaction(|| {
+            self.conn.execute(
+                "DELETE FROM project_links WHERE project_id = ?1",
+                params![project_id],
+            )?;
+            for (label, url) in links {
+                self.conn.execute(
+                    "INSERT INTO project_links (project_id, label, url) VALUES (?1, ?2, ?3)",
+                    params![project_id, label, url],
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Deactivate a project (soft delete)
+    pub fn deactivate_project(&self, id: i64) -> DbResult<()> {
+        let rows = self.conn.execute(
+            "UPDATE projects SET is_active = 0 WHERE id = ?1",
+            params![id],
+        )?;
+        if rows == 0 {
+            return Err(DatabaseError::ProjectNotFound(id));
+        }
+        Ok(())
+    }
+
+    /// Archive a project: deactivate it and hide it from the management view
+    pub fn archive_project(&self, id: i64) -> DbResult<()> {
+        let rows = self.conn.execute(
+            "UPDATE projects SET is_active = 0, archived = 1 WHERE id = ?1",
+            params![id],
+        )?;
+        if rows == 0 {
+            return Err(DatabaseError::ProjectNotFound(id));
+        }
+        Ok(())
+    }
+
+    /// Reactivate a project (also taking it out of the archive)
+    pub fn reactivate_project(&self, id: i64) -> DbResult<()> {
+        let rows = self.conn.execute(
+            "UPDATE projects SET is_active = 1, archived = 0 WHERE id = ?1",
+            params![id],
+        )?;
+        if rows == 0 {
+            return Err(DatabaseError::ProjectNotFound(id));
+        }
+        Ok(())
+    }
+
+    /// Deactivate the active activities of a project, or reactivate the ones deactivated
+    /// together with it. Activities switched off on their own stay off. Returns the number changed.
+    pub fn set_project_activities_active(&self, project_id: i64, active: bool) -> DbResult<usize> {
+        let sql = if active {
+            "UPDATE activity_types SET is_active = 1, deactivated_with_project = 0
+             WHERE project_id = ?1 AND deactivated_with_project = 1"
+        } else {
+            "UPDATE activity_types SET is_active = 0, deactivated_with_project = 1
+             WHERE project_id = ?1 AND is_active = 1"
+        };
+        let rows = self.conn.execute(sql, params![project_id])?;
+        Ok(rows)
+    }
+
+    /// Number of activities `set_project_activities_active` would change
+    pub fn count_project_activities_to_set(
+        &self,
+        project_id: i64,
+        active: bool,
+    ) -> DbResult<usize> {
+        let sql = if active {
+            "SELECT COUNT(*) FROM activity_types
+             WHERE project_id = ?1 AND deactivated_with_project = 1"
+        } else {
+            "SELECT COUNT(*) FROM activity_types WHERE project_id = ?1 AND is_active = 1"
+        };
+        let count: i64 = self
+            .conn
+            .query_row(sql, params![project_id], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    /// Active and archived flags of all projects
+    pub fn get_project_states(&self) -> DbResult<Vec<ActiveState>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, is_active, archived FROM projects ORDER BY id")?;
+        let states = stmt
+            .query_map([], |row| {
+                Ok(ActiveState {
+                    id: row.get(0)?,
+                    is_active: row.get(1)?,
+                    archived: row.get(2)?,
+                    with_project: false,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(states)
+    }
+
+    /// Active, archived and deactivated-with-project flags of all activities
+    pub fn get_activity_states(&self) -> DbResult<Vec<ActiveState>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, is_active, archived, deactivated_with_project
+             FROM activity_types ORDER BY id",
+        )?;
+        let states = stmt
+            .query_map([], |row| {
+                Ok(ActiveState {
                     id: row.get(0)?,
-                    project_id: row.get(1)?,
-                    name: row.get(2)?,
-                    is_active: row.get::<_, i32>(3)? == 1,
+                    is_active: row.get(1)?,
+                    archived: row.get(2)?,
+                    with_project: row.get(3)?,
                 })
             })?
             .collect::<Result<Vec<_>, _>>()?;
+        Ok(states)
+    }
+
+    /// Put back the flags of projects and activities, all or none
+    pub fn set_states(&self, projects: &[ActiveState], activities: &[ActiveState]) -> DbResult<()> {
+        self.transaction(|| {
+            for s in projects {
+                let rows = self.conn.execute(
+                    "UPDATE projects SET is_active = ?1, archived = ?2 WHERE id = ?3",
+                    params![s.is_active, s.archived, s.id],
+                )?;
+                if rows == 0 {
+                    return Err(DatabaseError::ProjectNotFound(s.id));
+                }
+            }
+            for s in activities {
+                let rows = self.conn.execute(
+                    "UPDATE activity_types
+                     SET is_active = ?1, archived = ?2, deactivated_with_project = ?3
+                     WHERE id = ?4",
+                    params![s.is_active, s.archived, s.with_project, s.id],
+                )?;
+                if rows == 0 {
+                    return Err(DatabaseError::ActivityNotFound(s.id));
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Deactivate the active activities of all inactive projects. Returns the number changed.
+    pub fn deactivate_activities_of_inactive_projects(&self) -> DbResult<usize> {
+        let rows = self.conn.execute(
+            "UPDATE activity_types SET is_active = 0, deactivated_with_project = 1
+             WHERE is_active = 1
+               AND project_id IN (SELECT id FROM projects WHERE is_active = 0)",
+            [],
+        )?;
+        Ok(rows)
+    }
+
+    /// Delete a project permanently (only if no activities exist)
+    pub fn delete_project(&self, id: i64) -> DbResult<()> {
+        // Check if project has any activities
+        let count: i32 = self.conn.query_row(
+            "SELECT COUNT(*) FROM activity_types WHERE project_id = ?1",
+            params![id],
+            |row| row.get(0),
+        )?;
+
+        if count > 0 {
+            return Err(DatabaseError::ProjectHasActivities);
+        }
+
+        self.conn.execute(
+            "DELETE FROM project_links WHERE project_id = ?1",
+            params![id],
+        )?;
+        let rows = self
+            .conn
+            .execute("DELETE FROM projects WHERE id = ?1", params![id])?;
+        if rows == 0 {
+            return Err(DatabaseError::ProjectNotFound(id));
+        }
+        Ok(())
+    }
+
+    /// Delete a project, returning what `restore_project` needs to undo it
+    pub fn take_project(&self, id: i64) -> DbResult<ProjectRecord> {
+        let record = self.get_project_record(id)?;
+        self.delete_project(id)?;
+        Ok(record)
+    }
+
+    // ==================== Activity Type Operations ====================
+
+    /// Create a new activity type for a project
+    pub fn create_activity_type(&self, project_id: i64, name: &str, icon: &str) -> DbResult<i64> {
+        self.conn.execute(
+            "INSERT INTO activity_types (project_id, name, icon) VALUES (?1, ?2, ?3)",
+            params![project_id, name, icon],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Get all activity types for a project
+    #[allow(dead_code)]
+    pub fn get_activity_types_for_project(
+        &self,
+        project_id: i64,
+        only_active: bool,
+    ) -> DbResult<Vec<ActivityType>> {
+        let filter = if only_active { "AND is_active = 1" } else { "" };
+        let sql = format!(
+            "SELECT {} FROM activity_types WHERE project_id = ?1 {} ORDER BY name",
+            ACTIVITY_COLUMNS, filter
+        );
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let activities = stmt
+            .query_map(params![project_id], activity_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
         Ok(activities)
     }
 
     /// Get all activity types (optionally only active ones)
     pub fn get_all_activity_types(&self, only_active: bool) -> DbResult<Vec<ActivityType>> {
-        let sql = if only_active {
-            "SELECT id, project_id, name, is_active FROM activity_types
-             WHERE is_active = 1 ORDER BY name"
+        let filter = if only_active {
+            "WHERE is_active = 1"
         } else {
-            "SELECT id, project_id, name, is_active FROM activity_types ORDER BY name"
+            ""
         };
+        let sql = format!(
+            "SELECT {} FROM activity_types {} ORDER BY name",
+            ACTIVITY_COLUMNS, filter
+        );
 
-        let mut stmt = self.conn.prepare(sql)?;
+        let mut stmt = self.conn.prepare(&sql)?;
         let activities = stmt
-            .query_map([], |row| {
-                Ok(ActivityType {
-                    id: row.get(0)?,
-                    project_id: row.get(1)?,
-                    name: row.get(2)?,
-                    is_active: row.get::<_, i32>(3)? == 1,
-                })
-            })?
+            .query_map([], activity_from_row)?
             .collect::<Result<Vec<_>, _>>()?;
         Ok(activities)
     }
@@ -307,25 +1500,21 @@ impl Database {
     pub fn get_activity_type(&self, id: i64) -> DbResult<ActivityType> {
         self.conn
             .query_row(
-                "SELECT id, project_id, name, is_active FROM activity_types WHERE id = ?1",
+                &format!(
+                    "SELECT {} FROM activity_types WHERE id = ?1",
+                    ACTIVITY_COLUMNS
+                ),
                 params![id],
-                |row| {
-                    Ok(ActivityType {
-                        id: row.get(0)?,
-                        project_id: row.get(1)?,
-                        name: row.get(2)?,
-                        is_active: row.get::<_, i32>(3)? == 1,
-                    })
-                },
+                activity_from_row,
             )
             .map_err(|_| DatabaseError::ActivityNotFound(id))
     }
 
     /// Update an activity type
-    pub fn update_activity_type(&self, id: i64, name: &str) -> DbResult<()> {
+    pub fn update_activity_type(&self, id: i64, name: &str, icon: &str) -> DbResult<()> {
         let rows = self.conn.execute(
-            "UPDATE activity_types SET name = ?1 WHERE id = ?2",
-            params![name, id],
+            "UPDATE activity_types SET name = ?1, icon = ?2 WHERE id = ?3",
+            params![name, icon, id],
         )?;
         if rows == 0 {
             return Err(DatabaseError::ActivityNotFound(id));
@@ -333,10 +1522,172 @@ impl Database {
         Ok(())
     }
 
+    /// Set the most time that may be booked on an activity per month (`None` removes the cap)
+    pub fn set_activity_monthly_cap(&self, id: i64, cap_minutes: Option<i32>) -> DbResult<()> {
+        let rows = self.conn.execute(
+            "UPDATE activity_types SET monthly_cap_minutes = ?1 WHERE id = ?2",
+            params![cap_minutes, id],
+        )?;
+        if rows == 0 {
+            return Err(DatabaseError::ActivityNotFound(id));
+        }
+        Ok(())
+    }
+
+    /// Set the cost center an activity is booked against
+    pub fn set_activity_cost_center(&self, id: i64, cost_center: &str) -> DbResult<()> {
+        let rows = self.conn.execute(
+            "UPDATE activity_types SET cost_center = ?1 WHERE id = ?2",
+            params![cost_center, id],
+        )?;
+        if rows == 0 {
+            return Err(DatabaseError::ActivityNotFound(id));
+        }
+        Ok(())
+    }
+
+    /// Set an activity's hourly rate in cents (`None` to use the project's)
+    pub fn set_activity_hourly_rate(&self, id: i64, rate_cents: Option<i64>) -> DbResult<()> {
+        let rows = self.conn.execute(
+            "UPDATE activity_types SET hourly_rate_cents = ?1 WHERE id = ?2",
+            params![rate_cents, id],
+        )?;
+        if rows == 0 {
+            return Err(DatabaseError::ActivityNotFound(id));
+        }
+        Ok(())
+    }
+
+    /// Set whether new entries on an activity are billable by default
+    pub fn set_activity_billable(&self, id: i64, is_billable: bool) -> DbResult<()> {
+        let rows = self.conn.execute(
+            "UPDATE activity_types SET is_billable = ?1 WHERE id = ?2",
+            params![is_billable as i32, id],
+        )?;
+        if rows == 0 {
+            return Err(DatabaseError::ActivityNotFound(id));
+        }
+        Ok(())
+    }
+
+    /// Set the period an activity may be used for new entries (`None` for open-ended)
+    pub fn set_activity_validity(
+        &self,
+        id: i64,
+        valid_from: Option<NaiveDate>,
+        valid_to: Option<NaiveDate>,
+    ) -> DbResult<()> {
+        let rows = self.conn.execute(
+            "UPDATE activity_types SET valid_from = ?1, valid_to = ?2 WHERE id = ?3",
+            params![
+                valid_from.map(|d| d.to_string()),
+                valid_to.map(|d| d.to_string()),
+                id
+            ],
+        )?;
+        if rows == 0 {
+            return Err(DatabaseError::ActivityNotFound(id));
+        }
+        Ok(())
+    }
+
+    /// Total time, time in the month of `month` and latest entry per activity
+    pub fn get_activity_usage(&self, month: NaiveDate) -> DbResult<HashMap<i64, Usage>> {
+        self.query_usage(
+            "SELECT activity_type_id, SUM(minutes),
+                    SUM(CASE WHEN substr(date, 1, 7) = ?1 THEN minutes ELSE 0 END),
+                    MAX(date)
+             FROM time_entries
+             WHERE kind != 'break'
+             GROUP BY activity_type_id",
+            month,
+        )
+    }
+
+    /// Total time, time in the month of `month` and latest entry per project
+    pub fn get_project_usage(&self, month: NaiveDate) -> DbResult<HashMap<i64, Usage>> {
+        self.query_usage(
+            "SELECT at.project_id, SUM(te.minutes),
+                    SUM(CASE WHEN substr(te.date, 1, 7) = ?1 THEN te.minutes ELSE 0 END),
+                    MAX(te.date)
+             FROM time_entries te
+             JOIN activity_types at ON te.activity_type_id = at.id
+             WHERE te.kind != 'break'
+             GROUP BY at.project_id",
+            month,
+        )
+    }
+
+    /// Activity of the latest entry in each project (by date, then creation);
+    /// breaks don't count, as they aren't work on the activity
+    pub fn get_last_activity_per_project(&self) -> DbResult<HashMap<i64, i64>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT project_id, activity_type_id FROM (
+                 SELECT at.project_id, te.activity_type_id,
+                        ROW_NUMBER() OVER (
+                            PARTITION BY at.project_id ORDER BY te.date DESC, te.id DESC
+                        ) AS rank
+                 FROM time_entries te
+                 JOIN activity_types at ON te.activity_type_id = at.id
+                 WHERE te.kind != 'break'
+             )
+             WHERE rank = 1",
+        )?;
+        let last = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        Ok(last)
+    }
+
+    fn query_usage(&self, sql: &str, month: NaiveDate) -> DbResult<HashMap<i64, Usage>> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let usage = stmt
+            .query_map(params![month.format("%Y-%m").to_string()], |row| {
+                Ok((
+                    row.get(0)?,
+                    Usage {
+                        total_minutes: row.get(1)?,
+                        month_minutes: row.get(2)?,
+                        last_used: parse_optional_date(row.get(3)?),
+                    },
+                ))
+            })?
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        Ok(usage)
+    }
+
+    /// Total minutes per activity in the month containing `date`
+    pub fn get_activity_totals_for_month(&self, date: NaiveDate) -> DbResult<HashMap<i64, i32>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT activity_type_id, SUM(minutes) FROM time_entries
+             WHERE substr(date, 1, 7) = ?1 AND kind != 'break'
+             GROUP BY activity_type_id",
+        )?;
+        let totals = stmt
+            .query_map(params![date.format("%Y-%m").to_string()], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        Ok(totals)
+    }
+
     /// Deactivate an activity type (soft delete)
     pub fn deactivate_activity_type(&self, id: i64) -> DbResult<()> {
         let rows = self.conn.execute(
-            "UPDATE activity_types SET is_active = 0 WHERE id = ?1",
+            "UPDATE activity_types SET is_active = 0, deactivated_with_project = 0 WHERE id = ?1",
+            params![id],
+        )?;
+        if rows == 0 {
+            return Err(DatabaseError::ActivityNotFound(id));
+        }
+        Ok(())
+    }
+
+    /// Archive an activity type: deactivate it and hide it from the management view
+    pub fn archive_activity_type(&self, id: i64) -> DbResult<()> {
+        let rows = self.conn.execute(
+            "UPDATE activity_types SET is_active = 0, archived = 1, deactivated_with_project = 0
+             WHERE id = ?1",
             params![id],
         )?;
         if rows == 0 {
@@ -345,10 +1696,11 @@ impl Database {
         Ok(())
     }
 
-    /// Reactivate an activity type
+    /// Reactivate an activity type (also taking it out of the archive)
     pub fn reactivate_activity_type(&self, id: i64) -> DbResult<()> {
         let rows = self.conn.execute(
-            "UPDATE activity_types SET is_active = 1 WHERE id = ?1",
+            "UPDATE activity_types SET is_active = 1, archived = 0, deactivated_with_project = 0
+             WHERE id = ?1",
             params![id],
         )?;
         if rows == 0 {
@@ -370,6 +1722,10 @@ impl Database {
             return Err(DatabaseError::ActivityHasEntries);
         }
 
+        self.conn.execute(
+            "DELETE FROM entry_templates WHERE activity_type_id = ?1",
+            params![id],
+        )?;
         let rows = self
             .conn
             .execute("DELETE FROM activity_types WHERE id = ?1", params![id])?;
@@ -379,6 +1735,193 @@ impl Database {
         Ok(())
     }
 
+    /// Delete an activity, returning what `restore_activity_type` needs to undo it
+    pub fn take_activity_type(&self, id: i64) -> DbResult<DeletedActivity> {
+        let activity = self.get_activity_type(id)?;
+        let templates = self
+            .get_entry_templates()?
+            .into_iter()
+            .filter(|t| t.activity_type_id == id)
+            .collect();
+        self.delete_activity_type(id)?;
+        Ok(DeletedActivity {
+            activity,
+            templates,
+        })
+    }
+
+    /// Put a deleted activity and its templates back under their old ids
+    pub fn restore_activity_type(&self, deleted: &DeletedActivity) -> DbResult<()> {
+        let a = &deleted.activity;
+        self.transaction(|| {
+            self.conn.execute(
+                &format!(
+                    "INSERT INTO activity_types ({})
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+                    ACTIVITY_COLUMNS
+                ),
+                params![
+                    a.id,
+                    a.project_id,
+                    a.name,
+                    a.is_active as i32,
+                    a.icon,
+                    a.code,
+                    a.uuid,
+                    a.monthly_cap_minutes,
+                    a.cost_center,
+                    a.valid_from.map(|d| d.to_string()),
+                    a.valid_to.map(|d| d.to_string()),
+                    a.archived as i32,
+                    a.is_billable as i32,
+                    a.hourly_rate_cents
+                ],
+            )?;
+            for t in &deleted.templates {
+                self.conn.execute(
+                    "INSERT INTO entry_templates (id, weekday, activity_type_id, minutes, comment)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![t.id, t.weekday, t.activity_type_id, t.minutes, t.comment],
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Write back the editable fields of an activity: name, icon, cap, cost center and validity
+    pub fn update_activity_record(&self, activity: &ActivityType) -> DbResult<()> {
+        self.update_activity_type(activity.id, &activity.name, &activity.icon)?;
+        self.set_activity_monthly_cap(activity.id, activity.monthly_cap_minutes)?;
+        self.set_activity_cost_center(activity.id, &activity.cost_center)?;
+        self.set_activity_validity(activity.id, activity.valid_from, activity.valid_to)?;
+        self.set_activity_billable(activity.id, activity.is_billable)?;
+        self.set_activity_hourly_rate(activity.id, activity.hourly_rate_cents)
+    }
+
+    // ==================== Sample Data ====================
+
+    /// Create the example projects and activities offered on first run
+    pub fn create_sample_data(&self) -> DbResult<()> {
+        for sample in SAMPLE_PROJECTS {
+            let project_id = self.create_project(sample.name, sample.description, sample.icon)?;
+            for activity in sample.activities {
+                self.create_activity_type(project_id, activity, "")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Delete the example activities that have no entries, and the example
+    /// projects left without activities. Returns the removed projects and
+    /// activities for `restore_project` and `restore_activity_type`.
+    pub fn remove_sample_data(&self) -> DbResult<(Vec<ProjectRecord>, Vec<DeletedActivity>)> {
+        let mut projects = Vec::new();
+        let mut activities = Vec::new();
+        for sample in SAMPLE_PROJECTS {
+            let project_id: Option<i64> = self
+                .conn
+                .query_row(
+                    "SELECT id FROM projects WHERE name = ?1",
+                    params![sample.name],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            let Some(project_id) = project_id else {
+                continue;
+            };
+            for activity in self.get_activity_types_for_project(project_id, false)? {
+                if !sample.activities.contains(&activity.name.as_str()) {
+                    continue;
+                }
+                match self.take_activity_type(activity.id) {
+                    Ok(deleted) => activities.push(deleted),
+                    Err(DatabaseError::ActivityHasEntries) => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            match self.take_project(project_id) {
+                Ok(record) => projects.push(record),
+                Err(DatabaseError::ProjectHasActivities) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok((projects, activities))
+    }
+
+    // ==================== Catalog Import ====================
+
+    /// Apply changes planned from the company catalog in a single transaction.
+    /// Returns the number of changes applied.
+    pub fn apply_catalog_changes(&self, changes: &[CatalogChange]) -> DbResult<usize> {
+        self.transaction(|| {
+        for change in changes {
+            match change {
+                // A uuid already in use (e.g. a hand-edited file) gets a fresh one
+                CatalogChange::AddProject { code, name, uuid } => {
+                    self.conn.execute(
+                        "INSERT INTO projects (name, code, uuid)
+                         VALUES (?1, ?2, CASE WHEN EXISTS (SELECT 1 FROM projects WHERE uuid = ?3)
+                                              THEN NULL ELSE NULLIF(?3, '') END)",
+                        params![name, code, uuid],
+                    )?;
+                }
+                CatalogChange::AddActivity {
+                    project_code,
+                    code,
+                    name,
+                    uuid,
+                } => {
+                    self.conn.execute(
+                        "INSERT INTO activity_types (project_id, name, code, uuid)
+                         SELECT id, ?2, ?3,
+                                CASE WHEN EXISTS (SELECT 1 FROM activity_types WHERE uuid = ?4)
+                                     THEN NULL ELSE NULLIF(?4, '') END
+                         FROM projects WHERE code = ?1",
+                        params![project_code, name, code, uuid],
+                    )?;
+                }
+                CatalogChange::LinkProject { id, code, .. } => {
+                    self.conn.execute(
+                        "UPDATE projects SET code = ?1 WHERE id = ?2",
+                        params![code, id],
+                    )?;
+                }
+                CatalogChange::LinkActivity { id, code, .. } => {
+                    self.conn.execute(
+                        "UPDATE activity_types SET code = ?1 WHERE id = ?2",
+                        params![code, id],
+                    )?;
+                }
+                CatalogChange::RenameProject { id, to, .. } => {
+                    self.conn.execute(
+                        "UPDATE projects SET name = ?1 WHERE id = ?2",
+                        params![to, id],
+                    )?;
+                }
+                CatalogChange::RenameActivity { id, to, .. } => {
+                    self.conn.execute(
+                        "UPDATE activity_types SET name = ?1 WHERE id = ?2",
+                        params![to, id],
+                    )?;
+                }
+                CatalogChange::DeactivateProject { id, .. } => {
+                    self.conn.execute(
+                        "UPDATE projects SET is_active = 0 WHERE id = ?1",
+                        params![id],
+                    )?;
+                }
+                CatalogChange::DeactivateActivity { id, .. } => {
+                    self.conn.execute(
+                        "UPDATE activity_types SET is_active = 0, deactivated_with_project = 0 WHERE id = ?1",
+                        params![id],
+                    )?;
+                }
+            }
+        }
+        Ok(changes.len())
+        })
+    }
+
     // ==================== Time Entry Operations ====================
 
     /// Create a new time entry
@@ -389,255 +1932,2432 @@ impl Database {
         minutes: i32,
         comment: &str,
     ) -> DbResult<i64> {
-        self.conn.execute(
-            "INSERT INTO time_entries (activity_type_id, date, minutes, comment)
-             VALUES (?1, ?2, ?3, ?4)",
-            params![activity_type_id, date.to_string(), minutes, comment],
-        )?;
-        Ok(self.conn.last_insert_rowid())
+        self.insert_time_entry(
+            activity_type_id,
+            date,
+            minutes,
+            comment,
+            EntryKind::Work,
+            "",
+        )
     }
 
-    /// Get all time entries for a specific date
-    pub fn get_time_entries_for_date(&self, date: NaiveDate) -> DbResult<Vec<TimeEntry>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, activity_type_id, date, minutes, comment
-             FROM time_entries WHERE date = ?1 ORDER BY id",
-        )?;
-        let entries = stmt
-            .query_map(params![date.to_string()], |row| {
-                let date_str: String = row.get(2)?;
-                Ok(TimeEntry {
-                    id: row.get(0)?,
-                    activity_type_id: row.get(1)?,
-                    date: NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
-                        .unwrap_or_else(|_| NaiveDate::from_ymd_opt(2000, 1, 1).unwrap()),
-                    minutes: row.get(3)?,
-                    comment: row.get(4)?,
-                })
-            })?
-            .collect::<Result<Vec<_>, _>>()?;
-        Ok(entries)
+    /// Create an adjustment entry correcting an earlier period (minutes may be negative)
+    pub fn create_adjustment(
+        &self,
+        activity_type_id: i64,
+        date: NaiveDate,
+        minutes: i32,
+        comment: &str,
+        reason: &str,
+    ) -> DbResult<i64> {
+        self.insert_time_entry(
+            activity_type_id,
+            date,
+            minutes,
+            comment,
+            EntryKind::Adjustment,
+            reason,
+        )
     }
 
-    /// Get time entries for a date range
-    #[allow(dead_code)]
-    pub fn get_time_entries_for_range(
+    /// Create a break; it is kept out of work totals, reports and exports
+    pub fn create_break(
         &self,
-        start_date: NaiveDate,
-        end_date: NaiveDate,
-    ) -> DbResult<Vec<TimeEntry>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, activity_type_id, date, minutes, comment
-             FROM time_entries WHERE date >= ?1 AND date <= ?2 ORDER BY date, id",
-        )?;
-        let entries = stmt
-            .query_map(
-                params![start_date.to_string(), end_date.to_string()],
-                |row| {
-                    let date_str: String = row.get(2)?;
-                    Ok(TimeEntry {
-                        id: row.get(0)?,
-                        activity_type_id: row.get(1)?,
-                        date: NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
-                            .unwrap_or_else(|_| NaiveDate::from_ymd_opt(2000, 1, 1).unwrap()),
-                        minutes: row.get(3)?,
-                        comment: row.get(4)?,
-                    })
-                },
-            )?
-            .collect::<Result<Vec<_>, _>>()?;
-        Ok(entries)
+        activity_type_id: i64,
+        date: NaiveDate,
+        minutes: i32,
+        comment: &str,
+    ) -> DbResult<i64> {
+        self.insert_time_entry(
+            activity_type_id,
+            date,
+            minutes,
+            comment,
+            EntryKind::Break,
+            "",
+        )
     }
 
-    /// Update a time entry
-    pub fn update_time_entry(&self, id: i64, minutes: i32, comment: &str) -> DbResult<()> {
+    /// Create a travel entry with its trip details
+    pub fn create_travel(
+        &self,
+        activity_type_id: i64,
+        date: NaiveDate,
+        minutes: i32,
+        comment: &str,
+        trip: &Trip,
+    ) -> DbResult<i64> {
+        self.transaction(|| {
+            let id = self.insert_time_entry(
+                activity_type_id,
+                date,
+                minutes,
+                comment,
+                EntryKind::Travel,
+                "",
+            )?;
+            self.insert_trip(id, trip)?;
+            Ok(id)
+        })
+    }
+
+    fn insert_trip(&self, entry_id: i64, trip: &Trip) -> DbResult<()> {
         self.conn.execute(
-            "UPDATE time_entries SET minutes = ?1, comment = ?2 WHERE id = ?3",
-            params![minutes, comment, id],
+            "INSERT INTO trips (entry_id, origin, destination, kilometers)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![entry_id, trip.origin, trip.destination, trip.kilometers],
         )?;
         Ok(())
     }
 
-    /// Delete a time entry
-    pub fn delete_time_entry(&self, id: i64) -> DbResult<()> {
-        self.conn
-            .execute("DELETE FROM time_entries WHERE id = ?1", params![id])?;
-        Ok(())
+    /// Write an entry from the journal under the uuid it was journaled with, together
+    /// with its trip and billable override. Returns `None` when the uuid is taken: an
+    /// earlier attempt wrote the entry but didn't get to update the journal.
+    pub fn create_journaled_entry(&self, entry: &PendingEntry) -> DbResult<Option<i64>> {
+        self.transaction(|| {
+            let Some(id) = self.insert_entry_row(
+                entry.activity_type_id,
+                entry.date,
+                entry.minutes,
+                &entry.comment,
+                entry.kind,
+                &entry.reason,
+                Some(&entry.uuid),
+            )?
+            else {
+                return Ok(None);
+            };
+            if entry.kind == EntryKind::Travel {
+                self.insert_trip(id, &entry.trip.clone().unwrap_or_default())?;
+            }
+            if let Some(is_billable) = entry.is_billable {
+                self.set_entry_billable(id, is_billable)?;
+            }
+            Ok(Some(id))
+        })
     }
 
-    // ==================== Summary Operations ====================
-
-    /// Get activity summaries for a specific date (total time per activity)
-    pub fn get_daily_summary(&self, date: NaiveDate) -> DbResult<Vec<ActivitySummary>> {
-        // First get all entries for the date with activity and project info
+    /// Trip details of the travel entries in a date range, by entry id
+    pub fn get_trips_between(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> DbResult<HashMap<i64, Trip>> {
         let mut stmt = self.conn.prepare(
+            "SELECT tr.entry_id, tr.origin, tr.destination, tr.kilometers
+             FROM trips tr
+             JOIN time_entries te ON te.id = tr.entry_id
+             WHERE te.date >= ?1 AND te.date <= ?2",
+        )?;
+        let trips = stmt
+            .query_map(params![start.to_string(), end.to_string()], |row| {
+                Ok((
+                    row.get(0)?,
+                    Trip {
+                        origin: row.get(1)?,
+                        destination: row.get(2)?,
+                        kilometers: row.get(3)?,
+                    },
+                ))
+            })?
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        Ok(trips)
+    }
+
+    /// Travel entries of a date range with their trip details, oldest first
+    pub fn get_travel_between(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> DbResult<Vec<TravelEntry>> {
+        let mut stmt = self.conn.prepare(&format!(
             r#"
-            SELECT
-                at.id as activity_type_id,
-                at.name as activity_name,
-                p.name as project_name,
-                te.id as entry_id,
-                te.minutes,
-                te.comment
+            SELECT {}, p.name, at.name, p.code, at.cost_center,
+                   tr.origin, tr.destination, tr.kilometers
             FROM time_entries te
             JOIN activity_types at ON te.activity_type_id = at.id
             JOIN projects p ON at.project_id = p.id
-            WHERE te.date = ?1
-            ORDER BY p.name, at.name, te.id
+            LEFT JOIN trips tr ON tr.entry_id = te.id
+            WHERE te.date >= ?1 AND te.date <= ?2 AND te.kind = 'travel'
+            ORDER BY te.date, te.id
             "#,
-        )?;
-
-        let rows: Vec<(i64, String, String, i64, i32, String)> = stmt
-            .query_map(params![date.to_string()], |row| {
-                Ok((
-                    row.get(0)?,
-                    row.get(1)?,
-                    row.get(2)?,
-                    row.get(3)?,
-                    row.get(4)?,
-                    row.get(5)?,
-                ))
+            JOINED_TIME_ENTRY_COLUMNS
+        ))?;
+        let travel = stmt
+            .query_map(params![start.to_string(), end.to_string()], |row| {
+                Ok(TravelEntry {
+                    detail: EntryDetail {
+                        entry: entry_from_row(row)?,
+                        project_name: row.get(9)?,
+                        activity_name: row.get(10)?,
+                        project_code: row.get(11)?,
+                        cost_center: row.get(12)?,
+                    },
+                    trip: Trip {
+                        origin: row.get::<_, Option<String>>(13)?.unwrap_or_default(),
+                        destination: row.get::<_, Option<String>>(14)?.unwrap_or_default(),
+                        kilometers: row.get(15)?,
+                    },
+                })
             })?
             .collect::<Result<Vec<_>, _>>()?;
+        Ok(travel)
+    }
 
-        // Group by activity type
-        let mut summaries: Vec<ActivitySummary> = Vec::new();
-        for (activity_type_id, activity_name, project_name, entry_id, minutes, comment) in rows {
-            if let Some(summary) = summaries
-                .iter_mut()
-                .find(|s| s.activity_type_id == activity_type_id)
-            {
-                summary.total_minutes += minutes;
-                summary.entries.push(TimeEntry {
-                    id: entry_id,
-                    activity_type_id,
-                    date,
-                    minutes,
-                    comment,
-                });
-            } else {
-                summaries.push(ActivitySummary {
-                    activity_type_id,
-                    activity_name,
-                    project_name,
-                    total_minutes: minutes,
-                    entries: vec![TimeEntry {
-                        id: entry_id,
-                        activity_type_id,
-                        date,
-                        minutes,
-                        comment,
-                    }],
-                });
-            }
+    fn insert_time_entry(
+        &self,
+        activity_type_id: i64,
+        date: NaiveDate,
+        minutes: i32,
+        comment: &str,
+        kind: EntryKind,
+        reason: &str,
+    ) -> DbResult<i64> {
+        // Without a uuid the entry gets a fresh one, so there is nothing to conflict with
+        let id =
+            self.insert_entry_row(activity_type_id, date, minutes, comment, kind, reason, None)?;
+        Ok(id.unwrap_or_default())
+    }
+
+    /// Insert an entry, under `uuid` if given. Returns `None` if the uuid is already taken.
+    #[allow(clippy::too_many_arguments)]
+    fn insert_entry_row(
+        &self,
+        activity_type_id: i64,
+        date: NaiveDate,
+        minutes: i32,
+        comment: &str,
+        kind: EntryKind,
+        reason: &str,
+        uuid: Option<&str>,
+    ) -> DbResult<Option<i64>> {
+        self.ensure_date_unlocked(date)?;
+        // Foreign keys are off: refuse entries on deleted activities
+        let exists: bool = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM activity_types WHERE id = ?1)",
+            params![activity_type_id],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            return Err(DatabaseError::ActivityNotFound(activity_type_id));
         }
+        // Billable as the activity is by default
+        let rows = self.conn.execute(
+            "INSERT INTO time_entries
+                 (activity_type_id, date, minutes, comment, kind, reason, is_billable, uuid)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6,
+                 COALESCE((SELECT is_billable FROM activity_types WHERE id = ?1), 0), ?7)
+             ON CONFLICT(uuid) DO NOTHING",
+            params![
+                activity_type_id,
+                date.to_string(),
+                minutes,
+                comment,
+                kind.as_str(),
+                reason,
+                uuid
+            ],
+        )?;
+        Ok((rows > 0).then(|| self.conn.last_insert_rowid()))
+    }
 
-        Ok(summaries)
+    /// Get all time entries for a specific date
+    pub fn get_time_entries_for_date(&self, date: NaiveDate) -> DbResult<Vec<TimeEntry>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM time_entries WHERE date = ?1 ORDER BY id",
+            TIME_ENTRY_COLUMNS
+        ))?;
+        let entries = stmt
+            .query_map(params![date.to_string()], entry_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(entries)
     }
 
-    /// Get total time for a specific date
-    #[allow(dead_code)]
-    pub fn get_total_time_for_date(&self, date: NaiveDate) -> DbResult<i32> {
-        let total: i32 = self.conn.query_row(
-            "SELECT COALESCE(SUM(minutes), 0) FROM time_entries WHERE date = ?1",
-            params![date.to_string()],
-            |row| row.get(0),
+    /// Get time entries for a date range
+    pub fn get_time_entries_for_range(
+        &self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> DbResult<Vec<TimeEntry>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM time_entries WHERE date >= ?1 AND date <= ?2 ORDER BY date, id",
+            TIME_ENTRY_COLUMNS
+        ))?;
+        let entries = stmt
+            .query_map(
+                params![start_date.to_string(), end_date.to_string()],
+                entry_from_row,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+
+    /// Get time entries for a date range, joined with activity and project names
+    pub fn get_entry_details_for_range(
+        &self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> DbResult<Vec<EntryDetail>> {
+        let mut stmt = self.conn.prepare(&format!(
+            r#"
+            SELECT {}, p.name, at.name, p.code, at.cost_center
+            FROM time_entries te
+            JOIN activity_types at ON te.activity_type_id = at.id
+            JOIN projects p ON at.project_id = p.id
+            WHERE te.date >= ?1 AND te.date <= ?2 AND te.kind != 'break'
+            ORDER BY te.date, te.id
+            "#,
+            JOINED_TIME_ENTRY_COLUMNS
+        ))?;
+        let details = stmt
+            .query_map(
+                params![start_date.to_string(), end_date.to_string()],
+                |row| {
+                    Ok(EntryDetail {
+                        entry: entry_from_row(row)?,
+                        project_name: row.get(9)?,
+                        activity_name: row.get(10)?,
+                        project_code: row.get(11)?,
+                        cost_center: row.get(12)?,
+                    })
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(details)
+    }
+
+    /// Total minutes per day in a date range; days without entries are left out
+    pub fn get_daily_totals(
+        &self,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> DbResult<HashMap<NaiveDate, i32>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT date, SUM(minutes) FROM time_entries
+             WHERE date >= ?1 AND date <= ?2 AND kind != 'break'
+             GROUP BY date",
         )?;
-        Ok(total)
+        let totals = stmt
+            .query_map(
+                params![start_date.to_string(), end_date.to_string()],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, i32>(1)?)),
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(totals
+            .into_iter()
+            .filter_map(|(date, minutes)| {
+                NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                    .ok()
+                    .map(|date| (date, minutes))
+            })
+            .collect())
     }
-}
 
-// ==================== Utility Functions ====================
+    // ==================== Statistics ====================
 
-/// Parse time string in format "HH:MM" to minutes
-pub fn parse_time_to_minutes(time_str: &str) -> Result<i32, DatabaseError> {
-    let parts: Vec<&str> = time_str.trim().split(':').collect();
-    if parts.len() != 2 {
-        return Err(DatabaseError::InvalidTimeFormat);
+    /// Sum of all entries in a date range (inclusive)
+    pub fn total_minutes_between(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        include_inactive: bool,
+    ) -> DbResult<i32> {
+        Ok(self.conn.query_row(
+            &format!(
+                "SELECT COALESCE(SUM(minutes), 0) FROM time_entries
+                 WHERE date >= ?1 AND date <= ?2 AND kind != 'break' AND {}",
+                ACTIVE_ENTRIES
+            ),
+            params![start.to_string(), end.to_string(), include_inactive],
+            |row| row.get(0),
+        )?)
     }
 
-    let hours: i32 = parts[0]
-        .parse()
-        .map_err(|_| DatabaseError::InvalidTimeFormat)?;
-    let minutes: i32 = parts[1]
-        .parse()
-        .map_err(|_| DatabaseError::InvalidTimeFormat)?;
+    /// Minutes per project in a date range, most time first
+    pub fn minutes_per_project_between(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        include_inactive: bool,
+    ) -> DbResult<Vec<ProjectMinutes>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT p.id, p.name, SUM(te.minutes) AS total
+             FROM time_entries te
+             JOIN activity_types at ON te.activity_type_id = at.id
+             JOIN projects p ON at.project_id = p.id
+             WHERE te.date >= ?1 AND te.date <= ?2 AND te.kind != 'break' AND {}
+             GROUP BY p.id
+             ORDER BY total DESC, p.name",
+            ACTIVE_ENTRIES
+        ))?;
+        let projects = stmt
+            .query_map(
+                params![start.to_string(), end.to_string(), include_inactive],
+                |row| {
+                    Ok(ProjectMinutes {
+                        project_id: row.get(0)?,
+                        project_name: row.get(1)?,
+                        minutes: row.get(2)?,
+                    })
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(projects)
+    }
 
-    if hours < 0 || minutes < 0 || minutes >= 60 {
-        return Err(DatabaseError::InvalidTimeFormat);
+    /// Minutes per activity in a date range, grouped by project name and
+    /// most time first within a project
+    pub fn minutes_per_activity_between(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> DbResult<Vec<ActivityMinutes>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT at.id, at.name, p.id, p.name, SUM(te.minutes) AS total, COUNT(DISTINCT te.date)
+             FROM time_entries te
+             JOIN activity_types at ON te.activity_type_id = at.id
+             JOIN projects p ON at.project_id = p.id
+             WHERE te.date >= ?1 AND te.date <= ?2 AND te.kind != 'break'
+             GROUP BY at.id
+             ORDER BY p.name, total DESC, at.name",
+        )?;
+        let activities = stmt
+            .query_map(params![start.to_string(), end.to_string()], |row| {
+                Ok(ActivityMinutes {
+                    activity_type_id: row.get(0)?,
+                    activity_name: row.get(1)?,
+                    project_id: row.get(2)?,
+                    project_name: row.get(3)?,
+                    minutes: row.get(4)?,
+                    days: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(activities)
     }
 
-    Ok(hours * 60 + minutes)
-}
+    /// Time and earnings per activity in a date range, by project and activity name
+    pub fn earnings_between(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        include_inactive: bool,
+    ) -> DbResult<Vec<Earning>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT p.name, at.name, SUM(te.minutes),
+                    COALESCE(at.hourly_rate_cents, p.hourly_rate_cents)
+             FROM time_entries te
+             JOIN activity_types at ON te.activity_type_id = at.id
+             JOIN projects p ON at.project_id = p.id
+             WHERE te.date >= ?1 AND te.date <= ?2 AND te.kind != 'break' AND {}
+             GROUP BY at.id
+             ORDER BY p.name, at.name",
+            ACTIVE_ENTRIES
+        ))?;
+        let earnings = stmt
+            .query_map(
+                params![start.to_string(), end.to_string(), include_inactive],
+                |row| {
+                    Ok(Earning {
+                        project_name: row.get(0)?,
+                        activity_name: row.get(1)?,
+                        minutes: row.get(2)?,
+                        rate_cents: row.get(3)?,
+                    })
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(earnings)
+    }
 
-/// Format minutes to "HH:MM" string
-pub fn format_minutes_to_time(total_minutes: i32) -> String {
-    let hours = total_minutes / 60;
-    let minutes = total_minutes % 60;
-    format!("{:02}:{:02}", hours, minutes)
-}
+    /// Minutes per day of the week in a date range, Monday first
+    pub fn minutes_per_weekday(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        include_inactive: bool,
+    ) -> DbResult<Vec<WeekdayMinutes>> {
+        // strftime('%w') counts from Sunday = 0; shift so Monday comes first
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT (CAST(strftime('%w', date) AS INTEGER) + 6) % 7 AS weekday,
+                    SUM(minutes), COUNT(DISTINCT date)
+             FROM time_entries
+             WHERE date >= ?1 AND date <= ?2 AND kind != 'break' AND {}
+             GROUP BY weekday
+             ORDER BY weekday",
+            ACTIVE_ENTRIES
+        ))?;
+        let weekdays = stmt
+            .query_map(
+                params![start.to_string(), end.to_string(), include_inactive],
+                |row| {
+                    let days_from_monday: u8 = row.get(0)?;
+                    Ok(WeekdayMinutes {
+                        weekday: chrono::Weekday::try_from(days_from_monday)
+                            .unwrap_or(chrono::Weekday::Mon),
+                        minutes: row.get(1)?,
+                        days: row.get(2)?,
+                    })
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(weekdays)
+    }
 
-/// Format minutes to decimal hours with Swedish comma separator (e.g., "1,5" for 90 minutes)
-pub fn format_minutes_to_decimal(total_minutes: i32) -> String {
-    let hours = total_minutes as f64 / 60.0;
-    // Format with 2 decimal places and replace . with ,
-    format!("{:.2}", hours).replace('.', ",")
-}
+    /// Number of days with at least one entry in a date range
+    pub fn distinct_logged_days(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        include_inactive: bool,
+    ) -> DbResult<i32> {
+        Ok(self.conn.query_row(
+            &format!(
+                "SELECT COUNT(DISTINCT date) FROM time_entries
+                 WHERE date >= ?1 AND date <= ?2 AND kind != 'break' AND {}",
+                ACTIVE_ENTRIES
+            ),
+            params![start.to_string(), end.to_string(), include_inactive],
+            |row| row.get(0),
+        )?)
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// All aggregates of a date range at once, optionally leaving out time on
+    /// inactive projects and activities
+    pub fn statistics_between(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        include_inactive: bool,
+    ) -> DbResult<RangeStatistics> {
+        Ok(RangeStatistics {
+            total_minutes: self.total_minutes_between(start, end, include_inactive)?,
+            logged_days: self.distinct_logged_days(start, end, include_inactive)?,
+            per_project: self.minutes_per_project_between(start, end, include_inactive)?,
+            per_weekday: self.minutes_per_weekday(start, end, include_inactive)?,
+        })
+    }
 
-    #[test]
-    fn test_parse_time_to_minutes() {
-        assert_eq!(parse_time_to_minutes("00:30").unwrap(), 30);
-        assert_eq!(parse_time_to_minutes("01:00").unwrap(), 60);
-        assert_eq!(parse_time_to_minutes("02:30").unwrap(), 150);
-        assert!(parse_time_to_minutes("invalid").is_err());
+    /// Get the latest dates that have entries (newest first)
+    pub fn get_recent_entry_dates(&self, limit: usize) -> DbResult<Vec<NaiveDate>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT DISTINCT date FROM time_entries ORDER BY date DESC LIMIT ?1")?;
+        let dates = stmt
+            .query_map(params![limit as i64], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(dates
+            .iter()
+            .filter_map(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+            .collect())
     }
 
-    #[test]
-    fn test_format_minutes_to_time() {
-        assert_eq!(format_minutes_to_time(30), "00:30");
-        assert_eq!(format_minutes_to_time(60), "01:00");
-        assert_eq!(format_minutes_to_time(150), "02:30");
+    /// Get the most recently used distinct comments for an activity (newest first)
+    pub fn get_recent_comments(
+        &self,
+        activity_type_id: i64,
+        limit: usize,
+    ) -> DbResult<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT comment FROM time_entries
+             WHERE activity_type_id = ?1 AND comment != ''
+             GROUP BY comment ORDER BY MAX(id) DESC LIMIT ?2",
+        )?;
+        let comments = stmt
+            .query_map(params![activity_type_id, limit as i64], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(comments)
     }
 
-    #[test]
-    fn test_format_minutes_to_decimal() {
-        assert_eq!(format_minutes_to_decimal(30), "0,50");
-        assert_eq!(format_minutes_to_decimal(60), "1,00");
-        assert_eq!(format_minutes_to_decimal(90), "1,50");
-        assert_eq!(format_minutes_to_decimal(150), "2,50");
-        assert_eq!(format_minutes_to_decimal(480), "8,00");
-        assert_eq!(format_minutes_to_decimal(15), "0,25");
-        assert_eq!(format_minutes_to_decimal(45), "0,75");
+    /// Entries whose comment contains every word of `query` (as word
+    /// prefixes), newest first, looked up in the full-text index
+    pub fn search_comments(&self, query: &str, limit: usize) -> DbResult<Vec<EntryDetail>> {
+        // Quote each word so FTS5 operators and punctuation are matched literally
+        let terms: Vec<String> = query
+            .split_whitespace()
+            .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+            .collect();
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut stmt = self.conn.prepare(&format!(
+            r#"
+            SELECT {}, p.name, at.name, p.code, at.cost_center
+            FROM time_entries_fts
+            JOIN time_entries te ON te.id = time_entries_fts.rowid
+            JOIN activity_types at ON te.activity_type_id = at.id
+            JOIN projects p ON at.project_id = p.id
+            WHERE time_entries_fts MATCH ?1
+            ORDER BY te.date DESC, te.id DESC
+            LIMIT ?2
+            "#,
+            JOINED_TIME_ENTRY_COLUMNS
+        ))?;
+        let details = stmt
+            .query_map(params![terms.join(" "), limit as i64], |row| {
+                Ok(EntryDetail {
+                    entry: entry_from_row(row)?,
+                    project_name: row.get(9)?,
+                    activity_name: row.get(10)?,
+                    project_code: row.get(11)?,
+                    cost_center: row.get(12)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(details)
     }
 
-    #[test]
-    fn test_database_operations() {
-        let db = Database::new_in_memory().unwrap();
+    /// Update a time entry
+    pub fn update_time_entry(
+        &self,
+        id: i64,
+        minutes: i32,
+        comment: &str,
+        reason: &str,
+    ) -> DbResult<()> {
+        self.ensure_entry_unlocked(id)?;
+        self.conn.execute(
+            "UPDATE time_entries SET minutes = ?1, comment = ?2, reason = ?3 WHERE id = ?4",
+            params![minutes, comment, reason, id],
+        )?;
+        Ok(())
+    }
 
-        // Create project
-        let project_id = db.create_project("Test Project", "Description").unwrap();
-        assert!(project_id > 0);
+    /// Mark a time entry as billable or not
+    pub fn set_entry_billable(&self, id: i64, is_billable: bool) -> DbResult<()> {
+        self.ensure_entry_unlocked(id)?;
+        self.conn.execute(
+            "UPDATE time_entries SET is_billable = ?1 WHERE id = ?2",
+            params![is_billable as i32, id],
+        )?;
+        Ok(())
+    }
 
-        // Create activity type
-        let activity_id = db
-            .create_activity_type(project_id, "Test Activity")
-            .unwrap();
-        assert!(activity_id > 0);
+    pub fn get_time_entry(&self, id: i64) -> DbResult<TimeEntry> {
+        self.conn
+            .query_row(
+                &format!(
+                    "SELECT {} FROM time_entries WHERE id = ?1",
+                    TIME_ENTRY_COLUMNS
+                ),
+                params![id],
+                entry_from_row,
+            )
+            .optional()?
+            .ok_or(DatabaseError::EntryNotFound(id))
+    }
 
-        // Create time entry
-        let today = chrono::Local::now().date_naive();
-        let entry_id = db
-            .create_time_entry(activity_id, today, 30, "Test comment")
-            .unwrap();
-        assert!(entry_id > 0);
+    /// Copy the entries of `from` to `to`, travel with its trip, optionally without
+    /// their comments. Adjustments are left out. Returns the new entries' ids.
+    pub fn copy_day(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+        clear_comments: bool,
+    ) -> DbResult<Vec<i64>> {
+        let entries = self.get_time_entries_for_date(from)?;
+        let trips = self.get_trips_between(from, from)?;
+        self.transaction(|| {
+            let mut ids = Vec::new();
+            for entry in entries.iter().filter(|e| e.kind != EntryKind::Adjustment) {
+                let comment = if clear_comments {
+                    ""
+                } else {
+                    entry.comment.as_str()
+                };
+                let id = self.insert_time_entry(
+                    entry.activity_type_id,
+                    to,
+                    entry.minutes,
+                    comment,
+                    entry.kind,
+                    "",
+                )?;
+                if entry.kind == EntryKind::Travel {
+                    self.insert_trip(id, &trips.get(&entry.id).cloned().unwrap_or_default())?;
+                }
+                self.set_entry_billable(id, entry.is_billable)?;
+                ids.push(id);
+            }
+            Ok(ids)
+        })
+    }
 
-        // Get summary
+    /// Replace the entries of `date` with `entries`, keeping their ids, all or none.
+    /// Returns the entries that were there before.
+    pub fn replace_day_entries(
+        &self,
+        date: NaiveDate,
+        entries: &[TimeEntry],
+    ) -> DbResult<Vec<TimeEntry>> {
+        self.transaction(|| {
+            let ids: Vec<i64> = self
+                .get_time_entries_for_date(date)?
+                .iter()
+                .map(|e| e.id)
+                .collect();
+            let before = self.take_time_entries(&ids)?;
+            self.restore_time_entries(entries)?;
+            Ok(before)
+        })
+    }
+
+    /// Delete a time entry, returning it for `restore_time_entry`
+    pub fn take_time_entry(&self, id: i64) -> DbResult<TimeEntry> {
+        let entry = self.get_time_entry(id)?;
+        self.delete_time_entry(id)?;
+        Ok(entry)
+    }
+
+    /// Delete several entries at once, returning them for `restore_time_entries`.
+    /// Nothing is deleted if one of them can't be.
+    pub fn take_time_entries(&self, ids: &[i64]) -> DbResult<Vec<TimeEntry>> {
+        self.transaction(|| {
+            let entries = ids
+                .iter()
+                .map(|id| self.take_time_entry(*id))
+                .collect::<DbResult<Vec<_>>>()?;
+            Ok(entries)
+        })
+    }
+
+    /// Put several deleted entries back, all or none
+    pub fn restore_time_entries(&self, entries: &[TimeEntry]) -> DbResult<()> {
+        self.transaction(|| {
+            for entry in entries {
+                self.restore_time_entry(entry)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Log `(activity, minutes)` pairs on `date` with a shared comment, all or none.
+    /// Returns the new ids.
+    pub fn create_time_entries(
+        &self,
+        date: NaiveDate,
+        entries: &[(i64, i32)],
+        comment: &str,
+    ) -> DbResult<Vec<i64>> {
+        self.transaction(|| {
+            let ids = entries
+                .iter()
+                .map(|(activity_type_id, minutes)| {
+                    self.create_time_entry(*activity_type_id, date, *minutes, comment)
+                })
+                .collect::<DbResult<Vec<_>>>()?;
+            Ok(ids)
+        })
+    }
+
+    /// Put a deleted time entry back under its old id
+    pub fn restore_time_entry(&self, entry: &TimeEntry) -> DbResult<()> {
+        self.ensure_date_unlocked(entry.date)?;
+        self.conn.execute(
+            &format!(
+                "INSERT INTO time_entries ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                TIME_ENTRY_COLUMNS
+            ),
+            params![
+                entry.id,
+                entry.activity_type_id,
+                entry.date.to_string(),
+                entry.minutes,
+                entry.comment,
+                entry.kind.as_str(),
+                entry.reason,
+                entry.is_billable as i32,
+                (!entry.uuid.is_empty()).then_some(&entry.uuid)
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Delete a time entry
+    pub fn delete_time_entry(&self, id: i64) -> DbResult<()> {
+        self.ensure_entry_unlocked(id)?;
+        self.conn
+            .execute("DELETE FROM time_entries WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    // ==================== Month Locking ====================
+
+    /// Lock the month containing `date`; its entries can no longer be added, edited or deleted
+    pub fn lock_month(&self, date: NaiveDate) -> DbResult<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO locked_months (month) VALUES (?1)",
+            params![date.format("%Y-%m").to_string()],
+        )?;
+        Ok(())
+    }
+
+    pub fn unlock_month(&self, date: NaiveDate) -> DbResult<()> {
+        self.conn.execute(
+            "DELETE FROM locked_months WHERE month = ?1",
+            params![date.format("%Y-%m").to_string()],
+        )?;
+        Ok(())
+    }
+
+    pub fn is_month_locked(&self, date: NaiveDate) -> DbResult<bool> {
+        let count: i32 = self.conn.query_row(
+            "SELECT COUNT(*) FROM locked_months WHERE month = ?1",
+            params![date.format("%Y-%m").to_string()],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
+    /// Lock entries older than `days` days; 0 turns the lock off
+    pub fn set_lock_after_days(&mut self, days: u32) {
+        self.lock_after_days = (days > 0).then_some(days as i64);
+    }
+
+    /// Whether `date` is past the edit window and was not unlocked this session
+    pub fn is_too_old(&self, date: NaiveDate) -> bool {
+        let today = chrono::Local::now().date_naive();
+        self.lock_after_days
+            .is_some_and(|days| (today - date).num_days() > days)
+            && !self.unlocked_days.contains(&date)
+    }
+
+    /// Allow editing an old day until the app is closed
+    pub fn unlock_day(&mut self, date: NaiveDate) {
+        self.unlocked_days.insert(date);
+    }
+
+    pub fn relock_day(&mut self, date: NaiveDate) {
+        self.unlocked_days.remove(&date);
+    }
+
+    /// Whether entries on `date` are read-only, by month lock or age
+    pub fn is_date_locked(&self, date: NaiveDate) -> DbResult<bool> {
+        Ok(self.is_too_old(date) || self.is_month_locked(date)?)
+    }
+
+    fn ensure_date_unlocked(&self, date: NaiveDate) -> DbResult<()> {
+        if self.is_month_locked(date)? {
+            return Err(DatabaseError::MonthLocked(date.format("%Y-%m").to_string()));
+        }
+        if let Some(days) = self.lock_after_days.filter(|_| self.is_too_old(date)) {
+            return Err(DatabaseError::DayTooOld(date.to_string(), days));
+        }
+        Ok(())
+    }
+
+    fn ensure_entry_unlocked(&self, id: i64) -> DbResult<()> {
+        let date: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT date FROM time_entries WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        match date.and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok()) {
+            Some(date) => self.ensure_date_unlocked(date),
+            None => Ok(()),
+        }
+    }
+
+    // ==================== Day Close-out Operations ====================
+
+    /// Mark a day as done
+    pub fn close_day(&self, date: NaiveDate) -> DbResult<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO closed_days (date) VALUES (?1)",
+            params![date.to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// Reopen a day previously marked as done
+    pub fn reopen_day(&self, date: NaiveDate) -> DbResult<()> {
+        self.conn.execute(
+            "DELETE FROM closed_days WHERE date = ?1",
+            params![date.to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// Check whether a day has been marked as done
+    pub fn is_day_closed(&self, date: NaiveDate) -> DbResult<bool> {
+        let count: i32 = self.conn.query_row(
+            "SELECT COUNT(*) FROM closed_days WHERE date = ?1",
+            params![date.to_string()],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
+    // ==================== Day Types ====================
+
+    /// Register a day type on a date, or clear it with `None`
+    pub fn set_day_type(&self, date: NaiveDate, day_type: Option<DayType>) -> DbResult<()> {
+        match day_type {
+            Some(day_type) => self.conn.execute(
+                "INSERT OR REPLACE INTO day_types (date, day_type) VALUES (?1, ?2)",
+                params![date.to_string(), day_type.as_str()],
+            )?,
+            None => self.conn.execute(
+                "DELETE FROM day_types WHERE date = ?1",
+                params![date.to_string()],
+            )?,
+        };
+        Ok(())
+    }
+
+    /// Register days off from older settings files as public holidays, leaving
+    /// dates that already have a type alone. Returns the number added.
+    pub fn import_days_off(&self, dates: &[NaiveDate]) -> DbResult<usize> {
+        self.transaction(|| {
+            let mut added = 0;
+            for date in dates {
+                added += self.conn.execute(
+                    "INSERT OR IGNORE INTO day_types (date, day_type) VALUES (?1, ?2)",
+                    params![date.to_string(), DayType::PublicHoliday.as_str()],
+                )?;
+            }
+            Ok(added)
+        })
+    }
+
+    /// All registered day types; unknown types are skipped
+    pub fn get_day_types(&self) -> DbResult<BTreeMap<NaiveDate, DayType>> {
+        let mut stmt = self.conn.prepare("SELECT date, day_type FROM day_types")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut day_types = BTreeMap::new();
+        for row in rows {
+            let (date, day_type) = row?;
+            if let (Ok(date), Some(day_type)) = (
+                NaiveDate::parse_from_str(&date, "%Y-%m-%d"),
+                DayType::parse(&day_type),
+            ) {
+                day_types.insert(date, day_type);
+            }
+        }
+        Ok(day_types)
+    }
+
+    // ==================== Export History ====================
+
+    /// Record a completed export
+    pub fn log_export(
+        &self,
+        kind: &str,
+        range: Option<(NaiveDate, NaiveDate)>,
+        path: &Path,
+        row_count: usize,
+    ) -> DbResult<()> {
+        self.conn.execute(
+            "INSERT INTO export_log (kind, start_date, end_date, path, row_count)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                kind,
+                range.map(|(start, _)| start.to_string()),
+                range.map(|(_, end)| end.to_string()),
+                path.display().to_string(),
+                row_count as i64
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Get the most recent exports, newest first
+    pub fn get_export_log(&self, limit: usize) -> DbResult<Vec<ExportRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT exported_at, kind, start_date, end_date, path, row_count
+             FROM export_log ORDER BY id DESC LIMIT ?1",
+        )?;
+        let records = stmt
+            .query_map(params![limit as i64], |row| {
+                Ok(ExportRecord {
+                    exported_at: row.get(0)?,
+                    kind: row.get(1)?,
+                    start_date: parse_optional_date(row.get(2)?),
+                    end_date: parse_optional_date(row.get(3)?),
+                    path: row.get(4)?,
+                    row_count: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(records)
+    }
+
+    // ==================== Entry Templates ====================
+
+    /// Add a template entry for a weekday (0 = Monday)
+    pub fn create_entry_template(
+        &self,
+        weekday: u32,
+        activity_type_id: i64,
+        minutes: i32,
+        comment: &str,
+    ) -> DbResult<i64> {
+        self.conn.execute(
+            "INSERT INTO entry_templates (weekday, activity_type_id, minutes, comment)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![weekday, activity_type_id, minutes, comment],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn delete_entry_template(&self, id: i64) -> DbResult<()> {
+        self.conn
+            .execute("DELETE FROM entry_templates WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    /// All templates, by weekday
+    pub fn get_entry_templates(&self) -> DbResult<Vec<EntryTemplate>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, weekday, activity_type_id, minutes, comment
+             FROM entry_templates ORDER BY weekday, id",
+        )?;
+        let templates = stmt
+            .query_map([], |row| {
+                Ok(EntryTemplate {
+                    id: row.get(0)?,
+                    weekday: row.get(1)?,
+                    activity_type_id: row.get(2)?,
+                    minutes: row.get(3)?,
+                    comment: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(templates)
+    }
+
+    // ==================== Expenses ====================
+
+    /// Record an expense; its `id` is ignored
+    pub fn create_expense(&self, expense: &Expense) -> DbResult<i64> {
+        self.ensure_date_unlocked(expense.date)?;
+        self.conn.execute(
+            "INSERT INTO expenses (date, amount_cents, currency, description, receipt_path)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                expense.date.to_string(),
+                expense.amount_cents,
+                expense.currency,
+                expense.description,
+                expense.receipt_path
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Expenses in a date range, oldest first
+    pub fn get_expenses_between(&self, start: NaiveDate, end: NaiveDate) -> DbResult<Vec<Expense>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, date, amount_cents, currency, description, receipt_path
+             FROM expenses WHERE date >= ?1 AND date <= ?2 ORDER BY date, id",
+        )?;
+        let expenses = stmt
+            .query_map(
+                params![start.to_string(), end.to_string()],
+                expense_from_row,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(expenses)
+    }
+
+    /// Delete an expense and return it, so the deletion can be undone
+    pub fn take_expense(&self, id: i64) -> DbResult<Expense> {
+        let expense = self.conn.query_row(
+            "SELECT id, date, amount_cents, currency, description, receipt_path
+             FROM expenses WHERE id = ?1",
+            params![id],
+            expense_from_row,
+        )?;
+        self.ensure_date_unlocked(expense.date)?;
+        self.conn
+            .execute("DELETE FROM expenses WHERE id = ?1", params![id])?;
+        Ok(expense)
+    }
+
+    /// Put a deleted expense back under its old id
+    pub fn restore_expense(&self, expense: &Expense) -> DbResult<()> {
+        self.ensure_date_unlocked(expense.date)?;
+        self.conn.execute(
+            "INSERT INTO expenses (id, date, amount_cents, currency, description, receipt_path)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                expense.id,
+                expense.date.to_string(),
+                expense.amount_cents,
+                expense.currency,
+                expense.description,
+                expense.receipt_path
+            ],
+        )?;
+        Ok(())
+    }
+
+    // ==================== Summary Operations ====================
+
+    /// Get activity summaries for a specific date (total time per activity)
+    pub fn get_daily_summary(&self, date: NaiveDate) -> DbResult<Vec<ActivitySummary>> {
+        // First get all entries for the date with activity and project info
+        let mut stmt = self.conn.prepare(&format!(
+            r#"
+            SELECT {}, at.name, p.name
+            FROM time_entries te
+            JOIN activity_types at ON te.activity_type_id = at.id
+            JOIN projects p ON at.project_id = p.id
+            WHERE te.date = ?1 AND te.kind != 'break'
+            ORDER BY p.name, at.name, te.id
+            "#,
+            JOINED_TIME_ENTRY_COLUMNS
+        ))?;
+
+        let rows: Vec<(TimeEntry, String, String)> = stmt
+            .query_map(params![date.to_string()], |row| {
+                Ok((entry_from_row(row)?, row.get(9)?, row.get(10)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Group by activity type
+        let mut summaries: Vec<ActivitySummary> = Vec::new();
+        for (entry, activity_name, project_name) in rows {
+            if let Some(summary) = summaries
+                .iter_mut()
+                .find(|s| s.activity_type_id == entry.activity_type_id)
+            {
+                summary.total_minutes += entry.minutes;
+                summary.entries.push(entry);
+            } else {
+                summaries.push(ActivitySummary {
+                    activity_type_id: entry.activity_type_id,
+                    activity_name,
+                    project_name,
+                    total_minutes: entry.minutes,
+                    entries: vec![entry],
+                });
+            }
+        }
+
+        Ok(summaries)
+    }
+
+    /// Get total time for a specific date
+    #[allow(dead_code)]
+    pub fn get_total_time_for_date(&self, date: NaiveDate) -> DbResult<i32> {
+        let total: i32 = self.conn.query_row(
+            "SELECT COALESCE(SUM(minutes), 0) FROM time_entries
+             WHERE date = ?1 AND kind != 'break'",
+            params![date.to_string()],
+            |row| row.get(0),
+        )?;
+        Ok(total)
+    }
+}
+
+// ==================== Utility Functions ====================
+
+/// Refuse snapshots that no longer match their checksum. Snapshots taken
+/// before checksums were stored are accepted.
+fn ensure_intact(snapshot: &Path) -> DbResult<()> {
+    match crate::backup::verify(snapshot) {
+        crate::backup::Integrity::Corrupted => Err(DatabaseError::CorruptedBackup(
+            snapshot.display().to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Parse time string in format "HH:MM" or decimal hours like "1.5h" / "1,5h"
+/// (with a leading "-" for a negative duration) to minutes
+pub fn parse_time_to_minutes(time_str: &str) -> Result<i32, DatabaseError> {
+    let (sign, time_str) = match time_str.trim().strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, time_str.trim()),
+    };
+    if let Some(hours) = time_str.strip_suffix(['h', 'H']) {
+        let hours: f64 = hours
+            .trim()
+            .replace(',', ".")
+            .parse()
+            .map_err(|_| DatabaseError::InvalidTimeFormat)?;
+        if !hours.is_finite() || hours < 0.0 {
+            return Err(DatabaseError::InvalidTimeFormat);
+        }
+        return Ok(sign * (hours * 60.0).round() as i32);
+    }
+    let parts: Vec<&str> = time_str.split(':').collect();
+    if parts.len() != 2 {
+        return Err(DatabaseError::InvalidTimeFormat);
+    }
+
+    let hours: i32 = parts[0]
+        .parse()
+        .map_err(|_| DatabaseError::InvalidTimeFormat)?;
+    let minutes: i32 = parts[1]
+        .parse()
+        .map_err(|_| DatabaseError::InvalidTimeFormat)?;
+
+    if hours < 0 || !(0..60).contains(&minutes) {
+        return Err(DatabaseError::InvalidTimeFormat);
+    }
+
+    Ok(sign * (hours * 60 + minutes))
+}
+
+/// Evaluate a duration expression such as "8:00-6:15" or "0:45*3": durations
+/// added and subtracted, each optionally multiplied or divided by plain
+/// numbers. A single duration is parsed as by `parse_time_to_minutes`.
+pub fn evaluate_time_expression(expr: &str) -> Result<i32, DatabaseError> {
+    let expr = expr.trim();
+    let (mut sign, rest) = match expr.strip_prefix('-') {
+        Some(rest) => (-1.0, rest),
+        None => (1.0, expr),
+    };
+    let mut total = 0.0;
+    let mut term = String::new();
+    for c in rest.chars().chain(std::iter::once('+')) {
+        if c == '+' || c == '-' {
+            total += sign * evaluate_product(&term)?;
+            sign = if c == '-' { -1.0 } else { 1.0 };
+            term.clear();
+        } else {
+            term.push(c);
+        }
+    }
+    Ok(total.round() as i32)
+}
+
+/// One term of `evaluate_time_expression`: a duration times or divided by numbers
+fn evaluate_product(term: &str) -> Result<f64, DatabaseError> {
+    let mut minutes = None;
+    let mut factor = 1.0;
+    let mut dividing = false;
+    let mut operand = String::new();
+    for c in term.chars().chain(std::iter::once('*')) {
+        if c != '*' && c != '/' {
+            operand.push(c);
+            continue;
+        }
+        let text = operand.trim();
+        if text.contains(':') || text.ends_with(['h', 'H']) {
+            // Only one duration per term, and never as a divisor
+            if minutes.is_some() || dividing {
+                return Err(DatabaseError::InvalidTimeFormat);
+            }
+            minutes = Some(parse_time_to_minutes(text)? as f64);
+        } else {
+            let number: f64 = text
+                .replace(',', ".")
+                .parse()
+                .map_err(|_| DatabaseError::InvalidTimeFormat)?;
+            if !number.is_finite() || number < 0.0 || (dividing && number == 0.0) {
+                return Err(DatabaseError::InvalidTimeFormat);
+            }
+            factor = if dividing {
+                factor / number
+            } else {
+                factor * number
+            };
+        }
+        dividing = c == '/';
+        operand.clear();
+    }
+    minutes
+        .map(|minutes| minutes * factor)
+        .ok_or(DatabaseError::InvalidTimeFormat)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time_to_minutes() {
+        assert_eq!(parse_time_to_minutes("00:30").unwrap(), 30);
+        assert_eq!(parse_time_to_minutes("01:00").unwrap(), 60);
+        assert_eq!(parse_time_to_minutes("02:30").unwrap(), 150);
+        assert!(parse_time_to_minutes("invalid").is_err());
+        assert_eq!(parse_time_to_minutes("-01:15").unwrap(), -75);
+        assert!(parse_time_to_minutes("--01:15").is_err());
+        assert_eq!(parse_time_to_minutes("1.5h").unwrap(), 90);
+        assert_eq!(parse_time_to_minutes("0,25h").unwrap(), 15);
+        assert_eq!(parse_time_to_minutes("-2H").unwrap(), -120);
+        assert!(parse_time_to_minutes("h").is_err());
+    }
+
+    #[test]
+    fn test_evaluate_time_expression() {
+        assert_eq!(evaluate_time_expression("01:30").unwrap(), 90);
+        assert_eq!(evaluate_time_expression("-0:15").unwrap(), -15);
+        assert_eq!(evaluate_time_expression("8:00-6:15").unwrap(), 105);
+        assert_eq!(evaluate_time_expression("0:45*3").unwrap(), 135);
+        assert_eq!(evaluate_time_expression("3 * 0:45").unwrap(), 135);
+        assert_eq!(evaluate_time_expression("1h + 0:30 - 2:00").unwrap(), -30);
+        assert_eq!(evaluate_time_expression("7:30/2").unwrap(), 225);
+        assert_eq!(evaluate_time_expression("1:00*1,5").unwrap(), 90);
+        assert_eq!(evaluate_time_expression("8:00-6:15+0:20*2").unwrap(), 145);
+        assert!(evaluate_time_expression("1:00*2:00").is_err());
+        assert!(evaluate_time_expression("2/1:00").is_err());
+        assert!(evaluate_time_expression("1:00/0").is_err());
+        assert!(evaluate_time_expression("1:00+").is_err());
+        assert!(evaluate_time_expression("3").is_err());
+    }
+
+    #[test]
+    fn test_database_operations() {
+        let db = Database::new_in_memory().unwrap();
+
+        // Create project
+        let project_id = db
+            .create_project("Test Project", "Description", "")
+            .unwrap();
+        assert!(project_id > 0);
+
+        // Create activity type
+        let activity_id = db
+            .create_activity_type(project_id, "Test Activity", "🧪")
+            .unwrap();
+        assert!(activity_id > 0);
+
+        // Create time entry
+        let today = chrono::Local::now().date_naive();
+        let entry_id = db
+            .create_time_entry(activity_id, today, 30, "Test comment")
+            .unwrap();
+        assert!(entry_id > 0);
+
+        // Get summary
         let summaries = db.get_daily_summary(today).unwrap();
         assert_eq!(summaries.len(), 1);
         assert_eq!(summaries[0].total_minutes, 30);
     }
+
+    #[test]
+    fn test_migrations_set_user_version() {
+        let db = Database::new_in_memory().unwrap();
+        let version: usize = db
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.len());
+
+        // Re-running is a no-op
+        db.run_migrations().unwrap();
+    }
+
+    #[test]
+    fn test_project_notes_and_links() {
+        let db = Database::new_in_memory().unwrap();
+        let project_id = db.create_project("Notes", "Description", "").unwrap();
+
+        db.update_project_notes(project_id, "Ask Anna about access")
+            .unwrap();
+        assert_eq!(
+            db.get_project(project_id).unwrap().notes,
+            "Ask Anna about access"
+        );
+
+        let links = vec![
+            ("Wiki".to_string(), "https://wiki.example.com".to_string()),
+            ("Repo".to_string(), "https://git.example.com".to_string()),
+        ];
+        db.set_project_links(project_id, &links).unwrap();
+        db.set_project_links(project_id, &links[1..]).unwrap();
+        let stored = db.get_all_project_links().unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].label, "Repo");
+
+        db.delete_project(project_id).unwrap();
+        assert!(db.get_all_project_links().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_export_log() {
+        let db = Database::new_in_memory().unwrap();
+        let start = NaiveDate::from_ymd_opt(2024, 10, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 10, 31).unwrap();
+        db.log_export("catalog", None, Path::new("catalog.csv"), 12)
+            .unwrap();
+        db.log_export("tickets", Some((start, end)), Path::new("tickets.csv"), 3)
+            .unwrap();
+
+        let log = db.get_export_log(10).unwrap();
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].kind, "tickets");
+        assert_eq!(log[0].start_date, Some(start));
+        assert_eq!(log[0].end_date, Some(end));
+        assert_eq!(log[0].row_count, 3);
+        assert_eq!(log[1].start_date, None);
+    }
+
+    #[test]
+    fn test_adjustment_entries() {
+        let db = Database::new_in_memory().unwrap();
+        let project_id = db.create_project("Adjust", "", "").unwrap();
+        let activity_id = db.create_activity_type(project_id, "A", "").unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 10, 3).unwrap();
+        db.create_time_entry(activity_id, date, 60, "Work").unwrap();
+        db.create_adjustment(activity_id, date, -15, "Correction", "Overbooked")
+            .unwrap();
+
+        let entries = db.get_time_entries_for_date(date).unwrap();
+        assert_eq!(entries[0].kind, EntryKind::Work);
+        assert_eq!(entries[1].kind, EntryKind::Adjustment);
+        assert_eq!(entries[1].reason, "Overbooked");
+        assert_eq!(db.get_daily_summary(date).unwrap()[0].total_minutes, 45);
+    }
+
+    #[test]
+    fn test_search_comments() {
+        let db = Database::new_in_memory().unwrap();
+        let project_id = db.create_project("Search", "", "").unwrap();
+        let activity_id = db.create_activity_type(project_id, "A", "").unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 10, 3).unwrap();
+        let deploy = db
+            .create_time_entry(activity_id, date, 30, "Deploy release 2.1")
+            .unwrap();
+        db.create_time_entry(activity_id, date.succ_opt().unwrap(), 60, "Review PR-12")
+            .unwrap();
+        db.create_time_entry(activity_id, date, 15, "Deployment notes")
+            .unwrap();
+
+        let found = db.search_comments("depl", 10).unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].project_name, "Search");
+        assert_eq!(db.search_comments("deploy release", 10).unwrap().len(), 1);
+        assert_eq!(db.search_comments("PR-12", 10).unwrap().len(), 1);
+        assert!(db.search_comments("  ", 10).unwrap().is_empty());
+        assert!(db.search_comments("\"OR", 10).unwrap().is_empty());
+
+        // The index follows edits and deletes
+        db.update_time_entry(deploy, 30, "Hotfix rollout", "")
+            .unwrap();
+        assert_eq!(db.search_comments("depl", 10).unwrap().len(), 1);
+        assert_eq!(db.search_comments("hotfix", 10).unwrap().len(), 1);
+        db.delete_time_entry(deploy).unwrap();
+        assert!(db.search_comments("hotfix", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_travel_entries() {
+        let db = Database::new_in_memory().unwrap();
+        let project_id = db.create_project("Travel", "", "").unwrap();
+        let activity_id = db
+            .create_activity_type(project_id, "Customer visit", "")
+            .unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 10, 3).unwrap();
+        let trip = Trip {
+            origin: "Office".to_string(),
+            destination: "Customer".to_string(),
+            kilometers: Some(42.5),
+        };
+        let id = db
+            .create_travel(activity_id, date, 90, "Kickoff", &trip)
+            .unwrap();
+        db.create_time_entry(activity_id, date, 60, "Workshop")
+            .unwrap();
+
+        let travel = db.get_travel_between(date, date).unwrap();
+        assert_eq!(travel.len(), 1);
+        assert_eq!(travel[0].detail.entry.kind, EntryKind::Travel);
+        assert_eq!(travel[0].trip, trip);
+        assert_eq!(db.get_trips_between(date, date).unwrap()[&id], trip);
+        // Travel time is work time
+        assert_eq!(db.total_minutes_between(date, date, true).unwrap(), 150);
+
+        // Undoing a delete brings the trip back with the entry
+        let entry = db.take_time_entry(id).unwrap();
+        assert!(db.get_travel_between(date, date).unwrap().is_empty());
+        db.restore_time_entry(&entry).unwrap();
+        assert_eq!(db.get_travel_between(date, date).unwrap()[0].trip, trip);
+    }
+
+    #[test]
+    fn test_deleted_entries_come_back_whole() {
+        let db = Database::new_in_memory().unwrap();
+        let project_id = db.create_project("P", "", "").unwrap();
+        let activity_id = db.create_activity_type(project_id, "A", "").unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 10, 3).unwrap();
+        let trip = Trip {
+            origin: "Office".to_string(),
+            destination: "Customer".to_string(),
+            kilometers: Some(12.5),
+        };
+        let work = db
+            .create_time_entry(activity_id, date, 90, "PROJ-7")
+            .unwrap();
+        db.set_entry_billable(work, false).unwrap();
+        let travel = db
+            .create_travel(activity_id, date, 30, "Drive", &trip)
+            .unwrap();
+        let lunch = db.create_break(activity_id, date, 30, "Lunch").unwrap();
+        let before = db.get_time_entries_for_date(date).unwrap();
+
+        // What undo runs for a deleted entry: DeleteTimeEntries, then its inverse
+        for id in [work, travel, lunch] {
+            let taken = db.take_time_entries(&[id]).unwrap();
+            db.restore_time_entries(&taken).unwrap();
+        }
+        assert_eq!(db.get_time_entries_for_date(date).unwrap(), before);
+        assert_eq!(db.get_trips_between(date, date).unwrap()[&travel], trip);
+    }
+
+    #[test]
+    fn test_batch_entry_changes() {
+        let db = Database::new_in_memory().unwrap();
+        let project_id = db.create_project("P", "", "").unwrap();
+        let activity_id = db.create_activity_type(project_id, "A", "").unwrap();
+        let october = NaiveDate::from_ymd_opt(2024, 10, 3).unwrap();
+        let november = NaiveDate::from_ymd_opt(2024, 11, 4).unwrap();
+        let ids = db
+            .create_time_entries(november, &[(activity_id, 30), (activity_id, 45)], "x")
+            .unwrap();
+        let locked = db.create_time_entry(activity_id, october, 60, "y").unwrap();
+        db.lock_month(october).unwrap();
+
+        // One locked entry keeps the whole batch
+        assert!(db.take_time_entries(&[ids[0], locked]).is_err());
+        assert_eq!(db.get_time_entries_for_date(november).unwrap().len(), 2);
+        assert!(db
+            .create_time_entries(november, &[(activity_id, 15), (activity_id + 1, 15)], "z")
+            .is_err());
+        assert_eq!(db.get_time_entries_for_date(november).unwrap().len(), 2);
+
+        // Restored entries keep their identity
+        let before = db.get_time_entries_for_date(november).unwrap();
+        let taken = db.take_time_entries(&ids).unwrap();
+        assert!(db.get_time_entries_for_date(november).unwrap().is_empty());
+        db.restore_time_entries(&taken).unwrap();
+        assert_eq!(db.get_time_entries_for_date(november).unwrap(), before);
+        assert_eq!(before[0].uuid.len(), 36);
+    }
+
+    #[test]
+    fn test_expenses() {
+        let db = Database::new_in_memory().unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 10, 3).unwrap();
+        let ticket = Expense {
+            id: 0,
+            date,
+            amount_cents: 4_250,
+            currency: "EUR".to_string(),
+            description: "Train ticket".to_string(),
+            receipt_path: "/receipts/train.pdf".to_string(),
+        };
+        let id = db.create_expense(&ticket).unwrap();
+        db.create_expense(&Expense {
+            date: date.succ_opt().unwrap(),
+            description: "Lunch".to_string(),
+            ..ticket.clone()
+        })
+        .unwrap();
+
+        let expenses = db.get_expenses_between(date, date).unwrap();
+        assert_eq!(
+            expenses,
+            [Expense {
+                id,
+                ..ticket.clone()
+            }]
+        );
+        assert_eq!(
+            db.get_expenses_between(date, date.succ_opt().unwrap())
+                .unwrap()
+                .len(),
+            2
+        );
+
+        let taken = db.take_expense(id).unwrap();
+        assert!(db.get_expenses_between(date, date).unwrap().is_empty());
+        db.restore_expense(&taken).unwrap();
+        assert_eq!(db.get_expenses_between(date, date).unwrap()[0].id, id);
+
+        // Locked months keep their expenses
+        db.lock_month(date).unwrap();
+        assert!(db.take_expense(id).is_err());
+        assert!(db.create_expense(&ticket).is_err());
+    }
+
+    #[test]
+    fn test_breaks_excluded_from_totals() {
+        let db = Database::new_in_memory().unwrap();
+        let project_id = db.create_project("Breaks", "", "").unwrap();
+        let activity_id = db.create_activity_type(project_id, "A", "").unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 10, 3).unwrap();
+        db.create_time_entry(activity_id, date, 240, "Work")
+            .unwrap();
+        db.create_break(activity_id, date, 45, "Lunch").unwrap();
+
+        let entries = db.get_time_entries_for_date(date).unwrap();
+        assert_eq!(entries[1].kind, EntryKind::Break);
+        assert_eq!(db.get_daily_totals(date, date).unwrap()[&date], 240);
+        assert_eq!(db.total_minutes_between(date, date, true).unwrap(), 240);
+        assert_eq!(db.get_daily_summary(date).unwrap()[0].entries.len(), 1);
+        assert_eq!(db.get_entry_details_for_range(date, date).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_billable_default_per_activity() {
+        let db = Database::new_in_memory().unwrap();
+        let project_id = db.create_project("Billing", "", "").unwrap();
+        let consulting = db
+            .create_activity_type(project_id, "Consulting", "")
+            .unwrap();
+        let internal = db.create_activity_type(project_id, "Internal", "").unwrap();
+        db.set_activity_billable(consulting, true).unwrap();
+        assert!(db.get_activity_type(consulting).unwrap().is_billable);
+
+        let date = NaiveDate::from_ymd_opt(2024, 10, 3).unwrap();
+        let billed = db
+            .create_time_entry(consulting, date, 60, "Workshop")
+            .unwrap();
+        let other = db.create_time_entry(internal, date, 30, "Mail").unwrap();
+        assert!(db.get_time_entry(billed).unwrap().is_billable);
+        assert!(!db.get_time_entry(other).unwrap().is_billable);
+
+        // Overridden per entry, kept when copying and when undoing a delete
+        db.set_entry_billable(other, true).unwrap();
+        let next = date + chrono::Duration::days(1);
+        db.copy_day(date, next, false).unwrap();
+        assert!(db
+            .get_time_entries_for_date(next)
+            .unwrap()
+            .iter()
+            .all(|e| e.is_billable));
+        let entry = db.take_time_entry(other).unwrap();
+        db.restore_time_entry(&entry).unwrap();
+        assert!(db.get_time_entry(other).unwrap().is_billable);
+    }
+
+    #[test]
+    fn test_uuids_assigned_on_insert() {
+        let db = Database::new_in_memory().unwrap();
+        let project_id = db.create_project("UUID", "", "").unwrap();
+        let activity_id = db.create_activity_type(project_id, "A", "").unwrap();
+        db.create_time_entry(
+            activity_id,
+            NaiveDate::from_ymd_opt(2024, 10, 3).unwrap(),
+            30,
+            "",
+        )
+        .unwrap();
+
+        let project = db.get_project(project_id).unwrap();
+        assert_eq!(project.uuid.len(), 36);
+        assert_eq!(&project.uuid[14..15], "4");
+        assert_ne!(
+            project.uuid,
+            db.get_activity_type(activity_id).unwrap().uuid
+        );
+        let missing: i64 = db
+            .conn
+            .query_row(
+                "SELECT COUNT(*) FROM time_entries WHERE uuid IS NULL",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(missing, 0);
+    }
+
+    #[test]
+    fn test_apply_catalog_changes() {
+        let db = Database::new_in_memory().unwrap();
+        let changes = vec![
+            CatalogChange::AddProject {
+                code: "P100".to_string(),
+                name: "Platform".to_string(),
+                uuid: String::new(),
+            },
+            CatalogChange::AddActivity {
+                project_code: "P100".to_string(),
+                code: "A1".to_string(),
+                name: "Development".to_string(),
+                uuid: String::new(),
+            },
+        ];
+        assert_eq!(db.apply_catalog_changes(&changes).unwrap(), 2);
+
+        let activities = db.get_all_activity_types(true).unwrap();
+        assert_eq!(activities.len(), 1);
+        assert_eq!(activities[0].code, "A1");
+
+        db.apply_catalog_changes(&[CatalogChange::DeactivateActivity {
+            id: activities[0].id,
+            name: "Development".to_string(),
+        }])
+        .unwrap();
+        assert!(db.get_all_activity_types(true).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_catalog_uuid_round_trip() {
+        use crate::catalog::{catalog_rows, plan_import};
+        let today = NaiveDate::from_ymd_opt(2024, 10, 3).unwrap();
+        let source = Database::new_in_memory().unwrap();
+        let project_id = source.create_project("Platform", "", "").unwrap();
+        source
+            .create_activity_type(project_id, "Development", "")
+            .unwrap();
+        let rows = catalog_rows(
+            &source.get_all_projects(false).unwrap(),
+            &source.get_all_activity_types(false).unwrap(),
+            today,
+        );
+
+        let target = Database::new_in_memory().unwrap();
+        let changes = plan_import(&rows, &[], &[], today);
+        target.apply_catalog_changes(&changes).unwrap();
+        let project = &target.get_all_projects(false).unwrap()[0];
+        assert_eq!(project.uuid, rows[0].project_uuid);
+        assert_eq!(
+            target.get_all_activity_types(false).unwrap()[0].uuid,
+            rows[1].activity_uuid
+        );
+
+        // A local rename doesn't break the match
+        target
+            .conn
+            .execute("UPDATE projects SET name = 'Renamed'", [])
+            .unwrap();
+        let changes = plan_import(
+            &rows,
+            &target.get_all_projects(false).unwrap(),
+            &target.get_all_activity_types(false).unwrap(),
+            today,
+        );
+        assert_eq!(
+            changes,
+            vec![CatalogChange::RenameProject {
+                id: project.id,
+                from: "Renamed".to_string(),
+                to: "Platform".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_cascade_activity_state() {
+        let db = Database::new_in_memory().unwrap();
+        let project_id = db.create_project("Cascade", "Description", "").unwrap();
+        let a = db.create_activity_type(project_id, "A", "").unwrap();
+        let b = db.create_activity_type(project_id, "B", "").unwrap();
+        db.deactivate_activity_type(b).unwrap();
+
+        assert_eq!(
+            db.set_project_activities_active(project_id, false).unwrap(),
+            1
+        );
+        assert!(!db.get_activity_type(a).unwrap().is_active);
+        assert_eq!(
+            db.count_project_activities_to_set(project_id, true)
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            db.set_project_activities_active(project_id, true).unwrap(),
+            1
+        );
+        assert!(db.get_activity_type(a).unwrap().is_active);
+        // Switched off before the project was: stays off
+        assert!(!db.get_activity_type(b).unwrap().is_active);
+    }
+
+    #[test]
+    fn test_close_and_reopen_day() {
+        let db = Database::new_in_memory().unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 10, 3).unwrap();
+
+        assert!(!db.is_day_closed(date).unwrap());
+        db.close_day(date).unwrap();
+        db.close_day(date).unwrap();
+        assert!(db.is_day_closed(date).unwrap());
+        db.reopen_day(date).unwrap();
+        assert!(!db.is_day_closed(date).unwrap());
+    }
+
+    #[test]
+    fn test_locked_month() {
+        let db = Database::new_in_memory().unwrap();
+        let project_id = db.create_project("Lock", "", "").unwrap();
+        let activity_id = db.create_activity_type(project_id, "A", "").unwrap();
+        let october = NaiveDate::from_ymd_opt(2024, 10, 3).unwrap();
+        let november = NaiveDate::from_ymd_opt(2024, 11, 1).unwrap();
+        let id = db
+            .create_time_entry(activity_id, october, 60, "Work")
+            .unwrap();
+
+        db.lock_month(october).unwrap();
+        assert!(db.is_month_locked(october).unwrap());
+        assert!(!db.is_month_locked(november).unwrap());
+        assert!(matches!(
+            db.create_time_entry(activity_id, october, 30, "Late"),
+            Err(DatabaseError::MonthLocked(_))
+        ));
+        assert!(db.update_time_entry(id, 90, "Work", "").is_err());
+        assert!(db.delete_time_entry(id).is_err());
+        db.create_adjustment(activity_id, november, -15, "Fix", "October was locked")
+            .unwrap();
+
+        db.unlock_month(october).unwrap();
+        db.delete_time_entry(id).unwrap();
+    }
+
+    #[test]
+    fn test_lock_old_days() {
+        let mut db = Database::new_in_memory().unwrap();
+        let project_id = db.create_project("Lock", "", "").unwrap();
+        let activity_id = db.create_activity_type(project_id, "A", "").unwrap();
+        let today = chrono::Local::now().date_naive();
+        let old = today - chrono::Duration::days(40);
+        let id = db.create_time_entry(activity_id, old, 60, "Work").unwrap();
+
+        db.set_lock_after_days(30);
+        assert!(db.is_date_locked(old).unwrap());
+        assert!(!db.is_date_locked(today).unwrap());
+        assert!(matches!(
+            db.update_time_entry(id, 90, "Work", ""),
+            Err(DatabaseError::DayTooOld(_, 30))
+        ));
+        assert!(db.delete_time_entry(id).is_err());
+        db.create_time_entry(activity_id, today, 30, "Work")
+            .unwrap();
+
+        db.unlock_day(old);
+        db.update_time_entry(id, 90, "Work", "").unwrap();
+        db.relock_day(old);
+        assert!(db.delete_time_entry(id).is_err());
+        db.set_lock_after_days(0);
+        db.delete_time_entry(id).unwrap();
+    }
+
+    #[test]
+    fn test_entry_templates() {
+        let db = Database::new_in_memory().unwrap();
+        let project_id = db.create_project("Templates", "", "").unwrap();
+        let activity_id = db.create_activity_type(project_id, "Planning", "").unwrap();
+        db.create_entry_template(0, activity_id, 60, "Weekly planning")
+            .unwrap();
+        let friday = db
+            .create_entry_template(4, activity_id, 30, "Timesheet")
+            .unwrap();
+
+        let templates = db.get_entry_templates().unwrap();
+        assert_eq!(templates.len(), 2);
+        assert_eq!(templates[0].comment, "Weekly planning");
+
+        db.delete_entry_template(friday).unwrap();
+        assert_eq!(db.get_entry_templates().unwrap().len(), 1);
+
+        // Removed together with their activity
+        db.delete_activity_type(activity_id).unwrap();
+        assert!(db.get_entry_templates().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_monthly_cap() {
+        let db = Database::new_in_memory().unwrap();
+        let project_id = db.create_project("Internal", "", "").unwrap();
+        let activity_id = db.create_activity_type(project_id, "Admin", "").unwrap();
+        let october = NaiveDate::from_ymd_opt(2024, 10, 3).unwrap();
+        db.create_time_entry(activity_id, october, 300, "Mail")
+            .unwrap();
+        db.create_time_entry(activity_id, october.succ_opt().unwrap(), 240, "Mail")
+            .unwrap();
+        db.create_time_entry(
+            activity_id,
+            NaiveDate::from_ymd_opt(2024, 11, 1).unwrap(),
+            60,
+            "Mail",
+        )
+        .unwrap();
+
+        db.set_activity_monthly_cap(activity_id, Some(480)).unwrap();
+        let activity = db.get_activity_type(activity_id).unwrap();
+        let totals = db.get_activity_totals_for_month(october).unwrap();
+        assert_eq!(totals[&activity_id], 540);
+        assert!(activity.exceeds_cap(totals[&activity_id]));
+
+        db.set_activity_monthly_cap(activity_id, None).unwrap();
+        assert!(!db.get_activity_type(activity_id).unwrap().exceeds_cap(540));
+    }
+
+    #[test]
+    fn test_cost_center_in_entry_details() {
+        let db = Database::new_in_memory().unwrap();
+        let project_id = db.create_project("Internal", "", "").unwrap();
+        let activity_id = db.create_activity_type(project_id, "Admin", "").unwrap();
+        db.set_activity_cost_center(activity_id, "CC-4100").unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 10, 3).unwrap();
+        db.create_time_entry(activity_id, date, 60, "Mail").unwrap();
+
+        let details = db.get_entry_details_for_range(date, date).unwrap();
+        assert_eq!(details[0].cost_center, "CC-4100");
+        assert_eq!(
+            db.get_activity_type(activity_id).unwrap().cost_center,
+            "CC-4100"
+        );
+    }
+
+    #[test]
+    fn test_snapshot_and_restore() {
+        let dir = std::env::temp_dir().join("chronos-log-test-snapshot");
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut db = Database::new(dir.join("chronos_log.db")).unwrap();
+        let project_id = db.create_project("Kept", "", "").unwrap();
+
+        let snapshot = db.snapshot("delete").unwrap().unwrap();
+        assert!(snapshot.starts_with(dir.join("backups")));
+        db.delete_project(project_id).unwrap();
+        assert!(db.get_all_projects(false).unwrap().is_empty());
+
+        db.restore(&snapshot).unwrap();
+        assert_eq!(db.get_all_projects(false).unwrap()[0].name, "Kept");
+        let snapshots = crate::backup::list_snapshots(&db.backup_dir().unwrap());
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].reason, "delete");
+        assert_eq!(
+            crate::backup::verify(&snapshot),
+            crate::backup::Integrity::Verified
+        );
+
+        // Damaged backups are refused and the data is left alone
+        std::fs::write(&snapshot, b"damaged").unwrap();
+        assert!(matches!(
+            db.restore(&snapshot),
+            Err(DatabaseError::CorruptedBackup(_))
+        ));
+        assert_eq!(db.get_all_projects(false).unwrap().len(), 1);
+        std::fs::remove_dir_all(&dir).ok();
+
+        // Nothing to back up in memory
+        assert!(Database::new_in_memory()
+            .unwrap()
+            .snapshot("delete")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_revert_day() {
+        let dir = std::env::temp_dir().join("chronos-log-test-revert-day");
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::create_dir_all(&dir).unwrap();
+        let db = Database::new(dir.join("chronos_log.db")).unwrap();
+        let project_id = db.create_project("Revert", "", "").unwrap();
+        let activity_id = db.create_activity_type(project_id, "A", "").unwrap();
+        let date = |d| NaiveDate::from_ymd_opt(2024, 10, d).unwrap();
+        db.create_time_entry(activity_id, date(1), 60, "kept")
+            .unwrap();
+        let snapshot = db.snapshot("daily").unwrap().unwrap();
+
+        // Mangle the day and touch another one after the snapshot
+        for entry in db.get_time_entries_for_date(date(1)).unwrap() {
+            db.delete_time_entry(entry.id).unwrap();
+        }
+        db.create_time_entry(activity_id, date(1), 480, "imported")
+            .unwrap();
+        db.create_time_entry(activity_id, date(2), 30, "later")
+            .unwrap();
+
+        let before = db.get_snapshot_entries(&snapshot, date(1)).unwrap();
+        assert_eq!(before.len(), 1);
+        assert_eq!(db.revert_day(&snapshot, date(1)).unwrap(), 1);
+        let entries = db.get_time_entries_for_date(date(1)).unwrap();
+        assert_eq!(entries, before);
+        assert_eq!(entries[0].comment, "kept");
+        assert_eq!(entries[0].minutes, 60);
+        assert_eq!(db.get_time_entries_for_date(date(2)).unwrap().len(), 1);
+
+        db.lock_month(date(1)).unwrap();
+        assert!(matches!(
+            db.revert_day(&snapshot, date(1)),
+            Err(DatabaseError::MonthLocked(_))
+        ));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_old_snapshot_entries() {
+        let path = std::env::temp_dir().join("chronos-log-test-old-snapshot.db");
+        std::fs::remove_file(&path).ok();
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE time_entries (
+                 id INTEGER PRIMARY KEY, activity_type_id INTEGER, date TEXT,
+                 minutes INTEGER, comment TEXT
+             );
+             INSERT INTO time_entries VALUES (1, 7, '2024-10-01', 60, 'Review');",
+        )
+        .unwrap();
+        drop(conn);
+
+        let db = Database::new_in_memory().unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 10, 1).unwrap();
+        let entries = db.get_snapshot_entries(&path, date).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].comment, "Review");
+        assert_eq!(entries[0].kind, EntryKind::Work);
+        assert_eq!(entries[0].reason, "");
+        assert!(!entries[0].is_billable);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_earnings() {
+        let db = Database::new_in_memory().unwrap();
+        let project_id = db.create_project("Customer", "", "").unwrap();
+        let consulting = db
+            .create_activity_type(project_id, "Consulting", "")
+            .unwrap();
+        let travel = db.create_activity_type(project_id, "Travel", "").unwrap();
+        let internal_id = db.create_project("Internal", "", "").unwrap();
+        let admin = db.create_activity_type(internal_id, "Admin", "").unwrap();
+        db.set_project_hourly_rate(project_id, Some(10_000))
+            .unwrap();
+        db.set_activity_hourly_rate(travel, Some(5_000)).unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2024, 10, 3).unwrap();
+        db.create_time_entry(consulting, date, 90, "Workshop")
+            .unwrap();
+        db.create_time_entry(travel, date, 45, "Train").unwrap();
+        db.create_break(travel, date, 30, "Lunch").unwrap();
+        db.create_time_entry(admin, date, 60, "Mail").unwrap();
+
+        let earnings = db.earnings_between(date, date, true).unwrap();
+        let amounts: Vec<_> = earnings
+            .iter()
+            .map(|e| (e.activity_name.as_str(), e.minutes, e.amount_cents()))
+            .collect();
+        assert_eq!(
+            amounts,
+            [
+                ("Consulting", 90, Some(15_000)),
+                ("Travel", 45, Some(3_750)),
+                ("Admin", 60, None)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_statistics() {
+        let db = Database::new_in_memory().unwrap();
+        let alpha = db.create_project("Alpha", "", "").unwrap();
+        let beta = db.create_project("Beta", "", "").unwrap();
+        let a = db.create_activity_type(alpha, "A", "").unwrap();
+        let b = db.create_activity_type(beta, "B", "").unwrap();
+        // 2024-09-30 is a Monday
+        let date = |d| NaiveDate::from_ymd_opt(2024, 10, 1).unwrap() + chrono::Duration::days(d);
+        db.create_time_entry(a, date(-1), 60, "").unwrap();
+        db.create_time_entry(a, date(6), 90, "").unwrap();
+        db.create_time_entry(b, date(6), 240, "").unwrap();
+        db.create_time_entry(b, date(1), 30, "").unwrap();
+        db.create_time_entry(b, date(40), 30, "outside").unwrap();
+        let (start, end) = (date(-1), date(6));
+
+        assert_eq!(db.total_minutes_between(start, end, true).unwrap(), 420);
+        assert_eq!(db.distinct_logged_days(start, end, true).unwrap(), 3);
+        let projects = db.minutes_per_project_between(start, end, true).unwrap();
+        assert_eq!(
+            projects
+                .iter()
+                .map(|p| (p.project_name.as_str(), p.minutes))
+                .collect::<Vec<_>>(),
+            [("Beta", 270), ("Alpha", 150)]
+        );
+        let weekdays = db.minutes_per_weekday(start, end, true).unwrap();
+        assert_eq!(
+            weekdays
+                .iter()
+                .map(|w| (w.weekday, w.minutes, w.days))
+                .collect::<Vec<_>>(),
+            [
+                (chrono::Weekday::Mon, 390, 2),
+                (chrono::Weekday::Wed, 30, 1)
+            ]
+        );
+        assert_eq!(weekdays[0].average(), 195);
+
+        let activities = db.minutes_per_activity_between(start, end).unwrap();
+        assert_eq!(
+            activities
+                .iter()
+                .map(|a| (
+                    a.project_name.as_str(),
+                    a.activity_name.as_str(),
+                    a.minutes,
+                    a.days
+                ))
+                .collect::<Vec<_>>(),
+            [("Alpha", "A", 150, 2), ("Beta", "B", 270, 2)]
+        );
+
+        let stats = db.statistics_between(start, end, true).unwrap();
+        assert_eq!(stats.average_per_logged_day(), 140);
+        let empty = db.statistics_between(date(100), date(101), true).unwrap();
+        assert_eq!(empty, RangeStatistics::default());
+        assert_eq!(empty.average_per_logged_day(), 0);
+
+        // Without inactive projects only Alpha's time is left
+        db.deactivate_project(beta).unwrap();
+        let active = db.statistics_between(start, end, false).unwrap();
+        assert_eq!(active.total_minutes, 150);
+        assert_eq!(active.logged_days, 2);
+        assert_eq!(active.per_project.len(), 1);
+        let earnings = db.earnings_between(start, end, false).unwrap();
+        assert_eq!(earnings.len(), 1);
+        assert_eq!(earnings[0].project_name, "Alpha");
+        assert_eq!(db.statistics_between(start, end, true).unwrap(), stats);
+    }
+
+    #[test]
+    fn test_remove_sample_data() {
+        let db = Database::new_in_memory().unwrap();
+        db.create_sample_data().unwrap();
+        let own = db.create_project("Own", "", "").unwrap();
+        db.create_activity_type(own, "Mine", "").unwrap();
+
+        // An example activity in use is kept, and with it its project
+        let used = db
+            .get_all_activity_types(false)
+            .unwrap()
+            .into_iter()
+            .find(|a| a.name == "Development - Bug fixes")
+            .unwrap();
+        db.create_time_entry(
+            used.id,
+            NaiveDate::from_ymd_opt(2024, 10, 1).unwrap(),
+            30,
+            "x",
+        )
+        .unwrap();
+
+        let (removed_projects, removed_activities) = db.remove_sample_data().unwrap();
+        assert_eq!((removed_projects.len(), removed_activities.len()), (1, 6));
+        let projects: Vec<String> = db
+            .get_all_projects(false)
+            .unwrap()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(projects, ["40 - Development", "Own"]);
+        assert_eq!(db.get_all_activity_types(false).unwrap().len(), 2);
+        let (none, _) = db.remove_sample_data().unwrap();
+        assert!(none.is_empty());
+
+        // Undo puts back exactly what was removed
+        for record in &removed_projects {
+            db.restore_project(record).unwrap();
+        }
+        for deleted in &removed_activities {
+            db.restore_activity_type(deleted).unwrap();
+        }
+        assert_eq!(db.get_all_projects(false).unwrap().len(), 3);
+        assert_eq!(db.get_all_activity_types(false).unwrap().len(), 8);
+    }
+
+    #[test]
+    fn test_recent_entry_dates() {
+        let db = Database::new_in_memory().unwrap();
+        let project_id = db.create_project("Dates", "", "").unwrap();
+        let activity_id = db.create_activity_type(project_id, "A", "").unwrap();
+        let date = |d| NaiveDate::from_ymd_opt(2024, 10, d).unwrap();
+        for day in [1, 3, 3, 2, 7] {
+            db.create_time_entry(activity_id, date(day), 30, "x")
+                .unwrap();
+        }
+        assert_eq!(
+            db.get_recent_entry_dates(3).unwrap(),
+            [date(7), date(3), date(2)]
+        );
+
+        let totals = db.get_daily_totals(date(2), date(6)).unwrap();
+        assert_eq!(totals.len(), 2);
+        assert_eq!(totals[&date(3)], 60);
+    }
+
+    #[test]
+    fn test_usage() {
+        let db = Database::new_in_memory().unwrap();
+        let project_id = db.create_project("Used", "", "").unwrap();
+        let a = db.create_activity_type(project_id, "A", "").unwrap();
+        let b = db.create_activity_type(project_id, "B", "").unwrap();
+        let date = |d| NaiveDate::from_ymd_opt(2024, 10, d).unwrap();
+        db.create_time_entry(a, date(3), 60, "x").unwrap();
+        db.create_time_entry(a, date(7), 30, "x").unwrap();
+        db.create_time_entry(b, date(5), 45, "x").unwrap();
+        let september = NaiveDate::from_ymd_opt(2024, 9, 30).unwrap();
+        db.create_time_entry(b, september, 15, "x").unwrap();
+
+        let activities = db.get_activity_usage(date(1)).unwrap();
+        assert_eq!(activities[&a].total_minutes, 90);
+        assert_eq!(activities[&a].last_used, Some(date(7)));
+        let projects = db.get_project_usage(date(1)).unwrap();
+        assert_eq!(projects[&project_id].total_minutes, 150);
+        assert_eq!(projects[&project_id].month_minutes, 135);
+        assert_eq!(projects[&project_id].last_used, Some(date(7)));
+        let projects = db.get_project_usage(september).unwrap();
+        assert_eq!(projects[&project_id].month_minutes, 15);
+    }
+
+    #[test]
+    fn test_project_budget() {
+        let db = Database::new_in_memory().unwrap();
+        let project_id = db.create_project("Budgeted", "", "").unwrap();
+        let activity_id = db.create_activity_type(project_id, "A", "").unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 10, 3).unwrap();
+        db.create_time_entry(activity_id, date, 300, "x").unwrap();
+        db.create_break(activity_id, date, 60, "Lunch").unwrap();
+        db.create_time_entry(activity_id, date + chrono::Duration::days(1), 240, "x")
+            .unwrap();
+        assert_eq!(db.get_project_total_minutes(project_id).unwrap(), 540);
+
+        db.set_project_budget(project_id, Some(600)).unwrap();
+        let project = db.get_project(project_id).unwrap();
+        assert_eq!(project.budget_minutes, Some(600));
+        assert!(!project.crosses_budget(540, 600));
+        assert!(project.crosses_budget(540, 630));
+        assert!(!project.crosses_budget(630, 660));
+    }
+
+    #[test]
+    fn test_day_types() {
+        let db = Database::new_in_memory().unwrap();
+        let date = |d| NaiveDate::from_ymd_opt(2024, 12, d).unwrap();
+        db.set_day_type(date(24), Some(DayType::PublicHoliday))
+            .unwrap();
+        db.set_day_type(date(27), Some(DayType::Vacation)).unwrap();
+        db.set_day_type(date(27), Some(DayType::SickLeave)).unwrap();
+        let day_types = db.get_day_types().unwrap();
+        assert_eq!(day_types.len(), 2);
+        assert_eq!(day_types[&date(27)], DayType::SickLeave);
+
+        db.set_day_type(date(24), None).unwrap();
+        assert!(!db.get_day_types().unwrap().contains_key(&date(24)));
+
+        assert_eq!(db.import_days_off(&[date(24), date(27)]).unwrap(), 1);
+        let day_types = db.get_day_types().unwrap();
+        assert_eq!(day_types[&date(24)], DayType::PublicHoliday);
+        assert_eq!(day_types[&date(27)], DayType::SickLeave);
+    }
+
+    #[test]
+    fn test_last_activity_per_project() {
+        let db = Database::new_in_memory().unwrap();
+        let project_id = db.create_project("Used", "", "").unwrap();
+        let other = db.create_project("Other", "", "").unwrap();
+        let a = db.create_activity_type(project_id, "A", "").unwrap();
+        let b = db.create_activity_type(project_id, "B", "").unwrap();
+        db.create_activity_type(other, "Unused", "").unwrap();
+        let date = |d| NaiveDate::from_ymd_opt(2024, 10, d).unwrap();
+        db.create_time_entry(a, date(7), 30, "x").unwrap();
+        db.create_time_entry(b, date(5), 45, "x").unwrap();
+
+        let last = db.get_last_activity_per_project().unwrap();
+        assert_eq!(last[&project_id], a);
+        assert!(!last.contains_key(&other));
+
+        // Same day: the entry added last wins
+        db.create_time_entry(b, date(7), 15, "x").unwrap();
+        assert_eq!(db.get_last_activity_per_project().unwrap()[&project_id], b);
+
+        // A later break doesn't make its activity the last one worked on
+        db.create_break(a, date(8), 30, "Lunch").unwrap();
+        assert_eq!(db.get_last_activity_per_project().unwrap()[&project_id], b);
+    }
+
+    #[test]
+    fn test_copy_day() {
+        let db = Database::new_in_memory().unwrap();
+        let project_id = db.create_project("P", "", "").unwrap();
+        let activity_id = db.create_activity_type(project_id, "A", "").unwrap();
+        let date = |d| NaiveDate::from_ymd_opt(2024, 10, d).unwrap();
+        db.create_time_entry(activity_id, date(3), 60, "Standup")
+            .unwrap();
+        db.create_time_entry(activity_id, date(3), 120, "PROJ-1")
+            .unwrap();
+        db.create_adjustment(activity_id, date(3), -15, "", "Too much")
+            .unwrap();
+
+        let ids = db.copy_day(date(3), date(4), false).unwrap();
+        assert_eq!(ids.len(), 2);
+        let copied = db.get_time_entries_for_date(date(4)).unwrap();
+        assert_eq!(copied.iter().map(|e| e.minutes).sum::<i32>(), 180);
+        assert_eq!(copied[0].comment, "Standup");
+
+        db.copy_day(date(3), date(7), true).unwrap();
+        let copied = db.get_time_entries_for_date(date(7)).unwrap();
+        assert!(copied.iter().all(|e| e.comment.is_empty()));
+    }
+
+    #[test]
+    fn test_copy_day_mixed_kinds() {
+        let db = Database::new_in_memory().unwrap();
+        let project_id = db.create_project("P", "", "").unwrap();
+        let activity_id = db.create_activity_type(project_id, "A", "").unwrap();
+        let date = |d| NaiveDate::from_ymd_opt(2024, 10, d).unwrap();
+        let trip = Trip {
+            origin: "Office".to_string(),
+            destination: "Customer".to_string(),
+            kilometers: Some(42.0),
+        };
+        db.create_time_entry(activity_id, date(3), 240, "Workshop")
+            .unwrap();
+        db.create_travel(activity_id, date(3), 45, "Drive", &trip)
+            .unwrap();
+        db.create_break(activity_id, date(3), 30, "Lunch").unwrap();
+        db.create_adjustment(activity_id, date(3), -15, "", "Too much")
+            .unwrap();
+
+        let ids = db.copy_day(date(3), date(4), false).unwrap();
+        assert_eq!(ids.len(), 3);
+        let copied = db.get_time_entries_for_date(date(4)).unwrap();
+        let kinds: Vec<_> = copied.iter().map(|e| e.kind).collect();
+        assert!(kinds.contains(&EntryKind::Work));
+        assert!(kinds.contains(&EntryKind::Travel));
+        assert!(kinds.contains(&EntryKind::Break));
+        assert!(!kinds.contains(&EntryKind::Adjustment));
+
+        let trips = db.get_trips_between(date(4), date(4)).unwrap();
+        assert_eq!(trips.len(), 1);
+        assert_eq!(trips.values().next().unwrap().destination, "Customer");
+    }
+
+    #[test]
+    fn test_take_and_restore() {
+        let db = Database::new_in_memory().unwrap();
+        let project_id = db.create_project("Gone", "For a while", "").unwrap();
+        db.set_project_links(
+            project_id,
+            &[("Wiki".to_string(), "https://wiki.example.com".to_string())],
+        )
+        .unwrap();
+        let activity_id = db.create_activity_type(project_id, "Work", "").unwrap();
+        db.set_activity_validity(
+            activity_id,
+            Some(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()),
+            None,
+        )
+        .unwrap();
+        db.create_entry_template(0, activity_id, 60, "Planning")
+            .unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 10, 3).unwrap();
+        let entry_id = db.create_time_entry(activity_id, date, 45, "x").unwrap();
+
+        let entry = db.take_time_entry(entry_id).unwrap();
+        let activity = db.take_activity_type(activity_id).unwrap();
+        assert_eq!(activity.templates.len(), 1);
+        let project = db.take_project(project_id).unwrap();
+        assert!(db.get_project(project_id).is_err());
+
+        db.restore_project(&project).unwrap();
+        db.restore_activity_type(&activity).unwrap();
+        db.restore_time_entry(&entry).unwrap();
+        assert_eq!(db.get_project_record(project_id).unwrap(), project);
+        assert_eq!(
+            db.get_activity_type(activity_id).unwrap(),
+            activity.activity
+        );
+        assert_eq!(db.get_entry_templates().unwrap(), activity.templates);
+        assert_eq!(db.get_time_entry(entry_id).unwrap(), entry);
+    }
+
+    #[test]
+    fn test_archive_and_cleanup() {
+        let db = Database::new_in_memory().unwrap();
+        let active = db.create_project("Active", "", "").unwrap();
+        let old = db.create_project("Old", "", "").unwrap();
+        let kept = db.create_activity_type(active, "Kept", "").unwrap();
+        let stale = db.create_activity_type(old, "Stale", "").unwrap();
+
+        db.deactivate_project(old).unwrap();
+        assert_eq!(db.deactivate_activities_of_inactive_projects().unwrap(), 1);
+        assert!(!db.get_activity_type(stale).unwrap().is_active);
+        assert!(db.get_activity_type(kept).unwrap().is_active);
+
+        db.archive_project(old).unwrap();
+        db.archive_activity_type(kept).unwrap();
+        let kept_activity = db.get_activity_type(kept).unwrap();
+        assert!(kept_activity.archived && !kept_activity.is_active);
+
+        db.reactivate_project(old).unwrap();
+        let project = db.get_project(old).unwrap();
+        assert!(project.is_active && !project.archived);
+    }
+
+    #[test]
+    fn test_activity_validity() {
+        let db = Database::new_in_memory().unwrap();
+        let project_id = db.create_project("Customer", "", "").unwrap();
+        let activity_id = db.create_activity_type(project_id, "Rollout", "").unwrap();
+        let date = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+        assert!(db
+            .get_activity_type(activity_id)
+            .unwrap()
+            .is_valid_on(date(1, 1)));
+
+        db.set_activity_validity(activity_id, Some(date(3, 1)), Some(date(6, 30)))
+            .unwrap();
+        let activity = db.get_activity_type(activity_id).unwrap();
+        assert_eq!(activity.valid_to, Some(date(6, 30)));
+        assert!(!activity.is_valid_on(date(2, 29)));
+        assert!(activity.is_valid_on(date(3, 1)));
+        assert!(activity.is_valid_on(date(6, 30)));
+        assert!(!activity.is_valid_on(date(7, 1)));
+    }
+
+    #[test]
+    fn test_combined_transaction() {
+        let db = Database::new_in_memory().unwrap();
+        let record = ProjectRecord {
+            project: Project {
+                id: 0,
+                name: "Linked".to_string(),
+                description: String::new(),
+                is_active: true,
+                icon: String::new(),
+                notes: "Notes".to_string(),
+                code: String::new(),
+                uuid: String::new(),
+                archived: false,
+                hourly_rate_cents: Some(9000),
+                budget_minutes: None,
+            },
+            links: vec![("Wiki".to_string(), "https://wiki".to_string())],
+        };
+        let id = db.create_project_record(&record).unwrap();
+        let saved = db.get_project_record(id).unwrap();
+        assert_eq!(saved.project.hourly_rate_cents, Some(9000));
+        assert_eq!(saved.links, record.links);
+
+        // A failure later in the outer transaction undoes the nested one too
+        let result: DbResult<()> = db.transaction(|| {
+            db.create_project_record(&ProjectRecord {
+                project: Project {
+                    name: "Dropped".to_string(),
+                    ..record.project.clone()
+                },
+                links: record.links.clone(),
+            })?;
+            Err(DatabaseError::ProjectNotFound(0))
+        });
+        assert!(result.is_err());
+        assert_eq!(db.get_all_projects(false).unwrap().len(), 1);
+        assert_eq!(db.get_all_project_links().unwrap().len(), 1);
+
+        let activity = db.create_activity_type(id, "A", "").unwrap();
+        let projects = db.get_project_states().unwrap();
+        let activities = db.get_activity_states().unwrap();
+        db.deactivate_project(id).unwrap();
+        db.set_project_activities_active(id, false).unwrap();
+        assert!(db.get_activity_states().unwrap()[0].with_project);
+        db.set_states(&projects, &activities).unwrap();
+        assert_eq!(db.get_project_states().unwrap(), projects);
+        assert_eq!(db.get_activity_states().unwrap(), activities);
+        assert!(db.get_activity_type(activity).unwrap().is_active);
+    }
 }
@@ -0,0 +1,578 @@
+// src/export.rs
+// File exports (CSV, HTML) for reports
+
+use crate::catalog::CatalogRow;
+use crate::database::{EntryDetail, Expense, TravelEntry};
+use crate::formatting::{
+    format_amount, format_kilometers, format_minutes_to_decimal, DurationStyle,
+};
+use crate::reports::{CostCenterTotal, TicketReport, WeekGrid};
+use crate::settings::{ExportProfile, ReportFormat};
+use chrono::NaiveDate;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Field separator for CSV files. Semicolon, since decimal hours use a comma.
+const CSV_SEPARATOR: char = ';';
+
+/// Quote a CSV field if it contains separators, quotes or line breaks
+fn csv_field(value: &str) -> String {
+    if value.contains([CSV_SEPARATOR, '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Join fields into a single CSV line
+fn csv_line(fields: &[&str]) -> String {
+    fields
+        .iter()
+        .map(|f| csv_field(f))
+        .collect::<Vec<_>>()
+        .join(&CSV_SEPARATOR.to_string())
+}
+
+/// Header and rows of a report, written as CSV or as an HTML table
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    pub header: Vec<&'static str>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Write a table as CSV. Returns the number of data rows written.
+fn write_csv(path: &Path, table: &Table) -> std::io::Result<usize> {
+    let mut file = std::fs::File::create(path)?;
+    writeln!(file, "{}", csv_line(&table.header))?;
+    for row in &table.rows {
+        let fields: Vec<&str> = row.iter().map(String::as_str).collect();
+        writeln!(file, "{}", csv_line(&fields))?;
+    }
+    Ok(table.rows.len())
+}
+
+/// Time per ticket, with hours rounded by the export profile
+pub fn ticket_table(report: &TicketReport, profile: &ExportProfile, style: DurationStyle) -> Table {
+    Table {
+        header: vec!["Ticket", "Hours", "Time", "Entries", "Raw hours"],
+        rows: report
+            .tickets
+            .iter()
+            .map(|ticket| {
+                let rounded = profile.round(ticket.total_minutes);
+                vec![
+                    ticket.ticket.clone(),
+                    format_minutes_to_decimal(rounded),
+                    style.format_total(rounded),
+                    ticket.entry_count.to_string(),
+                    format_minutes_to_decimal(ticket.total_minutes),
+                ]
+            })
+            .collect(),
+    }
+}
+
+/// Time per cost center and project code, with hours rounded by the export profile
+pub fn cost_center_table(
+    totals: &[CostCenterTotal],
+    profile: &ExportProfile,
+    style: DurationStyle,
+) -> Table {
+    Table {
+        header: vec![
+            "Cost center",
+            "Project code",
+            "Hours",
+            "Time",
+            "Entries",
+            "Raw hours",
+        ],
+        rows: totals
+            .iter()
+            .map(|total| {
+                let rounded = profile.round(total.total_minutes);
+                vec![
+                    total.cost_center.clone(),
+                    total.project_code.clone(),
+                    format_minutes_to_decimal(rounded),
+                    style.format_total(rounded),
+                    total.entry_count.to_string(),
+                    format_minutes_to_decimal(total.total_minutes),
+                ]
+            })
+            .collect(),
+    }
+}
+
+/// Individual time entries, one row each
+pub fn entries_table(entries: &[EntryDetail]) -> Table {
+    Table {
+        header: vec!["Date", "Project", "Activity", "Minutes", "Hours", "Comment"],
+        rows: entries
+            .iter()
+            .map(|detail| {
+                let entry = &detail.entry;
+                vec![
+                    entry.date.to_string(),
+                    detail.project_name.clone(),
+                    detail.activity_name.clone(),
+                    entry.minutes.to_string(),
+                    format_minutes_to_decimal(entry.minutes),
+                    entry.comment.clone(),
+                ]
+            })
+            .collect(),
+    }
+}
+
+/// Write the ticket report as CSV, with hours rounded by the export profile.
+/// Returns the number of data rows written.
+pub fn write_ticket_csv(
+    path: &Path,
+    report: &TicketReport,
+    profile: &ExportProfile,
+    style: DurationStyle,
+) -> std::io::Result<usize> {
+    write_csv(path, &ticket_table(report, profile, style))
+}
+
+/// Write time per cost center and project code, with hours rounded by the export profile.
+/// Returns the number of data rows written.
+pub fn write_cost_center_csv(
+    path: &Path,
+    totals: &[CostCenterTotal],
+    profile: &ExportProfile,
+    style: DurationStyle,
+) -> std::io::Result<usize> {
+    write_csv(path, &cost_center_table(totals, profile, style))
+}
+
+/// Write individual time entries, one row each, e.g. for payroll uploads.
+/// Returns the number of data rows written.
+pub fn write_entries_csv(path: &Path, entries: &[EntryDetail]) -> std::io::Result<usize> {
+    write_csv(path, &entries_table(entries))
+}
+
+/// Write travel entries with their trips, one row each, for expense claims.
+/// Returns the number of data rows written.
+pub fn write_travel_csv(path: &Path, travel: &[TravelEntry]) -> std::io::Result<usize> {
+    let mut file = std::fs::File::create(path)?;
+    writeln!(
+        file,
+        "{}",
+        csv_line(&[
+            "Date",
+            "From",
+            "To",
+            "Kilometers",
+            "Hours",
+            "Project",
+            "Activity",
+            "Comment",
+        ])
+    )?;
+    for item in travel {
+        let entry = &item.detail.entry;
+        writeln!(
+            file,
+            "{}",
+            csv_line(&[
+                &entry.date.to_string(),
+                &item.trip.origin,
+                &item.trip.destination,
+                &item
+                    .trip
+                    .kilometers
+                    .map(format_kilometers)
+                    .unwrap_or_default(),
+                &format_minutes_to_decimal(entry.minutes),
+                &item.detail.project_name,
+                &item.detail.activity_name,
+                &entry.comment,
+            ])
+        )?;
+    }
+    Ok(travel.len())
+}
+
+/// Write expenses, one row each, for expense claims.
+/// Returns the number of data rows written.
+pub fn write_expenses_csv(path: &Path, expenses: &[Expense]) -> std::io::Result<usize> {
+    let mut file = std::fs::File::create(path)?;
+    writeln!(
+        file,
+        "{}",
+        csv_line(&["Date", "Amount", "Currency", "Description", "Receipt"])
+    )?;
+    for expense in expenses {
+        writeln!(
+            file,
+            "{}",
+            csv_line(&[
+                &expense.date.to_string(),
+                &format_amount(expense.amount_cents),
+                &expense.currency,
+                &expense.description,
+                &expense.receipt_path,
+            ])
+        )?;
+    }
+    Ok(expenses.len())
+}
+
+/// Write catalog rows in the format read by the catalog import.
+/// Returns the number of data rows written.
+pub fn write_catalog_csv(path: &Path, rows: &[CatalogRow]) -> std::io::Result<usize> {
+    let mut file = std::fs::File::create(path)?;
+    writeln!(
+        file,
+        "{}",
+        csv_line(&[
+            "project_code",
+            "project_name",
+            "activity_code",
+            "activity_name",
+            "valid_from",
+            "valid_to",
+            "project_uuid",
+            "activity_uuid",
+        ])
+    )?;
+    let date = |d: Option<NaiveDate>| d.map(|d| d.to_string()).unwrap_or_default();
+    for row in rows {
+        writeln!(
+            file,
+            "{}",
+            csv_line(&[
+                &row.project_code,
+                &row.project_name,
+                &row.activity_code,
+                &row.activity_name,
+                &date(row.valid_from),
+                &date(row.valid_to),
+                &row.project_uuid,
+                &row.activity_uuid,
+            ])
+        )?;
+    }
+    Ok(rows.len())
+}
+
+/// Row colors of the HTML week report, one per project in order of appearance
+const PROJECT_COLORS: [&str; 6] = [
+    "#4e79a7", "#f28e2b", "#59a14f", "#e15759", "#b07aa1", "#76b7b2",
+];
+
+/// Escape text for HTML element content and attributes
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Start of a standalone HTML page with the report styles, up to an open `<table>`
+fn html_head(title: &str) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", html_escape(title)));
+    html.push_str(
+        "<style>\n\
+         body { font-family: Segoe UI, sans-serif; margin: 2em; }\n\
+         table { border-collapse: collapse; }\n\
+         th, td { padding: 4px 10px; border-bottom: 1px solid #ddd; }\n\
+         td.time { text-align: right; font-variant-numeric: tabular-nums; }\n\
+         th { background: #f3f3f3; text-align: right; }\n\
+         th.name { text-align: left; }\n\
+         tr.total td { font-weight: bold; border-top: 2px solid #333; }\n\
+         td.project { border-left: 6px solid; }\n\
+         </style>\n</head>\n<body>\n",
+    );
+    html.push_str(&format!("<h1>{}</h1>\n<table>\n", html_escape(title)));
+    html
+}
+
+/// Render a table as a standalone HTML page, for viewing or printing in a browser
+pub fn render_table_html(title: &str, table: &Table) -> String {
+    let mut html = html_head(title);
+    html.push_str("<tr>");
+    for column in &table.header {
+        html.push_str(&format!("<th class=\"name\">{}</th>", html_escape(column)));
+    }
+    html.push_str("</tr>\n");
+    for row in &table.rows {
+        html.push_str("<tr>");
+        for field in row {
+            html.push_str(&format!("<td>{}</td>", html_escape(field)));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+    html
+}
+
+/// Write a table as CSV or as an HTML page titled `title`.
+/// Returns the number of data rows written.
+pub fn write_table(
+    path: &Path,
+    table: &Table,
+    format: ReportFormat,
+    title: &str,
+) -> std::io::Result<usize> {
+    match format {
+        ReportFormat::Csv => write_csv(path, table),
+        ReportFormat::Html => {
+            std::fs::write(path, render_table_html(title, table))?;
+            Ok(table.rows.len())
+        }
+    }
+}
+
+/// Render a week as a standalone HTML page with a styled table and totals,
+/// for viewing or printing in a browser
+pub fn render_week_html(grid: &WeekGrid, style: DurationStyle) -> String {
+    let title = format!(
+        "Week {}, {} – {}",
+        grid.week_number(),
+        grid.days[0].format("%d %b"),
+        grid.days[6].format("%d %b %Y")
+    );
+    let cell = |minutes: i32| {
+        if minutes == 0 {
+            String::new()
+        } else {
+            style.format(minutes)
+        }
+    };
+
+    let mut html = html_head(&title);
+    html.push_str("<tr><th class=\"name\">Project</th><th class=\"name\">Activity</th>");
+    for day in &grid.days {
+        html.push_str(&format!("<th>{}</th>", day.format("%a %d")));
+    }
+    html.push_str("<th>Total</th></tr>\n");
+
+    let mut projects: Vec<&str> = Vec::new();
+    for row in &grid.rows {
+        let index = match projects.iter().position(|p| *p == row.project_name) {
+            Some(index) => index,
+            None => {
+                projects.push(&row.project_name);
+                projects.len() - 1
+            }
+        };
+        html.push_str(&format!(
+            "<tr><td class=\"project\" style=\"border-left-color: {}\">{}</td><td>{}</td>",
+            PROJECT_COLORS[index % PROJECT_COLORS.len()],
+            html_escape(&row.project_name),
+            html_escape(&row.activity_name)
+        ));
+        for minutes in row.minutes {
+            html.push_str(&format!("<td class=\"time\">{}</td>", cell(minutes)));
+        }
+        html.push_str(&format!(
+            "<td class=\"time\"><b>{}</b></td></tr>\n",
+            style.format_total(row.total())
+        ));
+    }
+
+    html.push_str("<tr class=\"total\"><td colspan=\"2\">Total</td>");
+    for i in 0..grid.days.len() {
+        html.push_str(&format!(
+            "<td class=\"time\">{}</td>",
+            cell(grid.day_total(i))
+        ));
+    }
+    html.push_str(&format!(
+        "<td class=\"time\">{}</td></tr>\n</table>\n</body>\n</html>\n",
+        style.format_total(grid.total())
+    ));
+    html
+}
+
+/// Write the HTML week report. Returns the number of activity rows written.
+pub fn write_week_html(
+    path: &Path,
+    grid: &WeekGrid,
+    style: DurationStyle,
+) -> std::io::Result<usize> {
+    std::fs::write(path, render_week_html(grid, style))?;
+    Ok(grid.rows.len())
+}
+
+/// Default file name for an export covering a date range
+pub fn export_file_name(prefix: &str, start: NaiveDate, end: NaiveDate) -> String {
+    format!("{}_{}_{}.csv", prefix, start, end)
+}
+
+/// Get the directory exports are written to
+pub fn get_export_dir() -> PathBuf {
+    // Try to use user's data directory
+    if let Some(data_dir) = dirs::data_local_dir() {
+        let export_dir = data_dir.join("chronos-log").join("exports");
+        if std::fs::create_dir_all(&export_dir).is_ok() {
+            return export_dir;
+        }
+    }
+
+    // Fallback to current directory
+    PathBuf::from(".")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field_quoting() {
+        assert_eq!(csv_field("PROJ-12"), "PROJ-12");
+        assert_eq!(csv_field("1,50"), "1,50");
+        assert_eq!(csv_field("a;b"), "\"a;b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn test_entries_csv() {
+        let detail = |minutes, comment: &str| EntryDetail {
+            entry: crate::database::TimeEntry::for_test(
+                1,
+                NaiveDate::from_ymd_opt(2024, 10, 3).unwrap(),
+                minutes,
+                comment,
+            ),
+            project_name: "Platform".to_string(),
+            activity_name: "Development".to_string(),
+            project_code: String::new(),
+            cost_center: String::new(),
+        };
+        let path = std::env::temp_dir().join("chronos-log-test-entries.csv");
+        let rows = [detail(90, "PROJ-1; review"), detail(15, "standup")];
+        assert_eq!(write_entries_csv(&path, &rows).unwrap(), 2);
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(
+            text,
+            "Date;Project;Activity;Minutes;Hours;Comment\n\
+             2024-10-03;Platform;Development;90;1,50;\"PROJ-1; review\"\n\
+             2024-10-03;Platform;Development;15;0,25;standup\n"
+        );
+    }
+
+    #[test]
+    fn test_table_html() {
+        let table = Table {
+            header: vec!["Ticket", "Hours"],
+            rows: vec![vec!["R&D-1".to_string(), "1,50".to_string()]],
+        };
+        let html = render_table_html("Tickets <October>", &table);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<h1>Tickets &lt;October&gt;</h1>"));
+        assert!(html.contains("<tr><td>R&amp;D-1</td><td>1,50</td></tr>"));
+    }
+
+    #[test]
+    fn test_travel_csv() {
+        let item = |kilometers| TravelEntry {
+            detail: EntryDetail {
+                entry: crate::database::TimeEntry {
+                    kind: crate::database::EntryKind::Travel,
+                    ..crate::database::TimeEntry::for_test(
+                        1,
+                        NaiveDate::from_ymd_opt(2024, 10, 3).unwrap(),
+                        90,
+                        "Kickoff",
+                    )
+                },
+                project_name: "Platform".to_string(),
+                activity_name: "Customer visit".to_string(),
+                project_code: String::new(),
+                cost_center: String::new(),
+            },
+            trip: crate::database::Trip {
+                origin: "Office".to_string(),
+                destination: "Customer; Site B".to_string(),
+                kilometers,
+            },
+        };
+        let path = std::env::temp_dir().join("chronos-log-test-travel.csv");
+        assert_eq!(
+            write_travel_csv(&path, &[item(Some(42.5)), item(None)]).unwrap(),
+            2
+        );
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(
+            text,
+            "Date;From;To;Kilometers;Hours;Project;Activity;Comment\n\
+             2024-10-03;Office;\"Customer; Site B\";42,5;1,50;Platform;Customer visit;Kickoff\n\
+             2024-10-03;Office;\"Customer; Site B\";;1,50;Platform;Customer visit;Kickoff\n"
+        );
+    }
+
+    #[test]
+    fn test_expenses_csv() {
+        let expense = Expense {
+            id: 1,
+            date: NaiveDate::from_ymd_opt(2024, 10, 3).unwrap(),
+            amount_cents: 4_250,
+            currency: "EUR".to_string(),
+            description: "Train; return".to_string(),
+            receipt_path: String::new(),
+        };
+        let path = std::env::temp_dir().join("chronos-log-test-expenses.csv");
+        assert_eq!(write_expenses_csv(&path, &[expense]).unwrap(), 1);
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(
+            text,
+            "Date;Amount;Currency;Description;Receipt\n\
+             2024-10-03;42,50;EUR;\"Train; return\";\n"
+        );
+    }
+
+    #[test]
+    fn test_catalog_round_trip() {
+        let rows = vec![
+            CatalogRow {
+                project_code: "P100".to_string(),
+                project_name: "Platform; core".to_string(),
+                activity_code: String::new(),
+                activity_name: String::new(),
+                valid_from: None,
+                valid_to: None,
+                project_uuid: "6f1c2b9e-8f5a-4c1d-9e3b-2a7d4c8e1f00".to_string(),
+                activity_uuid: String::new(),
+            },
+            CatalogRow {
+                project_code: "P100".to_string(),
+                project_name: String::new(),
+                activity_code: "A1".to_string(),
+                activity_name: "Development".to_string(),
+                valid_from: NaiveDate::from_ymd_opt(2024, 1, 1),
+                valid_to: NaiveDate::from_ymd_opt(2024, 12, 31),
+                project_uuid: "6f1c2b9e-8f5a-4c1d-9e3b-2a7d4c8e1f00".to_string(),
+                activity_uuid: "0b4e7a1c-3d2f-4e6a-8b9c-5d1e2f3a4b5c".to_string(),
+            },
+        ];
+        let path = std::env::temp_dir().join("chronos-log-test-catalog.csv");
+        assert_eq!(write_catalog_csv(&path, &rows).unwrap(), 2);
+        let parsed = crate::catalog::load_catalog(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(parsed, rows);
+    }
+
+    #[test]
+    fn test_week_html() {
+        let first = NaiveDate::from_ymd_opt(2024, 10, 7).unwrap();
+        let entry = crate::database::TimeEntry::for_test(1, first, 90, "");
+        let grid = WeekGrid::new(first, &[1], &[entry], |_| {
+            ("R&D <core>".to_string(), "Development".to_string())
+        });
+        let html = render_week_html(&grid, DurationStyle::default());
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<h1>Week 41, 07 Oct – 13 Oct 2024</h1>"));
+        assert!(html.contains("R&amp;D &lt;core&gt;"));
+        assert!(html.contains(PROJECT_COLORS[0]));
+        assert_eq!(html.matches("01:30").count(), 4); // Cell, row, day and week totals
+    }
+}
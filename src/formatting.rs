@@ -0,0 +1,241 @@
+// src/formatting.rs
+// Duration formatting shared by the UI, exports and clipboard copies
+
+use serde::{Deserialize, Serialize};
+
+/// How durations are displayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DurationStyle {
+    /// `01:30`
+    #[default]
+    Clock,
+    /// `1:30`
+    ShortClock,
+    /// `1h 30m`
+    HoursMinutes,
+    /// `90 min`
+    Minutes,
+    /// `1,50` (decimal hours)
+    Decimal,
+}
+
+impl DurationStyle {
+    pub const ALL: [DurationStyle; 5] = [
+        DurationStyle::Clock,
+        DurationStyle::ShortClock,
+        DurationStyle::HoursMinutes,
+        DurationStyle::Minutes,
+        DurationStyle::Decimal,
+    ];
+
+    /// Name shown in settings, with an example
+    pub fn label(self) -> &'static str {
+        match self {
+            DurationStyle::Clock => "01:30",
+            DurationStyle::ShortClock => "1:30",
+            DurationStyle::HoursMinutes => "1h 30m",
+            DurationStyle::Minutes => "90 min",
+            DurationStyle::Decimal => "1,50",
+        }
+    }
+
+    /// Format a duration in this style
+    pub fn format(self, minutes: i32) -> String {
+        match self {
+            DurationStyle::Clock => format_minutes_to_time(minutes),
+            DurationStyle::ShortClock => {
+                let sign = if minutes < 0 { "-" } else { "" };
+                format!("{}{}:{:02}", sign, minutes.abs() / 60, minutes.abs() % 60)
+            }
+            DurationStyle::HoursMinutes => format_hours_minutes(minutes),
+            DurationStyle::Minutes => format!("{} min", minutes),
+            DurationStyle::Decimal => format_minutes_to_decimal(minutes),
+        }
+    }
+
+    /// Format a total that may span several days (weekly or monthly reports).
+    /// Clock styles switch to `37h 30m` from 24 hours, since `25:00` reads as a
+    /// time of day and is misread by spreadsheets.
+    pub fn format_total(self, minutes: i32) -> String {
+        match self {
+            DurationStyle::Clock | DurationStyle::ShortClock if minutes.abs() >= 24 * 60 => {
+                format_hours_minutes(minutes)
+            }
+            _ => self.format(minutes),
+        }
+    }
+}
+
+/// How amounts of money are displayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MoneyStyle {
+    /// `1 234,50 EUR`
+    #[default]
+    CommaSuffix,
+    /// `EUR 1,234.50`
+    PointPrefix,
+}
+
+impl MoneyStyle {
+    pub const ALL: [MoneyStyle; 2] = [MoneyStyle::CommaSuffix, MoneyStyle::PointPrefix];
+
+    /// Name shown in settings, with an example
+    pub fn label(self) -> &'static str {
+        match self {
+            MoneyStyle::CommaSuffix => "1 234,50 EUR",
+            MoneyStyle::PointPrefix => "EUR 1,234.50",
+        }
+    }
+
+    /// Format an amount given in cents with its currency
+    pub fn format(self, cents: i64, currency: &str) -> String {
+        let (group, decimal) = match self {
+            MoneyStyle::CommaSuffix => (' ', ','),
+            MoneyStyle::PointPrefix => (',', '.'),
+        };
+        let digits = (cents.abs() / 100).to_string();
+        let mut units = String::new();
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                units.push(group);
+            }
+            units.push(digit);
+        }
+        let sign = if cents < 0 { "-" } else { "" };
+        let amount = format!("{}{}{}{:02}", sign, units, decimal, cents.abs() % 100);
+        match self {
+            MoneyStyle::CommaSuffix => format!("{} {}", amount, currency),
+            MoneyStyle::PointPrefix => format!("{} {}", currency, amount),
+        }
+    }
+}
+
+/// Format minutes to "HH:MM" string ("-HH:MM" when negative)
+pub fn format_minutes_to_time(total_minutes: i32) -> String {
+    let sign = if total_minutes < 0 { "-" } else { "" };
+    let hours = total_minutes.abs() / 60;
+    let minutes = total_minutes.abs() % 60;
+    format!("{}{:02}:{:02}", sign, hours, minutes)
+}
+
+/// Format minutes to decimal hours with Swedish comma separator (e.g., "1,5" for 90 minutes)
+pub fn format_minutes_to_decimal(total_minutes: i32) -> String {
+    let hours = total_minutes as f64 / 60.0;
+    // Format with 2 decimal places and replace . with ,
+    format!("{:.2}", hours).replace('.', ",")
+}
+
+/// Format minutes as hours and minutes, e.g. `1h 30m`, `2h` or `45m`
+pub fn format_hours_minutes(total_minutes: i32) -> String {
+    let sign = if total_minutes < 0 { "-" } else { "" };
+    match (total_minutes.abs() / 60, total_minutes.abs() % 60) {
+        (0, m) => format!("{}{}m", sign, m),
+        (h, 0) => format!("{}{}h", sign, h),
+        (h, m) => format!("{}{}h {}m", sign, h, m),
+    }
+}
+
+/// Format an amount of money given in cents, e.g. `12,50`
+pub fn format_amount(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    format!("{}{},{:02}", sign, cents.abs() / 100, cents.abs() % 100)
+}
+
+/// Parse an amount such as `12,50`, `12.5` or `12` into cents
+pub fn parse_amount(text: &str) -> Option<i64> {
+    let text = text.trim().replace(',', ".");
+    let (units, fraction) = text.split_once('.').unwrap_or((&text, ""));
+    if units.is_empty() || fraction.len() > 2 || !units.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    if !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let cents = format!("{:0<2}", fraction).parse::<i64>().ok()?;
+    units
+        .parse::<i64>()
+        .ok()?
+        .checked_mul(100)?
+        .checked_add(cents)
+}
+
+/// Format a distance with one decimal and a comma, e.g. `42,5`
+pub fn format_kilometers(kilometers: f64) -> String {
+    format!("{:.1}", kilometers).replace('.', ",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_money_style() {
+        assert_eq!(
+            MoneyStyle::CommaSuffix.format(123_450, "EUR"),
+            "1 234,50 EUR"
+        );
+        assert_eq!(
+            MoneyStyle::PointPrefix.format(123_450, "EUR"),
+            "EUR 1,234.50"
+        );
+        assert_eq!(MoneyStyle::CommaSuffix.format(5, "SEK"), "0,05 SEK");
+        assert_eq!(
+            MoneyStyle::PointPrefix.format(-100_000_000, "USD"),
+            "USD -1,000,000.00"
+        );
+    }
+
+    #[test]
+    fn test_format_minutes_to_time() {
+        assert_eq!(format_minutes_to_time(30), "00:30");
+        assert_eq!(format_minutes_to_time(60), "01:00");
+        assert_eq!(format_minutes_to_time(150), "02:30");
+        assert_eq!(format_minutes_to_time(-75), "-01:15");
+    }
+
+    #[test]
+    fn test_amounts() {
+        assert_eq!(format_amount(1250), "12,50");
+        assert_eq!(format_amount(5), "0,05");
+        assert_eq!(format_amount(-300), "-3,00");
+        assert_eq!(parse_amount("12,50"), Some(1250));
+        assert_eq!(parse_amount(" 12.5 "), Some(1250));
+        assert_eq!(parse_amount("7"), Some(700));
+        assert_eq!(parse_amount("0,05"), Some(5));
+        assert_eq!(parse_amount("1,234"), None);
+        assert_eq!(parse_amount("-3"), None);
+        assert_eq!(parse_amount("ten"), None);
+        assert_eq!(parse_amount(""), None);
+    }
+
+    #[test]
+    fn test_format_minutes_to_decimal() {
+        assert_eq!(format_minutes_to_decimal(30), "0,50");
+        assert_eq!(format_minutes_to_decimal(60), "1,00");
+        assert_eq!(format_minutes_to_decimal(90), "1,50");
+        assert_eq!(format_minutes_to_decimal(150), "2,50");
+        assert_eq!(format_minutes_to_decimal(480), "8,00");
+        assert_eq!(format_minutes_to_decimal(15), "0,25");
+        assert_eq!(format_minutes_to_decimal(45), "0,75");
+    }
+
+    #[test]
+    fn test_duration_styles() {
+        let formatted: Vec<String> = DurationStyle::ALL.iter().map(|s| s.format(90)).collect();
+        assert_eq!(formatted, ["01:30", "1:30", "1h 30m", "90 min", "1,50"]);
+        assert_eq!(DurationStyle::ShortClock.format(-75), "-1:15");
+        assert_eq!(DurationStyle::HoursMinutes.format(120), "2h");
+        assert_eq!(DurationStyle::HoursMinutes.format(45), "45m");
+    }
+
+    #[test]
+    fn test_format_total_over_a_day() {
+        assert_eq!(DurationStyle::Clock.format_total(1439), "23:59");
+        assert_eq!(DurationStyle::Clock.format_total(2250), "37h 30m");
+        assert_eq!(DurationStyle::ShortClock.format_total(1500), "25h");
+        assert_eq!(DurationStyle::Minutes.format_total(2250), "2250 min");
+        assert_eq!(DurationStyle::Decimal.format_total(2250), "37,50");
+    }
+}
@@ -0,0 +1,312 @@
+// src/journal.rs
+// Write-ahead journal for new time entries, so a failed database write is retried
+// instead of lost
+
+use crate::database::{Database, DatabaseError, EntryKind, Trip};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// A time entry submitted but not yet confirmed written to the database
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingEntry {
+    /// Assigned when journaled; the database skips an entry whose uuid it already has,
+    /// so replaying the journal never writes an entry twice
+    #[serde(default)]
+    pub uuid: String,
+    pub activity_type_id: i64,
+    pub date: NaiveDate,
+    pub minutes: i32,
+    pub comment: String,
+    #[serde(default)]
+    pub kind: EntryKind,
+    #[serde(default)]
+    pub reason: String,
+    /// Trip details of a travel entry
+    #[serde(default)]
+    pub trip: Option<Trip>,
+    /// Billable override; `None` follows the activity's default
+    #[serde(default)]
+    pub is_billable: Option<bool>,
+}
+
+/// Result of writing the journal to the database
+#[derive(Debug, Default)]
+pub struct FlushOutcome {
+    pub written: usize,
+    /// Entries the database refused for good (locked day, deleted activity, ...),
+    /// removed from the journal
+    pub rejected: Vec<(PendingEntry, DatabaseError)>,
+    /// First error of the entries kept for another attempt
+    pub retry_error: Option<DatabaseError>,
+    /// The journal file could not be updated after writing
+    pub journal_error: Option<std::io::Error>,
+}
+
+/// Pending entries, mirrored to a JSON-lines file
+#[derive(Debug)]
+pub struct Journal {
+    path: PathBuf,
+    pending: Vec<PendingEntry>,
+    /// Lines of the file that could not be read when it was opened
+    pub unreadable: usize,
+}
+
+impl Journal {
+    /// Open the journal file, loading entries left over from a previous run.
+    /// Unreadable lines are skipped and counted.
+    pub fn open(path: &Path) -> Self {
+        let text = std::fs::read_to_string(path).unwrap_or_default();
+        let lines: Vec<&str> = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .collect();
+        let mut pending: Vec<PendingEntry> = lines
+            .iter()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+        // Entries journaled before uuids were assigned
+        for entry in pending.iter_mut().filter(|e| e.uuid.is_empty()) {
+            entry.uuid = new_uuid().unwrap_or_default();
+        }
+        Self {
+            path: path.to_path_buf(),
+            unreadable: lines.len() - pending.len(),
+            pending,
+        }
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn pending(&self) -> &[PendingEntry] {
+        &self.pending
+    }
+
+    /// Drop a pending entry without writing it
+    pub fn discard(&mut self, index: usize) -> std::io::Result<()> {
+        if index < self.pending.len() {
+            self.pending.remove(index);
+            self.persist()?;
+        }
+        Ok(())
+    }
+
+    /// Record an entry before it is written to the database
+    pub fn push(&mut self, mut entry: PendingEntry) -> std::io::Result<()> {
+        if entry.uuid.is_empty() {
+            entry.uuid = new_uuid()?;
+        }
+        self.pending.push(entry);
+        self.persist()
+    }
+
+    /// Write pending entries to the database. Entries that fail with a retryable error
+    /// stay in the journal for the next attempt; the others are dropped and reported.
+    /// Entries an earlier attempt already wrote are dropped without writing them again.
+    pub fn flush(&mut self, db: &Database) -> FlushOutcome {
+        let mut outcome = FlushOutcome::default();
+        self.pending
+            .retain(|entry| match db.create_journaled_entry(entry) {
+                Ok(_) => {
+                    outcome.written += 1;
+                    false
+                }
+                Err(e) if e.is_retryable() => {
+                    outcome.retry_error.get_or_insert(e);
+                    true
+                }
+                Err(e) => {
+                    outcome.rejected.push((entry.clone(), e));
+                    false
+                }
+            });
+
+        if outcome.written > 0 || !outcome.rejected.is_empty() {
+            outcome.journal_error = self.persist().err();
+        }
+        outcome
+    }
+
+    /// Rewrite the journal file (via a temporary file, so it is never half-written)
+    fn persist(&self) -> std::io::Result<()> {
+        let tmp_path = self.path.with_extension("tmp");
+        {
+            let mut file = std::fs::File::create(&tmp_path)?;
+            for entry in &self.pending {
+                let line = serde_json::to_string(entry).map_err(std::io::Error::other)?;
+                writeln!(file, "{}", line)?;
+            }
+            file.sync_all()?;
+        }
+        std::fs::rename(&tmp_path, &self.path)
+    }
+}
+
+/// A random version 4 uuid, formatted like the ones the database assigns
+fn new_uuid() -> std::io::Result<String> {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).map_err(std::io::Error::other)?;
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    Ok(format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    ))
+}
+
+/// Get the journal file path
+pub fn get_journal_path() -> PathBuf {
+    // Keep it local, next to the default database location
+    if let Some(data_dir) = dirs::data_local_dir() {
+        let app_dir = data_dir.join("chronos-log");
+        if std::fs::create_dir_all(&app_dir).is_ok() {
+            return app_dir.join("pending_entries.jsonl");
+        }
+    }
+
+    // Fallback to current directory
+    PathBuf::from("chronos_log_pending_entries.jsonl")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_journal_survives_restart() {
+        let path = std::env::temp_dir().join("chronos-log-test-journal.jsonl");
+        std::fs::remove_file(&path).ok();
+
+        let mut journal = Journal::open(&path);
+        journal
+            .push(PendingEntry {
+                uuid: String::new(),
+                activity_type_id: 1,
+                date: NaiveDate::from_ymd_opt(2024, 10, 3).unwrap(),
+                minutes: 30,
+                comment: "Standup".to_string(),
+                kind: EntryKind::Work,
+                reason: String::new(),
+                trip: None,
+                is_billable: None,
+            })
+            .unwrap();
+
+        // Not flushed: still there after reopening
+        let mut journal = Journal::open(&path);
+        assert_eq!(journal.pending_count(), 1);
+
+        let db = Database::new_in_memory().unwrap();
+        let project_id = db.create_project("P", "", "").unwrap();
+        db.create_activity_type(project_id, "A", "").unwrap();
+        assert_eq!(journal.flush(&db).written, 1);
+        assert_eq!(Journal::open(&path).pending_count(), 0);
+
+        let date = NaiveDate::from_ymd_opt(2024, 10, 3).unwrap();
+        assert_eq!(db.get_time_entries_for_date(date).unwrap().len(), 1);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_journal_drops_rejected_entries() {
+        let path = std::env::temp_dir().join("chronos-log-test-journal-rejected.jsonl");
+        std::fs::remove_file(&path).ok();
+        let db = Database::new_in_memory().unwrap();
+        let project_id = db.create_project("P", "", "").unwrap();
+        let activity_id = db.create_activity_type(project_id, "A", "").unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 10, 3).unwrap();
+        db.lock_month(date).unwrap();
+
+        let mut journal = Journal::open(&path);
+        let entry = |activity_type_id, date| PendingEntry {
+            uuid: String::new(),
+            activity_type_id,
+            date,
+            minutes: 30,
+            comment: "x".to_string(),
+            kind: EntryKind::Work,
+            reason: String::new(),
+            trip: None,
+            is_billable: None,
+        };
+        journal.push(entry(activity_id, date)).unwrap();
+        journal
+            .push(entry(
+                activity_id + 1,
+                NaiveDate::from_ymd_opt(2024, 11, 5).unwrap(),
+            ))
+            .unwrap();
+        journal
+            .push(entry(
+                activity_id,
+                NaiveDate::from_ymd_opt(2024, 11, 4).unwrap(),
+            ))
+            .unwrap();
+
+        let outcome = journal.flush(&db);
+        assert_eq!(outcome.written, 1);
+        assert!(outcome.retry_error.is_none());
+        assert!(matches!(
+            outcome.rejected[0].1,
+            DatabaseError::MonthLocked(_)
+        ));
+        assert!(matches!(
+            outcome.rejected[1].1,
+            DatabaseError::ActivityNotFound(_)
+        ));
+        assert_eq!(Journal::open(&path).pending_count(), 0);
+
+        journal.push(entry(activity_id, date)).unwrap();
+        journal.discard(0).unwrap();
+        assert_eq!(Journal::open(&path).pending_count(), 0);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_journal_replay_is_idempotent() {
+        let path = std::env::temp_dir().join("chronos-log-test-journal-replay.jsonl");
+        std::fs::remove_file(&path).ok();
+        let db = Database::new_in_memory().unwrap();
+        let project_id = db.create_project("P", "", "").unwrap();
+        let activity_id = db.create_activity_type(project_id, "A", "").unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 10, 3).unwrap();
+
+        let mut journal = Journal::open(&path);
+        journal
+            .push(PendingEntry {
+                uuid: String::new(),
+                activity_type_id: activity_id,
+                date,
+                minutes: 30,
+                comment: "Standup".to_string(),
+                kind: EntryKind::Work,
+                reason: String::new(),
+                trip: None,
+                is_billable: Some(false),
+            })
+            .unwrap();
+        let uuid = journal.pending()[0].uuid.clone();
+        assert_eq!(uuid.len(), 36);
+
+        // Written, then the app died before the journal was rewritten
+        db.create_journaled_entry(&journal.pending()[0]).unwrap();
+        let mut journal = Journal::open(&path);
+        assert_eq!(journal.pending()[0].uuid, uuid);
+        assert_eq!(journal.flush(&db).written, 1);
+        assert_eq!(journal.pending_count(), 0);
+
+        let entries = db.get_time_entries_for_date(date).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].uuid, uuid);
+        assert!(!entries[0].is_billable);
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,15 @@
+// src/lib.rs
+// Data layer of the Work Tracker: database, import/export and settings.
+// The GUI (app and ui) lives in the binary.
+
+pub mod backup;
+pub mod catalog;
+pub mod clipboard;
+pub mod database;
+pub mod export;
+pub mod formatting;
+pub mod journal;
+pub mod links;
+pub mod models;
+pub mod reports;
+pub mod settings;
@@ -0,0 +1,120 @@
+// src/links.rs
+// Ticket reference detection and link building for comments
+
+use crate::settings::TicketLinkRule;
+use regex::Regex;
+
+/// Part of a comment: plain text or a detected ticket reference
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommentSegment<'a> {
+    Text(&'a str),
+    Link { text: &'a str, url: String },
+}
+
+/// Compiled ticket link rules
+#[derive(Debug, Clone, Default)]
+pub struct TicketLinker {
+    rules: Vec<(Regex, String)>,
+}
+
+impl TicketLinker {
+    /// Compile link rules, skipping rules with an empty or invalid pattern
+    pub fn new(rules: &[TicketLinkRule]) -> Self {
+        Self {
+            rules: rules
+                .iter()
+                .filter(|r| !r.pattern.trim().is_empty())
+                .filter_map(|r| {
+                    Regex::new(&r.pattern)
+                        .ok()
+                        .map(|re| (re, r.url_template.clone()))
+                })
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Split a comment into text and link segments
+    pub fn segments<'a>(&self, text: &'a str) -> Vec<CommentSegment<'a>> {
+        // Collect matches from all rules, earliest first
+        let mut matches: Vec<(usize, usize, String)> = Vec::new();
+        for (pattern, url_template) in &self.rules {
+            for m in pattern.find_iter(text) {
+                if m.is_empty() {
+                    continue;
+                }
+                matches.push((
+                    m.start(),
+                    m.end(),
+                    url_template.replace("{ticket}", m.as_str()),
+                ));
+            }
+        }
+        matches.sort_by_key(|(start, end, _)| (*start, std::cmp::Reverse(*end)));
+
+        let mut segments = Vec::new();
+        let mut pos = 0;
+        for (start, end, url) in matches {
+            // Skip matches overlapping an earlier one
+            if start < pos {
+                continue;
+            }
+            if start > pos {
+                segments.push(CommentSegment::Text(&text[pos..start]));
+            }
+            segments.push(CommentSegment::Link {
+                text: &text[start..end],
+                url,
+            });
+            pos = end;
+        }
+        if pos < text.len() {
+            segments.push(CommentSegment::Text(&text[pos..]));
+        }
+        segments
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str, url_template: &str) -> TicketLinkRule {
+        TicketLinkRule {
+            pattern: pattern.to_string(),
+            url_template: url_template.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_segments() {
+        let linker = TicketLinker::new(&[
+            rule(r"PROJ-\d+", "https://jira.example.com/browse/{ticket}"),
+            rule(r"INC\d+", "https://sn.example.com/{ticket}"),
+            rule(r"[", "invalid"),
+        ]);
+
+        assert_eq!(
+            linker.segments("Fixed PROJ-12 for INC0042"),
+            vec![
+                CommentSegment::Text("Fixed "),
+                CommentSegment::Link {
+                    text: "PROJ-12",
+                    url: "https://jira.example.com/browse/PROJ-12".to_string(),
+                },
+                CommentSegment::Text(" for "),
+                CommentSegment::Link {
+                    text: "INC0042",
+                    url: "https://sn.example.com/INC0042".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            linker.segments("No tickets"),
+            vec![CommentSegment::Text("No tickets")]
+        );
+    }
+}
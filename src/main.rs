@@ -4,11 +4,13 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // Hide console on Windows in release
 
 mod app;
-mod database;
-mod models;
 mod ui;
 
 use app::WorkTrackerApp;
+use chronos_log::{
+    backup, catalog, clipboard, database, export, formatting, journal, links, models, reports,
+    settings,
+};
 use eframe::egui;
 
 fn main() -> eframe::Result<()> {
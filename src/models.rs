@@ -1,27 +1,37 @@
 // src/models.rs
 // Shared data structures and application state
 
-use crate::database::{ActivityType, Project, TimeEntry};
-use chrono::NaiveDate;
+use crate::catalog::CatalogChange;
+use crate::database::{
+    ActiveState, ActivityType, DayType, DeletedActivity, EntryDetail, EntryKind, EntryTemplate,
+    Expense, ExportRecord, Project, ProjectLink, ProjectRecord, TimeEntry, TravelEntry, Trip,
+    Usage,
+};
+use crate::formatting::{format_minutes_to_time, DurationStyle};
+use crate::links::TicketLinker;
+use crate::settings::{CloseOutSettings, EntryField, WeekStart};
+use chrono::{Datelike, Months, NaiveDate};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
 
 /// Current view/tab in the application
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum AppView {
+    #[default]
     TimeTracking,
     ManageProjects,
     ManageActivities,
     DailySummary,
-}
-
-impl Default for AppView {
-    fn default() -> Self {
-        AppView::TimeTracking
-    }
+    MonthlyReport,
+    Timesheet,
+    Reports,
+    Settings,
 }
 
 /// Dialog state for editing/creating items
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub enum DialogState {
+    #[default]
     None,
     AddProject,
     EditProject(Project),
@@ -29,21 +39,403 @@ pub enum DialogState {
     EditActivity(ActivityType),
     EditTimeEntry(TimeEntry),
     ConfirmDelete(DeleteTarget),
+    ConfirmProjectState(ProjectStateChange),
+    ImportCatalog(CatalogImport),
+    CloseDay(CloseDay),
+    ConfirmUnlock(UnlockTarget),
+    MonthEnd(MonthEndWizard),
+    ExportEntries(EntryExport),
+    AddExpense(ExpenseForm),
+    RevertDay(RevertDay),
+    CopyDay(CopyDay),
+    DistributeTime(DistributeTime),
     ErrorMessage(String),
 }
 
-impl Default for DialogState {
-    fn default() -> Self {
-        DialogState::None
+/// Locked period the user asked to edit again
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnlockTarget {
+    /// The locked month containing the date
+    Month(NaiveDate),
+    /// A day past the edit window
+    Day(NaiveDate),
+}
+
+/// Day being closed, with its entries as of opening the dialog
+#[derive(Debug, Clone)]
+pub struct CloseDay {
+    pub date: NaiveDate,
+    pub entries: Vec<TimeEntry>,
+}
+
+/// Snapshots offered for reverting one day's entries
+#[derive(Debug, Clone)]
+pub struct RevertDay {
+    pub date: NaiveDate,
+    /// How the day looks now
+    pub entries: usize,
+    pub minutes: i32,
+    pub points: Vec<RevertPoint>,
+    /// Index into `points`
+    pub selected: Option<usize>,
+}
+
+/// Entries of an earlier day offered for copying to the selected day
+#[derive(Debug, Clone)]
+pub struct CopyDay {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub entries: Vec<TimeEntry>,
+    pub clear_comments: bool,
+}
+
+/// How the distribute dialog splits time across activities
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SplitMode {
+    #[default]
+    Even,
+    /// In proportion to the time already logged on each activity that day
+    Proportional,
+}
+
+/// Expense being recorded on a day; amount as typed
+#[derive(Debug, Clone)]
+pub struct ExpenseForm {
+    pub date: NaiveDate,
+    pub amount: String,
+    pub currency: String,
+    pub description: String,
+    pub receipt_path: String,
+}
+
+impl ExpenseForm {
+    pub fn new(date: NaiveDate, currency: &str) -> Self {
+        Self {
+            date,
+            amount: String::new(),
+            currency: currency.to_string(),
+            description: String::new(),
+            receipt_path: String::new(),
+        }
+    }
+
+    pub fn amount_error(&self) -> Option<String> {
+        crate::formatting::parse_amount(&self.amount)
+            .is_none()
+            .then(|| "Enter an amount such as 12,50".to_string())
+    }
+
+    pub fn description_error(&self) -> Option<String> {
+        self.description
+            .trim()
+            .is_empty()
+            .then(|| "Description required".to_string())
     }
+
+    /// The expense to store, once the form is valid
+    pub fn to_expense(&self) -> Option<Expense> {
+        if self.description_error().is_some() || self.currency.trim().is_empty() {
+            return None;
+        }
+        Some(Expense {
+            id: 0,
+            date: self.date,
+            amount_cents: crate::formatting::parse_amount(&self.amount)?,
+            currency: self.currency.trim().to_uppercase(),
+            description: self.description.trim().to_string(),
+            receipt_path: self.receipt_path.trim().to_string(),
+        })
+    }
+}
+
+/// Remaining time of a day, split across chosen activities
+#[derive(Debug, Clone)]
+pub struct DistributeTime {
+    pub date: NaiveDate,
+    pub time_str: String,
+    /// Chosen activities, in the order they were ticked
+    pub activities: Vec<i64>,
+    pub mode: SplitMode,
+    pub comment: String,
+    /// Minutes already worked that day per activity, without breaks and
+    /// negative adjustments; the weights of a proportional split
+    pub logged: HashMap<i64, u32>,
 }
 
+/// How the day looked in one snapshot
+#[derive(Debug, Clone)]
+pub struct RevertPoint {
+    pub snapshot: crate::backup::Snapshot,
+    pub entries: usize,
+    pub minutes: i32,
+}
+
+/// Database change requested by the UI. The app runs queued actions once per
+/// frame, reports failures and records how to undo them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppAction {
+    CloseDay(NaiveDate),
+    ReopenDay(NaiveDate),
+    LockMonth(NaiveDate),
+    UnlockMonth(NaiveDate),
+    /// Allow editing a day past the edit window until the app is closed
+    UnlockDay(NaiveDate),
+    RelockDay(NaiveDate),
+    DeactivateActivity(i64),
+    ReactivateActivity(i64),
+    CreateEntryTemplate {
+        weekday: u32,
+        activity_type_id: i64,
+        minutes: i32,
+        comment: String,
+    },
+    DeleteEntryTemplate(EntryTemplate),
+    RestoreBackup(std::path::PathBuf),
+    /// Delete the unused example projects and activities
+    RemoveSampleData,
+    RestoreSampleData {
+        projects: Vec<ProjectRecord>,
+        activities: Vec<DeletedActivity>,
+    },
+    /// Create a project with the add dialog's details and links
+    AddProject(ProjectRecord),
+    /// Save the edit dialog's name, description, icon, notes and links
+    UpdateProject(ProjectRecord),
+    /// Projects to delete, with their names for messages
+    DeleteProjects(Vec<(i64, String)>),
+    RestoreProjects(Vec<ProjectRecord>),
+    AddActivity {
+        project_id: i64,
+        name: String,
+        icon: String,
+    },
+    /// Save the edit dialog's fields of an activity
+    UpdateActivity(ActivityType),
+    /// Activities to delete, with their names for messages
+    DeleteActivities(Vec<(i64, String)>),
+    RestoreActivities(Vec<DeletedActivity>),
+    /// Activate or deactivate a project, optionally with its activities
+    SetProjectActive {
+        id: i64,
+        name: String,
+        active: bool,
+        include_activities: bool,
+    },
+    /// Activate, deactivate or archive the selected projects, all or none.
+    /// Deleting goes through `DeleteProjects`.
+    SetProjectsState {
+        items: Vec<(i64, String)>,
+        action: BulkAction,
+    },
+    /// Activate, deactivate or archive the selected activities, all or none
+    SetActivitiesState {
+        items: Vec<(i64, String)>,
+        action: BulkAction,
+    },
+    /// Deactivate the activities still active under inactive projects
+    DeactivateActivitiesOfInactiveProjects,
+    /// Put back the active and archived flags of projects and activities
+    RestoreStates {
+        projects: Vec<ActiveState>,
+        activities: Vec<ActiveState>,
+    },
+    /// Apply the changes selected in the catalog import dialog
+    ImportCatalog(Vec<CatalogChange>),
+    UpdateTimeEntry {
+        id: i64,
+        minutes: i32,
+        comment: String,
+        reason: String,
+        is_billable: bool,
+    },
+    DeleteTimeEntries(Vec<i64>),
+    RestoreTimeEntries(Vec<TimeEntry>),
+    /// Create work entries on `date`, one per (activity, minutes) pair
+    AddEntries {
+        date: NaiveDate,
+        entries: Vec<(i64, i32)>,
+        comment: String,
+    },
+    /// Duplicate the work entries of one day into another
+    CopyDay {
+        from: NaiveDate,
+        to: NaiveDate,
+        clear_comments: bool,
+    },
+    /// Replace the entries of a day with those in a backup
+    RevertDay {
+        date: NaiveDate,
+        snapshot: std::path::PathBuf,
+    },
+    /// Put back the entries a day had before it was reverted
+    ReplaceDayEntries {
+        date: NaiveDate,
+        entries: Vec<TimeEntry>,
+    },
+    AddExpense(Expense),
+    DeleteExpense(i64),
+    RestoreExpense(Expense),
+    /// Register a day type on a date, or clear it with `None`
+    SetDayType {
+        date: NaiveDate,
+        day_type: Option<DayType>,
+    },
+    /// Revert the most recent action on the undo stack
+    Undo,
+    /// Run the most recently undone action again
+    Redo,
+}
+
+impl AppAction {
+    /// Short description for messages and the undo button
+    pub fn describe(&self) -> String {
+        match self {
+            AppAction::CloseDay(date) => format!("Close {}", date),
+            AppAction::ReopenDay(date) => format!("Reopen {}", date),
+            AppAction::LockMonth(date) => format!("Lock {}", date.format("%B %Y")),
+            AppAction::UnlockMonth(date) => format!("Unlock {}", date.format("%B %Y")),
+            AppAction::UnlockDay(date) => format!("Unlock {}", date),
+            AppAction::RelockDay(date) => format!("Lock {}", date),
+            AppAction::DeactivateActivity(_) => "Deactivate activity".to_string(),
+            AppAction::ReactivateActivity(_) => "Reactivate activity".to_string(),
+            AppAction::CreateEntryTemplate { .. } => "Add template".to_string(),
+            AppAction::DeleteEntryTemplate(_) => "Delete template".to_string(),
+            AppAction::RestoreBackup(_) => "Restore backup".to_string(),
+            AppAction::RemoveSampleData => "Remove sample data".to_string(),
+            AppAction::RestoreSampleData { .. } => "Restore sample data".to_string(),
+            AppAction::AddProject(record) => format!("Add '{}'", record.project.name),
+            AppAction::UpdateProject(record) => format!("Edit '{}'", record.project.name),
+            AppAction::DeleteProjects(items) => match items.as_slice() {
+                [(_, name)] => format!("Delete '{}'", name),
+                items => format!("Delete {} projects", items.len()),
+            },
+            AppAction::RestoreProjects(_) => "Restore projects".to_string(),
+            AppAction::AddActivity { name, .. } => format!("Add '{}'", name),
+            AppAction::UpdateActivity(activity) => format!("Edit '{}'", activity.name),
+            AppAction::DeleteActivities(items) => match items.as_slice() {
+                [(_, name)] => format!("Delete '{}'", name),
+                items => format!("Delete {} activities", items.len()),
+            },
+            AppAction::RestoreActivities(_) => "Restore activities".to_string(),
+            AppAction::SetProjectActive { name, active, .. } => match active {
+                true => format!("Activate '{}'", name),
+                false => format!("Deactivate '{}'", name),
+            },
+            AppAction::SetProjectsState { items, action } => match items.as_slice() {
+                [(_, name)] => format!("{} '{}'", action.label(), name),
+                items => format!("{} {} projects", action.label(), items.len()),
+            },
+            AppAction::SetActivitiesState { items, action } => match items.as_slice() {
+                [(_, name)] => format!("{} '{}'", action.label(), name),
+                items => format!("{} {} activities", action.label(), items.len()),
+            },
+            AppAction::DeactivateActivitiesOfInactiveProjects => {
+                "Deactivate activities of inactive projects".to_string()
+            }
+            AppAction::RestoreStates { .. } => "Restore active states".to_string(),
+            AppAction::ImportCatalog(changes) => {
+                format!("Import {} catalog changes", changes.len())
+            }
+            AppAction::UpdateTimeEntry { .. } => "Edit entry".to_string(),
+            AppAction::DeleteTimeEntries(ids) => match ids.len() {
+                1 => "Delete entry".to_string(),
+                n => format!("Delete {} entries", n),
+            },
+            AppAction::RestoreTimeEntries(_) => "Restore entries".to_string(),
+            AppAction::AddEntries { entries, .. } => format!("Add {} entries", entries.len()),
+            AppAction::CopyDay { from, .. } => format!("Copy entries of {}", from),
+            AppAction::RevertDay { date, .. } => format!("Revert {}", date),
+            AppAction::ReplaceDayEntries { date, .. } => format!("Restore entries of {}", date),
+            AppAction::AddExpense(expense) => format!("Add expense '{}'", expense.description),
+            AppAction::DeleteExpense(_) => "Delete expense".to_string(),
+            AppAction::RestoreExpense(_) => "Restore expense".to_string(),
+            AppAction::SetDayType { date, day_type } => match day_type {
+                Some(day_type) => format!("Mark {} as {}", date, day_type.label()),
+                None => format!("Clear day type of {}", date),
+            },
+            AppAction::Undo => "Undo".to_string(),
+            AppAction::Redo => "Redo".to_string(),
+        }
+    }
+}
+
+/// How to revert an action that has run
+#[derive(Debug, Clone, PartialEq)]
+pub struct UndoStep {
+    /// Description of the original action
+    pub label: String,
+    pub inverse: AppAction,
+}
+
+/// Number of actions that can be undone (and redone)
+pub const UNDO_LIMIT: usize = 20;
+
 /// Target for deletion confirmation
 #[derive(Debug, Clone)]
 pub enum DeleteTarget {
     Project(i64, String),
     Activity(i64, String),
     TimeEntry(i64),
+    /// Several projects selected in the management view
+    Projects(Vec<(i64, String)>),
+    /// Several activities selected in the management view
+    Activities(Vec<(i64, String)>),
+}
+
+/// Action applied to every selected project or activity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkAction {
+    Activate,
+    Deactivate,
+    Archive,
+    Delete,
+}
+
+impl BulkAction {
+    pub fn label(self) -> &'static str {
+        match self {
+            BulkAction::Activate => "Activate",
+            BulkAction::Deactivate => "Deactivate",
+            BulkAction::Archive => "Archive",
+            BulkAction::Delete => "Delete",
+        }
+    }
+
+    /// Past tense, for the message after the action ran
+    pub fn done(self) -> &'static str {
+        match self {
+            BulkAction::Activate => "Activated",
+            BulkAction::Deactivate => "Deactivated",
+            BulkAction::Archive => "Archived",
+            BulkAction::Delete => "Deleted",
+        }
+    }
+}
+
+/// Pending activation/deactivation of a project, confirmed in a dialog
+#[derive(Debug, Clone)]
+pub struct ProjectStateChange {
+    pub project_id: i64,
+    pub project_name: String,
+    pub activate: bool,
+    /// Apply the same change to the project's activities
+    pub include_activities: bool,
+    /// Activities the change would apply to
+    pub activity_count: usize,
+}
+
+/// State of the catalog import dialog
+#[derive(Debug, Clone, Default)]
+pub struct CatalogImport {
+    pub path: String,
+    /// Changes from the last preview, `None` until previewed
+    pub changes: Option<Vec<CatalogChange>>,
+    /// Per-change include checkboxes, parallel to `changes`
+    pub included: Vec<bool>,
+    pub rows_parsed: usize,
+    /// Codes appearing on more than one catalog row
+    pub duplicates: Vec<String>,
+    pub error: Option<String>,
 }
 
 /// Form data for creating/editing a project
@@ -51,6 +443,14 @@ pub enum DeleteTarget {
 pub struct ProjectForm {
     pub name: String,
     pub description: String,
+    pub icon: String,
+    pub notes: String,
+    /// Reference links as (label, url)
+    pub links: Vec<(String, String)>,
+    /// Hourly rate as typed, empty for none
+    pub hourly_rate: String,
+    /// Budget in hours as typed, empty for none
+    pub budget: String,
 }
 
 impl ProjectForm {
@@ -58,28 +458,81 @@ impl ProjectForm {
         Self::default()
     }
 
-    pub fn from_project(project: &Project) -> Self {
+    pub fn from_project(project: &Project, links: &[&ProjectLink]) -> Self {
         Self {
             name: project.name.clone(),
             description: project.description.clone(),
+            icon: project.icon.clone(),
+            notes: project.notes.clone(),
+            links: links
+                .iter()
+                .map(|l| (l.label.clone(), l.url.clone()))
+                .collect(),
+            hourly_rate: project
+                .hourly_rate_cents
+                .map(crate::formatting::format_amount)
+                .unwrap_or_default(),
+            budget: project
+                .budget_minutes
+                .map(crate::formatting::format_minutes_to_decimal)
+                .unwrap_or_default(),
         }
     }
 
     pub fn clear(&mut self) {
         self.name.clear();
         self.description.clear();
+        self.icon.clear();
+        self.notes.clear();
+        self.links.clear();
+        self.hourly_rate.clear();
+        self.budget.clear();
+    }
+
+    /// The budget in minutes, `Ok(None)` when no budget is set
+    pub fn budget_minutes(&self) -> Result<Option<i32>, String> {
+        let budget = self.budget.trim();
+        if budget.is_empty() {
+            return Ok(None);
+        }
+        match crate::database::parse_time_to_minutes(&format!("{}h", budget)) {
+            Ok(minutes) if minutes > 0 => Ok(Some(minutes)),
+            _ => Err("Enter the budget in hours, such as 120 or 37,5".to_string()),
+        }
     }
 
     pub fn is_valid(&self) -> bool {
-        !self.name.trim().is_empty() && !self.description.trim().is_empty()
+        !self.name.trim().is_empty()
+            && !self.description.trim().is_empty()
+            && parse_hourly_rate(&self.hourly_rate).is_ok()
+            && self.budget_minutes().is_ok()
     }
 }
 
+/// An hourly rate as typed in the project and activity dialogs, `Ok(None)` when empty
+pub fn parse_hourly_rate(text: &str) -> Result<Option<i64>, String> {
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    crate::formatting::parse_amount(text)
+        .map(Some)
+        .ok_or_else(|| "Enter a rate such as 95,00".to_string())
+}
+
 /// Form data for creating/editing an activity type
 #[derive(Debug, Clone, Default)]
 pub struct ActivityForm {
     pub name: String,
     pub project_id: Option<i64>,
+    pub icon: String,
+    /// Monthly cap as HH:MM, empty for none
+    pub monthly_cap: String,
+    pub cost_center: String,
+    pub valid_from: Option<NaiveDate>,
+    pub valid_to: Option<NaiveDate>,
+    pub is_billable: bool,
+    /// Hourly rate as typed, empty to use the project's
+    pub hourly_rate: String,
 }
 
 impl ActivityForm {
@@ -91,12 +544,50 @@ impl ActivityForm {
         Self {
             name: activity.name.clone(),
             project_id: Some(activity.project_id),
+            icon: activity.icon.clone(),
+            monthly_cap: activity
+                .monthly_cap_minutes
+                .map(format_minutes_to_time)
+                .unwrap_or_default(),
+            cost_center: activity.cost_center.clone(),
+            valid_from: activity.valid_from,
+            valid_to: activity.valid_to,
+            is_billable: activity.is_billable,
+            hourly_rate: activity
+                .hourly_rate_cents
+                .map(crate::formatting::format_amount)
+                .unwrap_or_default(),
         }
     }
 
     pub fn clear(&mut self) {
         self.name.clear();
         self.project_id = None;
+        self.icon.clear();
+        self.monthly_cap.clear();
+        self.cost_center.clear();
+        self.valid_from = None;
+        self.valid_to = None;
+        self.is_billable = false;
+        self.hourly_rate.clear();
+    }
+
+    pub fn validity_error(&self) -> Option<String> {
+        match (self.valid_from, self.valid_to) {
+            (Some(from), Some(to)) if to < from => {
+                Some("Valid to must not be before valid from".to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// The monthly cap in minutes, `Ok(None)` when no cap is set
+    pub fn cap_minutes(&self) -> Result<Option<i32>, crate::database::DatabaseError> {
+        let cap = self.monthly_cap.trim();
+        if cap.is_empty() {
+            return Ok(None);
+        }
+        crate::database::parse_time_to_minutes(cap).map(Some)
     }
 
     pub fn is_valid(&self) -> bool {
@@ -110,6 +601,18 @@ pub struct TimeEntryForm {
     pub activity_type_id: Option<i64>,
     pub time_str: String,
     pub comment: String,
+    pub kind: EntryKind,
+    /// Reason for an adjustment entry
+    pub reason: String,
+    /// Trip details of a travel entry; kilometers as typed
+    pub trip_origin: String,
+    pub trip_destination: String,
+    pub kilometers: String,
+    /// Billable override; `None` follows the activity's default
+    pub is_billable: Option<bool>,
+    pub history: CommentHistory,
+    /// Field to focus on the next frame
+    pub focus_request: Option<EntryField>,
 }
 
 impl Default for TimeEntryForm {
@@ -118,6 +621,14 @@ impl Default for TimeEntryForm {
             activity_type_id: None,
             time_str: "00:30".to_string(),
             comment: String::new(),
+            kind: EntryKind::Work,
+            reason: String::new(),
+            trip_origin: String::new(),
+            trip_destination: String::new(),
+            kilometers: String::new(),
+            is_billable: None,
+            history: CommentHistory::default(),
+            focus_request: None,
         }
     }
 }
@@ -130,37 +641,246 @@ impl TimeEntryForm {
     pub fn from_entry(entry: &TimeEntry) -> Self {
         Self {
             activity_type_id: Some(entry.activity_type_id),
-            time_str: crate::database::format_minutes_to_time(entry.minutes),
+            time_str: format_minutes_to_time(entry.minutes),
             comment: entry.comment.clone(),
+            kind: entry.kind,
+            reason: entry.reason.clone(),
+            trip_origin: String::new(),
+            trip_destination: String::new(),
+            kilometers: String::new(),
+            is_billable: Some(entry.is_billable),
+            history: CommentHistory::default(),
+            focus_request: None,
         }
     }
 
-    pub fn clear(&mut self) {
+    /// Empty the form, leaving `default_minutes` in the time field
+    pub fn clear(&mut self, default_minutes: i32) {
         self.activity_type_id = None;
-        self.time_str = "00:30".to_string();
+        self.time_str = format_minutes_to_time(default_minutes);
         self.comment.clear();
+        self.kind = EntryKind::Work;
+        self.reason.clear();
+        self.trip_origin.clear();
+        self.trip_destination.clear();
+        self.kilometers.clear();
+        self.is_billable = None;
     }
 
-    pub fn is_valid(&self) -> bool {
-        self.activity_type_id.is_some()
-            && crate::database::parse_time_to_minutes(&self.time_str).is_ok()
-            && !self.comment.trim().is_empty()
+    /// Only adjustments may be negative, and they need a non-zero time and a reason.
+    /// No entry may be longer than `max_minutes`.
+    pub fn is_valid(&self, max_minutes: i32) -> bool {
+        self.activity_error().is_none()
+            && self
+                .time_error(max_minutes, DurationStyle::default())
+                .is_none()
+            && self.comment_error().is_none()
+            && self.reason_error().is_none()
+            && self.kilometers_error().is_none()
+    }
+
+    pub fn activity_error(&self) -> Option<String> {
+        self.activity_type_id
+            .is_none()
+            .then(|| "Activity required".to_string())
+    }
+
+    pub fn time_error(&self, max_minutes: i32, style: DurationStyle) -> Option<String> {
+        match (self.kind, self.get_minutes()) {
+            (_, None) => Some("Invalid time format — use HH:MM or 1.5h".to_string()),
+            (_, Some(minutes)) if minutes.abs() > max_minutes => Some(format!(
+                "An entry can be at most {} (see Settings)",
+                style.format(max_minutes)
+            )),
+            (EntryKind::Work | EntryKind::Break, Some(minutes)) if minutes < 0 => {
+                Some("Only adjustments can be negative".to_string())
+            }
+            (EntryKind::Adjustment, Some(0)) => {
+                Some("An adjustment needs a non-zero time".to_string())
+            }
+            _ => None,
+        }
+    }
+
+    pub fn comment_error(&self) -> Option<String> {
+        comment_error(&self.comment)
+    }
+
+    /// Adjustments must say why they were made
+    pub fn reason_error(&self) -> Option<String> {
+        (self.kind == EntryKind::Adjustment && self.reason.trim().is_empty())
+            .then(|| "Reason required".to_string())
+    }
+
+    /// Kilometers of a trip are optional, but must be a number when given
+    pub fn kilometers_error(&self) -> Option<String> {
+        (self.kind == EntryKind::Travel
+            && !self.kilometers.trim().is_empty()
+            && self.get_kilometers().is_none())
+        .then(|| "Kilometers must be a number".to_string())
+    }
+
+    pub fn get_kilometers(&self) -> Option<f64> {
+        self.kilometers
+            .trim()
+            .replace(',', ".")
+            .parse()
+            .ok()
+            .filter(|km: &f64| km.is_finite() && *km >= 0.0)
+    }
+
+    /// Trip details to store with a travel entry
+    pub fn trip(&self) -> Option<Trip> {
+        (self.kind == EntryKind::Travel).then(|| Trip {
+            origin: self.trip_origin.trim().to_string(),
+            destination: self.trip_destination.trim().to_string(),
+            kilometers: self.get_kilometers(),
+        })
     }
 
     pub fn get_minutes(&self) -> Option<i32> {
-        crate::database::parse_time_to_minutes(&self.time_str).ok()
+        crate::database::evaluate_time_expression(&self.time_str).ok()
+    }
+}
+
+/// Previous comments for an activity, navigated with Up/Down like a shell history
+#[derive(Debug, Clone, Default)]
+pub struct CommentHistory {
+    /// Activity the history was loaded for
+    pub activity_type_id: Option<i64>,
+    /// Most recent comment first
+    comments: Vec<String>,
+    index: Option<usize>,
+    /// Text typed before navigating into the history
+    draft: String,
+}
+
+impl CommentHistory {
+    /// Whether the history must be (re)loaded for the given activity
+    pub fn needs_reload(&self, activity_type_id: Option<i64>) -> bool {
+        self.activity_type_id != activity_type_id
+    }
+
+    pub fn set(&mut self, activity_type_id: Option<i64>, comments: Vec<String>) {
+        self.activity_type_id = activity_type_id;
+        self.comments = comments;
+        self.index = None;
+    }
+
+    /// Force a reload on next use (e.g. after a new entry was added)
+    pub fn invalidate(&mut self) {
+        self.activity_type_id = None;
+        self.index = None;
+    }
+
+    /// Step to an older comment. Returns the text to show, if any.
+    pub fn previous(&mut self, current: &str) -> Option<String> {
+        let next_index = match self.index {
+            None => {
+                self.draft = current.to_string();
+                0
+            }
+            Some(i) => i + 1,
+        };
+        let comment = self.comments.get(next_index)?;
+        self.index = Some(next_index);
+        Some(comment.clone())
+    }
+
+    /// Step to a newer comment, ending at the original draft
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<String> {
+        match self.index? {
+            0 => {
+                self.index = None;
+                Some(std::mem::take(&mut self.draft))
+            }
+            i => {
+                self.index = Some(i - 1);
+                self.comments.get(i - 1).cloned()
+            }
+        }
     }
 }
 
 /// Cached data for display
 #[derive(Debug, Clone, Default)]
 pub struct CachedData {
-    pub projects: Vec<Project>,
-    pub all_activities: Vec<ActivityType>,
+    /// Shared snapshots: views clone the `Arc`, not the items, to draw
+    /// while the cache is being marked dirty
+    pub projects: Arc<[Project]>,
+    pub all_activities: Arc<[ActivityType]>,
+    pub project_links: Vec<ProjectLink>,
     pub current_date_entries: Vec<TimeEntry>,
     pub daily_summary: Vec<crate::database::ActivitySummary>,
     pub summary_date: Option<chrono::NaiveDate>,
+    pub current_date_closed: bool,
+    /// Whether the month of the selected date is locked by month-end closing
+    pub current_date_locked: bool,
+    /// Minutes per activity in the month of the selected date, for monthly caps
+    pub month_activity_totals: HashMap<i64, i32>,
+    /// Latest dates with entries, offered from the date label
+    pub recent_entry_dates: Vec<NaiveDate>,
+    /// Day totals for the recent days sidebar
+    pub recent_day_totals: HashMap<NaiveDate, i32>,
+    /// Day totals for the week of the selected date
+    pub week_day_totals: HashMap<NaiveDate, i32>,
+    /// Billable and non-billable minutes of the selected date's week
+    pub week_billable: (i32, i32),
+    /// Day totals for the month shown in the date picker popup
+    pub calendar_day_totals: HashMap<NaiveDate, i32>,
+    /// Expenses recorded on the selected date
+    pub current_date_expenses: Vec<Expense>,
+    /// Trip details of the selected date's travel entries, by entry id
+    pub current_date_trips: HashMap<i64, Trip>,
+    /// Backups in the backup folder, newest first
+    pub snapshots: Vec<crate::backup::Snapshot>,
+    /// Latest background checksum check of each backup
+    pub snapshot_integrity: HashMap<std::path::PathBuf, crate::backup::Integrity>,
+    /// Changes requested while drawing, run by the app at the end of the frame
+    pub actions: Vec<AppAction>,
+    /// Inverses of the latest actions, most recent last
+    pub undo_stack: Vec<UndoStep>,
+    /// Inverses of undone actions, most recent last; cleared by any new action
+    pub redo_stack: Vec<UndoStep>,
+    /// Most recently used activity per project, preselected by the pickers
+    pub last_activity_by_project: HashMap<i64, i64>,
+    pub project_usage: Arc<HashMap<i64, Usage>>,
+    pub activity_usage: Arc<HashMap<i64, Usage>>,
+    pub report_entries: Vec<EntryDetail>,
+    pub report_range: Option<(NaiveDate, NaiveDate)>,
+    /// Entries matching the comment search, and the query they were found for
+    pub search_results: Vec<EntryDetail>,
+    pub search_query: Option<String>,
+    /// Aggregates of `report_range`
+    pub report_statistics: crate::database::RangeStatistics,
+    /// Time and earnings per activity in `report_range`
+    pub report_earnings: Vec<crate::database::Earning>,
+    /// Month the monthly report was loaded for, and its totals
+    pub monthly_month: Option<NaiveDate>,
+    pub monthly_statistics: crate::database::RangeStatistics,
+    pub monthly_activities: Vec<crate::database::ActivityMinutes>,
+    /// Travel entries of the shown month, for the travel report
+    pub monthly_travel: Vec<TravelEntry>,
+    /// Expenses of the shown month
+    pub monthly_expenses: Vec<Expense>,
+    /// Entries of the week shown in the timesheet
+    pub timesheet_entries: Vec<TimeEntry>,
+    /// Days of that week in locked months, shown read-only
+    pub timesheet_locked_days: Vec<NaiveDate>,
+    pub ticket_linker: TicketLinker,
+    /// How durations are displayed (from settings)
+    pub duration_style: DurationStyle,
+    /// First day of the week strip, from Settings
+    pub week_start: WeekStart,
+    /// Day types registered on dates, from the database
+    pub day_types: BTreeMap<NaiveDate, DayType>,
+    /// Recent exports, newest first
+    pub export_log: Vec<ExportRecord>,
+    pub entry_templates: Vec<EntryTemplate>,
     pub needs_refresh: bool,
+    /// View the cached data was loaded for; switching views reloads
+    pub loaded_view: Option<AppView>,
 }
 
 impl CachedData {
@@ -175,6 +895,11 @@ impl CachedData {
         self.needs_refresh = true;
     }
 
+    /// Request a database change; it runs after the frame is drawn
+    pub fn queue(&mut self, action: AppAction) {
+        self.actions.push(action);
+    }
+
     pub fn get_activity_by_id(&self, id: i64) -> Option<&ActivityType> {
         self.all_activities.iter().find(|a| a.id == id)
     }
@@ -183,12 +908,41 @@ impl CachedData {
         self.projects.iter().find(|p| p.id == id)
     }
 
+    pub fn get_links_for_project(&self, project_id: i64) -> Vec<&ProjectLink> {
+        self.project_links
+            .iter()
+            .filter(|l| l.project_id == project_id)
+            .collect()
+    }
+
     pub fn get_activities_for_project(&self, project_id: i64) -> Vec<&ActivityType> {
         self.all_activities
             .iter()
             .filter(|a| a.project_id == project_id && a.is_active)
             .collect()
     }
+
+    /// Templates suggested for a day, skipping activities that can't be logged on
+    pub fn templates_for_date(&self, date: NaiveDate) -> Vec<&EntryTemplate> {
+        let weekday = date.weekday().num_days_from_monday();
+        self.entry_templates
+            .iter()
+            .filter(|t| t.weekday == weekday && self.is_activity_selectable(t.activity_type_id))
+            .collect()
+    }
+
+    /// Whether new entries may be logged on an activity (it and its project are active)
+    pub fn is_activity_selectable(&self, activity_id: i64) -> bool {
+        self.get_activity_by_id(activity_id)
+            .map(|a| {
+                a.is_active
+                    && self
+                        .get_project_by_id(a.project_id)
+                        .map(|p| p.is_active)
+                        .unwrap_or(false)
+            })
+            .unwrap_or(false)
+    }
 }
 
 /// Message/notification to display to user
@@ -197,6 +951,8 @@ pub struct UserMessage {
     pub text: String,
     pub is_error: bool,
     pub timestamp: std::time::Instant,
+    /// Stays until the app is closed instead of expiring
+    pub persistent: bool,
 }
 
 impl UserMessage {
@@ -205,19 +961,342 @@ impl UserMessage {
             text: text.into(),
             is_error: false,
             timestamp: std::time::Instant::now(),
+            persistent: false,
+        }
+    }
+
+    pub fn error(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            is_error: true,
+            timestamp: std::time::Instant::now(),
+            persistent: false,
+        }
+    }
+
+    /// Error that stays on screen for the rest of the session
+    pub fn persistent_error(text: impl Into<String>) -> Self {
+        Self {
+            persistent: true,
+            ..Self::error(text)
         }
     }
 
     pub fn is_expired(&self) -> bool {
-        self.timestamp.elapsed().as_secs() > 5
+        !self.persistent && self.timestamp.elapsed().as_secs() > 5
+    }
+}
+
+/// Result of a single day close-out check
+#[derive(Debug, Clone)]
+pub struct CloseOutCheck {
+    pub label: String,
+    pub passed: bool,
+}
+
+/// Minutes of the entries that count as work, i.e. everything but breaks
+pub fn work_minutes<'a>(entries: impl IntoIterator<Item = &'a TimeEntry>) -> i32 {
+    entries
+        .into_iter()
+        .filter(|e| e.kind != EntryKind::Break)
+        .map(|e| e.minutes)
+        .sum()
+}
+
+/// Minutes of the break entries
+pub fn break_minutes<'a>(entries: impl IntoIterator<Item = &'a TimeEntry>) -> i32 {
+    entries
+        .into_iter()
+        .filter(|e| e.kind == EntryKind::Break)
+        .map(|e| e.minutes)
+        .sum()
+}
+
+/// Work minutes split into (billable, non-billable); breaks are left out
+pub fn billable_split<'a>(entries: impl IntoIterator<Item = &'a TimeEntry>) -> (i32, i32) {
+    entries
+        .into_iter()
+        .filter(|e| e.kind != EntryKind::Break)
+        .fold((0, 0), |(billable, other), e| {
+            if e.is_billable {
+                (billable + e.minutes, other)
+            } else {
+                (billable, other + e.minutes)
+            }
+        })
+}
+
+/// Run the enabled close-out checks against a day's entries
+pub fn run_close_out_checks(
+    entries: &[TimeEntry],
+    settings: &CloseOutSettings,
+    working_day: bool,
+    style: DurationStyle,
+) -> Vec<CloseOutCheck> {
+    let mut checks = Vec::new();
+
+    // No day target on weekends, holidays and absence days
+    if settings.check_total_range && working_day {
+        let total = work_minutes(entries);
+        let breaks = break_minutes(entries);
+        let range = if settings.min_total_minutes == settings.max_total_minutes {
+            style.format(settings.min_total_minutes)
+        } else {
+            format!(
+                "{}–{}",
+                style.format(settings.min_total_minutes),
+                style.format(settings.max_total_minutes)
+            )
+        };
+        checks.push(CloseOutCheck {
+            label: format!(
+                "Day total {}{} is within expected {}",
+                style.format(total),
+                if breaks > 0 {
+                    format!(" (plus {} breaks)", style.format(breaks))
+                } else {
+                    String::new()
+                },
+                range
+            ),
+            passed: total >= settings.min_total_minutes && total <= settings.max_total_minutes,
+        });
+    }
+
+    if settings.check_comments {
+        let missing = entries
+            .iter()
+            .filter(|e| e.comment.trim().is_empty())
+            .count();
+        checks.push(CloseOutCheck {
+            label: if missing == 0 {
+                "All entries have comments".to_string()
+            } else {
+                format!("{} entries are missing a comment", missing)
+            },
+            passed: missing == 0,
+        });
+    }
+
+    if settings.check_zero_entries {
+        let zero = entries.iter().filter(|e| e.minutes == 0).count();
+        checks.push(CloseOutCheck {
+            label: if zero == 0 {
+                "No entries with zero duration".to_string()
+            } else {
+                format!("{} entries have zero duration", zero)
+            },
+            passed: zero == 0,
+        });
+    }
+
+    checks
+}
+
+/// Export of individual entries for a chosen date range
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntryExport {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// Message of the last export attempt, and whether it failed
+    pub outcome: Option<(String, bool)>,
+    pub content: ExportContent,
+}
+
+impl EntryExport {
+    pub fn new(range: (NaiveDate, NaiveDate)) -> Self {
+        Self::of(ExportContent::Entries, range)
+    }
+
+    pub fn of(content: ExportContent, range: (NaiveDate, NaiveDate)) -> Self {
+        Self {
+            start: range.0,
+            end: range.1,
+            outcome: None,
+            content,
+        }
     }
 }
 
+/// What an `EntryExport` writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportContent {
+    /// Every entry
+    Entries,
+    /// Travel entries with their trips
+    Travel,
+    /// Recorded expenses
+    Expenses,
+}
+
+impl ExportContent {
+    pub fn title(self) -> &'static str {
+        match self {
+            ExportContent::Entries => "Export Entries",
+            ExportContent::Travel => "Export Travel",
+            ExportContent::Expenses => "Export Expenses",
+        }
+    }
+
+    /// Columns written, shown in the export dialog
+    pub fn description(self) -> &'static str {
+        match self {
+            ExportContent::Entries => {
+                "One row per entry: date, project, activity, minutes, hours, comment."
+            }
+            ExportContent::Travel => {
+                "One row per trip: date, from, to, kilometers, hours, project, activity, comment."
+            }
+            ExportContent::Expenses => {
+                "One row per expense: date, amount, currency, description, receipt file."
+            }
+        }
+    }
+
+    /// Kind recorded in the export history and used in the file name
+    pub fn kind(self) -> &'static str {
+        match self {
+            ExportContent::Entries => "entries",
+            ExportContent::Travel => "travel",
+            ExportContent::Expenses => "expenses",
+        }
+    }
+}
+
+/// Month-end closing wizard
+#[derive(Debug, Clone)]
+pub struct MonthEndWizard {
+    /// First day of the month being closed
+    pub month: NaiveDate,
+    /// Index into `MonthEndWizard::STEPS`
+    pub step: usize,
+    /// Index into the export profiles in settings
+    pub profile: usize,
+    /// Result of the export run from the wizard
+    pub export_result: Option<Result<String, String>>,
+    /// Entries of the month
+    pub entries: Vec<EntryDetail>,
+    /// Minutes logged per activity in the month, for the monthly caps
+    pub activity_totals: HashMap<i64, i32>,
+    pub locked: bool,
+    /// The data above is (re)loaded on the next frame, after queued actions ran
+    pub stale: bool,
+}
+
+impl MonthEndWizard {
+    pub const STEPS: [&'static str; 5] = [
+        "Missing days",
+        "Under and over target",
+        "Unexported weeks",
+        "Export",
+        "Lock month",
+    ];
+
+    pub fn new(date: NaiveDate) -> Self {
+        Self {
+            month: month_range(date).0,
+            step: 0,
+            profile: 0,
+            export_result: None,
+            entries: Vec::new(),
+            activity_totals: HashMap::new(),
+            locked: false,
+            stale: true,
+        }
+    }
+}
+
+/// State of the settings view
+#[derive(Debug, Clone)]
+pub struct SettingsViewState {
+    /// New weekday template entry (weekday 0 = Monday)
+    pub template_weekday: u32,
+    pub template_activity_id: Option<i64>,
+    pub template_time: String,
+    pub template_comment: String,
+    /// Backup waiting for a second click to be restored
+    pub confirm_restore: Option<std::path::PathBuf>,
+}
+
+impl Default for SettingsViewState {
+    fn default() -> Self {
+        Self {
+            template_weekday: 0,
+            template_activity_id: None,
+            template_time: "01:00".to_string(),
+            template_comment: String::new(),
+            confirm_restore: None,
+        }
+    }
+}
+
+/// Order of the project and activity management lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ListSort {
+    #[default]
+    Name,
+    Code,
+    /// Most recently used first
+    LastActivity,
+    /// Most hours first
+    TotalHours,
+}
+
+impl ListSort {
+    pub const ALL: [ListSort; 4] = [
+        ListSort::Name,
+        ListSort::Code,
+        ListSort::LastActivity,
+        ListSort::TotalHours,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ListSort::Name => "Name",
+            ListSort::Code => "Code",
+            ListSort::LastActivity => "Last activity",
+            ListSort::TotalHours => "Total hours",
+        }
+    }
+
+    /// Order two rows given their name, code and usage. Rows without a code
+    /// or without any entries go last.
+    pub fn compare(self, a: (&str, &str, Usage), b: (&str, &str, Usage)) -> std::cmp::Ordering {
+        let by_name = || a.0.to_lowercase().cmp(&b.0.to_lowercase());
+        match self {
+            ListSort::Name => by_name(),
+            ListSort::Code => (a.1.is_empty(), a.1)
+                .cmp(&(b.1.is_empty(), b.1))
+                .then_with(by_name),
+            ListSort::LastActivity => b.2.last_used.cmp(&a.2.last_used).then_with(by_name),
+            ListSort::TotalHours => b.2.total_minutes.cmp(&a.2.total_minutes).then_with(by_name),
+        }
+    }
+}
+
+/// Whether every word of `query` appears in one of `fields`, ignoring case
+pub fn matches_search(query: &str, fields: &[&str]) -> bool {
+    let fields: Vec<String> = fields.iter().map(|f| f.to_lowercase()).collect();
+    query
+        .to_lowercase()
+        .split_whitespace()
+        .all(|word| fields.iter().any(|f| f.contains(word)))
+}
+
 /// Filter state for lists
 #[derive(Debug, Clone, Default)]
 pub struct FilterState {
     pub show_inactive: bool,
+    pub show_archived: bool,
+    pub project_search: String,
+    pub project_sort: ListSort,
+    pub activity_search: String,
+    pub activity_sort: ListSort,
     pub selected_project_id: Option<i64>,
+    /// Projects ticked for a bulk action
+    pub selected_projects: BTreeSet<i64>,
+    /// Activities ticked for a bulk action
+    pub selected_activities: BTreeSet<i64>,
 }
 
 impl FilterState {
@@ -230,12 +1309,25 @@ impl FilterState {
 #[derive(Debug, Clone)]
 pub struct DateState {
     pub selected_date: NaiveDate,
+    /// Date typed into the date field ("2024-10-03", "-3")
+    pub input: String,
+    /// The typed date could not be parsed
+    pub input_error: bool,
+    /// First day of the month shown in the monthly report
+    pub month: NaiveDate,
+    /// First day of the month shown in the date picker popup
+    pub calendar_month: NaiveDate,
 }
 
 impl Default for DateState {
     fn default() -> Self {
+        let today = chrono::Local::now().date_naive();
         Self {
-            selected_date: chrono::Local::now().date_naive(),
+            selected_date: today,
+            input: String::new(),
+            input_error: false,
+            month: month_range(today).0,
+            calendar_month: month_range(today).0,
         }
     }
 }
@@ -245,6 +1337,11 @@ impl DateState {
         self.selected_date = chrono::Local::now().date_naive();
     }
 
+    /// Select the day `days` before today
+    pub fn days_ago(&mut self, days: i64) {
+        self.selected_date = chrono::Local::now().date_naive() - chrono::Duration::days(days);
+    }
+
     pub fn previous_day(&mut self) {
         if let Some(new_date) = self.selected_date.pred_opt() {
             self.selected_date = new_date;
@@ -256,4 +1353,640 @@ impl DateState {
             self.selected_date = new_date;
         }
     }
+
+    pub fn this_month(&mut self) {
+        self.month = month_range(chrono::Local::now().date_naive()).0;
+    }
+
+    pub fn previous_month(&mut self) {
+        if let Some(month) = self.month.checked_sub_months(Months::new(1)) {
+            self.month = month;
+        }
+    }
+
+    pub fn next_month(&mut self) {
+        if let Some(month) = self.month.checked_add_months(Months::new(1)) {
+            self.month = month;
+        }
+    }
+}
+
+/// Monday of the ISO week containing `date`
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    week_start_on(date, chrono::Weekday::Mon)
+}
+
+/// First day of the week containing `date`, for weeks starting on `first`
+pub fn week_start_on(date: NaiveDate, first: chrono::Weekday) -> NaiveDate {
+    date - chrono::Duration::days(date.weekday().days_since(first) as i64)
+}
+
+/// First and last day of the month containing `date`
+pub fn month_range(date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let first = date.with_day(1).unwrap_or(date);
+    let last = first
+        .checked_add_months(Months::new(1))
+        .and_then(|d| d.pred_opt())
+        .unwrap_or(first);
+    (first, last)
+}
+
+/// Every new entry needs a comment, wherever it is created
+pub fn comment_error(comment: &str) -> Option<String> {
+    comment
+        .trim()
+        .is_empty()
+        .then(|| "Comment required".to_string())
+}
+
+/// Split `total` minutes in proportion to `weights` so that the parts add up
+/// exactly; leftover minutes go to the largest remainders. All-zero weights
+/// split evenly.
+pub fn split_minutes(total: u32, weights: &[u32]) -> Vec<u32> {
+    if weights.is_empty() {
+        return Vec::new();
+    }
+    let weights: Vec<u64> = if weights.iter().all(|w| *w == 0) {
+        vec![1; weights.len()]
+    } else {
+        weights.iter().map(|w| *w as u64).collect()
+    };
+    let sum: u64 = weights.iter().sum();
+    let total = total as u64;
+    let mut parts: Vec<u64> = weights.iter().map(|w| total * w / sum).collect();
+    let mut by_remainder: Vec<usize> = (0..weights.len()).collect();
+    by_remainder.sort_by_key(|&i| std::cmp::Reverse(total * weights[i] % sum));
+    let left = (total - parts.iter().sum::<u64>()) as usize;
+    for &i in by_remainder.iter().take(left) {
+        parts[i] += 1;
+    }
+    parts.into_iter().map(|p| p as u32).collect()
+}
+
+/// Date typed as "2024-10-03", "10-03" (in `today`'s year), or a number of
+/// days relative to `today` such as "-3" or "+1"
+pub fn parse_date_input(input: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let input = input.trim();
+    if let Some(days) = input.strip_prefix(['-', '+']) {
+        let days: i64 = days.trim().parse().ok()?;
+        let days = if input.starts_with('-') { -days } else { days };
+        return today.checked_add_signed(chrono::Duration::days(days));
+    }
+    NaiveDate::parse_from_str(input, "%Y-%m-%d")
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(&format!("{}-{}", today.year(), input), "%Y-%m-%d").ok()
+        })
+}
+
+/// Monday and Sunday of an ISO week typed as "v42", "w42", "42" or "2024-W42".
+/// Without a year, `default_year` is used.
+pub fn parse_iso_week(input: &str, default_year: i32) -> Option<(NaiveDate, NaiveDate)> {
+    let input = input.trim().to_uppercase();
+    let (year, week) = match input.split_once("-W") {
+        Some((year, week)) => (year.parse().ok()?, week),
+        None => (
+            default_year,
+            input.trim_start_matches(['V', 'W']).trim_start(),
+        ),
+    };
+    let week: u32 = week.parse().ok()?;
+    let monday = NaiveDate::from_isoywd_opt(year, week, chrono::Weekday::Mon)?;
+    Some((monday, monday + chrono::Duration::days(6)))
+}
+
+/// Date range selection for the Reports view
+#[derive(Debug, Clone)]
+pub struct ReportState {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    /// Include entries on inactive projects/activities
+    pub include_inactive: bool,
+    /// Index into the export profiles in settings
+    pub profile: usize,
+    /// Week typed into the week field ("v42", "2024-W42")
+    pub week_input: String,
+    /// Day clicked in the missing days list, opened on the Time Tracking tab
+    pub open_date: Option<NaiveDate>,
+    /// Words searched for in entry comments, across all dates
+    pub search: String,
+}
+
+impl Default for ReportState {
+    fn default() -> Self {
+        let (start_date, end_date) = month_range(chrono::Local::now().date_naive());
+        Self {
+            start_date,
+            end_date,
+            include_inactive: true,
+            profile: 0,
+            week_input: String::new(),
+            open_date: None,
+            search: String::new(),
+        }
+    }
+}
+
+impl ReportState {
+    pub fn this_month(&mut self) {
+        (self.start_date, self.end_date) = month_range(chrono::Local::now().date_naive());
+    }
+
+    pub fn previous_month(&mut self) {
+        if let Some(date) = self.start_date.checked_sub_months(Months::new(1)) {
+            (self.start_date, self.end_date) = month_range(date);
+        }
+    }
+
+    pub fn next_month(&mut self) {
+        if let Some(date) = self.start_date.checked_add_months(Months::new(1)) {
+            (self.start_date, self.end_date) = month_range(date);
+        }
+    }
+}
+
+/// Rows and the cell being edited in the Timesheet view
+#[derive(Debug, Clone, Default)]
+pub struct TimesheetState {
+    /// Activities added as rows before they have entries in the week
+    pub extra_rows: Vec<i64>,
+    /// Activity picked in the "Add row" picker
+    pub new_row: Option<i64>,
+    /// Cell being edited, as (activity, day), with its text
+    pub editing: Option<((i64, NaiveDate), String)>,
+    /// Comment of the entries created from empty cells
+    pub comment: String,
+}
+
+/// Change that sets the total of an activity on a day (its `entries`) to
+/// `minutes`. The newest work entry takes up the difference, a new entry with
+/// `comment` is created when there is none to grow, and zero deletes the entries.
+/// Cells with adjustments or travel can't be cleared here, as those aren't plain work.
+pub fn timesheet_action(
+    entries: &[&TimeEntry],
+    activity_type_id: i64,
+    date: NaiveDate,
+    minutes: i32,
+    comment: &str,
+    max_minutes: i32,
+    style: DurationStyle,
+) -> Result<Option<AppAction>, String> {
+    let current: i32 = entries.iter().map(|e| e.minutes).sum();
+    let difference = minutes - current;
+    if difference == 0 {
+        return Ok(None);
+    }
+    if minutes < 0 {
+        return Err("Negative totals need an adjustment on the Time Tracking tab".to_string());
+    }
+    if minutes == 0 {
+        if entries.iter().any(|e| e.kind != EntryKind::Work) {
+            return Err(
+                "This cell has adjustments or travel; remove them on the Time Tracking tab"
+                    .to_string(),
+            );
+        }
+        return Ok(Some(AppAction::DeleteTimeEntries(
+            entries.iter().map(|e| e.id).collect(),
+        )));
+    }
+
+    let newest = entries
+        .iter()
+        .filter(|e| e.kind == EntryKind::Work)
+        .max_by_key(|e| e.id);
+    let new_minutes = newest.map_or(difference, |e| e.minutes + difference);
+    if new_minutes > max_minutes {
+        return Err(format!(
+            "An entry can be at most {} (see Settings)",
+            style.format(max_minutes)
+        ));
+    }
+    match newest {
+        Some(entry) if new_minutes > 0 => Ok(Some(AppAction::UpdateTimeEntry {
+            id: entry.id,
+            minutes: new_minutes,
+            comment: entry.comment.clone(),
+            reason: entry.reason.clone(),
+            is_billable: entry.is_billable,
+        })),
+        None if difference > 0 => match comment_error(comment) {
+            Some(error) => Err(format!("{}: fill in the comment for new entries", error)),
+            None => Ok(Some(AppAction::AddEntries {
+                date,
+                entries: vec![(activity_type_id, difference)],
+                comment: comment.trim().to_string(),
+            })),
+        },
+        _ => Err(format!(
+            "{} is split over several entries; lower them on the Time Tracking tab",
+            date
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(minutes: i32, comment: &str) -> TimeEntry {
+        TimeEntry::for_test(
+            1,
+            NaiveDate::from_ymd_opt(2024, 10, 3).unwrap(),
+            minutes,
+            comment,
+        )
+    }
+
+    #[test]
+    fn test_close_out_checks() {
+        let settings = CloseOutSettings::default();
+
+        let checks = run_close_out_checks(
+            &[entry(240, "a"), entry(240, "b")],
+            &settings,
+            true,
+            DurationStyle::Clock,
+        );
+        assert_eq!(checks.len(), 3);
+        assert!(checks.iter().all(|c| c.passed));
+
+        let checks = run_close_out_checks(
+            &[entry(240, ""), entry(0, "b")],
+            &settings,
+            true,
+            DurationStyle::Clock,
+        );
+        assert!(checks.iter().all(|c| !c.passed));
+
+        // Day off: no total check
+        let checks =
+            run_close_out_checks(&[entry(60, "a")], &settings, false, DurationStyle::Clock);
+        assert_eq!(checks.len(), 2);
+        assert!(checks.iter().all(|c| c.passed));
+
+        // Breaks are listed but don't count towards the day total
+        let lunch = TimeEntry {
+            kind: EntryKind::Break,
+            ..entry(45, "lunch")
+        };
+        let checks = run_close_out_checks(
+            &[entry(450, "a"), lunch],
+            &settings,
+            true,
+            DurationStyle::Clock,
+        );
+        assert!(!checks[0].passed);
+        assert_eq!(
+            checks[0].label,
+            "Day total 07:30 (plus 00:45 breaks) is within expected 08:00"
+        );
+        let checks =
+            run_close_out_checks(&[entry(450, "a")], &settings, true, DurationStyle::Decimal);
+        assert_eq!(checks[0].label, "Day total 7,50 is within expected 8,00");
+    }
+
+    #[test]
+    fn test_adjustment_form_validation() {
+        let mut form = TimeEntryForm {
+            activity_type_id: Some(1),
+            time_str: "-00:30".to_string(),
+            comment: "Double-booked review".to_string(),
+            ..Default::default()
+        };
+        assert!(!form.is_valid(720), "work entries can't be negative");
+
+        form.kind = EntryKind::Adjustment;
+        assert!(!form.is_valid(720), "adjustments need a reason");
+        form.reason = "Week 41 already exported".to_string();
+        assert!(form.is_valid(720));
+
+        form.time_str = "00:00".to_string();
+        assert!(!form.is_valid(720));
+        assert_eq!(
+            form.time_error(720, DurationStyle::Clock).as_deref(),
+            Some("An adjustment needs a non-zero time")
+        );
+
+        form.time_str = "1:3O".to_string();
+        assert_eq!(
+            form.time_error(720, DurationStyle::Clock).as_deref(),
+            Some("Invalid time format — use HH:MM or 1.5h")
+        );
+    }
+
+    #[test]
+    fn test_travel_form() {
+        let mut form = TimeEntryForm {
+            activity_type_id: Some(1),
+            time_str: "01:30".to_string(),
+            comment: "Customer kickoff".to_string(),
+            kind: EntryKind::Travel,
+            trip_origin: " Office ".to_string(),
+            trip_destination: "Customer".to_string(),
+            ..Default::default()
+        };
+        assert!(form.is_valid(720), "kilometers are optional");
+        assert_eq!(form.trip().unwrap().kilometers, None);
+
+        form.kilometers = "42,5".to_string();
+        let trip = form.trip().unwrap();
+        assert_eq!(trip.origin, "Office");
+        assert_eq!(trip.kilometers, Some(42.5));
+
+        form.kilometers = "far".to_string();
+        assert!(!form.is_valid(720));
+        form.kind = EntryKind::Work;
+        assert!(form.is_valid(720));
+        assert!(form.trip().is_none());
+    }
+
+    #[test]
+    fn test_entry_form_max_duration() {
+        let mut form = TimeEntryForm {
+            activity_type_id: Some(1),
+            time_str: "12:00".to_string(),
+            comment: "Offsite".to_string(),
+            ..Default::default()
+        };
+        assert!(form.is_valid(720));
+
+        form.time_str = "25:00".to_string();
+        assert!(!form.is_valid(720));
+
+        form.kind = EntryKind::Adjustment;
+        form.reason = "Typo".to_string();
+        form.time_str = "-25:00".to_string();
+        assert!(!form.is_valid(720));
+    }
+
+    #[test]
+    fn test_comment_history_navigation() {
+        let mut history = CommentHistory::default();
+        assert!(history.needs_reload(Some(1)));
+        history.set(Some(1), vec!["newest".to_string(), "older".to_string()]);
+        assert!(!history.needs_reload(Some(1)));
+
+        assert_eq!(history.next(), None);
+        assert_eq!(history.previous("draft").as_deref(), Some("newest"));
+        assert_eq!(history.previous("newest").as_deref(), Some("older"));
+        assert_eq!(history.previous("older"), None);
+        assert_eq!(history.next().as_deref(), Some("newest"));
+        assert_eq!(history.next().as_deref(), Some("draft"));
+        assert_eq!(history.next(), None);
+    }
+
+    #[test]
+    fn test_month_range() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(
+            month_range(date(2024, 2, 14)),
+            (date(2024, 2, 1), date(2024, 2, 29))
+        );
+        assert_eq!(
+            month_range(date(2024, 12, 31)),
+            (date(2024, 12, 1), date(2024, 12, 31))
+        );
+    }
+
+    #[test]
+    fn test_parse_iso_week() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let week_42 = Some((date(2024, 10, 14), date(2024, 10, 20)));
+        assert_eq!(parse_iso_week("v42", 2024), week_42);
+        assert_eq!(parse_iso_week("W42", 2024), week_42);
+        assert_eq!(parse_iso_week("42", 2024), week_42);
+        assert_eq!(parse_iso_week("2024-W42", 2020), week_42);
+        // ISO week 1 of 2025 starts in 2024
+        assert_eq!(
+            parse_iso_week("2025-w01", 2024),
+            Some((date(2024, 12, 30), date(2025, 1, 5)))
+        );
+        assert_eq!(parse_iso_week("v54", 2024), None);
+        assert_eq!(parse_iso_week("next week", 2024), None);
+    }
+
+    #[test]
+    fn test_billable_split() {
+        let billed = TimeEntry {
+            is_billable: true,
+            ..entry(90, "a")
+        };
+        let lunch = TimeEntry {
+            kind: EntryKind::Break,
+            is_billable: true,
+            ..entry(45, "lunch")
+        };
+        assert_eq!(billable_split(&[billed, entry(30, "b"), lunch]), (90, 30));
+    }
+
+    #[test]
+    fn test_close_out_checks_disabled() {
+        let settings = CloseOutSettings {
+            check_total_range: false,
+            check_comments: false,
+            check_zero_entries: false,
+            ..Default::default()
+        };
+        assert!(
+            run_close_out_checks(&[entry(0, "")], &settings, true, DurationStyle::Clock).is_empty()
+        );
+    }
+
+    #[test]
+    fn test_week_start() {
+        let monday = NaiveDate::from_ymd_opt(2024, 9, 30).unwrap();
+        assert_eq!(week_start(monday), monday);
+        assert_eq!(
+            week_start(NaiveDate::from_ymd_opt(2024, 10, 6).unwrap()),
+            monday
+        );
+        let sunday = NaiveDate::from_ymd_opt(2024, 10, 6).unwrap();
+        assert_eq!(week_start_on(sunday, chrono::Weekday::Sun), sunday);
+        assert_eq!(
+            week_start_on(monday, chrono::Weekday::Sun),
+            NaiveDate::from_ymd_opt(2024, 9, 29).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_split_minutes() {
+        assert_eq!(split_minutes(100, &[1, 1, 1]), [34, 33, 33]);
+        assert_eq!(split_minutes(90, &[60, 30]), [60, 30]);
+        assert_eq!(split_minutes(75, &[0, 0]), [38, 37]);
+        assert_eq!(split_minutes(10, &[0, 45]), [0, 10]);
+        assert_eq!(split_minutes(47, &[10, 20, 30]).iter().sum::<u32>(), 47);
+        assert!(split_minutes(60, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_timesheet_action() {
+        let date = NaiveDate::from_ymd_opt(2024, 10, 7).unwrap();
+        let entry = |id, minutes, kind| TimeEntry {
+            id,
+            kind,
+            ..TimeEntry::for_test(3, date, minutes, &format!("entry {}", id))
+        };
+        let first = entry(1, 60, EntryKind::Work);
+        let second = entry(2, 30, EntryKind::Work);
+        let adjustment = entry(3, -15, EntryKind::Adjustment);
+
+        assert_eq!(
+            timesheet_action(&[], 3, date, 0, "Planning", 720, DurationStyle::Clock),
+            Ok(None)
+        );
+        assert_eq!(
+            timesheet_action(&[], 3, date, 45, "Planning", 720, DurationStyle::Clock),
+            Ok(Some(AppAction::AddEntries {
+                date,
+                entries: vec![(3, 45)],
+                comment: "Planning".to_string(),
+            }))
+        );
+        assert_eq!(
+            timesheet_action(
+                &[&first, &second],
+                3,
+                date,
+                120,
+                "Planning",
+                720,
+                DurationStyle::Clock
+            ),
+            Ok(Some(AppAction::UpdateTimeEntry {
+                id: 2,
+                minutes: 60,
+                comment: "entry 2".to_string(),
+                reason: String::new(),
+                is_billable: false,
+            }))
+        );
+        assert_eq!(
+            timesheet_action(
+                &[&first, &second],
+                3,
+                date,
+                0,
+                "Planning",
+                720,
+                DurationStyle::Clock
+            ),
+            Ok(Some(AppAction::DeleteTimeEntries(vec![1, 2])))
+        );
+        assert!(timesheet_action(
+            &[&first, &adjustment],
+            3,
+            date,
+            0,
+            "Planning",
+            720,
+            DurationStyle::Clock
+        )
+        .is_err());
+        assert!(timesheet_action(
+            &[&first, &second],
+            3,
+            date,
+            20,
+            "Planning",
+            720,
+            DurationStyle::Clock
+        )
+        .is_err());
+        assert!(timesheet_action(
+            &[&first],
+            3,
+            date,
+            800,
+            "Planning",
+            720,
+            DurationStyle::Clock
+        )
+        .is_err());
+        assert!(timesheet_action(
+            &[&first],
+            3,
+            date,
+            -30,
+            "Planning",
+            720,
+            DurationStyle::Clock
+        )
+        .is_err());
+        assert_eq!(
+            timesheet_action(
+                &[&adjustment],
+                3,
+                date,
+                30,
+                "Planning",
+                720,
+                DurationStyle::Clock
+            ),
+            Ok(Some(AppAction::AddEntries {
+                date,
+                entries: vec![(3, 45)],
+                comment: "Planning".to_string(),
+            }))
+        );
+        assert!(timesheet_action(&[], 3, date, 45, " ", 720, DurationStyle::Clock).is_err());
+    }
+
+    #[test]
+    fn test_month_navigation() {
+        let mut state = DateState {
+            month: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            ..Default::default()
+        };
+        state.previous_month();
+        assert_eq!(state.month, NaiveDate::from_ymd_opt(2023, 12, 1).unwrap());
+        state.next_month();
+        state.next_month();
+        assert_eq!(state.month, NaiveDate::from_ymd_opt(2024, 2, 1).unwrap());
+        assert_eq!(
+            month_range(state.month).1,
+            NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_date_input() {
+        let today = NaiveDate::from_ymd_opt(2024, 10, 3).unwrap();
+        let date = |m, d| NaiveDate::from_ymd_opt(2024, m, d);
+        assert_eq!(parse_date_input("2024-09-30", today), date(9, 30));
+        assert_eq!(parse_date_input(" 10-01 ", today), date(10, 1));
+        assert_eq!(parse_date_input("-3", today), date(9, 30));
+        assert_eq!(parse_date_input("+1", today), date(10, 4));
+        assert_eq!(parse_date_input("2024-02-30", today), None);
+        assert_eq!(parse_date_input("-x", today), None);
+        assert_eq!(parse_date_input("", today), None);
+    }
+
+    #[test]
+    fn test_matches_search() {
+        assert!(matches_search("", &["Anything"]));
+        assert!(matches_search("it göte", &["IT-Support - Göteborg", "33"]));
+        assert!(matches_search("P33", &["Support", "p33"]));
+        assert!(!matches_search("it malmö", &["IT-Support - Göteborg"]));
+    }
+
+    #[test]
+    fn test_list_sort() {
+        use std::cmp::Ordering;
+        let used = |minutes, day| Usage {
+            total_minutes: minutes,
+            last_used: NaiveDate::from_ymd_opt(2024, 10, day),
+            ..Default::default()
+        };
+        let a = ("alpha", "", used(600, 1));
+        let b = ("Beta", "B1", used(60, 9));
+        let unused = ("gamma", "A1", Usage::default());
+
+        assert_eq!(ListSort::Name.compare(a, b), Ordering::Less);
+        assert_eq!(ListSort::Code.compare(a, b), Ordering::Greater);
+        assert_eq!(ListSort::Code.compare(unused, b), Ordering::Less);
+        assert_eq!(ListSort::LastActivity.compare(b, a), Ordering::Less);
+        assert_eq!(ListSort::LastActivity.compare(unused, a), Ordering::Greater);
+        assert_eq!(ListSort::TotalHours.compare(a, b), Ordering::Less);
+    }
 }
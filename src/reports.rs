@@ -0,0 +1,515 @@
+// src/reports.rs
+// Report aggregations computed from time entries
+
+use crate::database::{EntryDetail, TimeEntry};
+use crate::settings::CalendarSettings;
+use chrono::{Datelike, NaiveDate, Weekday};
+use regex::Regex;
+use std::collections::HashMap;
+
+/// Total time booked against a single ticket reference
+#[derive(Debug, Clone, PartialEq)]
+pub struct TicketTotal {
+    pub ticket: String,
+    pub total_minutes: i32,
+    pub entry_count: usize,
+}
+
+/// Time grouped by ticket references found in comments
+#[derive(Debug, Clone, Default)]
+pub struct TicketReport {
+    pub tickets: Vec<TicketTotal>,
+    /// Minutes logged on entries without any ticket reference
+    pub unmatched_minutes: i32,
+}
+
+/// Group entry time by the ticket references matched in their comments.
+/// An entry mentioning several tickets has its time split evenly between them.
+pub fn aggregate_by_ticket(entries: &[EntryDetail], pattern: &Regex) -> TicketReport {
+    let mut totals: HashMap<String, TicketTotal> = HashMap::new();
+    let mut unmatched_minutes = 0;
+
+    for detail in entries {
+        let mut tickets: Vec<&str> = pattern
+            .find_iter(&detail.entry.comment)
+            .map(|m| m.as_str())
+            .collect();
+        tickets.sort_unstable();
+        tickets.dedup();
+
+        if tickets.is_empty() {
+            unmatched_minutes += detail.entry.minutes;
+            continue;
+        }
+
+        // Split evenly, giving any remainder to the first ticket
+        let share = detail.entry.minutes / tickets.len() as i32;
+        let remainder = detail.entry.minutes % tickets.len() as i32;
+        for (i, ticket) in tickets.iter().enumerate() {
+            let total = totals
+                .entry(ticket.to_string())
+                .or_insert_with(|| TicketTotal {
+                    ticket: ticket.to_string(),
+                    total_minutes: 0,
+                    entry_count: 0,
+                });
+            total.total_minutes += share + if i == 0 { remainder } else { 0 };
+            total.entry_count += 1;
+        }
+    }
+
+    let mut tickets: Vec<TicketTotal> = totals.into_values().collect();
+    tickets.sort_by(|a, b| {
+        b.total_minutes
+            .cmp(&a.total_minutes)
+            .then_with(|| a.ticket.cmp(&b.ticket))
+    });
+
+    TicketReport {
+        tickets,
+        unmatched_minutes,
+    }
+}
+
+/// Total time booked against a cost center within one project
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostCenterTotal {
+    pub cost_center: String,
+    pub project_code: String,
+    pub total_minutes: i32,
+    pub entry_count: usize,
+}
+
+/// Group entry time by cost center and project code, sorted by both.
+/// Activities without a cost center are grouped under an empty one.
+pub fn aggregate_by_cost_center(entries: &[EntryDetail]) -> Vec<CostCenterTotal> {
+    let mut totals: Vec<CostCenterTotal> = Vec::new();
+    for detail in entries {
+        match totals
+            .iter_mut()
+            .find(|t| t.cost_center == detail.cost_center && t.project_code == detail.project_code)
+        {
+            Some(total) => {
+                total.total_minutes += detail.entry.minutes;
+                total.entry_count += 1;
+            }
+            None => totals.push(CostCenterTotal {
+                cost_center: detail.cost_center.clone(),
+                project_code: detail.project_code.clone(),
+                total_minutes: detail.entry.minutes,
+                entry_count: 1,
+            }),
+        }
+    }
+    totals.sort_by(|a, b| {
+        a.cost_center
+            .cmp(&b.cost_center)
+            .then_with(|| a.project_code.cmp(&b.project_code))
+    });
+    totals
+}
+
+/// Total time logged in one ISO week
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeekTotal {
+    pub year: i32,
+    pub week: u32,
+    /// Monday of the week
+    pub start: NaiveDate,
+    pub total_minutes: i32,
+}
+
+/// Subtotals per ISO week, in date order. Weeks without entries are left out.
+pub fn aggregate_by_week(entries: &[EntryDetail]) -> Vec<WeekTotal> {
+    let mut weeks: Vec<WeekTotal> = Vec::new();
+    for detail in entries {
+        let iso = detail.entry.date.iso_week();
+        match weeks
+            .iter_mut()
+            .find(|w| w.year == iso.year() && w.week == iso.week())
+        {
+            Some(week) => week.total_minutes += detail.entry.minutes,
+            None => weeks.push(WeekTotal {
+                year: iso.year(),
+                week: iso.week(),
+                start: NaiveDate::from_isoywd_opt(iso.year(), iso.week(), Weekday::Mon)
+                    .unwrap_or(detail.entry.date),
+                total_minutes: detail.entry.minutes,
+            }),
+        }
+    }
+    weeks.sort_by_key(|w| w.start);
+    weeks
+}
+
+/// One activity's time on each day of a week
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeekRow {
+    pub activity_type_id: i64,
+    pub project_name: String,
+    pub activity_name: String,
+    /// Minutes per day, in the order of `WeekGrid::days`
+    pub minutes: [i32; 7],
+}
+
+impl WeekRow {
+    pub fn total(&self) -> i32 {
+        self.minutes.iter().sum()
+    }
+}
+
+/// Time per activity and day of one week, as in the timesheet and the HTML week report
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeekGrid {
+    pub days: [NaiveDate; 7],
+    pub rows: Vec<WeekRow>,
+}
+
+impl WeekGrid {
+    /// One row per activity in `activities`, in that order. `names` gives the project and
+    /// activity name of an activity; entries outside the week are ignored.
+    pub fn new(
+        first: NaiveDate,
+        activities: &[i64],
+        entries: &[TimeEntry],
+        names: impl Fn(i64) -> (String, String),
+    ) -> Self {
+        let days = std::array::from_fn(|i| first + chrono::Duration::days(i as i64));
+        let rows = activities
+            .iter()
+            .map(|&activity_type_id| {
+                let (project_name, activity_name) = names(activity_type_id);
+                let mut minutes = [0; 7];
+                for entry in entries
+                    .iter()
+                    .filter(|e| e.activity_type_id == activity_type_id)
+                {
+                    if let Some(i) = days.iter().position(|d| *d == entry.date) {
+                        minutes[i] += entry.minutes;
+                    }
+                }
+                WeekRow {
+                    activity_type_id,
+                    project_name,
+                    activity_name,
+                    minutes,
+                }
+            })
+            .collect();
+        Self { days, rows }
+    }
+
+    /// ISO week number, from the middle day whichever day the week starts on
+    pub fn week_number(&self) -> u32 {
+        self.days[3].iso_week().week()
+    }
+
+    pub fn day_total(&self, index: usize) -> i32 {
+        self.rows.iter().map(|r| r.minutes[index]).sum()
+    }
+
+    pub fn total(&self) -> i32 {
+        self.rows.iter().map(|r| r.total()).sum()
+    }
+}
+
+/// Time logged on one day
+#[derive(Debug, Clone, PartialEq)]
+pub struct DayTotal {
+    pub date: NaiveDate,
+    pub total_minutes: i32,
+}
+
+/// Working days logged against a target over a range
+#[derive(Debug, Clone, PartialEq)]
+pub struct DayCoverage {
+    pub working_days: usize,
+    /// Working days with less than the target logged
+    pub missing: Vec<DayTotal>,
+}
+
+impl DayCoverage {
+    pub fn complete_days(&self) -> usize {
+        self.working_days - self.missing.len()
+    }
+}
+
+fn daily_totals(entries: &[EntryDetail]) -> HashMap<NaiveDate, i32> {
+    let mut totals: HashMap<NaiveDate, i32> = HashMap::new();
+    for detail in entries {
+        *totals.entry(detail.entry.date).or_default() += detail.entry.minutes;
+    }
+    totals
+}
+
+/// Days with more than `max_minutes` logged, in date order
+pub fn days_over_target(entries: &[EntryDetail], max_minutes: i32) -> Vec<DayTotal> {
+    let mut days: Vec<DayTotal> = daily_totals(entries)
+        .into_iter()
+        .filter(|(_, total)| *total > max_minutes)
+        .map(|(date, total_minutes)| DayTotal {
+            date,
+            total_minutes,
+        })
+        .collect();
+    days.sort_by_key(|d| d.date);
+    days
+}
+
+/// Parts of ISO weeks between `start` and `end` (clamped to the range) not covered
+/// by any single exported range
+pub fn unexported_weeks(
+    start: NaiveDate,
+    end: NaiveDate,
+    exported: &[(NaiveDate, NaiveDate)],
+) -> Vec<(NaiveDate, NaiveDate)> {
+    let mut weeks = Vec::new();
+    let mut week_start = start;
+    while week_start <= end {
+        let sunday = week_start
+            + chrono::Duration::days(6 - week_start.weekday().num_days_from_monday() as i64);
+        let week_end = sunday.min(end);
+        if !exported
+            .iter()
+            .any(|(from, to)| *from <= week_start && week_end <= *to)
+        {
+            weeks.push((week_start, week_end));
+        }
+        week_start = week_end + chrono::Duration::days(1);
+    }
+    weeks
+}
+
+/// Find working days from `start` to `end` (inclusive) with less than `target_minutes`
+/// of that day logged. Weekends, holidays and absence days from the calendar are skipped.
+pub fn find_missing_days(
+    entries: &[EntryDetail],
+    start: NaiveDate,
+    end: NaiveDate,
+    calendar: &CalendarSettings,
+    target_minutes: impl Fn(NaiveDate) -> i32,
+) -> DayCoverage {
+    let totals = daily_totals(entries);
+    let working_days: Vec<NaiveDate> = start
+        .iter_days()
+        .take_while(|date| *date <= end)
+        .filter(|date| calendar.is_working_day(*date))
+        .collect();
+    let missing = working_days
+        .iter()
+        .map(|date| DayTotal {
+            date: *date,
+            total_minutes: totals.get(date).copied().unwrap_or(0),
+        })
+        .filter(|day| day.total_minutes < target_minutes(day.date))
+        .collect();
+
+    DayCoverage {
+        working_days: working_days.len(),
+        missing,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::DayType;
+
+    fn detail(minutes: i32, comment: &str) -> EntryDetail {
+        detail_on(
+            NaiveDate::from_ymd_opt(2024, 10, 3).unwrap(),
+            minutes,
+            comment,
+        )
+    }
+
+    fn detail_on(date: NaiveDate, minutes: i32, comment: &str) -> EntryDetail {
+        EntryDetail {
+            entry: TimeEntry::for_test(1, date, minutes, comment),
+            project_name: "P".to_string(),
+            activity_name: "A".to_string(),
+            project_code: "P1".to_string(),
+            cost_center: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_aggregate_by_cost_center() {
+        let booked = |minutes, cost_center: &str, project_code: &str| EntryDetail {
+            cost_center: cost_center.to_string(),
+            project_code: project_code.to_string(),
+            ..detail(minutes, "")
+        };
+        let entries = vec![
+            booked(60, "CC-2", "P1"),
+            booked(30, "CC-1", "P2"),
+            booked(45, "CC-2", "P1"),
+            booked(15, "CC-1", "P1"),
+        ];
+        let totals = aggregate_by_cost_center(&entries);
+        let keys: Vec<_> = totals
+            .iter()
+            .map(|t| {
+                (
+                    t.cost_center.as_str(),
+                    t.project_code.as_str(),
+                    t.total_minutes,
+                )
+            })
+            .collect();
+        assert_eq!(
+            keys,
+            [("CC-1", "P1", 15), ("CC-1", "P2", 30), ("CC-2", "P1", 105)]
+        );
+        assert_eq!(totals[2].entry_count, 2);
+    }
+
+    #[test]
+    fn test_aggregate_by_ticket() {
+        let pattern = Regex::new(r"[A-Z]+-\d+").unwrap();
+        let entries = vec![
+            detail(60, "Fixed PROJ-12"),
+            detail(30, "Review PROJ-12 and OPS-7"),
+            detail(45, "Meeting"),
+            detail(15, "OPS-7 OPS-7 follow-up"),
+        ];
+
+        let report = aggregate_by_ticket(&entries, &pattern);
+        assert_eq!(report.unmatched_minutes, 45);
+        assert_eq!(
+            report.tickets,
+            vec![
+                TicketTotal {
+                    ticket: "PROJ-12".to_string(),
+                    total_minutes: 75,
+                    entry_count: 2,
+                },
+                TicketTotal {
+                    ticket: "OPS-7".to_string(),
+                    total_minutes: 30,
+                    entry_count: 2,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_aggregate_by_week() {
+        let date = |d| NaiveDate::from_ymd_opt(2024, 10, d).unwrap();
+        let entries = vec![
+            detail_on(date(14), 60, ""),
+            detail_on(date(6), 30, ""),
+            detail_on(date(20), 15, ""),
+        ];
+
+        let weeks = aggregate_by_week(&entries);
+        assert_eq!(weeks.len(), 2);
+        assert_eq!(
+            (weeks[0].week, weeks[0].start),
+            (40, NaiveDate::from_ymd_opt(2024, 9, 30).unwrap())
+        );
+        assert_eq!(weeks[0].total_minutes, 30);
+        assert_eq!((weeks[1].week, weeks[1].total_minutes), (42, 75));
+    }
+
+    #[test]
+    fn test_find_missing_days() {
+        let date = |d| NaiveDate::from_ymd_opt(2024, 10, d).unwrap();
+        let calendar = CalendarSettings {
+            day_types: [(date(9), DayType::Vacation)].into(),
+            ..Default::default()
+        };
+        let entries = vec![
+            detail_on(date(7), 480, ""),
+            detail_on(date(8), 240, ""),
+            detail_on(date(11), 480, ""),
+        ];
+
+        // Mon 7 - Sun 13, with Wednesday off
+        let coverage = find_missing_days(&entries, date(7), date(13), &calendar, |_| 480);
+        assert_eq!(coverage.working_days, 4);
+        assert_eq!(coverage.complete_days(), 2);
+        assert_eq!(
+            coverage.missing,
+            vec![
+                DayTotal {
+                    date: date(8),
+                    total_minutes: 240
+                },
+                DayTotal {
+                    date: date(10),
+                    total_minutes: 0
+                },
+            ]
+        );
+
+        // Tuesdays with a 4:00 target are complete with 4:00
+        let mut calendar = calendar;
+        calendar.daily_targets[1] = 240;
+        let coverage = find_missing_days(&entries, date(7), date(13), &calendar, |date| {
+            calendar.target_for(date)
+        });
+        assert_eq!(coverage.complete_days(), 3);
+    }
+
+    #[test]
+    fn test_days_over_target() {
+        let date = |d| NaiveDate::from_ymd_opt(2024, 10, d).unwrap();
+        let entries = vec![
+            detail_on(date(8), 300, ""),
+            detail_on(date(8), 240, ""),
+            detail_on(date(7), 480, ""),
+        ];
+        assert_eq!(
+            days_over_target(&entries, 480),
+            vec![DayTotal {
+                date: date(8),
+                total_minutes: 540
+            }]
+        );
+    }
+
+    #[test]
+    fn test_unexported_weeks() {
+        let date = |d| NaiveDate::from_ymd_opt(2024, 10, d).unwrap();
+        // October 2024 starts on a Tuesday; the first week is exported
+        let exported = vec![(NaiveDate::from_ymd_opt(2024, 9, 30).unwrap(), date(6))];
+        let weeks = unexported_weeks(date(1), date(31), &exported);
+        assert_eq!(
+            weeks,
+            vec![
+                (date(7), date(13)),
+                (date(14), date(20)),
+                (date(21), date(27)),
+                (date(28), date(31)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_week_grid() {
+        let date = |d| NaiveDate::from_ymd_opt(2024, 10, d).unwrap();
+        let entries: Vec<TimeEntry> = [
+            (1, date(7), 60),
+            (1, date(7), 30),
+            (2, date(9), 45),
+            (1, date(14), 120), // Next week
+        ]
+        .into_iter()
+        .map(|(activity_type_id, date, minutes)| {
+            TimeEntry::for_test(activity_type_id, date, minutes, "")
+        })
+        .collect();
+
+        let grid = WeekGrid::new(date(7), &[2, 1], &entries, |id| {
+            ("Platform".to_string(), format!("A{}", id))
+        });
+        assert_eq!(grid.days[6], date(13));
+        assert_eq!(grid.week_number(), 41);
+        assert_eq!(grid.rows[0].activity_name, "A2");
+        assert_eq!(grid.rows[0].minutes[2], 45);
+        assert_eq!(grid.rows[1].total(), 90);
+        assert_eq!(grid.day_total(0), 90);
+        assert_eq!(grid.total(), 135);
+    }
+}
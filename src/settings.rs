@@ -0,0 +1,928 @@
+// src/settings.rs
+// Persisted user configuration (TOML file in the config directory)
+
+use crate::database::DayType;
+use crate::formatting::{DurationStyle, MoneyStyle};
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SettingsError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not read settings: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("Could not write settings: {0}")]
+    Serialize(#[from] toml::ser::Error),
+}
+
+/// Checks run by the "End day" close-out dialog
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CloseOutSettings {
+    /// Require the day total to be within the expected range
+    pub check_total_range: bool,
+    pub min_total_minutes: i32,
+    pub max_total_minutes: i32,
+    /// Require every entry to have a comment
+    pub check_comments: bool,
+    /// Flag entries logged with zero minutes
+    pub check_zero_entries: bool,
+    /// Copy the formatted daily summary to the clipboard when a day is closed
+    pub copy_summary: bool,
+    /// Saved reports written when a day is closed, by name
+    pub run_reports: Vec<String>,
+}
+
+impl Default for CloseOutSettings {
+    fn default() -> Self {
+        Self {
+            check_total_range: true,
+            min_total_minutes: 480,
+            max_total_minutes: 480,
+            check_comments: true,
+            check_zero_entries: true,
+            copy_summary: true,
+            run_reports: Vec::new(),
+        }
+    }
+}
+
+/// Templates used when copying text to the clipboard
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClipboardSettings {
+    /// One line per activity in the daily summary
+    pub summary_line_template: String,
+    /// A single entry (or summary line), e.g. for pasting into a ticket worklog
+    pub entry_line_template: String,
+}
+
+impl Default for ClipboardSettings {
+    fn default() -> Self {
+        Self {
+            summary_line_template: "{project}\\t{activity}\\t{hours}".to_string(),
+            entry_line_template: "{time} – {project} – {activity} – {comment}".to_string(),
+        }
+    }
+}
+
+/// Report configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReportSettings {
+    /// Regex matching ticket references in comments (e.g. `PROJ-123`)
+    pub ticket_pattern: String,
+    /// Named reports run on demand from the Reports tab
+    pub saved: Vec<SavedReport>,
+}
+
+impl Default for ReportSettings {
+    fn default() -> Self {
+        Self {
+            ticket_pattern: r"[A-Z]+-\d+".to_string(),
+            saved: Vec::new(),
+        }
+    }
+}
+
+/// A report configuration saved under a name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedReport {
+    pub name: String,
+    pub range: ReportRange,
+    pub grouping: ReportGrouping,
+    pub format: ReportFormat,
+    /// Export profile rounding the hours, by name; the first profile when missing
+    pub profile: String,
+    /// Folder the file is written to; the exports folder when unset
+    pub folder: Option<PathBuf>,
+}
+
+impl Default for SavedReport {
+    fn default() -> Self {
+        Self {
+            name: "New report".to_string(),
+            range: ReportRange::default(),
+            grouping: ReportGrouping::default(),
+            format: ReportFormat::default(),
+            profile: String::new(),
+            folder: None,
+        }
+    }
+}
+
+impl SavedReport {
+    /// File name for a run covering `start` to `end`, e.g. `Team_tickets_2024-10-01_2024-10-31.csv`
+    pub fn file_name(&self, start: NaiveDate, end: NaiveDate) -> String {
+        let name: String = self
+            .name
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        format!(
+            "{}_{}_{}_{}.{}",
+            name,
+            self.grouping.kind(),
+            start,
+            end,
+            self.format.extension()
+        )
+    }
+}
+
+/// Period a saved report covers, relative to the day it is run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportRange {
+    ThisWeek,
+    LastWeek,
+    ThisMonth,
+    #[default]
+    LastMonth,
+}
+
+impl ReportRange {
+    pub const ALL: [ReportRange; 4] = [
+        ReportRange::ThisWeek,
+        ReportRange::LastWeek,
+        ReportRange::ThisMonth,
+        ReportRange::LastMonth,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ReportRange::ThisWeek => "This week",
+            ReportRange::LastWeek => "Last week",
+            ReportRange::ThisMonth => "This month",
+            ReportRange::LastMonth => "Last month",
+        }
+    }
+
+    /// First and last day of the range as of `today`, with weeks beginning on `week_start`
+    pub fn dates(self, today: NaiveDate, week_start: WeekStart) -> (NaiveDate, NaiveDate) {
+        let week_offset = (7 + today.weekday().num_days_from_monday()
+            - week_start.weekday().num_days_from_monday())
+            % 7;
+        let week = today - chrono::Duration::days(week_offset as i64);
+        let month = today.with_day(1).unwrap_or(today);
+        let month_end = |first: NaiveDate| {
+            first
+                .checked_add_months(chrono::Months::new(1))
+                .and_then(|next| next.pred_opt())
+                .unwrap_or(first)
+        };
+        match self {
+            ReportRange::ThisWeek => (week, week + chrono::Duration::days(6)),
+            ReportRange::LastWeek => (
+                week - chrono::Duration::days(7),
+                week - chrono::Duration::days(1),
+            ),
+            ReportRange::ThisMonth => (month, month_end(month)),
+            ReportRange::LastMonth => {
+                let first = month
+                    .checked_sub_months(chrono::Months::new(1))
+                    .unwrap_or(month);
+                (first, month_end(first))
+            }
+        }
+    }
+}
+
+/// What a saved report adds up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportGrouping {
+    #[default]
+    Ticket,
+    CostCenter,
+    Entries,
+}
+
+impl ReportGrouping {
+    pub const ALL: [ReportGrouping; 3] = [
+        ReportGrouping::Ticket,
+        ReportGrouping::CostCenter,
+        ReportGrouping::Entries,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ReportGrouping::Ticket => "By ticket",
+            ReportGrouping::CostCenter => "By cost center",
+            ReportGrouping::Entries => "All entries",
+        }
+    }
+
+    /// Kind recorded in the export history, shared with the exports on the Reports tab
+    pub fn kind(self) -> &'static str {
+        match self {
+            ReportGrouping::Ticket => "tickets",
+            ReportGrouping::CostCenter => "cost_centers",
+            ReportGrouping::Entries => "entries",
+        }
+    }
+}
+
+/// File format of a saved report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Csv,
+    Html,
+}
+
+impl ReportFormat {
+    pub const ALL: [ReportFormat; 2] = [ReportFormat::Csv, ReportFormat::Html];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ReportFormat::Csv => "CSV",
+            ReportFormat::Html => "HTML",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ReportFormat::Csv => "csv",
+            ReportFormat::Html => "html",
+        }
+    }
+}
+
+/// Turns matches of `pattern` in comments into links to `url_template`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TicketLinkRule {
+    pub pattern: String,
+    /// URL with a `{ticket}` placeholder for the matched text
+    pub url_template: String,
+}
+
+/// Ticket hyperlink configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LinkSettings {
+    pub rules: Vec<TicketLinkRule>,
+}
+
+/// Working days, used to skip targets on weekends, holidays and absence days
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalendarSettings {
+    /// Working weekdays, Monday first
+    pub working_weekdays: [bool; 7],
+    /// Days off kept here by older versions; moved into the database's day
+    /// types at startup and no longer written
+    #[serde(rename = "days_off", skip_serializing)]
+    pub legacy_days_off: Vec<NaiveDate>,
+    /// First day of the week strip on the Time Tracking tab
+    pub week_start: WeekStart,
+    /// Daily hours target in minutes per weekday, Monday first
+    pub daily_targets: [i32; 7],
+    /// Day types registered on dates, loaded from the database
+    #[serde(skip)]
+    pub day_types: BTreeMap<NaiveDate, DayType>,
+}
+
+impl Default for CalendarSettings {
+    fn default() -> Self {
+        Self {
+            working_weekdays: [true, true, true, true, true, false, false],
+            legacy_days_off: Vec::new(),
+            week_start: WeekStart::default(),
+            daily_targets: [480; 7],
+            day_types: BTreeMap::new(),
+        }
+    }
+}
+
+/// Day the week begins on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WeekStart {
+    #[default]
+    Monday,
+    Sunday,
+    Saturday,
+}
+
+impl WeekStart {
+    pub const ALL: [WeekStart; 3] = [WeekStart::Monday, WeekStart::Sunday, WeekStart::Saturday];
+
+    pub fn weekday(self) -> Weekday {
+        match self {
+            WeekStart::Monday => Weekday::Mon,
+            WeekStart::Sunday => Weekday::Sun,
+            WeekStart::Saturday => Weekday::Sat,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            WeekStart::Monday => "Monday",
+            WeekStart::Sunday => "Sunday",
+            WeekStart::Saturday => "Saturday",
+        }
+    }
+}
+
+impl CalendarSettings {
+    pub fn is_working_day(&self, date: NaiveDate) -> bool {
+        match self.day_types.get(&date) {
+            Some(day_type) => *day_type == DayType::Workday,
+            None => self.working_weekdays[date.weekday().num_days_from_monday() as usize],
+        }
+    }
+
+    /// Target minutes for `date`, zero on days that are not worked
+    pub fn target_for(&self, date: NaiveDate) -> i32 {
+        if self.is_working_day(date) {
+            self.daily_targets[date.weekday().num_days_from_monday() as usize]
+        } else {
+            0
+        }
+    }
+
+    /// Latest working day before `date`, looking back at most a month
+    pub fn previous_working_day(&self, date: NaiveDate) -> Option<NaiveDate> {
+        date.iter_days()
+            .rev()
+            .skip(1)
+            .take(31)
+            .find(|day| self.is_working_day(*day))
+    }
+}
+
+/// How durations are rounded to a profile's step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundingMode {
+    #[default]
+    Nearest,
+    Up,
+    Down,
+}
+
+/// Rounding rules of a target system (e.g. CATS wants quarter hours)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportProfile {
+    pub name: String,
+    /// Round to multiples of this many minutes; 0 or 1 disables rounding
+    pub step_minutes: i32,
+    pub mode: RoundingMode,
+}
+
+impl Default for ExportProfile {
+    fn default() -> Self {
+        Self {
+            name: "Exact minutes".to_string(),
+            step_minutes: 0,
+            mode: RoundingMode::Nearest,
+        }
+    }
+}
+
+impl ExportProfile {
+    pub fn rounds(&self) -> bool {
+        self.step_minutes > 1
+    }
+
+    /// Round a duration according to the profile. Negative totals round symmetrically.
+    pub fn round(&self, minutes: i32) -> i32 {
+        if !self.rounds() {
+            return minutes;
+        }
+        let step = self.step_minutes;
+        let abs = minutes.abs();
+        let rounded = match self.mode {
+            RoundingMode::Nearest => (abs + step / 2) / step * step,
+            RoundingMode::Up => (abs + step - 1) / step * step,
+            RoundingMode::Down => abs / step * step,
+        };
+        rounded * minutes.signum()
+    }
+}
+
+/// Export profiles for external systems
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportSettings {
+    pub profiles: Vec<ExportProfile>,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        Self {
+            profiles: vec![
+                ExportProfile::default(),
+                ExportProfile {
+                    name: "Quarter hours".to_string(),
+                    step_minutes: 15,
+                    mode: RoundingMode::Nearest,
+                },
+            ],
+        }
+    }
+}
+
+/// Directories last used in file dialogs, keyed by operation (e.g. `ticket_export`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FileDialogSettings {
+    pub last_dirs: BTreeMap<String, PathBuf>,
+}
+
+/// Field of the entry form focused after an entry is added
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryField {
+    #[default]
+    Activity,
+    Comment,
+}
+
+/// Time entry form behaviour and limits
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EntrySettings {
+    /// Longest duration a single entry may have
+    pub max_entry_minutes: i32,
+    /// Duration the time field starts with
+    pub default_minutes: i32,
+    pub focus_after_add: EntryField,
+    /// Minutes added (or removed, when negative) by the buttons next to the time field
+    pub quick_increments: Vec<i32>,
+    /// Show a slider next to the time field for adjusting the duration with the mouse
+    pub show_duration_slider: bool,
+    pub slider_step_minutes: i32,
+    /// Entries older than this many days are read-only; 0 to allow editing any day
+    pub lock_after_days: u32,
+}
+
+impl Default for EntrySettings {
+    fn default() -> Self {
+        Self {
+            max_entry_minutes: 12 * 60,
+            default_minutes: 30,
+            focus_after_add: EntryField::default(),
+            quick_increments: vec![15, 30, -15],
+            show_duration_slider: false,
+            slider_step_minutes: 15,
+            lock_after_days: 0,
+        }
+    }
+}
+
+/// Light or dark colours
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    /// Follow the operating system
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::System, Theme::Light, Theme::Dark];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Theme::System => "System",
+            Theme::Light => "Light",
+            Theme::Dark => "Dark",
+        }
+    }
+}
+
+/// How values are displayed
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplaySettings {
+    pub theme: Theme,
+    pub duration_style: DurationStyle,
+    /// Show the last 14 days in a sidebar on the Time Tracking and Daily Summary tabs
+    pub show_recent_days: bool,
+}
+
+/// When a backup is taken as the app starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupBackup {
+    Off,
+    /// On the first start of each day
+    #[default]
+    Daily,
+    EveryStart,
+}
+
+/// Automatic backups in the folder next to the database
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupSettings {
+    pub on_startup: StartupBackup,
+    /// Backups kept per reason; the oldest are removed when a new one is taken
+    pub keep: usize,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            on_startup: StartupBackup::default(),
+            keep: crate::backup::KEEP_SNAPSHOTS,
+        }
+    }
+}
+
+/// Defaults for recorded expenses
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExpenseSettings {
+    /// Currency filled in for new expenses
+    pub default_currency: String,
+}
+
+impl Default for ExpenseSettings {
+    fn default() -> Self {
+        Self {
+            default_currency: "EUR".to_string(),
+        }
+    }
+}
+
+/// Value of one unit of a currency in the report currency
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExchangeRate {
+    pub currency: String,
+    pub rate: f64,
+}
+
+impl Default for ExchangeRate {
+    fn default() -> Self {
+        Self {
+            currency: String::new(),
+            rate: 1.0,
+        }
+    }
+}
+
+/// How amounts of money are shown, and the manual exchange rates used to
+/// consolidate amounts in several currencies
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CurrencySettings {
+    pub style: MoneyStyle,
+    /// Currency consolidated totals are shown in
+    pub report_currency: String,
+    pub exchange_rates: Vec<ExchangeRate>,
+}
+
+impl Default for CurrencySettings {
+    fn default() -> Self {
+        Self {
+            style: MoneyStyle::default(),
+            report_currency: "EUR".to_string(),
+            exchange_rates: Vec::new(),
+        }
+    }
+}
+
+impl CurrencySettings {
+    /// An amount in the report currency, `None` when `currency` has no exchange rate
+    pub fn convert(&self, cents: i64, currency: &str) -> Option<i64> {
+        if currency.eq_ignore_ascii_case(self.report_currency.trim()) {
+            return Some(cents);
+        }
+        self.exchange_rates
+            .iter()
+            .find(|r| r.currency.trim().eq_ignore_ascii_case(currency))
+            .map(|r| (cents as f64 * r.rate).round() as i64)
+    }
+
+    /// Format an amount in the configured style
+    pub fn format(&self, cents: i64, currency: &str) -> String {
+        self.style.format(cents, currency)
+    }
+}
+
+/// Where the data lives
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageSettings {
+    /// Database file to open at startup; the default location when unset
+    pub database_path: Option<PathBuf>,
+}
+
+/// Groups the user collapsed, keyed by view (e.g. `daily_summary`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutSettings {
+    pub collapsed_groups: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl LayoutSettings {
+    pub fn is_collapsed(&self, view: &str, group: &str) -> bool {
+        self.collapsed_groups
+            .get(view)
+            .is_some_and(|groups| groups.contains(group))
+    }
+
+    pub fn set_collapsed(&mut self, view: &str, group: &str, collapsed: bool) {
+        let groups = self.collapsed_groups.entry(view.to_string()).or_default();
+        if collapsed {
+            groups.insert(group.to_string());
+        } else {
+            groups.remove(group);
+        }
+    }
+}
+
+/// All user settings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub close_out: CloseOutSettings,
+    pub clipboard: ClipboardSettings,
+    pub reports: ReportSettings,
+    pub links: LinkSettings,
+    pub calendar: CalendarSettings,
+    pub export: ExportSettings,
+    pub file_dialogs: FileDialogSettings,
+    pub layout: LayoutSettings,
+    pub display: DisplaySettings,
+    pub entries: EntrySettings,
+    pub storage: StorageSettings,
+    pub backups: BackupSettings,
+    pub expenses: ExpenseSettings,
+    pub currency: CurrencySettings,
+}
+
+impl Settings {
+    /// Load settings from the default location (defaults when no file exists yet)
+    pub fn load() -> Result<Self, SettingsError> {
+        match Self::load_from(&get_settings_path()) {
+            Err(SettingsError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+                Ok(Self::default())
+            }
+            result => result,
+        }
+    }
+
+    /// Load settings from a specific file
+    pub fn load_from(path: &Path) -> Result<Self, SettingsError> {
+        let text = std::fs::read_to_string(path)?;
+        Ok(toml::from_str(&text)?)
+    }
+
+    /// Save settings to the default location
+    pub fn save(&self) -> Result<(), SettingsError> {
+        self.save_to(&get_settings_path())
+    }
+
+    /// Save settings to a specific file
+    pub fn save_to(&self, path: &Path) -> Result<(), SettingsError> {
+        let text = toml::to_string_pretty(self)?;
+        std::fs::write(path, text)?;
+        Ok(())
+    }
+
+    /// Write a settings bundle for another machine, without the machine-local
+    /// database path, dialog directories and collapsed groups
+    pub fn export_to(&self, path: &Path) -> Result<(), SettingsError> {
+        let mut bundle = self.clone();
+        bundle.storage = StorageSettings::default();
+        bundle.file_dialogs = FileDialogSettings::default();
+        bundle.layout = LayoutSettings::default();
+        bundle.save_to(path)
+    }
+
+    /// Read a settings bundle, keeping this machine's local settings
+    pub fn import_from(&self, path: &Path) -> Result<Self, SettingsError> {
+        let mut imported = Self::load_from(path)?;
+        imported.storage = self.storage.clone();
+        imported.file_dialogs = self.file_dialogs.clone();
+        imported.layout = self.layout.clone();
+        Ok(imported)
+    }
+}
+
+/// Get the settings file path
+pub fn get_settings_path() -> PathBuf {
+    // Try to use user's config directory
+    if let Some(config_dir) = dirs::config_dir() {
+        let app_dir = config_dir.join("chronos-log");
+        if std::fs::create_dir_all(&app_dir).is_ok() {
+            return app_dir.join("settings.toml");
+        }
+    }
+
+    // Fallback to current directory
+    PathBuf::from("chronos_log_settings.toml")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_currency_conversion() {
+        let mut currency = CurrencySettings::default();
+        currency.exchange_rates.push(ExchangeRate {
+            currency: "SEK".to_string(),
+            rate: 0.087,
+        });
+        assert_eq!(currency.convert(1_250, "EUR"), Some(1_250));
+        assert_eq!(currency.convert(10_000, "sek"), Some(870));
+        assert_eq!(currency.convert(10_000, "USD"), None);
+    }
+
+    #[test]
+    fn test_settings_roundtrip() {
+        let mut settings = Settings::default();
+        settings.close_out.min_total_minutes = 420;
+        settings.close_out.check_comments = false;
+        settings.calendar.week_start = WeekStart::Sunday;
+        settings.display.theme = Theme::Dark;
+        settings.storage.database_path = Some(PathBuf::from("/data/chronos_log.db"));
+        settings.links.rules.push(TicketLinkRule {
+            pattern: r"PROJ-\d+".to_string(),
+            url_template: "https://jira.example.com/browse/{ticket}".to_string(),
+        });
+
+        let text = toml::to_string_pretty(&settings).unwrap();
+        let loaded: Settings = toml::from_str(&text).unwrap();
+        assert_eq!(loaded, settings);
+    }
+
+    #[test]
+    fn test_bundle_keeps_local_settings() {
+        let path = std::env::temp_dir().join("chronos-log-test-bundle.toml");
+        let mut settings = Settings::default();
+        settings.close_out.min_total_minutes = 420;
+        settings.storage.database_path = Some(PathBuf::from("/data/chronos_log.db"));
+        settings
+            .file_dialogs
+            .last_dirs
+            .insert("export".to_string(), PathBuf::from("/home/me"));
+        settings
+            .layout
+            .set_collapsed("daily_summary", "Admin", true);
+        settings.export_to(&path).unwrap();
+
+        let bundle = Settings::load_from(&path).unwrap();
+        assert_eq!(bundle.close_out.min_total_minutes, 420);
+        assert_eq!(bundle.storage, StorageSettings::default());
+        assert!(bundle.file_dialogs.last_dirs.is_empty());
+        assert!(bundle.layout.collapsed_groups.is_empty());
+
+        let mut local = Settings::default();
+        local.storage.database_path = Some(PathBuf::from("/other/chronos_log.db"));
+        local.layout.set_collapsed("reports", "Travel", true);
+        let imported = local.import_from(&path).unwrap();
+        assert_eq!(imported.close_out.min_total_minutes, 420);
+        assert_eq!(imported.storage, local.storage);
+        assert_eq!(imported.layout, local.layout);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_collapsed_groups() {
+        let mut layout = LayoutSettings::default();
+        layout.set_collapsed("daily_summary", "40 - Development", true);
+        assert!(layout.is_collapsed("daily_summary", "40 - Development"));
+        assert!(!layout.is_collapsed("reports", "40 - Development"));
+
+        layout.set_collapsed("daily_summary", "40 - Development", false);
+        assert!(!layout.is_collapsed("daily_summary", "40 - Development"));
+    }
+
+    #[test]
+    fn test_missing_fields_use_defaults() {
+        let loaded: Settings = toml::from_str("[close_out]\ncheck_comments = false\n").unwrap();
+        assert!(!loaded.close_out.check_comments);
+        assert_eq!(loaded.close_out.min_total_minutes, 480);
+        assert!(loaded.close_out.run_reports.is_empty());
+    }
+
+    #[test]
+    fn test_profile_rounding() {
+        let mut profile = ExportProfile {
+            name: "CATS".to_string(),
+            step_minutes: 15,
+            mode: RoundingMode::Nearest,
+        };
+        assert_eq!(profile.round(52), 45);
+        assert_eq!(profile.round(53), 60);
+        assert_eq!(profile.round(-53), -60);
+        profile.mode = RoundingMode::Up;
+        assert_eq!(profile.round(46), 60);
+        profile.mode = RoundingMode::Down;
+        assert_eq!(profile.round(59), 45);
+        assert_eq!(ExportProfile::default().round(59), 59);
+    }
+
+    #[test]
+    fn test_working_days() {
+        let mut calendar = CalendarSettings::default();
+        let date = |d| NaiveDate::from_ymd_opt(2024, 12, d).unwrap();
+        assert!(calendar.is_working_day(date(23))); // Monday
+        assert!(!calendar.is_working_day(date(21))); // Saturday
+
+        calendar.day_types.insert(date(24), DayType::PublicHoliday);
+        assert!(!calendar.is_working_day(date(24)));
+
+        // Over the weekend and the day off
+        assert_eq!(calendar.previous_working_day(date(25)), Some(date(23)));
+        assert_eq!(calendar.previous_working_day(date(23)), Some(date(20)));
+        calendar.working_weekdays = [false; 7];
+        assert_eq!(calendar.previous_working_day(date(23)), None);
+    }
+
+    #[test]
+    fn test_daily_targets() {
+        let mut calendar = CalendarSettings::default();
+        let date = |d| NaiveDate::from_ymd_opt(2024, 12, d).unwrap();
+        assert_eq!(calendar.target_for(date(23)), 480);
+        assert_eq!(calendar.target_for(date(21)), 0); // Saturday
+
+        calendar.daily_targets[4] = 360;
+        assert_eq!(calendar.target_for(date(27)), 360); // Friday
+        calendar.day_types.insert(date(27), DayType::SickLeave);
+        assert_eq!(calendar.target_for(date(27)), 0);
+    }
+
+    #[test]
+    fn test_saved_report_ranges() {
+        let date = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+        let today = date(10, 9); // Wednesday
+        assert_eq!(
+            ReportRange::ThisWeek.dates(today, WeekStart::Monday),
+            (date(10, 7), date(10, 13))
+        );
+        assert_eq!(
+            ReportRange::LastWeek.dates(today, WeekStart::Sunday),
+            (date(9, 29), date(10, 5))
+        );
+        assert_eq!(
+            ReportRange::ThisMonth.dates(today, WeekStart::Monday),
+            (date(10, 1), date(10, 31))
+        );
+        assert_eq!(
+            ReportRange::LastMonth.dates(date(3, 31), WeekStart::Monday),
+            (date(2, 1), date(2, 29))
+        );
+
+        let report = SavedReport {
+            name: "Team A/B".to_string(),
+            grouping: ReportGrouping::CostCenter,
+            format: ReportFormat::Html,
+            ..Default::default()
+        };
+        assert_eq!(
+            report.file_name(date(9, 1), date(9, 30)),
+            "Team_A_B_cost_centers_2024-09-01_2024-09-30.html"
+        );
+    }
+
+    #[test]
+    fn test_legacy_days_off() {
+        let loaded: Settings = toml::from_str("[calendar]\ndays_off = [\"2024-12-24\"]\n").unwrap();
+        assert_eq!(
+            loaded.calendar.legacy_days_off,
+            [NaiveDate::from_ymd_opt(2024, 12, 24).unwrap()]
+        );
+        let text = toml::to_string_pretty(&loaded).unwrap();
+        assert!(!text.contains("days_off"));
+    }
+
+    #[test]
+    fn test_day_types() {
+        let mut calendar = CalendarSettings::default();
+        let date = |d| NaiveDate::from_ymd_opt(2024, 12, d).unwrap();
+        calendar.day_types.insert(date(23), DayType::Vacation);
+        calendar.day_types.insert(date(21), DayType::Workday); // Saturday
+        assert!(!calendar.is_working_day(date(23)));
+        assert!(calendar.is_working_day(date(21)));
+        assert_eq!(calendar.target_for(date(21)), 480);
+        assert_eq!(calendar.previous_working_day(date(24)), Some(date(21)));
+
+        for day_type in DayType::ALL {
+            assert_eq!(DayType::parse(day_type.as_str()), Some(day_type));
+        }
+        assert_eq!(DayType::parse("other"), None);
+    }
+}
@@ -1,24 +1,219 @@
 // src/ui.rs
 // GUI components and rendering functions
 
+use crate::catalog::CatalogChange;
+use crate::clipboard::{
+    format_daily_summary, format_entry_line, format_jira_worklog, ENTRY_PLACEHOLDERS,
+    SUMMARY_PLACEHOLDERS,
+};
 use crate::database::{
-    format_minutes_to_decimal, format_minutes_to_time, ActivitySummary, Database,
+    ActivitySummary, ActivityType, Database, DayType, EntryKind, Project, ProjectRecord,
+};
+use crate::formatting::{
+    format_hours_minutes, format_kilometers, format_minutes_to_decimal, format_minutes_to_time,
+    DurationStyle, MoneyStyle,
 };
+use crate::journal::{FlushOutcome, Journal, PendingEntry};
+use crate::links::{CommentSegment, TicketLinker};
 use crate::models::*;
+use crate::reports::WeekGrid;
+use crate::settings::{
+    EntryField, EntrySettings, ExportProfile, ReportFormat, ReportGrouping, ReportRange,
+    RoundingMode, SavedReport, Settings, StartupBackup, Theme, TicketLinkRule, WeekStart,
+};
+use chrono::Datelike;
 use egui::{Align, Color32, Layout, RichText, Ui, Vec2};
+use std::sync::Arc;
 
 /// Draw the main navigation bar
 pub fn draw_nav_bar(ui: &mut Ui, current_view: &mut AppView) {
     ui.horizontal(|ui| {
         ui.selectable_value(current_view, AppView::TimeTracking, "⏱ Time Tracking");
         ui.selectable_value(current_view, AppView::DailySummary, "📊 Daily Summary");
+        ui.selectable_value(current_view, AppView::MonthlyReport, "🗓 Monthly");
+        ui.selectable_value(current_view, AppView::Timesheet, "▦ Timesheet");
+        ui.selectable_value(current_view, AppView::Reports, "📈 Reports");
         ui.separator();
         ui.selectable_value(current_view, AppView::ManageProjects, "📁 Projects");
         ui.selectable_value(current_view, AppView::ManageActivities, "📋 Activities");
+        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+            ui.selectable_value(current_view, AppView::Settings, "⚙ Settings");
+        });
     });
     ui.separator();
 }
 
+/// Draw the status bar at the bottom of the window
+pub fn draw_status_bar(
+    ui: &mut Ui,
+    journal: &mut Journal,
+    cache: &mut CachedData,
+    messages: &mut Vec<UserMessage>,
+    db: &Database,
+) {
+    ui.horizontal(|ui| {
+        if let Some(step) = cache.undo_stack.last() {
+            if ui
+                .small_button(format!("↶ Undo: {}", step.label))
+                .on_hover_text("Ctrl+Z")
+                .clicked()
+            {
+                cache.queue(AppAction::Undo);
+            }
+        }
+        if let Some(step) = cache.redo_stack.last() {
+            if ui
+                .small_button(format!("↷ Redo: {}", step.label))
+                .on_hover_text("Ctrl+Y")
+                .clicked()
+            {
+                cache.queue(AppAction::Redo);
+            }
+        }
+        if !cache.undo_stack.is_empty() || !cache.redo_stack.is_empty() {
+            ui.separator();
+        }
+        if db.is_in_memory() {
+            ui.colored_label(Color32::RED, "⚠ In-memory database: nothing will be saved");
+            ui.separator();
+        }
+
+        let pending = journal.pending_count();
+        if pending == 0 {
+            ui.label(
+                RichText::new("✔ All entries saved")
+                    .small()
+                    .color(Color32::GRAY),
+            );
+            return;
+        }
+
+        ui.colored_label(
+            Color32::from_rgb(200, 120, 0),
+            format!(
+                "⏳ {} entries waiting to be written to the database",
+                pending
+            ),
+        );
+        if ui.small_button("Retry now").clicked() {
+            let outcome = journal.flush(db);
+            if outcome.written > 0 {
+                messages.push(UserMessage::info(format!(
+                    "Saved {} pending entries",
+                    outcome.written
+                )));
+            }
+            messages.extend(flush_messages(outcome));
+            cache.mark_dirty();
+        }
+        ui.menu_button("Discard…", |ui| {
+            let mut discard = None;
+            for (index, entry) in journal.pending().iter().enumerate() {
+                let label = format!(
+                    "{} {} {}",
+                    entry.date.format("%Y-%m-%d"),
+                    format_minutes_to_time(entry.minutes),
+                    activity_label(cache, entry.activity_type_id)
+                        .unwrap_or_else(|| "Unknown activity".to_string())
+                );
+                if ui
+                    .button(label)
+                    .on_hover_text("Drop this entry without saving it")
+                    .clicked()
+                {
+                    discard = Some(index);
+                }
+            }
+            if let Some(index) = discard {
+                if let Err(e) = journal.discard(index) {
+                    messages.push(journal_error(e));
+                }
+                ui.close_menu();
+            }
+        });
+    });
+}
+
+/// Native file dialog starting in the directory last used for `operation`
+fn file_dialog(
+    settings: &Settings,
+    operation: &str,
+    filter: &str,
+    extensions: &[&str],
+) -> rfd::FileDialog {
+    any_file_dialog(settings, operation).add_filter(filter, extensions)
+}
+
+/// `file_dialog` without a file type filter, e.g. for picking a folder
+fn any_file_dialog(settings: &Settings, operation: &str) -> rfd::FileDialog {
+    let dir = settings
+        .file_dialogs
+        .last_dirs
+        .get(operation)
+        .filter(|dir| dir.is_dir())
+        .cloned()
+        .unwrap_or_else(crate::export::get_export_dir);
+    rfd::FileDialog::new().set_directory(dir)
+}
+
+/// Remember the directory of a picked file, or a picked folder, for the next dialog of `operation`
+fn remember_dir(
+    settings: &mut Settings,
+    operation: &str,
+    path: &std::path::Path,
+    messages: &mut Vec<UserMessage>,
+) {
+    let dir = if path.is_dir() {
+        Some(path)
+    } else {
+        path.parent()
+    };
+    if let Some(dir) = dir {
+        settings
+            .file_dialogs
+            .last_dirs
+            .insert(operation.to_string(), dir.to_path_buf());
+        if let Err(e) = settings.save() {
+            messages.push(UserMessage::error(format!(
+                "Failed to save settings: {}",
+                e
+            )));
+        }
+    }
+}
+
+/// Colour used for adjustment entries
+const ADJUSTMENT_COLOR: Color32 = Color32::from_rgb(200, 120, 0);
+const BREAK_COLOR: Color32 = Color32::from_rgb(90, 140, 200);
+
+/// "Office → Customer, 42,5 km"; parts that weren't entered are left out
+fn trip_route(trip: &crate::database::Trip) -> String {
+    let mut route = match (trip.origin.as_str(), trip.destination.as_str()) {
+        ("", "") => String::new(),
+        (origin, "") => origin.to_string(),
+        (origin, destination) => format!("{} → {}", origin, destination)
+            .trim_start()
+            .to_string(),
+    };
+    if let Some(kilometers) = trip.kilometers {
+        if !route.is_empty() {
+            route.push_str(", ");
+        }
+        route.push_str(&format!("{} km", format_kilometers(kilometers)));
+    }
+    route
+}
+
+/// Marker for adjustment entries, with the reason on hover
+fn draw_adjustment_badge(ui: &mut Ui, reason: &str) {
+    ui.label(
+        RichText::new("± adjustment")
+            .small()
+            .color(ADJUSTMENT_COLOR),
+    )
+    .on_hover_text(format!("Reason: {}", reason));
+}
+
 /// Draw the date selector
 pub fn draw_date_selector(ui: &mut Ui, date_state: &mut DateState, cache: &mut CachedData) {
     ui.horizontal(|ui| {
@@ -27,129 +222,624 @@ pub fn draw_date_selector(ui: &mut Ui, date_state: &mut DateState, cache: &mut C
             cache.mark_dirty();
         }
 
-        ui.label(
-            RichText::new(date_state.selected_date.format("%A, %Y-%m-%d").to_string())
-                .size(18.0)
-                .strong(),
-        );
+        let label = ui
+            .add(
+                egui::Label::new(
+                    RichText::new(date_state.selected_date.format("%A, %Y-%m-%d").to_string())
+                        .size(18.0)
+                        .strong(),
+                )
+                .sense(egui::Sense::click()),
+            )
+            .on_hover_text("Right-click for recent days with entries");
+        label.context_menu(|ui| {
+            if cache.recent_entry_dates.is_empty() {
+                ui.label("No entries yet");
+            }
+            let mut picked = None;
+            for date in &cache.recent_entry_dates {
+                if ui.button(date.format("%a %Y-%m-%d").to_string()).clicked() {
+                    picked = Some(*date);
+                    ui.close_menu();
+                }
+            }
+            if let Some(date) = picked {
+                date_state.selected_date = date;
+                cache.mark_dirty();
+            }
+        });
 
         if ui.button("Next ▶").clicked() {
             date_state.next_day();
             cache.mark_dirty();
         }
 
+        let calendar = ui.button("🗓").on_hover_text("Pick a date from a calendar");
+        let popup_id = ui.make_persistent_id("date_calendar");
+        if calendar.clicked() {
+            date_state.calendar_month = month_range(date_state.selected_date).0;
+            cache.mark_dirty();
+            ui.memory_mut(|m| m.toggle_popup(popup_id));
+        }
+        egui::popup_below_widget(
+            ui,
+            popup_id,
+            &calendar,
+            egui::PopupCloseBehavior::CloseOnClickOutside,
+            |ui| {
+                if draw_calendar(ui, date_state, cache) {
+                    ui.memory_mut(|m| m.close_popup());
+                }
+            },
+        );
+
         ui.separator();
 
         if ui.button("📅 Today").clicked() {
             date_state.today();
             cache.mark_dirty();
         }
+        if ui.button("Yesterday").clicked() {
+            date_state.days_ago(1);
+            cache.mark_dirty();
+        }
+        if ui
+            .button("−7d")
+            .on_hover_text("One week before today")
+            .clicked()
+        {
+            date_state.days_ago(7);
+            cache.mark_dirty();
+        }
+
+        ui.separator();
+
+        // Typed date, applied with Enter
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut date_state.input)
+                .desired_width(90.0)
+                .hint_text("YYYY-MM-DD, -3"),
+        );
+        if response.changed() {
+            date_state.input_error = false;
+        }
+        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            let today = chrono::Local::now().date_naive();
+            match parse_date_input(&date_state.input, today) {
+                Some(date) => {
+                    date_state.selected_date = date;
+                    date_state.input.clear();
+                    cache.mark_dirty();
+                }
+                None => date_state.input_error = true,
+            }
+        }
+        if date_state.input_error {
+            ui.label(
+                RichText::new("Invalid date")
+                    .small()
+                    .color(Color32::from_rgb(255, 0, 0)),
+            )
+            .on_hover_text("Use YYYY-MM-DD, MM-DD, or days from today such as -3 or +1");
+        }
+    });
+
+    draw_week_strip(ui, date_state, cache);
+}
+
+/// Month grid of the date picker popup. Days with entries are bold with a dot.
+/// Returns true once a day was picked.
+fn draw_calendar(ui: &mut Ui, date_state: &mut DateState, cache: &mut CachedData) -> bool {
+    let month = date_state.calendar_month;
+    ui.horizontal(|ui| {
+        if ui.small_button("◀").clicked() {
+            date_state.calendar_month = month_range(month - chrono::Duration::days(1)).0;
+            cache.mark_dirty();
+        }
+        ui.label(RichText::new(month.format("%B %Y").to_string()).strong());
+        if ui.small_button("▶").clicked() {
+            date_state.calendar_month = month_range(month).1 + chrono::Duration::days(1);
+            cache.mark_dirty();
+        }
+    });
+
+    let today = chrono::Local::now().date_naive();
+    let first = week_start_on(month, cache.week_start.weekday());
+    let mut picked = None;
+    egui::Grid::new("calendar_grid")
+        .spacing([2.0, 2.0])
+        .show(ui, |ui| {
+            for day in first.iter_days().take(7) {
+                ui.label(RichText::new(day.format("%a").to_string()).small());
+            }
+            ui.end_row();
+            for week in 0..6 {
+                for day in first.iter_days().skip(week * 7).take(7) {
+                    let total = cache.calendar_day_totals.get(&day).copied().unwrap_or(0);
+                    let mut text = RichText::new(format!(
+                        "{:>2}{}",
+                        day.day(),
+                        if total > 0 { "•" } else { " " }
+                    ))
+                    .monospace();
+                    if total > 0 {
+                        text = text.strong();
+                    }
+                    let day_type = cache.day_types.get(&day).copied();
+                    if day.month() != month.month() {
+                        text = text.color(Color32::GRAY);
+                    } else if day_type.is_some_and(|t| t != DayType::Workday) {
+                        text = text.color(DAY_OFF_COLOR);
+                    } else if day == today {
+                        text = text.underline();
+                    }
+                    let mut hover = Vec::new();
+                    if let Some(day_type) = day_type {
+                        hover.push(format!("{} {}", day_type.icon(), day_type.label()));
+                    }
+                    if total > 0 {
+                        hover.push(cache.duration_style.format(total));
+                    }
+                    let response = ui.selectable_label(day == date_state.selected_date, text);
+                    let response = if hover.is_empty() {
+                        response
+                    } else {
+                        response.on_hover_text(hover.join("\n"))
+                    };
+                    day_type_menu(&response, day, cache);
+                    if response.clicked() {
+                        picked = Some(day);
+                    }
+                }
+                ui.end_row();
+            }
+        });
+
+    if let Some(date) = picked {
+        date_state.selected_date = date;
+        cache.mark_dirty();
+    }
+    picked.is_some()
+}
+
+/// Day totals for the selected date's week; click a day to switch to it
+fn draw_week_strip(ui: &mut Ui, date_state: &mut DateState, cache: &mut CachedData) {
+    let first = week_start_on(date_state.selected_date, cache.week_start.weekday());
+    let mut picked = None;
+    ui.horizontal(|ui| {
+        for date in (0..7).map(|days| first + chrono::Duration::days(days)) {
+            let total = cache.week_day_totals.get(&date).copied().unwrap_or(0);
+            let day_type = cache.day_types.get(&date).copied();
+            let text = RichText::new(format!(
+                "{}{} {}",
+                day_type.map(|t| t.icon()).unwrap_or(""),
+                date.format("%a"),
+                cache.duration_style.format(total)
+            ))
+            .small()
+            .monospace();
+            let text = if day_type.is_some_and(|t| t != DayType::Workday) {
+                text.color(DAY_OFF_COLOR)
+            } else if total == 0 {
+                text.color(Color32::GRAY)
+            } else {
+                text
+            };
+            let hover = match day_type {
+                Some(day_type) => format!("{} – {}", date, day_type.label()),
+                None => date.to_string(),
+            };
+            let response = ui
+                .selectable_label(date == date_state.selected_date, text)
+                .on_hover_text(hover);
+            day_type_menu(&response, date, cache);
+            if response.clicked() {
+                picked = Some(date);
+            }
+        }
+        ui.separator();
+        let week_total: i32 = cache.week_day_totals.values().sum();
+        // The middle day names the ISO week, whichever day the strip starts on
+        let middle = first + chrono::Duration::days(3);
+        ui.label(
+            RichText::new(format!(
+                "v{}: {}",
+                middle.iso_week().week(),
+                cache.duration_style.format_total(week_total)
+            ))
+            .small()
+            .strong(),
+        );
+        let (billable, other) = cache.week_billable;
+        if billable > 0 {
+            ui.label(
+                RichText::new(format!(
+                    "💶 {}",
+                    cache.duration_style.format_total(billable)
+                ))
+                .small(),
+            )
+            .on_hover_text(format!(
+                "Billable this week; {} not billable",
+                cache.duration_style.format_total(other)
+            ));
+        }
+    });
+    if let Some(date) = picked {
+        date_state.selected_date = date;
+        cache.mark_dirty();
+    }
+}
+
+/// Color of days registered as vacation, sick leave or public holiday
+const DAY_OFF_COLOR: Color32 = Color32::from_rgb(80, 140, 220);
+
+/// Right-click menu of a day in the week strip or calendar to set its day type
+fn day_type_menu(response: &egui::Response, date: chrono::NaiveDate, cache: &mut CachedData) {
+    response.context_menu(|ui| {
+        let current = cache.day_types.get(&date).copied();
+        for day_type in DayType::ALL {
+            let label = format!("{} {}", day_type.icon(), day_type.label());
+            if ui
+                .radio(current == Some(day_type), label)
+                .on_hover_text(match day_type {
+                    DayType::Workday => "Count this day as worked, even on a weekend",
+                    _ => "Leave this day out of targets, missing days and averages",
+                })
+                .clicked()
+            {
+                cache.queue(AppAction::SetDayType {
+                    date,
+                    day_type: Some(day_type),
+                });
+                ui.close_menu();
+            }
+        }
+        ui.separator();
+        if ui
+            .add_enabled(current.is_some(), egui::Button::new("Clear day type"))
+            .clicked()
+        {
+            cache.queue(AppAction::SetDayType {
+                date,
+                day_type: None,
+            });
+            ui.close_menu();
+        }
     });
 }
 
+/// Billable and non-billable totals, shown once anything is billable
+fn draw_billable_split(ui: &mut Ui, (billable, other): (i32, i32), style: DurationStyle) {
+    if billable == 0 {
+        return;
+    }
+    ui.label(
+        RichText::new(format!(
+            "💶 {} billable · {} not billable",
+            style.format_total(billable),
+            style.format_total(other)
+        ))
+        .color(Color32::GRAY),
+    );
+}
+
+/// Sidebar with the last 14 days, their totals and how complete they are
+pub fn draw_recent_days(
+    ui: &mut Ui,
+    date_state: &mut DateState,
+    cache: &mut CachedData,
+    settings: &Settings,
+) {
+    ui.heading("Recent days");
+    ui.add_space(5.0);
+    let today = chrono::Local::now().date_naive();
+    let mut picked = None;
+    for date in (0..14).map(|days| today - chrono::Duration::days(days)) {
+        let total = cache.recent_day_totals.get(&date).copied().unwrap_or(0);
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("●").color(day_color(date, total, settings)));
+            let text = format!(
+                "{}  {}",
+                date.format("%a %m-%d"),
+                cache.duration_style.format(total)
+            );
+            if ui
+                .selectable_label(
+                    date == date_state.selected_date,
+                    RichText::new(text).monospace(),
+                )
+                .clicked()
+            {
+                picked = Some(date);
+            }
+        });
+    }
+    if let Some(date) = picked {
+        date_state.selected_date = date;
+        cache.mark_dirty();
+    }
+}
+
+/// Completeness color for a day: green when the day's target is reached,
+/// orange when partly logged, red when a working day is empty, gray for days off
+fn day_color(date: chrono::NaiveDate, total: i32, settings: &Settings) -> Color32 {
+    let target = settings.calendar.target_for(date);
+    if total > 0 && total >= target {
+        Color32::from_rgb(0, 150, 0)
+    } else if total > 0 {
+        Color32::from_rgb(255, 140, 0)
+    } else if target > 0 {
+        Color32::from_rgb(255, 0, 0)
+    } else {
+        Color32::GRAY
+    }
+}
+
 /// Draw the time tracking view
+#[allow(clippy::too_many_arguments)]
 pub fn draw_time_tracking_view(
     ui: &mut Ui,
     date_state: &mut DateState,
     cache: &mut CachedData,
     dialog: &mut DialogState,
     entry_form: &mut TimeEntryForm,
+    journal: &mut Journal,
+    messages: &mut Vec<UserMessage>,
+    settings: &Settings,
     db: &Database,
 ) {
     draw_date_selector(ui, date_state, cache);
     ui.add_space(10.0);
 
+    // An activity whose project was deactivated can no longer be logged on
+    if let Some(id) = entry_form.activity_type_id {
+        if !cache.is_activity_selectable(id) {
+            entry_form.activity_type_id = None;
+        }
+    }
+
     // Quick add section
     ui.group(|ui| {
         ui.heading("Add Time Entry");
         ui.horizontal(|ui| {
             ui.label("Project/Activity:");
-
-            // Activity selector as combo box
-            let activity_label = entry_form
-                .activity_type_id
-                .and_then(|id| cache.get_activity_by_id(id))
-                .map(|a| {
-                    let project = cache.get_project_by_id(a.project_id);
-                    format!(
-                        "{} - {}",
-                        project.map(|p| p.name.as_str()).unwrap_or("Unknown"),
-                        a.name
-                    )
-                })
-                .unwrap_or_else(|| "Select activity...".to_string());
-
-            egui::ComboBox::from_id_salt("activity_select")
-                .selected_text(activity_label)
-                .width(300.0)
-                .show_ui(ui, |ui| {
-                    for project in &cache.projects {
-                        if !project.is_active {
-                            continue;
-                        }
-                        let activities = cache.get_activities_for_project(project.id);
-                        if activities.is_empty() {
-                            continue;
-                        }
-
-                        ui.label(RichText::new(&project.name).strong());
-                        for activity in activities {
-                            ui.selectable_value(
-                                &mut entry_form.activity_type_id,
-                                Some(activity.id),
-                                format!("  {}", activity.name),
-                            );
-                        }
-                        ui.separator();
-                    }
-                });
+            let picker = activity_picker(
+                ui,
+                "activity_select",
+                cache,
+                Some(date_state.selected_date),
+                &mut entry_form.activity_type_id,
+                Some(dialog),
+            );
+            if entry_form.focus_request == Some(EntryField::Activity) {
+                picker.request_focus();
+                entry_form.focus_request = None;
+            }
         });
+        // Required-field messages wait until the form has been started
+        if !entry_form.comment.trim().is_empty() {
+            draw_field_error(ui, entry_form.activity_error());
+        }
+        draw_validity_warning(ui, entry_form, date_state.selected_date, cache);
 
         ui.horizontal(|ui| {
             ui.label("Time (HH:MM):");
-            ui.add(egui::TextEdit::singleline(&mut entry_form.time_str).desired_width(60.0));
+            let time =
+                ui.add(egui::TextEdit::singleline(&mut entry_form.time_str).desired_width(60.0));
+            evaluate_on_blur(&time, &mut entry_form.time_str);
+            draw_duration_slider(ui, &mut entry_form.time_str, &settings.entries);
 
             // Quick time buttons
-            if ui.button("+15m").clicked() {
-                add_time_to_form(entry_form, 15);
-            }
-            if ui.button("+30m").clicked() {
-                add_time_to_form(entry_form, 30);
+            for &minutes in &settings.entries.quick_increments {
+                if ui.button(increment_label(minutes)).clicked() {
+                    add_time_to_form(entry_form, minutes);
+                }
             }
-            if ui.button("-15m").clicked() {
-                add_time_to_form(entry_form, -15);
+
+            let day_total = work_minutes(&cache.current_date_entries);
+            let remaining = settings.calendar.target_for(date_state.selected_date) - day_total;
+            if ui
+                .add_enabled(
+                    remaining > 0,
+                    egui::Button::new(format!(
+                        "⏩ Fill to target ({} left)",
+                        format_minutes_to_time(remaining.max(0))
+                    )),
+                )
+                .on_hover_text("Set the time to what is missing to the day's target")
+                .clicked()
+            {
+                entry_form.time_str = format_minutes_to_time(remaining);
             }
         });
+        draw_field_error(
+            ui,
+            entry_form.time_error(settings.entries.max_entry_minutes, cache.duration_style),
+        );
+        draw_cap_warning(ui, entry_form, cache);
 
         let mut submit_entry = false;
 
+        // Load comment history for the selected activity
+        if entry_form.history.needs_reload(entry_form.activity_type_id) {
+            let comments = entry_form
+                .activity_type_id
+                .and_then(|id| db.get_recent_comments(id, 50).ok())
+                .unwrap_or_default();
+            entry_form
+                .history
+                .set(entry_form.activity_type_id, comments);
+        }
+
         ui.horizontal(|ui| {
             ui.label("Comment *:");
             let comment_response = ui.add(
                 egui::TextEdit::singleline(&mut entry_form.comment)
                     .desired_width(400.0)
-                    .hint_text("What did you do? (required)"),
+                    .hint_text("What did you do? (required) ↑/↓ for history"),
             );
+            if entry_form.focus_request == Some(EntryField::Comment) {
+                comment_response.request_focus();
+                entry_form.focus_request = None;
+            }
+
+            // Up/Down cycle through previous comments for this activity
+            if comment_response.has_focus() {
+                let (up, down) = ui.input_mut(|i| {
+                    (
+                        i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                        i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+                    )
+                });
+                let recalled = if up {
+                    entry_form.history.previous(&entry_form.comment)
+                } else if down {
+                    entry_form.history.next()
+                } else {
+                    None
+                };
+                if let Some(comment) = recalled {
+                    entry_form.comment = comment;
+                }
+            }
 
             // Check if Enter was pressed in the comment field
-            if comment_response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
-                if entry_form.is_valid() {
-                    submit_entry = true;
+            if comment_response.lost_focus()
+                && ui.input(|i| i.key_pressed(egui::Key::Enter))
+                && entry_form.is_valid(settings.entries.max_entry_minutes)
+                && !cache.current_date_locked
+            {
+                submit_entry = true;
+            }
+        });
+        if entry_form.activity_type_id.is_some() {
+            draw_field_error(ui, entry_form.comment_error());
+        }
+
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut entry_form.kind, EntryKind::Work, "Work");
+            ui.radio_value(&mut entry_form.kind, EntryKind::Break, "☕ Break")
+                .on_hover_text(
+                    "A pause such as lunch: listed with the day's entries and in the \
+                     close-out checks, but not counted in totals, reports or exports",
+                );
+            ui.radio_value(&mut entry_form.kind, EntryKind::Adjustment, "± Adjustment")
+                .on_hover_text(
+                    "Correct an already closed or exported period without editing it. \
+                     Enter a negative time (e.g. -00:30) to remove time.",
+                );
+            ui.radio_value(&mut entry_form.kind, EntryKind::Travel, "🚗 Travel")
+                .on_hover_text(
+                    "Travel time, with an optional route and distance for expense claims",
+                );
+            match entry_form.kind {
+                EntryKind::Adjustment => {
+                    ui.label("Reason *:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut entry_form.reason)
+                            .desired_width(300.0)
+                            .hint_text("Why is this correction needed? (required)"),
+                    );
+                }
+                EntryKind::Travel => {
+                    ui.label("From:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut entry_form.trip_origin)
+                            .desired_width(120.0),
+                    );
+                    ui.label("To:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut entry_form.trip_destination)
+                            .desired_width(120.0),
+                    );
+                    ui.label("km:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut entry_form.kilometers).desired_width(50.0),
+                    );
+                }
+                EntryKind::Work | EntryKind::Break => {}
+            }
+            if entry_form.kind != EntryKind::Break {
+                let default = entry_form
+                    .activity_type_id
+                    .and_then(|id| cache.get_activity_by_id(id))
+                    .is_some_and(|a| a.is_billable);
+                let mut billable = entry_form.is_billable.unwrap_or(default);
+                if ui
+                    .checkbox(&mut billable, "💶 Billable")
+                    .on_hover_text("Defaults to the activity's setting")
+                    .changed()
+                {
+                    entry_form.is_billable = Some(billable);
                 }
             }
         });
+        draw_field_error(ui, entry_form.reason_error());
+        draw_field_error(ui, entry_form.kilometers_error());
 
         ui.horizontal(|ui| {
-            let can_add = entry_form.is_valid();
-            if ui
-                .add_enabled(can_add, egui::Button::new("➕ Add Entry"))
-                .clicked()
-            {
+            let can_add = entry_form.is_valid(settings.entries.max_entry_minutes)
+                && !cache.current_date_locked;
+            let label = match entry_form.kind {
+                EntryKind::Work => "➕ Add Entry",
+                EntryKind::Adjustment => "➕ Add Adjustment",
+                EntryKind::Break => "➕ Add Break",
+                EntryKind::Travel => "➕ Add Travel",
+            };
+            if ui.add_enabled(can_add, egui::Button::new(label)).clicked() {
                 submit_entry = true;
             }
 
             if ui.button("Clear").clicked() {
-                entry_form.clear();
+                entry_form.clear(settings.entries.default_minutes);
+            }
+
+            // What the day adds up to with this entry, for topping up to the target
+            if let Some(minutes) = entry_form
+                .get_minutes()
+                .filter(|_| can_add && entry_form.kind != EntryKind::Break)
+            {
+                let day_total = work_minutes(&cache.current_date_entries);
+                let new_total = day_total + minutes;
+                let target = settings.calendar.target_for(date_state.selected_date);
+                let color = if (target..=settings.close_out.max_total_minutes).contains(&new_total)
+                {
+                    Color32::from_rgb(0, 150, 0)
+                } else {
+                    Color32::GRAY
+                };
+                ui.label(
+                    RichText::new(format!(
+                        "Day total becomes {}",
+                        cache.duration_style.format_total(new_total)
+                    ))
+                    .color(color),
+                );
+            }
+
+            if cache.current_date_locked {
+                let date = date_state.selected_date;
+                if db.is_too_old(date) {
+                    ui.label(
+                        RichText::new(format!(
+                            "🔒 Older than {} days",
+                            settings.entries.lock_after_days
+                        ))
+                        .color(Color32::GRAY)
+                        .strong(),
+                    );
+                    if ui.small_button("Unlock…").clicked() {
+                        *dialog = DialogState::ConfirmUnlock(UnlockTarget::Day(date));
+                    }
+                } else {
+                    ui.label(
+                        RichText::new("🔒 Month locked")
+                            .color(Color32::GRAY)
+                            .strong(),
+                    )
+                    .on_hover_text("Unlock it from the month-end wizard on the Reports tab");
+                }
             }
         });
 
@@ -158,18 +848,37 @@ pub fn draw_time_tracking_view(
             if let (Some(activity_id), Some(minutes)) =
                 (entry_form.activity_type_id, entry_form.get_minutes())
             {
-                if let Err(e) = db.create_time_entry(
-                    activity_id,
-                    date_state.selected_date,
+                // Journal first, so the entry survives a failed database write
+                if let Err(e) = journal.push(PendingEntry {
+                    uuid: String::new(),
+                    activity_type_id: activity_id,
+                    date: date_state.selected_date,
                     minutes,
-                    &entry_form.comment,
-                ) {
-                    eprintln!("Error creating entry: {}", e);
-                } else {
-                    entry_form.comment.clear();
-                    entry_form.time_str = "00:30".to_string();
-                    cache.mark_dirty();
+                    comment: entry_form.comment.clone(),
+                    kind: entry_form.kind,
+                    reason: entry_form.reason.clone(),
+                    trip: entry_form.trip(),
+                    is_billable: entry_form.is_billable,
+                }) {
+                    messages.push(journal_error(e));
                 }
+                let outcome = journal.flush(db);
+                if outcome.rejected.is_empty() && outcome.retry_error.is_none() {
+                    messages.extend(budget_warning(db, cache, activity_id, minutes));
+                }
+                messages.extend(flush_messages(outcome));
+                entry_form.comment.clear();
+                entry_form.time_str = format_minutes_to_time(settings.entries.default_minutes);
+                entry_form.kind = EntryKind::Work;
+                entry_form.reason.clear();
+                entry_form.trip_origin.clear();
+                entry_form.trip_destination.clear();
+                entry_form.kilometers.clear();
+                entry_form.is_billable = None;
+                entry_form.history.invalidate();
+                entry_form.focus_request = Some(settings.entries.focus_after_add);
+                ui.ctx().request_repaint();
+                cache.mark_dirty();
             }
         }
     });
@@ -181,6 +890,9 @@ pub fn draw_time_tracking_view(
 
     if cache.current_date_entries.is_empty() {
         ui.label("No entries for this date yet.");
+        if !cache.current_date_closed && !cache.current_date_locked {
+            draw_template_suggestions(ui, date_state.selected_date, cache, journal, messages, db);
+        }
     } else {
         egui::ScrollArea::vertical()
             .max_height(300.0)
@@ -193,11 +905,15 @@ pub fn draw_time_tracking_view(
 
                     ui.horizontal(|ui| {
                         // Time in HH:MM format
-                        ui.label(
-                            RichText::new(format_minutes_to_time(entry.minutes))
-                                .monospace()
-                                .strong(),
-                        );
+                        let mut time = RichText::new(cache.duration_style.format(entry.minutes))
+                            .monospace()
+                            .strong();
+                        match entry.kind {
+                            EntryKind::Adjustment => time = time.color(ADJUSTMENT_COLOR),
+                            EntryKind::Break => time = time.color(BREAK_COLOR),
+                            EntryKind::Work | EntryKind::Travel => {}
+                        }
+                        ui.label(time);
 
                         // Time in decimal format
                         ui.label(
@@ -212,22 +928,77 @@ pub fn draw_time_tracking_view(
                         // Activity and project
                         ui.label(format!(
                             "{} - {}",
-                            project.map(|p| p.name.as_str()).unwrap_or("?"),
-                            activity.map(|a| a.name.as_str()).unwrap_or("?"),
+                            project
+                                .map(|p| p.display_name())
+                                .unwrap_or_else(|| "?".to_string()),
+                            activity
+                                .map(|a| a.display_name())
+                                .unwrap_or_else(|| "?".to_string()),
                         ));
+                        match entry.kind {
+                            EntryKind::Adjustment => draw_adjustment_badge(ui, &entry.reason),
+                            EntryKind::Break => {
+                                ui.label(RichText::new("☕ break").small().color(BREAK_COLOR))
+                                    .on_hover_text("Not counted in the day total");
+                            }
+                            EntryKind::Travel => {
+                                let route = cache
+                                    .current_date_trips
+                                    .get(&entry.id)
+                                    .map(trip_route)
+                                    .unwrap_or_default();
+                                ui.label(
+                                    RichText::new(format!("🚗 {}", route).trim_end())
+                                        .small()
+                                        .color(Color32::GRAY),
+                                );
+                            }
+                            EntryKind::Work => {}
+                        }
+                        if !cache.is_activity_selectable(entry.activity_type_id) {
+                            ui.colored_label(Color32::from_rgb(200, 120, 0), "⚠")
+                                .on_hover_text(
+                                    "This activity or its project is inactive; \
+                                     it can't be picked for new entries",
+                                );
+                        }
 
                         // Comment
                         if !entry.comment.is_empty() {
-                            ui.label(format!("\"{}\"", entry.comment));
+                            draw_comment(
+                                ui,
+                                &format!("\"{}\"", entry.comment),
+                                &cache.ticket_linker,
+                                false,
+                            );
                         }
 
                         ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-                            if ui.small_button("🗑").clicked() {
-                                *dialog =
-                                    DialogState::ConfirmDelete(DeleteTarget::TimeEntry(entry.id));
+                            if !cache.current_date_locked {
+                                if ui.small_button("🗑").clicked() {
+                                    *dialog = DialogState::ConfirmDelete(DeleteTarget::TimeEntry(
+                                        entry.id,
+                                    ));
+                                }
+                                if ui.small_button("✏").clicked() {
+                                    entry_to_edit = Some(entry.clone());
+                                }
                             }
-                            if ui.small_button("✏").clicked() {
-                                entry_to_edit = Some(entry.clone());
+                            if ui
+                                .small_button("📋")
+                                .on_hover_text("Copy entry line")
+                                .clicked()
+                            {
+                                ui.output_mut(|o| {
+                                    o.copied_text = format_entry_line(
+                                        &settings.clipboard.entry_line_template,
+                                        settings.display.duration_style,
+                                        entry.minutes,
+                                        project.map(|p| p.name.as_str()).unwrap_or(""),
+                                        activity.map(|a| a.name.as_str()).unwrap_or(""),
+                                        &entry.comment,
+                                    );
+                                });
                             }
                         });
                     });
@@ -242,25 +1013,19 @@ pub fn draw_time_tracking_view(
     }
 
     // Total for the day
-    let total_minutes: i32 = cache.current_date_entries.iter().map(|e| e.minutes).sum();
+    let total_minutes = work_minutes(&cache.current_date_entries);
+    let breaks = break_minutes(&cache.current_date_entries);
     ui.add_space(10.0);
 
-    // Check total against 8 hours (480 minutes)
-    let eight_hours = 480;
+    let target = settings.calendar.target_for(date_state.selected_date);
 
     ui.horizontal(|ui| {
         ui.label(RichText::new("Total:").strong());
 
-        let (color, show_warning) = if total_minutes < eight_hours {
-            (Color32::from_rgb(255, 200, 0), false) // Yellow - under 8 hours
-        } else if total_minutes == eight_hours {
-            (Color32::from_rgb(0, 150, 0), false) // Green - exactly 8 hours
-        } else {
-            (Color32::from_rgb(255, 0, 0), true) // Red - over 8 hours with warning
-        };
+        let color = target_color(total_minutes, target);
 
         ui.label(
-            RichText::new(format_minutes_to_time(total_minutes))
+            RichText::new(cache.duration_style.format(total_minutes))
                 .size(18.0)
                 .strong()
                 .color(color),
@@ -272,773 +1037,4503 @@ pub fn draw_time_tracking_view(
                 .color(color),
         );
 
-        // Show warning icon and message if over 8 hours
-        if show_warning {
-            ui.label(
-                RichText::new("⚠")
-                    .size(18.0)
-                    .color(Color32::from_rgb(255, 0, 0)),
-            );
+        if breaks > 0 {
             ui.label(
-                RichText::new("Over 8 hours!")
-                    .color(Color32::from_rgb(255, 0, 0))
-                    .strong(),
+                RichText::new(format!(
+                    "☕ + {} breaks",
+                    cache.duration_style.format(breaks)
+                ))
+                .color(BREAK_COLOR),
             );
         }
+        draw_billable_split(
+            ui,
+            billable_split(&cache.current_date_entries),
+            cache.duration_style,
+        );
+
+        draw_target_status(ui, total_minutes, target, cache.duration_style);
+
+        // Day close-out
+        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+            if cache.current_date_closed {
+                if ui.small_button("Reopen").clicked() {
+                    cache.queue(AppAction::ReopenDay(date_state.selected_date));
+                }
+                ui.label(
+                    RichText::new("✅ Day closed")
+                        .color(Color32::from_rgb(0, 150, 0))
+                        .strong(),
+                );
+            } else if ui.button("🏁 End day").clicked() {
+                *dialog = DialogState::CloseDay(CloseDay {
+                    date: date_state.selected_date,
+                    entries: db
+                        .get_time_entries_for_date(date_state.selected_date)
+                        .unwrap_or_default(),
+                });
+            }
+            if !cache.current_date_locked
+                && ui
+                    .button("↩ Revert day…")
+                    .on_hover_text("Restore this day's entries from a backup")
+                    .clicked()
+            {
+                *dialog = DialogState::RevertDay(revert_points(
+                    db,
+                    &cache.snapshots,
+                    date_state.selected_date,
+                ));
+            }
+            let remaining = settings.calendar.target_for(date_state.selected_date) - total_minutes;
+            if remaining > 0
+                && !cache.current_date_locked
+                && ui
+                    .button("⚖ Distribute remaining…")
+                    .on_hover_text("Split the time missing to the day's target across activities")
+                    .clicked()
+            {
+                let mut logged = std::collections::HashMap::new();
+                for entry in db
+                    .get_time_entries_for_date(date_state.selected_date)
+                    .unwrap_or_default()
+                    .iter()
+                    .filter(|e| e.kind != EntryKind::Break && e.minutes > 0)
+                {
+                    *logged.entry(entry.activity_type_id).or_default() += entry.minutes as u32;
+                }
+                *dialog = DialogState::DistributeTime(DistributeTime {
+                    date: date_state.selected_date,
+                    time_str: format_minutes_to_time(remaining),
+                    activities: Vec::new(),
+                    mode: SplitMode::default(),
+                    comment: String::new(),
+                    logged,
+                });
+            }
+            let previous = settings
+                .calendar
+                .previous_working_day(date_state.selected_date);
+            if let Some(from) = previous.filter(|_| !cache.current_date_locked) {
+                if ui
+                    .button("📄 Copy previous day…")
+                    .on_hover_text(format!(
+                        "Copy the entries of {}",
+                        from.format("%A %Y-%m-%d")
+                    ))
+                    .clicked()
+                {
+                    *dialog = DialogState::CopyDay(CopyDay {
+                        from,
+                        to: date_state.selected_date,
+                        entries: db.get_time_entries_for_date(from).unwrap_or_default(),
+                        clear_comments: false,
+                    });
+                }
+            }
+        });
     });
-}
 
-fn add_time_to_form(form: &mut TimeEntryForm, minutes_to_add: i32) {
-    if let Ok(current) = crate::database::parse_time_to_minutes(&form.time_str) {
-        let new_minutes = (current + minutes_to_add).max(0);
-        form.time_str = format_minutes_to_time(new_minutes);
-    }
+    ui.add_space(10.0);
+    draw_day_expenses(ui, date_state.selected_date, cache, dialog, settings);
 }
 
-/// Draw the daily summary view
-pub fn draw_daily_summary_view(
+/// Expenses recorded on the selected date
+fn draw_day_expenses(
     ui: &mut Ui,
-    date_state: &mut DateState,
+    date: chrono::NaiveDate,
     cache: &mut CachedData,
-    db: &Database,
+    dialog: &mut DialogState,
+    settings: &Settings,
 ) {
-    draw_date_selector(ui, date_state, cache);
-    ui.add_space(10.0);
-
-    ui.heading("Daily Summary");
-    ui.label("Total time per activity (for entering into time management system):");
-    ui.add_space(10.0);
-
-    // Check if we need to refresh summary for date change
-    if cache.summary_date != Some(date_state.selected_date) {
-        cache.daily_summary = db
-            .get_daily_summary(date_state.selected_date)
-            .unwrap_or_default();
-        cache.summary_date = Some(date_state.selected_date);
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("Expenses").strong());
+        if !cache.current_date_locked && ui.small_button("➕ Add expense…").clicked() {
+            *dialog = DialogState::AddExpense(ExpenseForm::new(
+                date,
+                &settings.expenses.default_currency,
+            ));
+        }
+    });
+    let mut delete = None;
+    for expense in &cache.current_date_expenses {
+        ui.horizontal(|ui| {
+            ui.label(
+                RichText::new(
+                    settings
+                        .currency
+                        .format(expense.amount_cents, &expense.currency),
+                )
+                .monospace()
+                .strong(),
+            );
+            ui.label(&expense.description);
+            if !expense.receipt_path.is_empty() {
+                ui.label(RichText::new("🧾").small())
+                    .on_hover_text(&expense.receipt_path);
+            }
+            if !cache.current_date_locked && ui.small_button("🗑").clicked() {
+                delete = Some(expense.id);
+            }
+        });
     }
-
-    if cache.daily_summary.is_empty() {
-        ui.label("No entries for this date.");
-        return;
+    if let Some(id) = delete {
+        cache.queue(AppAction::DeleteExpense(id));
     }
+}
 
-    // Group by project
-    let mut by_project: std::collections::HashMap<String, Vec<&ActivitySummary>> =
-        std::collections::HashMap::new();
-    for summary in &cache.daily_summary {
-        by_project
-            .entry(summary.project_name.clone())
-            .or_default()
-            .push(summary);
+/// Draw an entry comment, rendering ticket references as links
+fn draw_comment(ui: &mut Ui, comment: &str, linker: &TicketLinker, small: bool) {
+    let style = |text: &str| {
+        let text = RichText::new(text);
+        if small {
+            text.small().italics()
+        } else {
+            text
+        }
+    };
+
+    if linker.is_empty() {
+        ui.label(style(comment));
+        return;
     }
 
-    let mut total_day_minutes = 0;
+    ui.horizontal(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+        for segment in linker.segments(comment) {
+            match segment {
+                CommentSegment::Text(text) => {
+                    ui.label(style(text));
+                }
+                CommentSegment::Link { text, url } => {
+                    ui.hyperlink_to(style(text), url);
+                }
+            }
+        }
+    });
+}
 
-    egui::ScrollArea::vertical().show(ui, |ui| {
-        // Sort projects by name for consistent ordering
-        let mut sorted_projects: Vec<_> = by_project.iter().collect();
-        sorted_projects.sort_by(|a, b| a.0.cmp(b.0));
+/// Logged against budgeted hours of a project; red once over budget
+fn draw_budget_bar(ui: &mut Ui, logged: i32, budget: i32) {
+    let fraction = logged as f32 / budget as f32;
+    let bar = egui::ProgressBar::new(fraction.min(1.0))
+        .desired_width(300.0)
+        .text(format!(
+            "{}h of {}h budget ({:.0}%)",
+            format_minutes_to_decimal(logged),
+            format_minutes_to_decimal(budget),
+            fraction * 100.0
+        ));
+    ui.add(if logged > budget {
+        bar.fill(Color32::from_rgb(200, 60, 60))
+    } else {
+        bar
+    });
+}
 
-        for (project_name, activities) in sorted_projects {
-            ui.group(|ui| {
-                ui.heading(project_name);
+/// Warning for an entry of `added` minutes that took its project over budget.
+/// Call after the entry is stored.
+pub fn budget_warning(
+    db: &Database,
+    cache: &CachedData,
+    activity_type_id: i64,
+    added: i32,
+) -> Option<UserMessage> {
+    let project = cache
+        .get_activity_by_id(activity_type_id)
+        .and_then(|a| cache.get_project_by_id(a.project_id))?;
+    let total = db.get_project_total_minutes(project.id).ok()?;
+    project.crosses_budget(total - added, total).then(|| {
+        UserMessage::error(format!(
+            "⚠ {} is over budget: {}h of {}h logged",
+            project.name,
+            format_minutes_to_decimal(total),
+            format_minutes_to_decimal(project.budget_minutes.unwrap_or(0))
+        ))
+    })
+}
 
-                // Sort activities by name for consistent ordering
-                let mut sorted_activities = activities.clone();
-                sorted_activities.sort_by(|a, b| a.activity_name.cmp(&b.activity_name));
+/// Hour budget field of the project dialogs
+fn draw_budget_field(ui: &mut Ui, project_form: &mut ProjectForm) {
+    ui.horizontal(|ui| {
+        ui.label("Budget:");
+        ui.add(egui::TextEdit::singleline(&mut project_form.budget).desired_width(70.0))
+            .on_hover_text("Hours for the whole project; leave empty for none");
+        ui.label(RichText::new("hours").color(Color32::GRAY));
+    });
+    draw_field_error(ui, project_form.budget_minutes().err());
+}
 
-                for summary in sorted_activities {
-                    total_day_minutes += summary.total_minutes;
+/// Hourly rate field of the project and activity dialogs
+fn draw_hourly_rate_field(ui: &mut Ui, rate: &mut String, currency: &str) {
+    ui.horizontal(|ui| {
+        ui.label("Hourly rate:");
+        ui.add(egui::TextEdit::singleline(rate).desired_width(70.0))
+            .on_hover_text("Used by the earnings report; leave empty for none");
+        ui.label(RichText::new(currency).color(Color32::GRAY));
+    });
+    draw_field_error(ui, parse_hourly_rate(rate).err());
+}
 
-                    ui.horizontal(|ui| {
-                        // Activity name
-                        ui.label(RichText::new(&summary.activity_name).strong());
+/// Notes and reference link fields of the project dialogs
+fn draw_project_notes_editor(ui: &mut Ui, project_form: &mut ProjectForm) {
+    ui.label("Notes:");
+    ui.add(
+        egui::TextEdit::multiline(&mut project_form.notes)
+            .id(project_notes_id())
+            .desired_rows(4)
+            .desired_width(350.0),
+    );
 
-                        // Total time in HH:MM format
-                        ui.label(
-                            RichText::new(format_minutes_to_time(summary.total_minutes))
-                                .monospace()
-                                .color(Color32::from_rgb(0, 100, 200)),
-                        );
+    ui.label("Links:");
+    let mut remove: Option<usize> = None;
+    egui::Grid::new("project_links_editor")
+        .num_columns(3)
+        .show(ui, |ui| {
+            for (i, (label, url)) in project_form.links.iter_mut().enumerate() {
+                ui.add(
+                    egui::TextEdit::singleline(label)
+                        .desired_width(100.0)
+                        .hint_text("Wiki"),
+                );
+                ui.add(
+                    egui::TextEdit::singleline(url)
+                        .desired_width(230.0)
+                        .hint_text("https://..."),
+                );
+                if ui.small_button("🗑").clicked() {
+                    remove = Some(i);
+                }
+                ui.end_row();
+            }
+        });
+    if let Some(i) = remove {
+        project_form.links.remove(i);
+    }
+    if ui.small_button("➕ Add link").clicked() {
+        project_form.links.push((String::new(), String::new()));
+    }
+}
 
-                        // Total time in decimal format
-                        ui.label(
-                            RichText::new(format!(
-                                "({}h)",
-                                format_minutes_to_decimal(summary.total_minutes)
-                            ))
-                            .monospace()
-                            .color(Color32::from_rgb(100, 100, 100)),
-                        );
+/// `project` with the name, description, icon, notes, rate, budget and links from the form
+fn project_record(project_form: &ProjectForm, project: Project) -> ProjectRecord {
+    ProjectRecord {
+        project: Project {
+            name: project_form.name.trim().to_string(),
+            description: project_form.description.trim().to_string(),
+            icon: project_form.icon.trim().to_string(),
+            notes: project_form.notes.trim().to_string(),
+            hourly_rate_cents: parse_hourly_rate(&project_form.hourly_rate).ok().flatten(),
+            budget_minutes: project_form.budget_minutes().ok().flatten(),
+            ..project
+        },
+        links: form_links(project_form),
+    }
+}
 
-                        // Copy button for decimal format
-                        if ui.small_button("📋 Copy").clicked() {
-                            ui.output_mut(|o| {
-                                o.copied_text = format_minutes_to_decimal(summary.total_minutes);
-                            });
-                        }
-                    });
+/// Links from the project form as (label, url) pairs, skipping those without a URL
+fn form_links(project_form: &ProjectForm) -> Vec<(String, String)> {
+    project_form
+        .links
+        .iter()
+        .filter(|(_, url)| !url.trim().is_empty())
+        .map(|(label, url)| (label.trim().to_string(), url.trim().to_string()))
+        .collect()
+}
 
-                    // Show individual entries
-                    ui.indent(summary.activity_type_id, |ui| {
-                        for entry in &summary.entries {
-                            ui.horizontal(|ui| {
-                                ui.label(
-                                    RichText::new(format_minutes_to_time(entry.minutes))
-                                        .small()
-                                        .monospace(),
-                                );
-                                if !entry.comment.is_empty() {
-                                    ui.label(RichText::new(&entry.comment).small().italics());
-                                }
-                            });
-                        }
-                    });
+/// Emoji offered in the icon picker
+const ICON_CHOICES: &[&str] = &[
+    "💻", "🛠", "🐛", "📞", "📧", "📝", "📊", "📚", "🎓", "🤝", "🚗", "✈", "🏠", "🏢", "🔒", "⭐",
+];
 
-                    ui.add_space(5.0);
+/// Small text field for an icon plus a menu of common emoji
+fn icon_picker(ui: &mut Ui, icon: &mut String) {
+    ui.add(egui::TextEdit::singleline(icon).desired_width(30.0));
+    ui.menu_button("😀", |ui| {
+        ui.horizontal_wrapped(|ui| {
+            ui.set_max_width(200.0);
+            for choice in ICON_CHOICES {
+                if ui.button(*choice).clicked() {
+                    *icon = choice.to_string();
+                    ui.close_menu();
                 }
-            });
-            ui.add_space(5.0);
+            }
+        });
+        if ui.button("No icon").clicked() {
+            icon.clear();
+            ui.close_menu();
         }
+    });
+}
 
-        // Grand total
-        ui.add_space(10.0);
-        ui.separator();
-
-        // Check total against 8 hours (480 minutes)
-        let eight_hours = 480;
-
-        ui.horizontal(|ui| {
-            ui.label(RichText::new("TOTAL FOR DAY:").strong().size(16.0));
+const WEEKDAY_NAMES: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
 
-            let (color, show_warning) = if total_day_minutes < eight_hours {
-                (Color32::from_rgb(255, 200, 0), false) // Yellow - under 8 hours
-            } else if total_day_minutes == eight_hours {
-                (Color32::from_rgb(0, 150, 0), false) // Green - exactly 8 hours
-            } else {
-                (Color32::from_rgb(255, 0, 0), true) // Red - over 8 hours with warning
-            };
+/// "Project - Activity" with icons
+fn activity_label(cache: &CachedData, activity_id: i64) -> Option<String> {
+    cache.get_activity_by_id(activity_id).map(|a| {
+        let project = cache.get_project_by_id(a.project_id);
+        format!(
+            "{} - {}",
+            project
+                .map(|p| p.display_name())
+                .unwrap_or_else(|| "Unknown".to_string()),
+            a.display_name()
+        )
+    })
+}
 
-            ui.label(
-                RichText::new(format_minutes_to_time(total_day_minutes))
-                    .strong()
-                    .size(18.0)
-                    .color(color),
-            );
+/// Activity picker grouped by project. With a `date`, activities whose
+/// assignment doesn't cover it are left out. Clicking a project heading
+/// picks the activity last used in that project. With a `dialog`, each
+/// project ends with a row that opens the New Activity dialog for it.
+fn activity_picker(
+    ui: &mut Ui,
+    id_salt: &str,
+    cache: &CachedData,
+    date: Option<chrono::NaiveDate>,
+    selected: &mut Option<i64>,
+    mut dialog: Option<&mut DialogState>,
+) -> egui::Response {
+    let label = selected
+        .and_then(|id| activity_label(cache, id))
+        .unwrap_or_else(|| "Select activity...".to_string());
 
-            ui.label(
-                RichText::new(format!(
-                    "({}h)",
-                    format_minutes_to_decimal(total_day_minutes)
-                ))
-                .strong()
-                .size(16.0)
-                .color(color),
-            );
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(label)
+        .width(300.0)
+        .show_ui(ui, |ui| {
+            for project in cache.projects.iter() {
+                if !project.is_active {
+                    continue;
+                }
+                let activities: Vec<_> = cache
+                    .get_activities_for_project(project.id)
+                    .into_iter()
+                    .filter(|a| date.is_none_or(|date| a.is_valid_on(date)))
+                    .collect();
+                // Empty projects are only worth showing to add their first activity
+                if activities.is_empty() && dialog.is_none() {
+                    continue;
+                }
 
-            // Show warning icon and message if over 8 hours
-            if show_warning {
-                ui.label(
-                    RichText::new("⚠")
-                        .size(18.0)
-                        .color(Color32::from_rgb(255, 0, 0)),
-                );
-                ui.label(
-                    RichText::new("Over 8 hours!")
-                        .color(Color32::from_rgb(255, 0, 0))
-                        .strong(),
-                );
+                if ui
+                    .selectable_label(false, RichText::new(project.display_name()).strong())
+                    .on_hover_text("Pick the activity you used last in this project")
+                    .clicked()
+                {
+                    let last = cache.last_activity_by_project.get(&project.id);
+                    if let Some(activity) = activities
+                        .iter()
+                        .find(|a| Some(&a.id) == last)
+                        .or(activities.first())
+                    {
+                        *selected = Some(activity.id);
+                    }
+                }
+                for activity in activities {
+                    ui.selectable_value(
+                        selected,
+                        Some(activity.id),
+                        format!("  {}", activity.display_name()),
+                    );
+                }
+                if let Some(dialog) = dialog.as_deref_mut() {
+                    if ui
+                        .selectable_label(false, RichText::new("  ➕ New activity…").weak())
+                        .clicked()
+                    {
+                        *dialog = DialogState::AddActivity(project.id);
+                    }
+                }
+                ui.separator();
             }
-        });
-    });
+        })
+        .response
 }
 
-/// Draw the projects management view
-pub fn draw_projects_view(
+/// Offer the weekday's template entries for an empty day
+fn draw_template_suggestions(
     ui: &mut Ui,
+    date: chrono::NaiveDate,
     cache: &mut CachedData,
-    dialog: &mut DialogState,
-    filter: &mut FilterState,
+    journal: &mut Journal,
+    messages: &mut Vec<UserMessage>,
     db: &Database,
 ) {
-    ui.horizontal(|ui| {
-        ui.heading("Manage Projects");
-        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-            if ui.button("➕ New Project").clicked() {
-                *dialog = DialogState::AddProject;
+    let templates: Vec<_> = cache
+        .templates_for_date(date)
+        .into_iter()
+        .cloned()
+        .collect();
+    if templates.is_empty() {
+        return;
+    }
+
+    let mut to_add = Vec::new();
+    ui.group(|ui| {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(format!("{} template", date.format("%A"))).strong());
+            if ui.button("➕ Add all").clicked() {
+                to_add = templates.clone();
             }
         });
+        for template in &templates {
+            ui.horizontal(|ui| {
+                if ui.small_button("➕").clicked() {
+                    to_add.push(template.clone());
+                }
+                ui.label(RichText::new(cache.duration_style.format(template.minutes)).monospace());
+                ui.label(
+                    activity_label(cache, template.activity_type_id)
+                        .unwrap_or_else(|| "?".to_string()),
+                );
+                ui.label(RichText::new(&template.comment).italics());
+            });
+        }
     });
 
-    ui.checkbox(&mut filter.show_inactive, "Show inactive projects");
-    ui.add_space(10.0);
+    if to_add.is_empty() {
+        return;
+    }
+    for template in to_add {
+        if let Err(e) = journal.push(PendingEntry {
+            uuid: String::new(),
+            activity_type_id: template.activity_type_id,
+            date,
+            minutes: template.minutes,
+            comment: template.comment,
+            kind: EntryKind::Work,
+            reason: String::new(),
+            trip: None,
+            is_billable: None,
+        }) {
+            messages.push(journal_error(e));
+        }
+    }
+    messages.extend(flush_messages(journal.flush(db)));
+    cache.mark_dirty();
+}
 
-    // Clone the data we need to avoid borrow issues
-    let projects: Vec<_> = cache
-        .projects
-        .iter()
-        .filter(|p| filter.show_inactive || p.is_active)
-        .cloned()
-        .collect();
+/// Message for an entry that could not be written to the journal
+fn journal_error(e: std::io::Error) -> UserMessage {
+    UserMessage::error(format!("Failed to write the entry journal: {}", e))
+}
 
-    // Track actions to perform after iteration
-    let mut action_deactivate: Option<i64> = None;
-    let mut action_activate: Option<i64> = None;
+/// Messages for journal entries the database refused or could not take yet
+pub fn flush_messages(outcome: FlushOutcome) -> Vec<UserMessage> {
+    let mut messages: Vec<UserMessage> = outcome
+        .rejected
+        .into_iter()
+        .map(|(entry, e)| {
+            UserMessage::error(format!(
+                "The entry on {} was not saved and has been discarded: {}",
+                entry.date.format("%Y-%m-%d"),
+                e
+            ))
+        })
+        .collect();
+    if let Some(e) = outcome.retry_error {
+        messages.push(UserMessage::error(format!(
+            "Failed to save the entry, it is kept and retried: {}",
+            e
+        )));
+    }
+    if let Some(e) = outcome.journal_error {
+        messages.push(UserMessage::error(format!(
+            "Entries were saved, but the entry journal could not be updated: {}",
+            e
+        )));
+    }
+    messages
+}
 
-    egui::ScrollArea::vertical().show(ui, |ui| {
-        for project in &projects {
-            // Count activities for this project
-            let activity_count = cache
-                .all_activities
-                .iter()
-                .filter(|a| a.project_id == project.id && a.is_active)
-                .count();
-
-            ui.group(|ui| {
-                ui.horizontal(|ui| {
-                    // Status indicator
-                    if project.is_active {
-                        ui.label(RichText::new("●").color(Color32::GREEN));
-                    } else {
-                        ui.label(RichText::new("●").color(Color32::GRAY));
-                    }
-
-                    // Project name
-                    ui.label(RichText::new(&project.name).strong());
-
-                    // Description
-                    if !project.description.is_empty() {
-                        ui.label(format!("- {}", project.description));
-                    }
-
-                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-                        // Delete button
-                        if ui.small_button("🗑").clicked() {
-                            *dialog = DialogState::ConfirmDelete(DeleteTarget::Project(
-                                project.id,
-                                project.name.clone(),
-                            ));
-                        }
+/// Red validation message under a form field
+fn draw_field_error(ui: &mut Ui, error: Option<String>) {
+    if let Some(error) = error {
+        ui.label(
+            RichText::new(error)
+                .small()
+                .color(Color32::from_rgb(255, 0, 0)),
+        );
+    }
+}
 
-                        // Edit button
-                        if ui.small_button("✏").clicked() {
-                            *dialog = DialogState::EditProject(project.clone());
-                        }
+/// Warn when the selected activity's assignment doesn't cover the entry date
+fn draw_validity_warning(
+    ui: &mut Ui,
+    form: &TimeEntryForm,
+    date: chrono::NaiveDate,
+    cache: &CachedData,
+) {
+    let Some(activity) = form
+        .activity_type_id
+        .and_then(|id| cache.get_activity_by_id(id))
+    else {
+        return;
+    };
+    if activity.is_valid_on(date) {
+        return;
+    }
+    let period = match (activity.valid_from, activity.valid_to) {
+        (Some(from), Some(to)) => format!("from {} to {}", from, to),
+        (Some(from), None) => format!("from {}", from),
+        (None, Some(to)) => format!("until {}", to),
+        (None, None) => String::new(),
+    };
+    ui.label(
+        RichText::new(format!("⚠ {} is only assigned {}", activity.name, period))
+            .small()
+            .color(Color32::from_rgb(255, 140, 0)),
+    );
+}
 
-                        // Activate/Deactivate
-                        if project.is_active {
-                            if ui.small_button("Deactivate").clicked() {
-                                action_deactivate = Some(project.id);
-                            }
-                        } else {
-                            if ui.small_button("Activate").clicked() {
-                                action_activate = Some(project.id);
-                            }
-                        }
+/// Warn when the entry would take its activity over the monthly cap
+fn draw_cap_warning(ui: &mut Ui, form: &TimeEntryForm, cache: &CachedData) {
+    let Some(activity) = form
+        .activity_type_id
+        .and_then(|id| cache.get_activity_by_id(id))
+    else {
+        return;
+    };
+    let Some(cap) = activity.monthly_cap_minutes else {
+        return;
+    };
+    let logged = cache
+        .month_activity_totals
+        .get(&activity.id)
+        .copied()
+        .unwrap_or(0);
+    let total = logged + form.get_minutes().unwrap_or(0);
+    if activity.exceeds_cap(total) {
+        ui.label(
+            RichText::new(format!(
+                "⚠ {} this month with this entry, over the {} cap for {}",
+                cache.duration_style.format_total(total),
+                cache.duration_style.format_total(cap),
+                activity.name
+            ))
+            .small()
+            .color(Color32::from_rgb(255, 140, 0)),
+        );
+    }
+}
 
-                        // Add activity button
-                        if ui.small_button("+ Activity").clicked() {
-                            *dialog = DialogState::AddActivity(project.id);
-                        }
-                    });
-                });
+/// Replace an arithmetic expression such as "8:00-6:15" with its result once
+/// the time field loses focus. Invalid expressions are left for the field error.
+fn evaluate_on_blur(response: &egui::Response, time_str: &mut String) {
+    if !response.lost_focus() || crate::database::parse_time_to_minutes(time_str).is_ok() {
+        return;
+    }
+    if let Ok(minutes) = crate::database::evaluate_time_expression(time_str) {
+        *time_str = format_minutes_to_time(minutes);
+    }
+}
 
-                // Show activities count
-                ui.label(format!("Activities: {}", activity_count));
-            });
-        }
-    });
+fn add_time_to_form(form: &mut TimeEntryForm, minutes_to_add: i32) {
+    if let Ok(current) = crate::database::evaluate_time_expression(&form.time_str) {
+        let new_minutes = (current + minutes_to_add).max(0);
+        form.time_str = format_minutes_to_time(new_minutes);
+    }
+}
 
-    // Execute deferred actions
-    if let Some(id) = action_deactivate {
-        if let Err(e) = db.deactivate_project(id) {
-            eprintln!("Error: {}", e);
-        }
-        cache.mark_dirty();
+/// Optional slider for the time field; moving it rewrites the text as HH:MM
+fn draw_duration_slider(ui: &mut Ui, time_str: &mut String, entries: &EntrySettings) {
+    if !entries.show_duration_slider {
+        return;
     }
-    if let Some(id) = action_activate {
-        if let Err(e) = db.reactivate_project(id) {
-            eprintln!("Error: {}", e);
-        }
-        cache.mark_dirty();
+    let mut minutes = crate::database::parse_time_to_minutes(time_str).unwrap_or(0);
+    let step = entries.slider_step_minutes.max(1);
+    let max = entries.max_entry_minutes.max(step);
+    if ui
+        .add(
+            egui::Slider::new(&mut minutes, 0..=max)
+                .step_by(step as f64)
+                .show_value(false),
+        )
+        .on_hover_text(format!("Drag to adjust in steps of {} min", step))
+        .changed()
+    {
+        *time_str = format_minutes_to_time(minutes);
     }
 }
 
-/// Draw the activities management view
-pub fn draw_activities_view(
+/// Button label of a quick increment, e.g. `+15m` or `-1h`
+fn increment_label(minutes: i32) -> String {
+    let sign = if minutes > 0 { "+" } else { "" };
+    format!("{}{}", sign, format_hours_minutes(minutes))
+}
+
+/// Draw the monthly report: hours per project and activity in one month
+pub fn draw_monthly_report_view(
     ui: &mut Ui,
+    date_state: &mut DateState,
     cache: &mut CachedData,
     dialog: &mut DialogState,
-    filter: &mut FilterState,
+    settings: &Settings,
     db: &Database,
 ) {
     ui.horizontal(|ui| {
-        ui.heading("Manage Activities");
+        if ui.button("◀").on_hover_text("Previous month").clicked() {
+            date_state.previous_month();
+        }
+        ui.label(
+            RichText::new(date_state.month.format("%B %Y").to_string())
+                .size(18.0)
+                .strong(),
+        );
+        if ui.button("▶").on_hover_text("Next month").clicked() {
+            date_state.next_month();
+        }
+        ui.separator();
+        if ui.button("📅 This month").clicked() {
+            date_state.this_month();
+        }
+    });
+    ui.add_space(10.0);
+
+    // Reload when the month changed
+    if cache.monthly_month != Some(date_state.month) {
+        let (start, end) = month_range(date_state.month);
+        cache.monthly_statistics = db.statistics_between(start, end, true).unwrap_or_default();
+        cache.monthly_activities = db
+            .minutes_per_activity_between(start, end)
+            .unwrap_or_default();
+        cache.monthly_travel = db.get_travel_between(start, end).unwrap_or_default();
+        cache.monthly_expenses = db.get_expenses_between(start, end).unwrap_or_default();
+        cache.monthly_month = Some(date_state.month);
+    }
+    let style = cache.duration_style;
+    let stats = &cache.monthly_statistics;
+
+    ui.horizontal(|ui| {
+        ui.heading("Monthly Report");
+        ui.separator();
+        ui.label(
+            RichText::new(format!(
+                "Total: {} ({}h)",
+                style.format_total(stats.total_minutes),
+                format_minutes_to_decimal(stats.total_minutes)
+            ))
+            .strong(),
+        );
+        ui.separator();
+        ui.label(format!("{} days with entries", stats.logged_days));
+        let (start, end) = month_range(date_state.month);
+        for day_type in DayType::ALL {
+            let days = cache
+                .day_types
+                .range(start..=end)
+                .filter(|(_, t)| **t == day_type)
+                .count();
+            if days > 0 && day_type != DayType::Workday {
+                ui.label(format!(
+                    "{} {} × {}",
+                    day_type.icon(),
+                    days,
+                    day_type.label()
+                ));
+            }
+        }
         ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-            let has_projects = !cache.projects.is_empty();
             if ui
-                .add_enabled(has_projects, egui::Button::new("➕ New Activity"))
+                .button("📄 Export CSV…")
+                .on_hover_text("Export individual entries for a date range")
                 .clicked()
             {
-                // Use filtered project if set, otherwise use first active project
-                let default_project_id = filter
-                    .selected_project_id
-                    .or_else(|| cache.projects.iter().find(|p| p.is_active).map(|p| p.id));
-
-                if let Some(project_id) = default_project_id {
-                    *dialog = DialogState::AddActivity(project_id);
-                }
+                *dialog =
+                    DialogState::ExportEntries(EntryExport::new(month_range(date_state.month)));
             }
         });
     });
-
-    ui.horizontal(|ui| {
-        ui.checkbox(&mut filter.show_inactive, "Show inactive");
-
-        ui.separator();
-
-        ui.label("Filter by project:");
-        egui::ComboBox::from_id_salt("project_filter")
-            .selected_text(
-                filter
-                    .selected_project_id
-                    .and_then(|id| cache.get_project_by_id(id))
-                    .map(|p| p.name.as_str())
-                    .unwrap_or("All projects"),
-            )
-            .show_ui(ui, |ui| {
-                ui.selectable_value(&mut filter.selected_project_id, None, "All projects");
-                for project in &cache.projects {
-                    ui.selectable_value(
-                        &mut filter.selected_project_id,
-                        Some(project.id),
-                        &project.name,
-                    );
-                }
-            });
-    });
-
     ui.add_space(10.0);
 
-    // Clone the data we need to avoid borrow issues
-    let activities: Vec<_> = cache
-        .all_activities
-        .iter()
-        .filter(|a| {
-            (filter.show_inactive || a.is_active)
-                && filter
-                    .selected_project_id
-                    .map(|pid| a.project_id == pid)
-                    .unwrap_or(true)
-        })
-        .cloned()
-        .collect();
-
-    // Pre-fetch project names
-    let project_names: std::collections::HashMap<i64, String> = cache
-        .projects
-        .iter()
-        .map(|p| (p.id, p.name.clone()))
-        .collect();
-
-    // Track actions to perform after iteration
-    let mut action_deactivate: Option<i64> = None;
-    let mut action_activate: Option<i64> = None;
+    if stats.per_project.is_empty() {
+        ui.label(RichText::new("No entries in this month.").italics());
+        return;
+    }
 
     egui::ScrollArea::vertical().show(ui, |ui| {
-        for activity in &activities {
-            let project_name = project_names
-                .get(&activity.project_id)
-                .map(|s| s.as_str())
-                .unwrap_or("Unknown");
-
+        for project in &stats.per_project {
             ui.group(|ui| {
                 ui.horizontal(|ui| {
-                    // Status indicator
-                    if activity.is_active {
-                        ui.label(RichText::new("●").color(Color32::GREEN));
-                    } else {
-                        ui.label(RichText::new("●").color(Color32::GRAY));
-                    }
-
-                    // Activity name
-                    ui.label(RichText::new(&activity.name).strong());
-
-                    // Project name
-                    ui.label(format!("({})", project_name));
-
+                    ui.label(RichText::new(&project.project_name).strong());
                     ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-                        // Delete button
-                        if ui.small_button("🗑").clicked() {
-                            *dialog = DialogState::ConfirmDelete(DeleteTarget::Activity(
-                                activity.id,
-                                activity.name.clone(),
-                            ));
-                        }
-
-                        // Edit button
-                        if ui.small_button("✏").clicked() {
-                            *dialog = DialogState::EditActivity(activity.clone());
-                        }
-
-                        // Activate/Deactivate
-                        if activity.is_active {
-                            if ui.small_button("Deactivate").clicked() {
-                                action_deactivate = Some(activity.id);
-                            }
-                        } else {
-                            if ui.small_button("Activate").clicked() {
-                                action_activate = Some(activity.id);
-                            }
-                        }
+                        ui.label(
+                            RichText::new(format!(
+                                "{} ({}h)",
+                                style.format_total(project.minutes),
+                                format_minutes_to_decimal(project.minutes)
+                            ))
+                            .monospace()
+                            .strong(),
+                        );
                     });
                 });
+                egui::Grid::new(("monthly_activities", project.project_id))
+                    .striped(true)
+                    .num_columns(3)
+                    .min_col_width(80.0)
+                    .show(ui, |ui| {
+                        for activity in cache
+                            .monthly_activities
+                            .iter()
+                            .filter(|a| a.project_id == project.project_id)
+                        {
+                            ui.label(format!("  {}", activity.activity_name));
+                            ui.label(
+                                RichText::new(format!("{} days", activity.days))
+                                    .color(Color32::GRAY),
+                            );
+                            ui.label(
+                                RichText::new(format!(
+                                    "{} ({}h)",
+                                    style.format_total(activity.minutes),
+                                    format_minutes_to_decimal(activity.minutes)
+                                ))
+                                .monospace(),
+                            );
+                            ui.end_row();
+                        }
+                    });
             });
+            ui.add_space(5.0);
         }
-    });
-
-    // Execute deferred actions
-    if let Some(id) = action_deactivate {
-        if let Err(e) = db.deactivate_activity_type(id) {
-            eprintln!("Error: {}", e);
+        if !cache.monthly_travel.is_empty() {
+            draw_travel_report(ui, &cache.monthly_travel, style, dialog, date_state.month);
         }
-        cache.mark_dirty();
-    }
-    if let Some(id) = action_activate {
-        if let Err(e) = db.reactivate_activity_type(id) {
-            eprintln!("Error: {}", e);
+        if !cache.monthly_expenses.is_empty() {
+            ui.add_space(5.0);
+            draw_expense_report(
+                ui,
+                &cache.monthly_expenses,
+                &settings.currency,
+                dialog,
+                date_state.month,
+            );
         }
-        cache.mark_dirty();
-    }
+    });
 }
 
-/// Draw dialogs
-pub fn draw_dialog(
-    ctx: &egui::Context,
+/// Expenses of the month with a total per currency, and the grand total in
+/// the report currency once every currency has an exchange rate
+fn draw_expense_report(
+    ui: &mut Ui,
+    expenses: &[crate::database::Expense],
+    currency: &crate::settings::CurrencySettings,
     dialog: &mut DialogState,
-    project_form: &mut ProjectForm,
-    activity_form: &mut ActivityForm,
-    entry_form: &mut TimeEntryForm,
-    cache: &mut CachedData,
-    db: &Database,
+    month: chrono::NaiveDate,
 ) {
-    let mut should_close = false;
+    let mut totals: std::collections::BTreeMap<&str, i64> = std::collections::BTreeMap::new();
+    for expense in expenses {
+        *totals.entry(expense.currency.as_str()).or_default() += expense.amount_cents;
+    }
+    ui.group(|ui| {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("🧾 Expenses").strong());
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                if ui
+                    .button("📄 Export expenses CSV…")
+                    .on_hover_text("Expenses with amount, currency and receipt, for claims")
+                    .clicked()
+                {
+                    *dialog = DialogState::ExportEntries(EntryExport::of(
+                        ExportContent::Expenses,
+                        month_range(month),
+                    ));
+                }
+                let consolidated: Option<i64> = totals
+                    .iter()
+                    .map(|(code, cents)| currency.convert(*cents, code))
+                    .sum();
+                let report_currency = currency.report_currency.trim();
+                let single = totals.len() == 1
+                    && totals
+                        .keys()
+                        .all(|code| code.eq_ignore_ascii_case(report_currency));
+                match consolidated {
+                    _ if single => {}
+                    Some(cents) => {
+                        ui.label(
+                            RichText::new(format!("= {}", currency.format(cents, report_currency)))
+                                .monospace()
+                                .strong(),
+                        )
+                        .on_hover_text("Converted with the exchange rates in Settings");
+                    }
+                    None => {
+                        ui.colored_label(Color32::from_rgb(200, 120, 0), "⚠")
+                            .on_hover_text(format!(
+                                "Add exchange rates to {} in Settings for a grand total",
+                                report_currency
+                            ));
+                    }
+                }
+                let totals: Vec<String> = totals
+                    .iter()
+                    .map(|(code, cents)| currency.format(*cents, code))
+                    .collect();
+                ui.label(RichText::new(totals.join(", ")).monospace().strong());
+            });
+        });
+        egui::Grid::new("monthly_expenses")
+            .striped(true)
+            .num_columns(4)
+            .show(ui, |ui| {
+                for expense in expenses {
+                    ui.label(format!("  {}", expense.date.format("%a %m-%d")));
+                    ui.label(
+                        RichText::new(currency.format(expense.amount_cents, &expense.currency))
+                            .monospace(),
+                    );
+                    ui.label(&expense.description);
+                    ui.label(RichText::new(&expense.receipt_path).color(Color32::GRAY));
+                    ui.end_row();
+                }
+            });
+    });
+}
+
+/// Logged hours per activity multiplied by its hourly rate, with the total
+fn draw_earnings_report(
+    ui: &mut Ui,
+    earnings: &[crate::database::Earning],
+    currency: &crate::settings::CurrencySettings,
+    style: DurationStyle,
+) {
+    let code = currency.report_currency.trim();
+    let total: i64 = earnings.iter().filter_map(|e| e.amount_cents()).sum();
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("Earnings").strong());
+        ui.separator();
+        ui.label(
+            RichText::new(currency.format(total, code))
+                .monospace()
+                .strong(),
+        );
+    });
+    egui::Grid::new("earnings_report")
+        .striped(true)
+        .num_columns(5)
+        .show(ui, |ui| {
+            for earning in earnings {
+                ui.label(&earning.project_name);
+                ui.label(&earning.activity_name);
+                ui.label(
+                    RichText::new(format!(
+                        "{} ({}h)",
+                        style.format_total(earning.minutes),
+                        format_minutes_to_decimal(earning.minutes)
+                    ))
+                    .monospace(),
+                );
+                match (earning.rate_cents, earning.amount_cents()) {
+                    (Some(rate), Some(amount)) => {
+                        ui.label(
+                            RichText::new(format!("× {}/h", currency.format(rate, code)))
+                                .monospace()
+                                .color(Color32::GRAY),
+                        );
+                        ui.label(RichText::new(currency.format(amount, code)).monospace());
+                    }
+                    _ => {
+                        ui.label(RichText::new("no rate").italics().color(Color32::GRAY));
+                        ui.label("");
+                    }
+                }
+                ui.end_row();
+            }
+        });
+}
+
+/// Trips of the month with route, distance and time, for expense claims
+fn draw_travel_report(
+    ui: &mut Ui,
+    travel: &[crate::database::TravelEntry],
+    style: DurationStyle,
+    dialog: &mut DialogState,
+    month: chrono::NaiveDate,
+) {
+    ui.group(|ui| {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("🚗 Travel").strong());
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                if ui
+                    .button("📄 Export travel CSV…")
+                    .on_hover_text("Trips with route, kilometers and hours, for expense claims")
+                    .clicked()
+                {
+                    *dialog = DialogState::ExportEntries(EntryExport::of(
+                        ExportContent::Travel,
+                        month_range(month),
+                    ));
+                }
+                let kilometers: f64 = travel.iter().filter_map(|t| t.trip.kilometers).sum();
+                let minutes: i32 = travel.iter().map(|t| t.detail.entry.minutes).sum();
+                ui.label(
+                    RichText::new(format!(
+                        "{} km, {}",
+                        format_kilometers(kilometers),
+                        style.format_total(minutes)
+                    ))
+                    .monospace()
+                    .strong(),
+                );
+            });
+        });
+        egui::Grid::new("monthly_travel")
+            .striped(true)
+            .num_columns(5)
+            .show(ui, |ui| {
+                for item in travel {
+                    let entry = &item.detail.entry;
+                    ui.label(format!("  {}", entry.date.format("%a %m-%d")));
+                    let route = trip_route(&crate::database::Trip {
+                        kilometers: None,
+                        ..item.trip.clone()
+                    });
+                    ui.label(if route.is_empty() {
+                        "—".to_string()
+                    } else {
+                        route
+                    });
+                    ui.label(
+                        RichText::new(
+                            item.trip
+                                .kilometers
+                                .map(|km| format!("{} km", format_kilometers(km)))
+                                .unwrap_or_default(),
+                        )
+                        .monospace(),
+                    );
+                    ui.label(RichText::new(style.format(entry.minutes)).monospace());
+                    ui.label(RichText::new(&entry.comment).color(Color32::GRAY));
+                    ui.end_row();
+                }
+            });
+    });
+}
+
+/// Draw the week timesheet: activities as rows and days as columns. Typing a
+/// time into a cell sets the activity's total for that day.
+pub fn draw_timesheet_view(
+    ui: &mut Ui,
+    date_state: &mut DateState,
+    timesheet: &mut TimesheetState,
+    cache: &mut CachedData,
+    messages: &mut Vec<UserMessage>,
+    settings: &Settings,
+) {
+    let first = week_start_on(date_state.selected_date, cache.week_start.weekday());
+    let days: Vec<_> = first.iter_days().take(7).collect();
+    let today = chrono::Local::now().date_naive();
+    let mut open_report = false;
+
+    ui.horizontal(|ui| {
+        if ui.button("◀").on_hover_text("Previous week").clicked() {
+            date_state.selected_date -= chrono::Duration::days(7);
+            cache.mark_dirty();
+        }
+        ui.label(
+            RichText::new(format!(
+                "Week {}, {} – {}",
+                (first + chrono::Duration::days(3)).iso_week().week(),
+                first.format("%d %b"),
+                days[6].format("%d %b %Y")
+            ))
+            .size(18.0)
+            .strong(),
+        );
+        if ui.button("▶").on_hover_text("Next week").clicked() {
+            date_state.selected_date += chrono::Duration::days(7);
+            cache.mark_dirty();
+        }
+        ui.separator();
+        if ui.button("📅 This week").clicked() {
+            date_state.today();
+            cache.mark_dirty();
+        }
+        ui.separator();
+        if ui
+            .button("🖨 Open in browser")
+            .on_hover_text("Open the week as an HTML report, e.g. for printing")
+            .clicked()
+        {
+            open_report = true;
+        }
+    });
+    ui.add_space(10.0);
+
+    // Activities with entries this week and rows added by hand, in catalog order
+    let mut rows: Vec<i64> = cache
+        .timesheet_entries
+        .iter()
+        .map(|e| e.activity_type_id)
+        .chain(timesheet.extra_rows.iter().copied())
+        .collect();
+    rows.sort_by_key(|id| cache.all_activities.iter().position(|a| a.id == *id));
+    rows.dedup();
+    let grid = WeekGrid::new(first, &rows, &cache.timesheet_entries, |id| {
+        let activity = cache.get_activity_by_id(id);
+        let project = activity.and_then(|a| cache.get_project_by_id(a.project_id));
+        (
+            project
+                .map(|p| p.display_name())
+                .unwrap_or_else(|| "Unknown".to_string()),
+            activity
+                .map(|a| a.display_name())
+                .unwrap_or_else(|| "Unknown".to_string()),
+        )
+    });
+
+    let style = cache.duration_style;
+    if open_report {
+        open_week_report(ui.ctx(), &grid, style, messages);
+    }
+    let mut actions = Vec::new();
+    egui::ScrollArea::both().show(ui, |ui| {
+        egui::Grid::new("timesheet_grid")
+            .striped(true)
+            .min_col_width(60.0)
+            .show(ui, |ui| {
+                ui.label(RichText::new("Activity").strong());
+                for day in &days {
+                    let text = RichText::new(day.format("%a %d").to_string()).strong();
+                    ui.label(if *day == today {
+                        text.underline()
+                    } else {
+                        text
+                    });
+                }
+                ui.label(RichText::new("Total").strong());
+                ui.end_row();
+
+                for row in &grid.rows {
+                    let activity = row.activity_type_id;
+                    ui.label(format!("{} - {}", row.project_name, row.activity_name));
+                    for (&day, &total) in grid.days.iter().zip(&row.minutes) {
+                        let cell: Vec<_> = cache
+                            .timesheet_entries
+                            .iter()
+                            .filter(|e| e.activity_type_id == activity && e.date == day)
+                            .collect();
+                        if cache.timesheet_locked_days.contains(&day) {
+                            ui.label(RichText::new(style.format(total)).monospace())
+                                .on_hover_text("This day is locked for editing");
+                            continue;
+                        }
+
+                        let key = (activity, day);
+                        let mut text = match &timesheet.editing {
+                            Some((editing, text)) if *editing == key => text.clone(),
+                            _ if total == 0 => String::new(),
+                            _ => format_minutes_to_time(total),
+                        };
+                        let response = ui.add(
+                            egui::TextEdit::singleline(&mut text)
+                                .desired_width(55.0)
+                                .horizontal_align(Align::RIGHT),
+                        );
+                        if response.has_focus() {
+                            timesheet.editing = Some((key, text));
+                        } else if response.lost_focus() {
+                            timesheet.editing = None;
+                            let minutes = if text.trim().is_empty() {
+                                Ok(0)
+                            } else {
+                                crate::database::evaluate_time_expression(&text)
+                                    .map_err(|_| format!("\"{}\" is not a valid time", text.trim()))
+                            };
+                            match minutes.and_then(|minutes| {
+                                timesheet_action(
+                                    &cell,
+                                    activity,
+                                    day,
+                                    minutes,
+                                    &timesheet.comment,
+                                    settings.entries.max_entry_minutes,
+                                    cache.duration_style,
+                                )
+                            }) {
+                                Ok(action) => actions.extend(action),
+                                Err(e) => messages.push(UserMessage::error(e)),
+                            }
+                        }
+                    }
+                    ui.label(
+                        RichText::new(style.format_total(row.total()))
+                            .monospace()
+                            .strong(),
+                    );
+                    ui.end_row();
+                }
+
+                ui.label(RichText::new("Total").strong());
+                for i in 0..grid.days.len() {
+                    ui.label(
+                        RichText::new(style.format(grid.day_total(i)))
+                            .monospace()
+                            .strong(),
+                    );
+                }
+                ui.label(
+                    RichText::new(style.format_total(grid.total()))
+                        .monospace()
+                        .strong(),
+                );
+                ui.end_row();
+            });
+
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.label("Add row:");
+            activity_picker(
+                ui,
+                "timesheet_new_row",
+                cache,
+                None,
+                &mut timesheet.new_row,
+                None,
+            );
+            ui.separator();
+            ui.label("Comment for new entries:");
+            ui.add(
+                egui::TextEdit::singleline(&mut timesheet.comment)
+                    .hint_text("(required)")
+                    .desired_width(200.0),
+            );
+        });
+    });
+
+    if let Some(activity) = timesheet.new_row.take() {
+        if !rows.contains(&activity) {
+            timesheet.extra_rows.push(activity);
+        }
+    }
+    for action in actions {
+        cache.queue(action);
+    }
+}
+
+/// Write the HTML report of a week to the export folder and open it in the browser
+fn open_week_report(
+    ctx: &egui::Context,
+    grid: &WeekGrid,
+    style: DurationStyle,
+    messages: &mut Vec<UserMessage>,
+) {
+    let path = crate::export::get_export_dir().join(format!(
+        "week_{}-W{:02}.html",
+        grid.days[3].iso_week().year(),
+        grid.week_number()
+    ));
+    match crate::export::write_week_html(&path, grid, style) {
+        Ok(_) => {
+            let path = std::path::absolute(&path).unwrap_or(path);
+            let url = format!(
+                "file:///{}",
+                path.display()
+                    .to_string()
+                    .replace('\\', "/")
+                    .trim_start_matches('/')
+            );
+            ctx.open_url(egui::OpenUrl::new_tab(url));
+            messages.push(UserMessage::info(format!(
+                "Week report written to {}",
+                path.display()
+            )));
+        }
+        Err(e) => messages.push(UserMessage::error(format!(
+            "Failed to write the week report: {}",
+            e
+        ))),
+    }
+}
+
+/// Saved report configurations, each with inline editors and a Run button.
+/// Returns true when a configuration was added, changed or deleted.
+fn draw_saved_reports(
+    ui: &mut Ui,
+    settings: &mut Settings,
+    cache: &mut CachedData,
+    messages: &mut Vec<UserMessage>,
+    db: &Database,
+) -> bool {
+    let mut changed = false;
+    egui::CollapsingHeader::new(format!("Saved reports ({})", settings.reports.saved.len()))
+        .id_salt("saved_reports")
+        .show(ui, |ui| {
+            let profiles: Vec<String> = settings
+                .export
+                .profiles
+                .iter()
+                .map(|p| p.name.clone())
+                .collect();
+            let mut run = None;
+            let mut remove = None;
+            let mut pick_folder = None;
+            egui::Grid::new("saved_reports_grid")
+                .striped(true)
+                .num_columns(7)
+                .show(ui, |ui| {
+                    for (i, report) in settings.reports.saved.iter_mut().enumerate() {
+                        changed |= ui
+                            .add(egui::TextEdit::singleline(&mut report.name).desired_width(140.0))
+                            .changed();
+                        egui::ComboBox::from_id_salt(("saved_report_range", i))
+                            .selected_text(report.range.label())
+                            .show_ui(ui, |ui| {
+                                for range in ReportRange::ALL {
+                                    changed |= ui
+                                        .selectable_value(&mut report.range, range, range.label())
+                                        .changed();
+                                }
+                            });
+                        egui::ComboBox::from_id_salt(("saved_report_grouping", i))
+                            .selected_text(report.grouping.label())
+                            .show_ui(ui, |ui| {
+                                for grouping in ReportGrouping::ALL {
+                                    changed |= ui
+                                        .selectable_value(
+                                            &mut report.grouping,
+                                            grouping,
+                                            grouping.label(),
+                                        )
+                                        .changed();
+                                }
+                            });
+                        egui::ComboBox::from_id_salt(("saved_report_format", i))
+                            .selected_text(report.format.label())
+                            .show_ui(ui, |ui| {
+                                for format in ReportFormat::ALL {
+                                    changed |= ui
+                                        .selectable_value(
+                                            &mut report.format,
+                                            format,
+                                            format.label(),
+                                        )
+                                        .changed();
+                                }
+                            });
+                        let profile_label = if profiles.contains(&report.profile) {
+                            report.profile.clone()
+                        } else {
+                            profiles.first().cloned().unwrap_or_default()
+                        };
+                        egui::ComboBox::from_id_salt(("saved_report_profile", i))
+                            .selected_text(profile_label)
+                            .show_ui(ui, |ui| {
+                                for name in &profiles {
+                                    changed |= ui
+                                        .selectable_value(&mut report.profile, name.clone(), name)
+                                        .changed();
+                                }
+                            });
+                        ui.horizontal(|ui| {
+                            let folder = report
+                                .folder
+                                .as_ref()
+                                .map(|f| f.display().to_string())
+                                .unwrap_or_else(|| "Exports folder".to_string());
+                            if ui
+                                .button(format!("📁 {}", folder))
+                                .on_hover_text("Choose the folder the report is written to")
+                                .clicked()
+                            {
+                                pick_folder = Some(i);
+                            }
+                            if report.folder.is_some()
+                                && ui
+                                    .small_button("×")
+                                    .on_hover_text("Write to the exports folder")
+                                    .clicked()
+                            {
+                                report.folder = None;
+                                changed = true;
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            if ui
+                                .button("▶ Run")
+                                .on_hover_text("Write the report for its range as of today")
+                                .clicked()
+                            {
+                                run = Some(i);
+                            }
+                            if ui.small_button("🗑").on_hover_text("Delete").clicked() {
+                                remove = Some(i);
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+            if let Some(i) = pick_folder {
+                if let Some(folder) = any_file_dialog(settings, "saved_report").pick_folder() {
+                    remember_dir(settings, "saved_report", &folder, messages);
+                    settings.reports.saved[i].folder = Some(folder);
+                    changed = true;
+                }
+            }
+            if let Some(i) = run {
+                write_saved_report(&settings.reports.saved[i], settings, cache, messages, db);
+            }
+            if let Some(i) = remove {
+                settings.reports.saved.remove(i);
+                changed = true;
+            }
+            if ui.button("➕ Add report").clicked() {
+                settings.reports.saved.push(SavedReport::default());
+                changed = true;
+            }
+        });
+    changed
+}
+
+/// Run a saved report and tell the user where it went
+fn write_saved_report(
+    report: &SavedReport,
+    settings: &Settings,
+    cache: &mut CachedData,
+    messages: &mut Vec<UserMessage>,
+    db: &Database,
+) {
+    match run_saved_report(report, settings, messages, db) {
+        Ok((path, rows)) => {
+            cache.mark_dirty();
+            messages.push(UserMessage::info(format!(
+                "Report '{}' written to {} ({} rows)",
+                report.name,
+                path.display(),
+                rows
+            )));
+        }
+        Err(e) => messages.push(UserMessage::error(format!(
+            "Report '{}' failed: {}",
+            report.name, e
+        ))),
+    }
+}
+
+/// Saved reports picked in the close-out settings; names no longer saved are skipped
+fn close_out_reports(settings: &Settings) -> Vec<&SavedReport> {
+    settings
+        .reports
+        .saved
+        .iter()
+        .filter(|r| settings.close_out.run_reports.contains(&r.name))
+        .collect()
+}
+
+/// Write a saved report for its range as of today and record it in the export history.
+/// Returns the written file and its row count.
+fn run_saved_report(
+    report: &SavedReport,
+    settings: &Settings,
+    messages: &mut Vec<UserMessage>,
+    db: &Database,
+) -> Result<(std::path::PathBuf, usize), String> {
+    let today = chrono::Local::now().date_naive();
+    let (start, end) = report.range.dates(today, settings.calendar.week_start);
+    let entries = db
+        .get_entry_details_for_range(start, end)
+        .map_err(|e| e.to_string())?;
+    let profiles = &settings.export.profiles;
+    let profile = profiles
+        .iter()
+        .find(|p| p.name == report.profile)
+        .or(profiles.first())
+        .cloned()
+        .unwrap_or_default();
+    let style = settings.display.duration_style;
+    let table = match report.grouping {
+        ReportGrouping::Ticket => {
+            let pattern = regex::Regex::new(&settings.reports.ticket_pattern)
+                .map_err(|e| format!("invalid ticket pattern: {}", e))?;
+            let tickets = crate::reports::aggregate_by_ticket(&entries, &pattern);
+            crate::export::ticket_table(&tickets, &profile, style)
+        }
+        ReportGrouping::CostCenter => crate::export::cost_center_table(
+            &crate::reports::aggregate_by_cost_center(&entries),
+            &profile,
+            style,
+        ),
+        ReportGrouping::Entries => crate::export::entries_table(&entries),
+    };
+    let path = report
+        .folder
+        .clone()
+        .unwrap_or_else(crate::export::get_export_dir)
+        .join(report.file_name(start, end));
+    let title = format!("{}, {} – {}", report.name, start, end);
+    let rows = crate::export::write_table(&path, &table, report.format, &title)
+        .map_err(|e| e.to_string())?;
+    if let Err(e) = db.log_export(report.grouping.kind(), Some((start, end)), &path, rows) {
+        messages.push(export_log_error(e));
+    }
+    Ok((path, rows))
+}
+
+/// Search box for entry comments across all dates, with the matches below it.
+/// "Open" shows a match's day on the Time Tracking tab.
+fn draw_comment_search(
+    ui: &mut Ui,
+    report_state: &mut ReportState,
+    cache: &mut CachedData,
+    db: &Database,
+) {
+    ui.horizontal(|ui| {
+        ui.label("🔍 Search comments:");
+        ui.add(
+            egui::TextEdit::singleline(&mut report_state.search)
+                .desired_width(250.0)
+                .hint_text("words or ticket numbers"),
+        );
+        if !report_state.search.is_empty() && ui.small_button("✖").clicked() {
+            report_state.search.clear();
+        }
+    });
+
+    let query = report_state.search.trim();
+    if query.is_empty() {
+        return;
+    }
+    if cache.search_query.as_deref() != Some(query) {
+        cache.search_results = db.search_comments(query, 200).unwrap_or_default();
+        cache.search_query = Some(query.to_string());
+    }
+
+    egui::CollapsingHeader::new(format!("Matches ({})", cache.search_results.len()))
+        .id_salt("comment_search")
+        .default_open(true)
+        .show(ui, |ui| {
+            if cache.search_results.is_empty() {
+                ui.label(RichText::new("No comments match.").italics());
+                return;
+            }
+            egui::ScrollArea::vertical()
+                .id_salt("comment_search_results")
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("comment_search_grid")
+                        .striped(true)
+                        .num_columns(5)
+                        .show(ui, |ui| {
+                            for detail in &cache.search_results {
+                                ui.label(detail.entry.date.format("%a %Y-%m-%d").to_string());
+                                ui.label(
+                                    RichText::new(
+                                        cache.duration_style.format(detail.entry.minutes),
+                                    )
+                                    .monospace(),
+                                );
+                                ui.label(format!(
+                                    "{} - {}",
+                                    detail.project_name, detail.activity_name
+                                ));
+                                draw_comment(ui, &detail.entry.comment, &cache.ticket_linker, true);
+                                if ui.small_button("Open").clicked() {
+                                    report_state.open_date = Some(detail.entry.date);
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+        });
+}
+
+/// Draw the daily summary view. Returns true when settings were changed.
+pub fn draw_daily_summary_view(
+    ui: &mut Ui,
+    date_state: &mut DateState,
+    cache: &mut CachedData,
+    dialog: &mut DialogState,
+    settings: &mut Settings,
+    db: &Database,
+) -> bool {
+    let mut settings_changed = false;
+    draw_date_selector(ui, date_state, cache);
+    ui.add_space(10.0);
+
+    ui.horizontal(|ui| {
+        ui.heading("Daily Summary");
+        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+            if ui
+                .button("📄 Export CSV…")
+                .on_hover_text("Export individual entries for a date range")
+                .clicked()
+            {
+                let date = date_state.selected_date;
+                *dialog = DialogState::ExportEntries(EntryExport::new((date, date)));
+            }
+            if ui
+                .add_enabled(
+                    !cache.daily_summary.is_empty(),
+                    egui::Button::new("📋 Copy summary"),
+                )
+                .clicked()
+            {
+                ui.output_mut(|o| {
+                    o.copied_text = format_daily_summary(
+                        &cache.daily_summary,
+                        &settings.clipboard.summary_line_template,
+                        settings.display.duration_style,
+                    );
+                });
+            }
+        });
+    });
+    ui.label("Total time per activity (for entering into time management system):");
+    ui.add_space(10.0);
+
+    // Check if we need to refresh summary for date change
+    if cache.summary_date != Some(date_state.selected_date) {
+        cache.daily_summary = db
+            .get_daily_summary(date_state.selected_date)
+            .unwrap_or_default();
+        cache.summary_date = Some(date_state.selected_date);
+    }
+
+    if cache.daily_summary.is_empty() {
+        ui.label("No entries for this date.");
+        return settings_changed;
+    }
+
+    // Group by project
+    let mut by_project: std::collections::HashMap<String, Vec<&ActivitySummary>> =
+        std::collections::HashMap::new();
+    for summary in &cache.daily_summary {
+        by_project
+            .entry(summary.project_name.clone())
+            .or_default()
+            .push(summary);
+    }
+
+    let total_day_minutes: i32 = cache.daily_summary.iter().map(|s| s.total_minutes).sum();
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        // Sort projects by name for consistent ordering
+        let mut sorted_projects: Vec<_> = by_project.iter().collect();
+        sorted_projects.sort_by(|a, b| a.0.cmp(b.0));
+
+        for (project_name, activities) in sorted_projects {
+            let project_minutes: i32 = activities.iter().map(|s| s.total_minutes).sum();
+            let collapsed = settings.layout.is_collapsed("daily_summary", project_name);
+            let header = egui::CollapsingHeader::new(
+                RichText::new(format!(
+                    "{}  {}",
+                    project_name,
+                    cache.duration_style.format(project_minutes)
+                ))
+                .heading(),
+            )
+            .id_salt(("daily_summary_group", project_name))
+            .open(Some(!collapsed))
+            .show(ui, |ui| {
+                // Sort activities by name for consistent ordering
+                let mut sorted_activities = activities.clone();
+                sorted_activities.sort_by(|a, b| a.activity_name.cmp(&b.activity_name));
+
+                for summary in sorted_activities {
+                    ui.horizontal(|ui| {
+                        // Activity name, with copy actions on right-click
+                        ui.add(
+                            egui::Label::new(RichText::new(&summary.activity_name).strong())
+                                .sense(egui::Sense::click()),
+                        )
+                        .on_hover_text("Right-click for more copy options")
+                        .context_menu(|ui| {
+                            if ui.button("Copy as Jira worklog").clicked() {
+                                ui.output_mut(|o| {
+                                    o.copied_text = format_jira_worklog(summary);
+                                });
+                                ui.close_menu();
+                            }
+                        });
+
+                        // Total time in HH:MM format
+                        ui.label(
+                            RichText::new(cache.duration_style.format(summary.total_minutes))
+                                .monospace()
+                                .color(Color32::from_rgb(0, 100, 200)),
+                        );
+
+                        // Total time in decimal format
+                        ui.label(
+                            RichText::new(format!(
+                                "({}h)",
+                                format_minutes_to_decimal(summary.total_minutes)
+                            ))
+                            .monospace()
+                            .color(Color32::from_rgb(100, 100, 100)),
+                        );
+
+                        // Copy button for decimal format
+                        if ui.small_button("📋 Copy").clicked() {
+                            ui.output_mut(|o| {
+                                o.copied_text = format_minutes_to_decimal(summary.total_minutes);
+                            });
+                        }
+
+                        // Copy the whole line with the entry template
+                        if ui
+                            .small_button("📋 Line")
+                            .on_hover_text("Copy the line with the entry line template")
+                            .clicked()
+                        {
+                            let comments: Vec<&str> = summary
+                                .entries
+                                .iter()
+                                .map(|e| e.comment.trim())
+                                .filter(|c| !c.is_empty())
+                                .collect();
+                            ui.output_mut(|o| {
+                                o.copied_text = format_entry_line(
+                                    &settings.clipboard.entry_line_template,
+                                    settings.display.duration_style,
+                                    summary.total_minutes,
+                                    &summary.project_name,
+                                    &summary.activity_name,
+                                    &comments.join("; "),
+                                );
+                            });
+                        }
+                    });
+
+                    // Show individual entries
+                    ui.indent(summary.activity_type_id, |ui| {
+                        for entry in &summary.entries {
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    RichText::new(cache.duration_style.format(entry.minutes))
+                                        .small()
+                                        .monospace(),
+                                );
+                                if entry.kind == EntryKind::Adjustment {
+                                    draw_adjustment_badge(ui, &entry.reason);
+                                }
+                                if !entry.comment.is_empty() {
+                                    draw_comment(ui, &entry.comment, &cache.ticket_linker, true);
+                                }
+                                if ui
+                                    .small_button("📋")
+                                    .on_hover_text("Copy entry line")
+                                    .clicked()
+                                {
+                                    ui.output_mut(|o| {
+                                        o.copied_text = format_entry_line(
+                                            &settings.clipboard.entry_line_template,
+                                            settings.display.duration_style,
+                                            entry.minutes,
+                                            &summary.project_name,
+                                            &summary.activity_name,
+                                            &entry.comment,
+                                        );
+                                    });
+                                }
+                            });
+                        }
+                    });
+
+                    ui.add_space(5.0);
+                }
+            });
+            if header.header_response.clicked() {
+                settings
+                    .layout
+                    .set_collapsed("daily_summary", project_name, !collapsed);
+                settings_changed = true;
+            }
+            ui.add_space(5.0);
+        }
+
+        // Grand total
+        ui.add_space(10.0);
+        ui.separator();
+
+        let target = settings.calendar.target_for(date_state.selected_date);
+
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("TOTAL FOR DAY:").strong().size(16.0));
+
+            let color = target_color(total_day_minutes, target);
+
+            ui.label(
+                RichText::new(cache.duration_style.format(total_day_minutes))
+                    .strong()
+                    .size(18.0)
+                    .color(color),
+            );
+
+            ui.label(
+                RichText::new(format!(
+                    "({}h)",
+                    format_minutes_to_decimal(total_day_minutes)
+                ))
+                .strong()
+                .size(16.0)
+                .color(color),
+            );
+
+            draw_target_status(ui, total_day_minutes, target, cache.duration_style);
+        });
+        draw_billable_split(
+            ui,
+            billable_split(cache.daily_summary.iter().flat_map(|s| &s.entries)),
+            cache.duration_style,
+        );
+    });
+
+    settings_changed
+}
+
+/// Draw the reports view. Returns true when a setting was changed.
+pub fn draw_reports_view(
+    ui: &mut Ui,
+    report_state: &mut ReportState,
+    cache: &mut CachedData,
+    dialog: &mut DialogState,
+    settings: &mut Settings,
+    messages: &mut Vec<UserMessage>,
+    db: &Database,
+) -> bool {
+    let mut settings_changed = false;
+
+    ui.horizontal(|ui| {
+        ui.heading("Reports");
+        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+            if ui
+                .button("🗓 Close month…")
+                .on_hover_text("Month-end checks, export and lock for the month of the From date")
+                .clicked()
+            {
+                *dialog = DialogState::MonthEnd(MonthEndWizard::new(report_state.start_date));
+            }
+        });
+    });
+    ui.add_space(5.0);
+    draw_comment_search(ui, report_state, cache, db);
+    settings_changed |= draw_saved_reports(ui, settings, cache, messages, db);
+
+    // Range selector
+    ui.horizontal(|ui| {
+        if ui.button("◀ Month").clicked() {
+            report_state.previous_month();
+        }
+        ui.label("From:");
+        ui.add(
+            egui_extras::DatePickerButton::new(&mut report_state.start_date)
+                .id_salt("report_start"),
+        );
+        ui.label("To:");
+        ui.add(
+            egui_extras::DatePickerButton::new(&mut report_state.end_date).id_salt("report_end"),
+        );
+        if ui.button("Month ▶").clicked() {
+            report_state.next_month();
+        }
+        ui.separator();
+        if ui.button("📅 This month").clicked() {
+            report_state.this_month();
+        }
+        ui.separator();
+        ui.label("Week:");
+        let week_response = ui.add(
+            egui::TextEdit::singleline(&mut report_state.week_input)
+                .desired_width(70.0)
+                .hint_text("v42"),
+        );
+        if week_response.lost_focus() {
+            let year = report_state.start_date.iso_week().year();
+            if let Some((start, end)) = parse_iso_week(&report_state.week_input, year) {
+                report_state.start_date = start;
+                report_state.end_date = end;
+            }
+        }
+        ui.separator();
+        if ui
+            .checkbox(&mut report_state.include_inactive, "Include inactive")
+            .on_hover_text("Include time logged on inactive projects and activities")
+            .changed()
+        {
+            // The overview and earnings are summed in the database
+            cache.report_range = None;
+        }
+    });
+    if report_state.end_date < report_state.start_date {
+        report_state.end_date = report_state.start_date;
+    }
+
+    // Reload entries when the range changed
+    let range = (report_state.start_date, report_state.end_date);
+    if cache.report_range != Some(range) {
+        cache.report_entries = db
+            .get_entry_details_for_range(range.0, range.1)
+            .unwrap_or_default();
+        cache.report_statistics = db
+            .statistics_between(range.0, range.1, report_state.include_inactive)
+            .unwrap_or_default();
+        cache.report_earnings = db
+            .earnings_between(range.0, range.1, report_state.include_inactive)
+            .unwrap_or_default();
+        cache.report_range = Some(range);
+    }
+    let entries: Vec<_> = cache
+        .report_entries
+        .iter()
+        .filter(|d| {
+            report_state.include_inactive || cache.is_activity_selectable(d.entry.activity_type_id)
+        })
+        .cloned()
+        .collect();
+
+    // Working days below the day total target, up to today
+    let today = chrono::Local::now().date_naive();
+    let coverage = crate::reports::find_missing_days(
+        &cache.report_entries,
+        range.0,
+        range.1.min(today),
+        &settings.calendar,
+        |date| settings.calendar.target_for(date),
+    );
+
+    draw_report_overview(
+        ui,
+        &cache.report_statistics,
+        coverage.working_days,
+        cache.duration_style,
+    );
+    if coverage.working_days > 0 {
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Logged days").strong());
+            ui.add(
+                egui::ProgressBar::new(
+                    coverage.complete_days() as f32 / coverage.working_days as f32,
+                )
+                .desired_width(200.0)
+                .text(format!(
+                    "{} of {} working days",
+                    coverage.complete_days(),
+                    coverage.working_days
+                )),
+            );
+        });
+        if !coverage.missing.is_empty() {
+            egui::CollapsingHeader::new(format!("Missing days ({})", coverage.missing.len()))
+                .id_salt("missing_days")
+                .show(ui, |ui| {
+                    egui::Grid::new("missing_days_grid")
+                        .striped(true)
+                        .num_columns(3)
+                        .show(ui, |ui| {
+                            for day in &coverage.missing {
+                                ui.label(day.date.format("%a %Y-%m-%d").to_string());
+                                ui.label(
+                                    RichText::new(cache.duration_style.format(day.total_minutes))
+                                        .monospace()
+                                        .color(if day.total_minutes == 0 {
+                                            Color32::from_rgb(255, 0, 0)
+                                        } else {
+                                            Color32::from_rgb(255, 200, 0)
+                                        }),
+                                );
+                                if ui.small_button("Open").clicked() {
+                                    report_state.open_date = Some(day.date);
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+        }
+    }
+
+    // Week subtotals when the range spans several weeks
+    if range.0.iso_week() != range.1.iso_week() {
+        ui.add_space(10.0);
+        ui.label(RichText::new("By week").strong());
+        egui::Grid::new("week_report")
+            .striped(true)
+            .num_columns(3)
+            .show(ui, |ui| {
+                for week in crate::reports::aggregate_by_week(&entries) {
+                    ui.label(
+                        RichText::new(format!("v{}", week.week))
+                            .monospace()
+                            .strong(),
+                    )
+                    .on_hover_text(format!("{}-W{:02}", week.year, week.week));
+                    ui.label(
+                        RichText::new(format!(
+                            "{} – {}",
+                            week.start.max(range.0).format("%m-%d"),
+                            (week.start + chrono::Duration::days(6))
+                                .min(range.1)
+                                .format("%m-%d")
+                        ))
+                        .color(Color32::GRAY),
+                    );
+                    ui.label(
+                        RichText::new(format!(
+                            "{} ({}h)",
+                            cache.duration_style.format_total(week.total_minutes),
+                            format_minutes_to_decimal(week.total_minutes)
+                        ))
+                        .monospace(),
+                    );
+                    ui.end_row();
+                }
+            });
+    }
+
+    ui.add_space(10.0);
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("By ticket").strong());
+        ui.separator();
+        ui.label("Ticket pattern (regex):");
+        settings_changed |= ui
+            .add(
+                egui::TextEdit::singleline(&mut settings.reports.ticket_pattern)
+                    .desired_width(150.0)
+                    .font(egui::TextStyle::Monospace),
+            )
+            .changed();
+    });
+
+    let pattern = match regex::Regex::new(&settings.reports.ticket_pattern) {
+        Ok(pattern) => pattern,
+        Err(_) => {
+            ui.colored_label(Color32::RED, "Invalid regular expression");
+            return settings_changed;
+        }
+    };
+    let report = crate::reports::aggregate_by_ticket(&entries, &pattern);
+
+    // Export profile (rounding rules of the target system)
+    let profiles = &settings.export.profiles;
+    if report_state.profile >= profiles.len() {
+        report_state.profile = 0;
+    }
+    let profile = profiles
+        .get(report_state.profile)
+        .cloned()
+        .unwrap_or_default();
+
+    let mut export_clicked = false;
+    ui.horizontal(|ui| {
+        ui.label("Export profile:");
+        egui::ComboBox::from_id_salt("export_profile")
+            .selected_text(&profile.name)
+            .show_ui(ui, |ui| {
+                for (i, p) in profiles.iter().enumerate() {
+                    ui.selectable_value(&mut report_state.profile, i, &p.name);
+                }
+            });
+        export_clicked = ui
+            .add_enabled(
+                !report.tickets.is_empty(),
+                egui::Button::new("📄 Export CSV…"),
+            )
+            .clicked();
+
+        // Warn before sending the same range twice
+        if let Some(previous) = cache.export_log.iter().find(|r| {
+            r.kind == "tickets" && r.start_date == Some(range.0) && r.end_date == Some(range.1)
+        }) {
+            ui.label(
+                RichText::new(format!("✔ Already exported {}", previous.exported_at))
+                    .small()
+                    .color(Color32::GRAY),
+            )
+            .on_hover_text(&previous.path);
+        }
+    });
+    if export_clicked {
+        match export_ticket_report(settings, &report, &profile, range, messages, db) {
+            Some(Ok((path, rows))) => {
+                cache.mark_dirty();
+                messages.push(UserMessage::info(format!(
+                    "Exported {} tickets to {}",
+                    rows,
+                    path.display()
+                )))
+            }
+            Some(Err(e)) => messages.push(UserMessage::error(format!("Export failed: {}", e))),
+            None => {}
+        }
+    }
+    ui.add_space(5.0);
+
+    if report.tickets.is_empty() {
+        ui.label("No ticket references found in this range.");
+    }
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        egui::Grid::new("ticket_report")
+            .striped(true)
+            .num_columns(if profile.rounds() { 5 } else { 4 })
+            .show(ui, |ui| {
+                for ticket in &report.tickets {
+                    ui.label(RichText::new(&ticket.ticket).monospace().strong());
+                    ui.label(
+                        RichText::new(cache.duration_style.format_total(ticket.total_minutes))
+                            .monospace()
+                            .color(Color32::from_rgb(0, 100, 200)),
+                    );
+                    ui.label(
+                        RichText::new(format!(
+                            "({}h)",
+                            format_minutes_to_decimal(ticket.total_minutes)
+                        ))
+                        .monospace()
+                        .color(Color32::from_rgb(100, 100, 100)),
+                    );
+                    if profile.rounds() {
+                        ui.label(
+                            RichText::new(format!(
+                                "→ {}h",
+                                format_minutes_to_decimal(profile.round(ticket.total_minutes))
+                            ))
+                            .monospace(),
+                        )
+                        .on_hover_text(format!("Rounded by the '{}' profile", profile.name));
+                    }
+                    ui.label(format!("{} entries", ticket.entry_count));
+                    ui.end_row();
+                }
+            });
+
+        if profile.rounds() && !report.tickets.is_empty() {
+            let raw: i32 = report.tickets.iter().map(|t| t.total_minutes).sum();
+            let rounded: i32 = report
+                .tickets
+                .iter()
+                .map(|t| profile.round(t.total_minutes))
+                .sum();
+            ui.add_space(5.0);
+            ui.label(format!(
+                "Total: {}h raw, {}h rounded",
+                format_minutes_to_decimal(raw),
+                format_minutes_to_decimal(rounded)
+            ));
+        }
+
+        if report.unmatched_minutes > 0 {
+            ui.add_space(10.0);
+            ui.label(
+                RichText::new(format!(
+                    "Without ticket reference: {} ({}h)",
+                    cache.duration_style.format_total(report.unmatched_minutes),
+                    format_minutes_to_decimal(report.unmatched_minutes)
+                ))
+                .italics(),
+            );
+        }
+
+        // Cost centers, once any activity has one
+        if entries.iter().any(|d| !d.cost_center.is_empty()) {
+            let totals = crate::reports::aggregate_by_cost_center(&entries);
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("By cost center").strong());
+                if ui.button("📄 Export CSV…").clicked() {
+                    let style = settings.display.duration_style;
+                    match export_report(
+                        settings,
+                        "cost_center_export",
+                        "cost_centers",
+                        range,
+                        messages,
+                        db,
+                        |path| crate::export::write_cost_center_csv(path, &totals, &profile, style),
+                    ) {
+                        Some(Ok((path, rows))) => {
+                            cache.mark_dirty();
+                            messages.push(UserMessage::info(format!(
+                                "Exported {} cost center rows to {}",
+                                rows,
+                                path.display()
+                            )))
+                        }
+                        Some(Err(e)) => {
+                            messages.push(UserMessage::error(format!("Export failed: {}", e)))
+                        }
+                        None => {}
+                    }
+                }
+            });
+            egui::Grid::new("cost_center_report")
+                .striped(true)
+                .num_columns(4)
+                .show(ui, |ui| {
+                    for total in &totals {
+                        ui.label(
+                            RichText::new(if total.cost_center.is_empty() {
+                                "—"
+                            } else {
+                                &total.cost_center
+                            })
+                            .monospace()
+                            .strong(),
+                        );
+                        ui.label(RichText::new(&total.project_code).monospace());
+                        ui.label(
+                            RichText::new(format!(
+                                "{} ({}h)",
+                                cache.duration_style.format_total(total.total_minutes),
+                                format_minutes_to_decimal(total.total_minutes)
+                            ))
+                            .monospace(),
+                        );
+                        ui.label(format!("{} entries", total.entry_count));
+                        ui.end_row();
+                    }
+                });
+        }
+
+        // Earnings, once any project or activity has an hourly rate
+        if cache.report_earnings.iter().any(|e| e.rate_cents.is_some()) {
+            ui.add_space(10.0);
+            draw_earnings_report(
+                ui,
+                &cache.report_earnings,
+                &settings.currency,
+                cache.duration_style,
+            );
+        }
+
+        ui.add_space(10.0);
+        egui::CollapsingHeader::new(format!("Export history ({})", cache.export_log.len()))
+            .id_salt("export_history")
+            .show(ui, |ui| {
+                if cache.export_log.is_empty() {
+                    ui.label("Nothing exported yet.");
+                }
+                egui::Grid::new("export_history_grid")
+                    .striped(true)
+                    .num_columns(5)
+                    .show(ui, |ui| {
+                        for record in &cache.export_log {
+                            ui.label(RichText::new(&record.exported_at).monospace());
+                            ui.label(&record.kind);
+                            ui.label(match (record.start_date, record.end_date) {
+                                (Some(start), Some(end)) => format!("{} – {}", start, end),
+                                _ => "—".to_string(),
+                            });
+                            ui.label(format!("{} rows", record.row_count));
+                            ui.label(RichText::new(&record.path).small());
+                            ui.end_row();
+                        }
+                    });
+            });
+    });
+
+    settings_changed
+}
+
+/// Checkbox enabling a date picker; unticked means no date
+fn optional_date_picker(
+    ui: &mut Ui,
+    id_salt: &str,
+    label: &str,
+    date: &mut Option<chrono::NaiveDate>,
+) {
+    let mut enabled = date.is_some();
+    if ui.checkbox(&mut enabled, label).changed() {
+        *date = enabled.then(|| chrono::Local::now().date_naive());
+    }
+    if let Some(date) = date {
+        ui.add(egui_extras::DatePickerButton::new(date).id_salt(id_salt));
+    }
+}
+
+/// Dates with their logged time, as listed by the month-end wizard
+fn draw_day_totals(
+    ui: &mut Ui,
+    id_salt: &str,
+    days: &[crate::reports::DayTotal],
+    cache: &CachedData,
+) {
+    egui::Grid::new(id_salt)
+        .striped(true)
+        .num_columns(2)
+        .show(ui, |ui| {
+            for day in days {
+                ui.label(day.date.format("%a %Y-%m-%d").to_string());
+                ui.label(RichText::new(cache.duration_style.format(day.total_minutes)).monospace());
+                ui.end_row();
+            }
+        });
+}
+
+/// Ask where to save the ticket report, write it and record the export.
+/// Returns the path and row count, or None when the file dialog was cancelled.
+fn export_ticket_report(
+    settings: &mut Settings,
+    report: &crate::reports::TicketReport,
+    profile: &ExportProfile,
+    range: (chrono::NaiveDate, chrono::NaiveDate),
+    messages: &mut Vec<UserMessage>,
+    db: &Database,
+) -> Option<std::io::Result<(std::path::PathBuf, usize)>> {
+    let style = settings.display.duration_style;
+    export_report(
+        settings,
+        "ticket_export",
+        "tickets",
+        range,
+        messages,
+        db,
+        |path| crate::export::write_ticket_csv(path, report, profile, style),
+    )
+}
+
+/// Ask where to save a report of the given kind, write it with `write` and record the export.
+/// `dialog_key` names the remembered folder. Returns the path and row count, or None when
+/// the file dialog was cancelled.
+fn export_report(
+    settings: &mut Settings,
+    dialog_key: &str,
+    kind: &str,
+    range: (chrono::NaiveDate, chrono::NaiveDate),
+    messages: &mut Vec<UserMessage>,
+    db: &Database,
+    write: impl FnOnce(&std::path::Path) -> std::io::Result<usize>,
+) -> Option<std::io::Result<(std::path::PathBuf, usize)>> {
+    let path = file_dialog(settings, dialog_key, "CSV", &["csv"])
+        .set_file_name(crate::export::export_file_name(kind, range.0, range.1))
+        .save_file()?;
+    remember_dir(settings, dialog_key, &path, messages);
+    let result = write(&path).map(|rows| {
+        if let Err(e) = db.log_export(kind, Some(range), &path, rows) {
+            messages.push(export_log_error(e));
+        }
+        (path, rows)
+    });
+    Some(result)
+}
+
+/// Message for an export that was written but is missing from the export history
+fn export_log_error(e: crate::database::DatabaseError) -> UserMessage {
+    UserMessage::error(format!(
+        "The file was exported, but the export history could not be updated: {}",
+        e
+    ))
+}
+
+/// Draw the projects management view
+pub fn draw_projects_view(
+    ui: &mut Ui,
+    cache: &mut CachedData,
+    dialog: &mut DialogState,
+    filter: &mut FilterState,
+    settings: &mut Settings,
+    messages: &mut Vec<UserMessage>,
+    db: &Database,
+) {
+    ui.horizontal(|ui| {
+        ui.heading("Manage Projects");
+        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+            if ui.button("➕ New Project").clicked() {
+                *dialog = DialogState::AddProject;
+            }
+            if cache
+                .projects
+                .iter()
+                .any(|p| crate::database::is_sample_project(&p.name))
+                && ui
+                    .button("🧹 Remove sample data")
+                    .on_hover_text(
+                        "Delete the example projects and activities created on first run, \
+                         except those with time entries",
+                    )
+                    .clicked()
+            {
+                cache.queue(AppAction::RemoveSampleData);
+            }
+            if ui
+                .button("📥 Import catalog")
+                .on_hover_text("Import projects and activities from a company CSV/JSON catalog")
+                .clicked()
+            {
+                *dialog = DialogState::ImportCatalog(CatalogImport::default());
+            }
+            if ui
+                .button("📤 Export catalog…")
+                .on_hover_text("Export projects, activities and codes (no time entries)")
+                .clicked()
+            {
+                let today = chrono::Local::now().date_naive();
+                if let Some(path) = file_dialog(settings, "catalog_export", "CSV", &["csv"])
+                    .set_file_name(format!("catalog_{}.csv", today))
+                    .save_file()
+                {
+                    remember_dir(settings, "catalog_export", &path, messages);
+                    let rows =
+                        crate::catalog::catalog_rows(&cache.projects, &cache.all_activities, today);
+                    match crate::export::write_catalog_csv(&path, &rows) {
+                        Ok(count) => {
+                            if let Err(e) = db.log_export("catalog", None, &path, count) {
+                                messages.push(export_log_error(e));
+                            }
+                            cache.mark_dirty();
+                            messages.push(UserMessage::info(format!(
+                                "Exported {} catalog rows to {}",
+                                count,
+                                path.display()
+                            )))
+                        }
+                        Err(e) => {
+                            messages.push(UserMessage::error(format!("Export failed: {}", e)))
+                        }
+                    }
+                }
+            }
+        });
+    });
+
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut filter.show_inactive, "Show inactive projects");
+        ui.checkbox(&mut filter.show_archived, "Show archived");
+        ui.separator();
+        draw_search_and_sort(
+            ui,
+            "project_sort",
+            &mut filter.project_search,
+            &mut filter.project_sort,
+        );
+    });
+
+    // Borrow from snapshots of the lists so the cache stays free to mark dirty
+    let all_projects = Arc::clone(&cache.projects);
+    let project_usage = Arc::clone(&cache.project_usage);
+    let mut projects: Vec<&Project> = all_projects
+        .iter()
+        .filter(|p| {
+            (filter.show_inactive || p.is_active)
+                && (filter.show_archived || !p.archived)
+                && matches_search(&filter.project_search, &[&p.name, &p.code, &p.description])
+        })
+        .collect();
+    let usage = |id: i64| project_usage.get(&id).copied().unwrap_or_default();
+    projects.sort_by(|a, b| {
+        filter.project_sort.compare(
+            (&a.name, &a.code, usage(a.id)),
+            (&b.name, &b.code, usage(b.id)),
+        )
+    });
+
+    // Bulk actions on the ticked projects
+    filter
+        .selected_projects
+        .retain(|id| cache.get_project_by_id(*id).is_some());
+    let visible: Vec<i64> = projects.iter().map(|p| p.id).collect();
+    if let Some(action) = draw_bulk_bar(ui, &mut filter.selected_projects, &visible) {
+        let items: Vec<(i64, String)> = filter
+            .selected_projects
+            .iter()
+            .filter_map(|id| cache.get_project_by_id(*id))
+            .map(|p| (p.id, p.name.clone()))
+            .collect();
+        if action == BulkAction::Delete {
+            *dialog = DialogState::ConfirmDelete(DeleteTarget::Projects(items));
+        } else {
+            cache.queue(AppAction::SetProjectsState { items, action });
+            filter.selected_projects.clear();
+        }
+    }
+    ui.add_space(10.0);
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        for &project in &projects {
+            // Count activities for this project
+            let activity_count = cache
+                .all_activities
+                .iter()
+                .filter(|a| a.project_id == project.id && a.is_active)
+                .count();
+
+            ui.group(|ui| {
+                ui.horizontal(|ui| {
+                    let mut ticked = filter.selected_projects.contains(&project.id);
+                    if ui.checkbox(&mut ticked, "").changed() {
+                        toggle_selection(&mut filter.selected_projects, project.id, ticked);
+                    }
+
+                    // Status indicator
+                    if project.is_active {
+                        ui.label(RichText::new("●").color(Color32::GREEN));
+                    } else {
+                        ui.label(RichText::new("●").color(Color32::GRAY));
+                    }
+
+                    // Project name
+                    ui.label(RichText::new(project.display_name()).strong());
+                    if !project.code.is_empty() {
+                        ui.label(
+                            RichText::new(&project.code)
+                                .small()
+                                .monospace()
+                                .color(Color32::GRAY),
+                        );
+                    }
+
+                    // Description
+                    if !project.description.is_empty() {
+                        ui.label(format!("- {}", project.description));
+                    }
+                    if project.archived {
+                        ui.label(RichText::new("archived").small().color(Color32::GRAY));
+                    }
+
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        // Delete button
+                        if ui.small_button("🗑").clicked() {
+                            *dialog = DialogState::ConfirmDelete(DeleteTarget::Project(
+                                project.id,
+                                project.name.clone(),
+                            ));
+                        }
+
+                        // Edit button
+                        if ui.small_button("✏").clicked() {
+                            *dialog = DialogState::EditProject(project.clone());
+                        }
+
+                        // Activate/Deactivate (confirmed, optionally including activities)
+                        let label = if project.is_active {
+                            "Deactivate"
+                        } else {
+                            "Activate"
+                        };
+                        if ui.small_button(label).clicked() {
+                            *dialog = DialogState::ConfirmProjectState(ProjectStateChange {
+                                project_id: project.id,
+                                project_name: project.name.clone(),
+                                activate: !project.is_active,
+                                include_activities: true,
+                                activity_count: db
+                                    .count_project_activities_to_set(project.id, !project.is_active)
+                                    .unwrap_or(0),
+                            });
+                        }
+
+                        // Add activity button
+                        if ui.small_button("+ Activity").clicked() {
+                            *dialog = DialogState::AddActivity(project.id);
+                        }
+                    });
+                });
+
+                // Show activities count and when the project was last used
+                ui.horizontal(|ui| {
+                    let project_usage = usage(project.id);
+                    ui.label(format!("Activities: {}", activity_count));
+                    ui.separator();
+                    ui.label(format!(
+                        "This month: {}",
+                        cache
+                            .duration_style
+                            .format_total(project_usage.month_minutes)
+                    ));
+                    ui.label(format!(
+                        "All time: {}",
+                        cache
+                            .duration_style
+                            .format_total(project_usage.total_minutes)
+                    ));
+                    draw_last_used(ui, project_usage);
+                });
+                if let Some(budget) = project.budget_minutes {
+                    draw_budget_bar(ui, usage(project.id).total_minutes, budget);
+                }
+
+                // Notes and links
+                let links = cache.get_links_for_project(project.id);
+                if !project.notes.is_empty() || !links.is_empty() {
+                    egui::CollapsingHeader::new("Notes & links")
+                        .id_salt(("project_notes", project.id))
+                        .show(ui, |ui| {
+                            if !project.notes.is_empty() {
+                                ui.label(&project.notes);
+                            }
+                            for link in links {
+                                let text = if link.label.is_empty() {
+                                    &link.url
+                                } else {
+                                    &link.label
+                                };
+                                ui.hyperlink_to(text, &link.url).on_hover_text(&link.url);
+                            }
+                        });
+                }
+            });
+        }
+    });
+}
+
+/// Draw the activities management view
+pub fn draw_activities_view(
+    ui: &mut Ui,
+    cache: &mut CachedData,
+    dialog: &mut DialogState,
+    filter: &mut FilterState,
+) {
+    ui.horizontal(|ui| {
+        ui.heading("Manage Activities");
+        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+            let orphaned = cache
+                .all_activities
+                .iter()
+                .filter(|a| {
+                    a.is_active
+                        && cache
+                            .get_project_by_id(a.project_id)
+                            .is_some_and(|p| !p.is_active)
+                })
+                .count();
+            if ui
+                .add_enabled(orphaned > 0, egui::Button::new("🧹 Clean up"))
+                .on_hover_text("Deactivate all activities of inactive projects")
+                .clicked()
+            {
+                cache.queue(AppAction::DeactivateActivitiesOfInactiveProjects);
+            }
+            let has_projects = !cache.projects.is_empty();
+            if ui
+                .add_enabled(has_projects, egui::Button::new("➕ New Activity"))
+                .clicked()
+            {
+                // Use filtered project if set, otherwise use first active project
+                let default_project_id = filter
+                    .selected_project_id
+                    .filter(|id| cache.get_project_by_id(*id).is_some_and(|p| p.is_active))
+                    .or_else(|| cache.projects.iter().find(|p| p.is_active).map(|p| p.id));
+
+                if let Some(project_id) = default_project_id {
+                    *dialog = DialogState::AddActivity(project_id);
+                }
+            }
+        });
+    });
+
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut filter.show_inactive, "Show inactive");
+        ui.checkbox(&mut filter.show_archived, "Show archived");
+
+        ui.separator();
+
+        ui.label("Filter by project:");
+        egui::ComboBox::from_id_salt("project_filter")
+            .selected_text(
+                filter
+                    .selected_project_id
+                    .and_then(|id| cache.get_project_by_id(id))
+                    .map(|p| p.name.as_str())
+                    .unwrap_or("All projects"),
+            )
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut filter.selected_project_id, None, "All projects");
+                for project in cache.projects.iter() {
+                    ui.selectable_value(
+                        &mut filter.selected_project_id,
+                        Some(project.id),
+                        &project.name,
+                    );
+                }
+            });
+
+        ui.separator();
+        draw_search_and_sort(
+            ui,
+            "activity_sort",
+            &mut filter.activity_search,
+            &mut filter.activity_sort,
+        );
+    });
+
+    ui.add_space(10.0);
+
+    // Borrow from snapshots of the lists so the cache stays free to mark dirty
+    let all_projects = Arc::clone(&cache.projects);
+    let all_activities = Arc::clone(&cache.all_activities);
+    let activity_usage = Arc::clone(&cache.activity_usage);
+    let projects_by_id: std::collections::HashMap<i64, &Project> =
+        all_projects.iter().map(|p| (p.id, p)).collect();
+
+    let mut activities: Vec<&ActivityType> = all_activities
+        .iter()
+        .filter(|a| {
+            let project_name = projects_by_id
+                .get(&a.project_id)
+                .map_or("", |p| p.name.as_str());
+            (filter.show_inactive || a.is_active)
+                && (filter.show_archived || !a.archived)
+                && filter
+                    .selected_project_id
+                    .map(|pid| a.project_id == pid)
+                    .unwrap_or(true)
+                && matches_search(
+                    &filter.activity_search,
+                    &[&a.name, &a.code, &a.cost_center, project_name],
+                )
+        })
+        .collect();
+    let usage = |id: i64| activity_usage.get(&id).copied().unwrap_or_default();
+    activities.sort_by(|a, b| {
+        filter.activity_sort.compare(
+            (&a.name, &a.code, usage(a.id)),
+            (&b.name, &b.code, usage(b.id)),
+        )
+    });
+
+    // Bulk actions on the ticked activities
+    filter
+        .selected_activities
+        .retain(|id| cache.get_activity_by_id(*id).is_some());
+    let visible: Vec<i64> = activities.iter().map(|a| a.id).collect();
+    if let Some(action) = draw_bulk_bar(ui, &mut filter.selected_activities, &visible) {
+        let items: Vec<(i64, String)> = filter
+            .selected_activities
+            .iter()
+            .filter_map(|id| cache.get_activity_by_id(*id))
+            .map(|a| (a.id, a.name.clone()))
+            .collect();
+        if action == BulkAction::Delete {
+            *dialog = DialogState::ConfirmDelete(DeleteTarget::Activities(items));
+        } else {
+            cache.queue(AppAction::SetActivitiesState { items, action });
+            filter.selected_activities.clear();
+        }
+    }
+
+    // Track actions to perform after iteration
+    let mut action_deactivate: Option<i64> = None;
+    let mut action_activate: Option<i64> = None;
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        for &activity in &activities {
+            let project_name = projects_by_id
+                .get(&activity.project_id)
+                .map_or("Unknown", |p| p.name.as_str());
+
+            ui.group(|ui| {
+                ui.horizontal(|ui| {
+                    let mut ticked = filter.selected_activities.contains(&activity.id);
+                    if ui.checkbox(&mut ticked, "").changed() {
+                        toggle_selection(&mut filter.selected_activities, activity.id, ticked);
+                    }
+
+                    // Status indicator
+                    if activity.is_active {
+                        ui.label(RichText::new("●").color(Color32::GREEN));
+                    } else {
+                        ui.label(RichText::new("●").color(Color32::GRAY));
+                    }
+
+                    // Activity name
+                    ui.label(RichText::new(activity.display_name()).strong());
+                    if !activity.code.is_empty() {
+                        ui.label(
+                            RichText::new(&activity.code)
+                                .small()
+                                .monospace()
+                                .color(Color32::GRAY),
+                        );
+                    }
+
+                    // Project name
+                    ui.label(format!("({})", project_name));
+                    if activity.archived {
+                        ui.label(RichText::new("archived").small().color(Color32::GRAY));
+                    }
+                    draw_last_used(ui, usage(activity.id));
+                    let project_active = projects_by_id
+                        .get(&activity.project_id)
+                        .is_some_and(|p| p.is_active);
+                    if activity.is_active && !project_active {
+                        ui.label(
+                            RichText::new("project inactive")
+                                .small()
+                                .color(Color32::GRAY),
+                        )
+                        .on_hover_text(
+                            "Not available for new entries until the project is activated",
+                        );
+                    }
+
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        // Delete button
+                        if ui.small_button("🗑").clicked() {
+                            *dialog = DialogState::ConfirmDelete(DeleteTarget::Activity(
+                                activity.id,
+                                activity.name.clone(),
+                            ));
+                        }
+
+                        // Edit button
+                        if ui.small_button("✏").clicked() {
+                            *dialog = DialogState::EditActivity(activity.clone());
+                        }
+
+                        // Activate/Deactivate
+                        if activity.is_active {
+                            if ui.small_button("Deactivate").clicked() {
+                                action_deactivate = Some(activity.id);
+                            }
+                        } else {
+                            if ui.small_button("Activate").clicked() {
+                                action_activate = Some(activity.id);
+                            }
+                        }
+                    });
+                });
+            });
+        }
+    });
+
+    if let Some(id) = action_deactivate {
+        cache.queue(AppAction::DeactivateActivity(id));
+    }
+    if let Some(id) = action_activate {
+        cache.queue(AppAction::ReactivateActivity(id));
+    }
+}
+
+/// Search box and sort order for a management view
+fn draw_search_and_sort(ui: &mut Ui, id_salt: &str, search: &mut String, sort: &mut ListSort) {
+    ui.label("🔍");
+    ui.add(
+        egui::TextEdit::singleline(search)
+            .desired_width(150.0)
+            .hint_text("Name or code"),
+    );
+    ui.label("Sort:");
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(sort.label())
+        .show_ui(ui, |ui| {
+            for option in ListSort::ALL {
+                ui.selectable_value(sort, option, option.label());
+            }
+        });
+}
+
+fn draw_last_used(ui: &mut Ui, usage: crate::database::Usage) {
+    let text = match usage.last_used {
+        Some(date) => format!("last used {}", date),
+        None => "never used".to_string(),
+    };
+    ui.label(RichText::new(text).small().color(Color32::GRAY));
+}
+
+/// "Select all" and the bulk actions for the ticked rows of a management view.
+/// Returns the action clicked this frame.
+fn draw_bulk_bar(
+    ui: &mut Ui,
+    selected: &mut std::collections::BTreeSet<i64>,
+    visible: &[i64],
+) -> Option<BulkAction> {
+    let mut action = None;
+    ui.horizontal(|ui| {
+        let mut all = !visible.is_empty() && visible.iter().all(|id| selected.contains(id));
+        if ui.checkbox(&mut all, "Select all").changed() {
+            for id in visible {
+                toggle_selection(selected, *id, all);
+            }
+        }
+        if selected.is_empty() {
+            return;
+        }
+        ui.separator();
+        ui.label(format!("{} selected:", selected.len()));
+        for bulk in [
+            BulkAction::Activate,
+            BulkAction::Deactivate,
+            BulkAction::Archive,
+        ] {
+            if ui.small_button(bulk.label()).clicked() {
+                action = Some(bulk);
+            }
+        }
+        if ui
+            .small_button(RichText::new("Delete…").color(Color32::RED))
+            .clicked()
+        {
+            action = Some(BulkAction::Delete);
+        }
+        if ui.small_button("Clear").clicked() {
+            selected.clear();
+        }
+    });
+    action
+}
+
+fn toggle_selection(selected: &mut std::collections::BTreeSet<i64>, id: i64, ticked: bool) {
+    if ticked {
+        selected.insert(id);
+    } else {
+        selected.remove(&id);
+    }
+}
+
+/// Names for a bulk confirmation, shortened when many are selected
+fn bulk_names(items: &[(i64, String)]) -> String {
+    const SHOWN: usize = 8;
+    let names: Vec<&str> = items.iter().take(SHOWN).map(|(_, n)| n.as_str()).collect();
+    if items.len() > SHOWN {
+        format!("{}, … ({} more)", names.join(", "), items.len() - SHOWN)
+    } else {
+        names.join(", ")
+    }
+}
+
+/// Run `apply` for each item, collecting a "name: error" line per failure
+/// Totals of the report range, by project and by day of the week
+fn draw_report_overview(
+    ui: &mut Ui,
+    stats: &crate::database::RangeStatistics,
+    working_days: usize,
+    style: DurationStyle,
+) {
+    ui.add_space(10.0);
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("Overview").strong());
+        ui.separator();
+        ui.label(format!(
+            "{} on {} days, ⌀ {} per day",
+            style.format_total(stats.total_minutes),
+            stats.logged_days,
+            style.format(stats.average_per_logged_day())
+        ));
+        // Vacation, sick leave and holidays don't count as working days
+        if working_days > 0 {
+            ui.separator();
+            ui.label(format!(
+                "⌀ {} per working day",
+                style.format(stats.total_minutes / working_days as i32)
+            ))
+            .on_hover_text("Average over the working days up to today, without days off");
+        }
+    });
+    if stats.total_minutes == 0 {
+        return;
+    }
+
+    ui.columns(2, |columns| {
+        egui::Grid::new("overview_projects")
+            .striped(true)
+            .num_columns(2)
+            .show(&mut columns[0], |ui| {
+                for project in &stats.per_project {
+                    ui.label(&project.project_name);
+                    ui.label(RichText::new(style.format_total(project.minutes)).monospace());
+                    ui.end_row();
+                }
+            });
+        egui::Grid::new("overview_weekdays")
+            .striped(true)
+            .num_columns(3)
+            .show(&mut columns[1], |ui| {
+                for day in &stats.per_weekday {
+                    ui.label(day.weekday.to_string());
+                    ui.label(RichText::new(style.format_total(day.minutes)).monospace());
+                    ui.label(
+                        RichText::new(format!("⌀ {}", style.format(day.average())))
+                            .color(Color32::GRAY),
+                    );
+                    ui.end_row();
+                }
+            });
+    });
+}
+
+/// Result of the last checksum check of a backup
+fn draw_integrity(ui: &mut Ui, integrity: Option<crate::backup::Integrity>) {
+    use crate::backup::Integrity;
+    match integrity {
+        Some(Integrity::Verified) => {
+            ui.label(RichText::new("✔").color(Color32::from_rgb(0, 150, 0)))
+                .on_hover_text("Matches its SHA-256 checksum");
+        }
+        Some(Integrity::Corrupted) => {
+            ui.label(RichText::new("⚠ corrupted").color(Color32::RED))
+                .on_hover_text("Does not match its checksum and cannot be restored");
+        }
+        Some(Integrity::Missing) => {
+            ui.label(RichText::new("–").color(Color32::GRAY))
+                .on_hover_text("Taken before checksums were stored");
+        }
+        None => {
+            ui.label(RichText::new("…").color(Color32::GRAY))
+                .on_hover_text("Not checked yet");
+        }
+    }
+}
+
+/// How the day looked in each backup, newest first
+fn revert_points(
+    db: &Database,
+    snapshots: &[crate::backup::Snapshot],
+    date: chrono::NaiveDate,
+) -> RevertDay {
+    let points = snapshots
+        .iter()
+        .filter_map(|snapshot| {
+            let entries = db.get_snapshot_entries(&snapshot.path, date).ok()?;
+            Some(RevertPoint {
+                snapshot: snapshot.clone(),
+                entries: entries.len(),
+                minutes: work_minutes(&entries),
+            })
+        })
+        .collect();
+    let current = db.get_time_entries_for_date(date).unwrap_or_default();
+    RevertDay {
+        date,
+        entries: current.len(),
+        minutes: work_minutes(&current),
+        points,
+        selected: None,
+    }
+}
+
+/// Note in confirmation dialogs of operations preceded by a snapshot
+fn draw_backup_note(ui: &mut Ui) {
+    ui.label(
+        RichText::new("💾 A backup of the database is saved first (Settings → Backups).")
+            .small()
+            .color(Color32::GRAY),
+    );
+}
+
+/// Id of the first input in a dialog, focused when the dialog opens
+pub fn dialog_first_field_id() -> egui::Id {
+    egui::Id::new("dialog_first_field")
+}
+
+fn project_notes_id() -> egui::Id {
+    egui::Id::new("project_notes")
+}
+
+/// Draw dialogs
+#[allow(clippy::too_many_arguments)]
+pub fn draw_dialog(
+    ctx: &egui::Context,
+    dialog: &mut DialogState,
+    project_form: &mut ProjectForm,
+    activity_form: &mut ActivityForm,
+    entry_form: &mut TimeEntryForm,
+    cache: &mut CachedData,
+    settings: &mut Settings,
+    messages: &mut Vec<UserMessage>,
+    db: &Database,
+) {
+    let mut should_close = false;
+
+    // Enter confirms and Escape cancels. Enter in the multi-line notes field adds a line.
+    let (confirm, cancel) = ctx.input(|i| {
+        (
+            i.key_pressed(egui::Key::Enter),
+            i.key_pressed(egui::Key::Escape),
+        )
+    });
+    let confirm = confirm && ctx.memory(|m| m.focused()) != Some(project_notes_id());
 
     match dialog.clone() {
         DialogState::None => {}
 
-        DialogState::AddProject => {
-            egui::Window::new("New Project")
+        DialogState::AddProject => {
+            egui::Window::new("New Project")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Name *:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut project_form.name)
+                                .id(dialog_first_field_id()),
+                        );
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Description *:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut project_form.description)
+                                .hint_text("(required)"),
+                        );
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Icon:");
+                        icon_picker(ui, &mut project_form.icon);
+                    });
+                    draw_hourly_rate_field(
+                        ui,
+                        &mut project_form.hourly_rate,
+                        &settings.currency.report_currency,
+                    );
+                    draw_budget_field(ui, project_form);
+                    draw_project_notes_editor(ui, project_form);
+
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        if ui.button("Cancel").clicked() || cancel {
+                            should_close = true;
+                            project_form.clear();
+                        }
+
+                        let can_save = project_form.is_valid();
+                        if ui
+                            .add_enabled(can_save, egui::Button::new("Create"))
+                            .clicked()
+                            || (confirm && can_save)
+                        {
+                            cache.queue(AppAction::AddProject(project_record(
+                                project_form,
+                                Project {
+                                    id: 0,
+                                    name: String::new(),
+                                    description: String::new(),
+                                    is_active: true,
+                                    icon: String::new(),
+                                    notes: String::new(),
+                                    code: String::new(),
+                                    uuid: String::new(),
+                                    archived: false,
+                                    hourly_rate_cents: None,
+                                    budget_minutes: None,
+                                },
+                            )));
+                            should_close = true;
+                            project_form.clear();
+                        }
+                    });
+                });
+        }
+
+        DialogState::EditProject(project) => {
+            egui::Window::new("Edit Project")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Name *:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut project_form.name)
+                                .id(dialog_first_field_id()),
+                        );
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Description *:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut project_form.description)
+                                .hint_text("(required)"),
+                        );
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Icon:");
+                        icon_picker(ui, &mut project_form.icon);
+                    });
+                    draw_hourly_rate_field(
+                        ui,
+                        &mut project_form.hourly_rate,
+                        &settings.currency.report_currency,
+                    );
+                    draw_budget_field(ui, project_form);
+                    draw_project_notes_editor(ui, project_form);
+
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        if ui.button("Cancel").clicked() || cancel {
+                            should_close = true;
+                            project_form.clear();
+                        }
+
+                        let can_save = project_form.is_valid();
+                        if ui
+                            .add_enabled(can_save, egui::Button::new("Save"))
+                            .clicked()
+                            || (confirm && can_save)
+                        {
+                            cache.queue(AppAction::UpdateProject(project_record(
+                                project_form,
+                                project.clone(),
+                            )));
+                            should_close = true;
+                            project_form.clear();
+                        }
+                    });
+                });
+        }
+
+        DialogState::AddActivity(_project_id) => {
+            egui::Window::new("New Activity")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Project:");
+                        egui::ComboBox::from_id_salt("project_select_dialog")
+                            .selected_text(
+                                activity_form
+                                    .project_id
+                                    .and_then(|id| cache.get_project_by_id(id))
+                                    .map(|p| p.display_name())
+                                    .unwrap_or_else(|| "Select...".to_string()),
+                            )
+                            .show_ui(ui, |ui| {
+                                for project in cache.projects.iter() {
+                                    if project.is_active {
+                                        ui.selectable_value(
+                                            &mut activity_form.project_id,
+                                            Some(project.id),
+                                            project.display_name(),
+                                        );
+                                    }
+                                }
+                            });
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label("Name:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut activity_form.name)
+                                .id(dialog_first_field_id()),
+                        );
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Icon:");
+                        icon_picker(ui, &mut activity_form.icon);
+                    });
+
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        if ui.button("Cancel").clicked() || cancel {
+                            should_close = true;
+                            activity_form.clear();
+                        }
+
+                        let can_save = activity_form.is_valid();
+                        if ui
+                            .add_enabled(can_save, egui::Button::new("Create"))
+                            .clicked()
+                            || (confirm && can_save)
+                        {
+                            if let Some(project_id) = activity_form.project_id {
+                                cache.queue(AppAction::AddActivity {
+                                    project_id,
+                                    name: activity_form.name.trim().to_string(),
+                                    icon: activity_form.icon.trim().to_string(),
+                                });
+                                should_close = true;
+                                activity_form.clear();
+                            }
+                        }
+                    });
+                });
+        }
+
+        DialogState::EditActivity(activity) => {
+            egui::Window::new("Edit Activity")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Name:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut activity_form.name)
+                                .id(dialog_first_field_id()),
+                        );
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Icon:");
+                        icon_picker(ui, &mut activity_form.icon);
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Monthly cap:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut activity_form.monthly_cap)
+                                .desired_width(60.0)
+                                .hint_text("HH:MM"),
+                        )
+                        .on_hover_text("Warn when more time than this is booked in a month");
+                    });
+                    if activity_form.cap_minutes().is_err() {
+                        draw_field_error(ui, Some("Invalid time format".to_string()));
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label("Cost center:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut activity_form.cost_center)
+                                .desired_width(100.0),
+                        )
+                        .on_hover_text("Booked in the ERP together with the project code");
+                    });
+                    ui.horizontal(|ui| {
+                        optional_date_picker(
+                            ui,
+                            "activity_valid_from",
+                            "Valid from",
+                            &mut activity_form.valid_from,
+                        );
+                        optional_date_picker(
+                            ui,
+                            "activity_valid_to",
+                            "Valid to",
+                            &mut activity_form.valid_to,
+                        );
+                    });
+                    draw_field_error(ui, activity_form.validity_error());
+                    ui.checkbox(&mut activity_form.is_billable, "💶 Billable by default")
+                        .on_hover_text("Preset for new entries; changing it leaves existing ones");
+                    draw_hourly_rate_field(
+                        ui,
+                        &mut activity_form.hourly_rate,
+                        &settings.currency.report_currency,
+                    );
+
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        if ui.button("Cancel").clicked() || cancel {
+                            should_close = true;
+                            activity_form.clear();
+                        }
+
+                        let can_save = !activity_form.name.trim().is_empty()
+                            && activity_form.cap_minutes().is_ok()
+                            && activity_form.validity_error().is_none()
+                            && parse_hourly_rate(&activity_form.hourly_rate).is_ok();
+                        if ui
+                            .add_enabled(can_save, egui::Button::new("Save"))
+                            .clicked()
+                            || (confirm && can_save)
+                        {
+                            cache.queue(AppAction::UpdateActivity(ActivityType {
+                                name: activity_form.name.trim().to_string(),
+                                icon: activity_form.icon.trim().to_string(),
+                                monthly_cap_minutes: activity_form.cap_minutes().ok().flatten(),
+                                cost_center: activity_form.cost_center.trim().to_string(),
+                                valid_from: activity_form.valid_from,
+                                valid_to: activity_form.valid_to,
+                                is_billable: activity_form.is_billable,
+                                hourly_rate_cents: parse_hourly_rate(&activity_form.hourly_rate)
+                                    .ok()
+                                    .flatten(),
+                                ..activity.clone()
+                            }));
+                            should_close = true;
+                            activity_form.clear();
+                        }
+                    });
+                });
+        }
+
+        DialogState::EditTimeEntry(entry) => {
+            egui::Window::new("Edit Time Entry")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+                .default_width(400.0)
+                .show(ctx, |ui| {
+                    // Show which activity this entry is for
+                    if let Some(activity) = cache.get_activity_by_id(entry.activity_type_id) {
+                        if let Some(project) = cache.get_project_by_id(activity.project_id) {
+                            ui.label(
+                                RichText::new(format!(
+                                    "{} - {}",
+                                    project.display_name(),
+                                    activity.display_name()
+                                ))
+                                .strong(),
+                            );
+                            ui.add_space(5.0);
+                        }
+                    }
+
+                    ui.horizontal(|ui| {
+                        ui.label("Time (HH:MM):");
+                        let time = ui.add(
+                            egui::TextEdit::singleline(&mut entry_form.time_str)
+                                .id(dialog_first_field_id())
+                                .desired_width(80.0),
+                        );
+                        evaluate_on_blur(&time, &mut entry_form.time_str);
+                        draw_duration_slider(ui, &mut entry_form.time_str, &settings.entries);
+                    });
+                    draw_field_error(
+                        ui,
+                        entry_form
+                            .time_error(settings.entries.max_entry_minutes, cache.duration_style),
+                    );
+
+                    ui.horizontal(|ui| {
+                        ui.label("Comment *:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut entry_form.comment)
+                                .desired_width(300.0)
+                                .hint_text("(required)"),
+                        );
+                    });
+                    draw_field_error(ui, entry_form.comment_error());
+
+                    if entry_form.kind == EntryKind::Adjustment {
+                        ui.horizontal(|ui| {
+                            ui.label("Reason *:");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut entry_form.reason)
+                                    .desired_width(300.0)
+                                    .hint_text("(required)"),
+                            );
+                        });
+                        draw_field_error(ui, entry_form.reason_error());
+                    }
+                    if entry_form.kind != EntryKind::Break {
+                        let mut billable = entry_form.is_billable.unwrap_or(entry.is_billable);
+                        if ui.checkbox(&mut billable, "💶 Billable").changed() {
+                            entry_form.is_billable = Some(billable);
+                        }
+                    }
+
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        if ui.button("Cancel").clicked() || cancel {
+                            should_close = true;
+                            entry_form.clear(settings.entries.default_minutes);
+                        }
+
+                        let can_save = entry_form.is_valid(settings.entries.max_entry_minutes);
+                        if ui
+                            .add_enabled(can_save, egui::Button::new("Save"))
+                            .clicked()
+                            || (confirm && can_save)
+                        {
+                            if let Some(minutes) = entry_form.get_minutes() {
+                                cache.queue(AppAction::UpdateTimeEntry {
+                                    id: entry.id,
+                                    minutes,
+                                    comment: entry_form.comment.clone(),
+                                    reason: entry_form.reason.clone(),
+                                    is_billable: entry_form
+                                        .is_billable
+                                        .unwrap_or(entry.is_billable),
+                                });
+                                should_close = true;
+                                entry_form.clear(settings.entries.default_minutes);
+                            }
+                        }
+                    });
+                });
+        }
+
+        DialogState::ConfirmDelete(target) => {
+            let (title, message) = match &target {
+                DeleteTarget::Project(_, name) => (
+                    "Delete Project?",
+                    format!(
+                        "Are you sure you want to permanently delete '{}'?\n\
+                         This will also delete all activities and time entries!",
+                        name
+                    ),
+                ),
+                DeleteTarget::Activity(_, name) => (
+                    "Delete Activity?",
+                    format!(
+                        "Are you sure you want to permanently delete '{}'?\n\
+                         This will also delete all time entries!",
+                        name
+                    ),
+                ),
+                DeleteTarget::TimeEntry(_) => (
+                    "Delete Entry?",
+                    "Are you sure you want to delete this time entry?".to_string(),
+                ),
+                DeleteTarget::Projects(items) => (
+                    "Delete Projects?",
+                    format!(
+                        "Permanently delete {} projects?\n{}\n\
+                         Projects that still have activities are kept.",
+                        items.len(),
+                        bulk_names(items)
+                    ),
+                ),
+                DeleteTarget::Activities(items) => (
+                    "Delete Activities?",
+                    format!(
+                        "Permanently delete {} activities?\n{}\n\
+                         Activities that still have time entries are kept.",
+                        items.len(),
+                        bulk_names(items)
+                    ),
+                ),
+            };
+
+            // Projects and activities take their history with them: back up first.
+            // A deleted entry comes back whole with undo, so it needs no backup.
+            let backup = !matches!(target, DeleteTarget::TimeEntry(_));
+
+            egui::Window::new(title)
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+                .show(ctx, |ui| {
+                    ui.label(message);
+                    if backup {
+                        draw_backup_note(ui);
+                    } else {
+                        ui.label(
+                            RichText::new("↶ Can be undone with Undo (Ctrl+Z).")
+                                .small()
+                                .color(Color32::GRAY),
+                        );
+                    }
+                    ui.add_space(10.0);
+
+                    ui.horizontal(|ui| {
+                        if ui.button("Cancel").clicked() || cancel {
+                            should_close = true;
+                        }
+
+                        if ui
+                            .button(RichText::new("Delete").color(Color32::RED))
+                            .clicked()
+                            || confirm
+                        {
+                            cache.queue(match &target {
+                                DeleteTarget::Project(id, name) => {
+                                    AppAction::DeleteProjects(vec![(*id, name.clone())])
+                                }
+                                DeleteTarget::Activity(id, name) => {
+                                    AppAction::DeleteActivities(vec![(*id, name.clone())])
+                                }
+                                DeleteTarget::TimeEntry(id) => {
+                                    AppAction::DeleteTimeEntries(vec![*id])
+                                }
+                                DeleteTarget::Projects(items) => {
+                                    AppAction::DeleteProjects(items.clone())
+                                }
+                                DeleteTarget::Activities(items) => {
+                                    AppAction::DeleteActivities(items.clone())
+                                }
+                            });
+                            should_close = true;
+                        }
+                    });
+                });
+        }
+
+        DialogState::ConfirmProjectState(mut change) => {
+            let (title, verb) = if change.activate {
+                ("Activate Project?", "Activate")
+            } else {
+                ("Deactivate Project?", "Deactivate")
+            };
+            let activity_count = change.activity_count;
+
+            egui::Window::new(title)
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+                .show(ctx, |ui| {
+                    ui.label(format!("{} '{}'?", verb, change.project_name));
+                    if activity_count > 0 {
+                        let label = if change.activate {
+                            format!(
+                                "Also activate the {} activities deactivated with it",
+                                activity_count
+                            )
+                        } else {
+                            format!("Also deactivate its {} active activities", activity_count)
+                        };
+                        if ui.checkbox(&mut change.include_activities, label).changed() {
+                            *dialog = DialogState::ConfirmProjectState(change.clone());
+                        }
+                    }
+                    ui.add_space(10.0);
+
+                    ui.horizontal(|ui| {
+                        if ui.button("Cancel").clicked() || cancel {
+                            should_close = true;
+                        }
+
+                        if ui.button(verb).clicked() || confirm {
+                            cache.queue(AppAction::SetProjectActive {
+                                id: change.project_id,
+                                name: change.project_name.clone(),
+                                active: change.activate,
+                                include_activities: change.include_activities,
+                            });
+                            should_close = true;
+                        }
+                    });
+                });
+        }
+
+        DialogState::ImportCatalog(mut import) => {
+            egui::Window::new("Import Catalog")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+                .show(ctx, |ui| {
+                    ui.label("CSV or JSON file with project_code, project_name, activity_code, activity_name, valid_from, valid_to:");
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add(egui::TextEdit::singleline(&mut import.path).desired_width(300.0))
+                            .changed()
+                        {
+                            import.changes = None;
+                        }
+                        if ui.button("Browse…").clicked() {
+                            if let Some(path) = file_dialog(
+                                settings,
+                                "catalog_import",
+                                "Catalog",
+                                &["csv", "json"],
+                            )
+                            .pick_file()
+                            {
+                                remember_dir(settings, "catalog_import", &path, messages);
+                                import.path = path.display().to_string();
+                                import.changes = None;
+                            }
+                        }
+                        if ui.button("Preview").clicked() {
+                            let today = chrono::Local::now().date_naive();
+                            match crate::catalog::load_catalog(std::path::Path::new(
+                                import.path.trim(),
+                            )) {
+                                Ok(rows) => {
+                                    let changes = crate::catalog::plan_import(
+                                        &rows,
+                                        &cache.projects,
+                                        &cache.all_activities,
+                                        today,
+                                    );
+                                    import.included = vec![true; changes.len()];
+                                    import.changes = Some(changes);
+                                    import.rows_parsed = rows.len();
+                                    import.duplicates = crate::catalog::duplicate_codes(&rows);
+                                    import.error = None;
+                                }
+                                Err(e) => {
+                                    import.changes = None;
+                                    import.error = Some(e.to_string());
+                                }
+                            }
+                        }
+                    });
+
+                    if let Some(error) = &import.error {
+                        ui.colored_label(Color32::RED, error);
+                    }
+                    if let Some(changes) = &import.changes {
+                        ui.add_space(5.0);
+                        let count = |f: fn(&CatalogChange) -> bool| {
+                            changes.iter().filter(|c| f(c)).count()
+                        };
+                        ui.label(format!(
+                            "{} rows parsed: {} projects and {} activities to create, {} to deactivate",
+                            import.rows_parsed,
+                            count(|c| matches!(c, CatalogChange::AddProject { .. })),
+                            count(|c| matches!(c, CatalogChange::AddActivity { .. })),
+                            count(|c| matches!(
+                                c,
+                                CatalogChange::DeactivateProject { .. }
+                                    | CatalogChange::DeactivateActivity { .. }
+                            )),
+                        ));
+                        if !import.duplicates.is_empty() {
+                            ui.colored_label(
+                                Color32::from_rgb(200, 120, 0),
+                                format!(
+                                    "⚠ Duplicate codes (first row used): {}",
+                                    import.duplicates.join(", ")
+                                ),
+                            );
+                        }
+                        if changes.is_empty() {
+                            ui.label("Everything is up to date.");
+                        } else {
+                            egui::ScrollArea::vertical()
+                                .max_height(250.0)
+                                .show(ui, |ui| {
+                                    for (change, include) in
+                                        changes.iter().zip(import.included.iter_mut())
+                                    {
+                                        ui.checkbox(include, change.to_string());
+                                    }
+                                });
+                        }
+                    }
+                    draw_backup_note(ui);
+                    ui.add_space(10.0);
+
+                    ui.horizontal(|ui| {
+                        if ui.button("Cancel").clicked() || cancel {
+                            should_close = true;
+                        }
+
+                        let selected = crate::catalog::select_changes(
+                            import.changes.as_deref().unwrap_or_default(),
+                            &import.included,
+                        );
+                        if ui
+                            .add_enabled(
+                                !selected.is_empty(),
+                                egui::Button::new(format!("Apply {} changes", selected.len())),
+                            )
+                            .clicked()
+                            || (confirm && !selected.is_empty())
+                        {
+                            cache.queue(AppAction::ImportCatalog(selected));
+                            should_close = true;
+                        }
+                    });
+                });
+
+            if matches!(dialog, DialogState::ImportCatalog(_)) {
+                *dialog = DialogState::ImportCatalog(import);
+            }
+        }
+
+        DialogState::CloseDay(CloseDay { date, entries }) => {
+            let working_day = settings.calendar.is_working_day(date);
+            let checks = run_close_out_checks(
+                &entries,
+                &settings.close_out,
+                working_day,
+                cache.duration_style,
+            );
+            let all_passed = checks.iter().all(|c| c.passed);
+
+            egui::Window::new("End Day")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+                .show(ctx, |ui| {
+                    ui.label(RichText::new(date.format("%A, %Y-%m-%d").to_string()).strong());
+                    if !working_day {
+                        ui.label(
+                            RichText::new("Day off: the day total is not checked")
+                                .italics()
+                                .color(Color32::GRAY),
+                        );
+                    }
+                    ui.add_space(5.0);
+
+                    if checks.is_empty() {
+                        ui.label("No close-out checks are enabled in Settings.");
+                    }
+                    for check in &checks {
+                        let (icon, color) = if check.passed {
+                            ("✔", Color32::from_rgb(0, 150, 0))
+                        } else {
+                            ("✘", Color32::from_rgb(255, 0, 0))
+                        };
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new(icon).color(color).strong());
+                            ui.label(&check.label);
+                        });
+                    }
+
+                    if settings.close_out.copy_summary {
+                        ui.add_space(5.0);
+                        ui.label(
+                            RichText::new("📋 The daily summary will be copied to the clipboard")
+                                .small(),
+                        );
+                    }
+                    let reports = close_out_reports(settings);
+                    if !reports.is_empty() {
+                        ui.label(
+                            RichText::new(format!(
+                                "📄 Reports to write: {}",
+                                reports
+                                    .iter()
+                                    .map(|r| r.name.as_str())
+                                    .collect::<Vec<_>>()
+                                    .join(", ")
+                            ))
+                            .small(),
+                        );
+                    }
+
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        if ui.button("Cancel").clicked() || cancel {
+                            should_close = true;
+                        }
+
+                        let label = if all_passed {
+                            RichText::new("Mark day done")
+                        } else {
+                            RichText::new("Close anyway").color(Color32::from_rgb(255, 0, 0))
+                        };
+                        if ui.button(label).clicked() || confirm {
+                            cache.queue(AppAction::CloseDay(date));
+                            if settings.close_out.copy_summary {
+                                let summaries = db.get_daily_summary(date).unwrap_or_default();
+                                ui.output_mut(|o| {
+                                    o.copied_text = format_daily_summary(
+                                        &summaries,
+                                        &settings.clipboard.summary_line_template,
+                                        settings.display.duration_style,
+                                    );
+                                });
+                            }
+                            for report in close_out_reports(settings) {
+                                write_saved_report(report, settings, cache, messages, db);
+                            }
+                            should_close = true;
+                        }
+                    });
+                });
+        }
+
+        DialogState::ConfirmUnlock(target) => {
+            let (title, text, action) = match target {
+                UnlockTarget::Month(date) => (
+                    format!("Unlock {}", date.format("%B %Y")),
+                    "Entries of this month can then be added, edited and deleted again. \
+                     The month may already have been reported."
+                        .to_string(),
+                    AppAction::UnlockMonth(date),
+                ),
+                UnlockTarget::Day(date) => (
+                    format!("Unlock {}", date.format("%a %Y-%m-%d")),
+                    format!(
+                        "Entries older than {} days are read-only. This day can be edited \
+                         until the app is closed.",
+                        settings.entries.lock_after_days
+                    ),
+                    AppAction::UnlockDay(date),
+                ),
+            };
+            egui::Window::new(title)
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+                .show(ctx, |ui| {
+                    ui.label(text);
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        if ui.button("Cancel").clicked() || cancel {
+                            should_close = true;
+                        }
+                        if ui
+                            .button(RichText::new("🔓 Unlock").color(Color32::from_rgb(255, 0, 0)))
+                            .clicked()
+                            || confirm
+                        {
+                            cache.queue(action.clone());
+                            should_close = true;
+                        }
+                    });
+                });
+        }
+
+        DialogState::RevertDay(mut revert) => {
+            egui::Window::new("Revert Day")
                 .collapsible(false)
                 .resizable(false)
                 .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
                 .show(ctx, |ui| {
-                    ui.horizontal(|ui| {
-                        ui.label("Name *:");
-                        ui.text_edit_singleline(&mut project_form.name);
-                    });
-                    ui.horizontal(|ui| {
-                        ui.label("Description *:");
-                        ui.add(
-                            egui::TextEdit::singleline(&mut project_form.description)
-                                .hint_text("(required)"),
-                        );
-                    });
+                    ui.label(
+                        RichText::new(revert.date.format("%A, %Y-%m-%d").to_string()).strong(),
+                    );
+                    ui.label(format!(
+                        "Now: {} entries, {}",
+                        revert.entries,
+                        cache.duration_style.format(revert.minutes)
+                    ));
+                    ui.add_space(5.0);
 
+                    if revert.points.is_empty() {
+                        ui.label(RichText::new("No backups yet.").italics());
+                    }
+                    ui.label("Replace this day's entries with how they looked at:");
+                    egui::ScrollArea::vertical()
+                        .max_height(240.0)
+                        .show(ui, |ui| {
+                            for (i, point) in revert.points.iter().enumerate() {
+                                let text = format!(
+                                    "{} ({}) – {} entries, {}",
+                                    point.snapshot.taken_at.format("%a %Y-%m-%d %H:%M"),
+                                    point.snapshot.reason,
+                                    point.entries,
+                                    cache.duration_style.format(point.minutes)
+                                );
+                                ui.radio_value(&mut revert.selected, Some(i), text);
+                            }
+                        });
+                    ui.add_space(5.0);
+                    ui.label(
+                        RichText::new("Other days are not changed.")
+                            .small()
+                            .color(Color32::GRAY),
+                    );
+                    draw_backup_note(ui);
                     ui.add_space(10.0);
+
                     ui.horizontal(|ui| {
-                        if ui.button("Cancel").clicked() {
+                        if ui.button("Cancel").clicked() || cancel {
                             should_close = true;
-                            project_form.clear();
                         }
 
-                        let can_save = project_form.is_valid();
-                        if ui
-                            .add_enabled(can_save, egui::Button::new("Create"))
-                            .clicked()
-                        {
-                            if let Err(e) = db.create_project(
-                                project_form.name.trim(),
-                                project_form.description.trim(),
-                            ) {
-                                eprintln!("Error creating project: {}", e);
-                            } else {
-                                cache.mark_dirty();
-                                should_close = true;
-                                project_form.clear();
-                            }
+                        let point = revert.selected.and_then(|i| revert.points.get(i));
+                        let clicked = ui
+                            .add_enabled(
+                                point.is_some(),
+                                egui::Button::new(RichText::new("Revert").color(Color32::RED)),
+                            )
+                            .clicked();
+                        if let Some(point) = point.filter(|_| clicked || confirm) {
+                            cache.queue(AppAction::RevertDay {
+                                date: revert.date,
+                                snapshot: point.snapshot.path.clone(),
+                            });
+                            should_close = true;
                         }
                     });
                 });
+
+            if matches!(dialog, DialogState::RevertDay(_)) {
+                *dialog = DialogState::RevertDay(revert);
+            }
         }
 
-        DialogState::EditProject(project) => {
-            egui::Window::new("Edit Project")
+        DialogState::AddExpense(mut form) => {
+            egui::Window::new("Add Expense")
                 .collapsible(false)
                 .resizable(false)
                 .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
                 .show(ctx, |ui| {
+                    ui.label(form.date.format("%A %Y-%m-%d").to_string());
+                    ui.add_space(5.0);
                     ui.horizontal(|ui| {
-                        ui.label("Name *:");
-                        ui.text_edit_singleline(&mut project_form.name);
+                        ui.label("Amount:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut form.amount)
+                                .id(dialog_first_field_id())
+                                .desired_width(80.0),
+                        );
+                        ui.add(egui::TextEdit::singleline(&mut form.currency).desired_width(40.0));
                     });
+                    if !form.amount.trim().is_empty() {
+                        draw_field_error(ui, form.amount_error());
+                    }
                     ui.horizontal(|ui| {
-                        ui.label("Description *:");
+                        ui.label("Description:");
                         ui.add(
-                            egui::TextEdit::singleline(&mut project_form.description)
-                                .hint_text("(required)"),
+                            egui::TextEdit::singleline(&mut form.description).desired_width(250.0),
                         );
                     });
-
+                    ui.horizontal(|ui| {
+                        ui.label("Receipt:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut form.receipt_path).desired_width(220.0),
+                        );
+                        if ui
+                            .button("📂")
+                            .on_hover_text("Choose receipt file")
+                            .clicked()
+                        {
+                            if let Some(picked) = any_file_dialog(settings, "receipt").pick_file() {
+                                remember_dir(settings, "receipt", &picked, messages);
+                                form.receipt_path = picked.display().to_string();
+                            }
+                        }
+                    });
                     ui.add_space(10.0);
+
+                    let expense = form.to_expense();
                     ui.horizontal(|ui| {
-                        if ui.button("Cancel").clicked() {
+                        if ui.button("Cancel").clicked() || cancel {
                             should_close = true;
-                            project_form.clear();
                         }
-
-                        let can_save = project_form.is_valid();
                         if ui
-                            .add_enabled(can_save, egui::Button::new("Save"))
+                            .add_enabled(expense.is_some(), egui::Button::new("Add"))
                             .clicked()
+                            || (confirm && expense.is_some())
                         {
-                            if let Err(e) = db.update_project(
-                                project.id,
-                                project_form.name.trim(),
-                                project_form.description.trim(),
-                            ) {
-                                eprintln!("Error updating project: {}", e);
-                            } else {
-                                cache.mark_dirty();
-                                should_close = true;
-                                project_form.clear();
+                            if let Some(expense) = expense {
+                                cache.queue(AppAction::AddExpense(expense));
                             }
+                            should_close = true;
                         }
                     });
                 });
+
+            if matches!(dialog, DialogState::AddExpense(_)) {
+                *dialog = DialogState::AddExpense(form);
+            }
         }
 
-        DialogState::AddActivity(_project_id) => {
-            egui::Window::new("New Activity")
+        DialogState::DistributeTime(mut distribute) => {
+            let logged = &distribute.logged;
+            let total = crate::database::evaluate_time_expression(&distribute.time_str).ok();
+            let weights: Vec<u32> = distribute
+                .activities
+                .iter()
+                .map(|id| match distribute.mode {
+                    SplitMode::Even => 1,
+                    SplitMode::Proportional => logged.get(id).copied().unwrap_or(0),
+                })
+                .collect();
+            let parts = split_minutes(total.unwrap_or(0).max(0) as u32, &weights);
+            let comment_error = comment_error(&distribute.comment);
+
+            egui::Window::new("Distribute Remaining Time")
                 .collapsible(false)
                 .resizable(false)
                 .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
                 .show(ctx, |ui| {
                     ui.horizontal(|ui| {
-                        ui.label("Project:");
-                        egui::ComboBox::from_id_salt("project_select_dialog")
-                            .selected_text(
-                                activity_form
-                                    .project_id
-                                    .and_then(|id| cache.get_project_by_id(id))
-                                    .map(|p| p.name.as_str())
-                                    .unwrap_or("Select..."),
-                            )
-                            .show_ui(ui, |ui| {
-                                for project in &cache.projects {
-                                    if project.is_active {
-                                        ui.selectable_value(
-                                            &mut activity_form.project_id,
-                                            Some(project.id),
-                                            &project.name,
-                                        );
-                                    }
-                                }
-                            });
+                        ui.label("Time to distribute:");
+                        let time = ui.add(
+                            egui::TextEdit::singleline(&mut distribute.time_str)
+                                .id(dialog_first_field_id())
+                                .desired_width(60.0),
+                        );
+                        evaluate_on_blur(&time, &mut distribute.time_str);
                     });
-
+                    if total.is_none() {
+                        draw_field_error(ui, Some("Invalid time format".to_string()));
+                    }
                     ui.horizontal(|ui| {
-                        ui.label("Name:");
-                        ui.text_edit_singleline(&mut activity_form.name);
+                        ui.radio_value(&mut distribute.mode, SplitMode::Even, "Evenly");
+                        ui.radio_value(
+                            &mut distribute.mode,
+                            SplitMode::Proportional,
+                            "In proportion to today's time",
+                        );
                     });
+                    ui.add_space(5.0);
 
+                    ui.label("Activities:");
+                    egui::ScrollArea::vertical()
+                        .max_height(220.0)
+                        .show(ui, |ui| {
+                            for project in cache.projects.iter().filter(|p| p.is_active) {
+                                let activities: Vec<_> = cache
+                                    .get_activities_for_project(project.id)
+                                    .into_iter()
+                                    .filter(|a| a.is_valid_on(distribute.date))
+                                    .collect();
+                                if activities.is_empty() {
+                                    continue;
+                                }
+                                ui.label(RichText::new(project.display_name()).strong());
+                                for activity in activities {
+                                    let position = distribute
+                                        .activities
+                                        .iter()
+                                        .position(|id| *id == activity.id);
+                                    let mut ticked = position.is_some();
+                                    ui.horizontal(|ui| {
+                                        if ui
+                                            .checkbox(&mut ticked, activity.display_name())
+                                            .changed()
+                                        {
+                                            match position {
+                                                Some(i) => {
+                                                    distribute.activities.remove(i);
+                                                }
+                                                None => distribute.activities.push(activity.id),
+                                            }
+                                        }
+                                        if let Some(minutes) = logged.get(&activity.id) {
+                                            ui.label(
+                                                RichText::new(format!(
+                                                    "({} today)",
+                                                    cache.duration_style.format(*minutes as i32)
+                                                ))
+                                                .small()
+                                                .color(Color32::GRAY),
+                                            );
+                                        }
+                                        if let Some(part) = position.and_then(|i| parts.get(i)) {
+                                            ui.label(
+                                                RichText::new(format!(
+                                                    "→ {}",
+                                                    cache.duration_style.format(*part as i32)
+                                                ))
+                                                .strong(),
+                                            );
+                                        }
+                                    });
+                                }
+                            }
+                        });
+                    ui.horizontal(|ui| {
+                        ui.label("Comment:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut distribute.comment)
+                                .hint_text("(required)")
+                                .desired_width(250.0),
+                        );
+                    });
+                    if !distribute.activities.is_empty() {
+                        draw_field_error(ui, comment_error.clone());
+                    }
                     ui.add_space(10.0);
+
+                    let entries: Vec<(i64, i32)> = distribute
+                        .activities
+                        .iter()
+                        .copied()
+                        .zip(parts.iter().map(|part| *part as i32))
+                        .filter(|(_, minutes)| *minutes > 0)
+                        .collect();
                     ui.horizontal(|ui| {
-                        if ui.button("Cancel").clicked() {
+                        if ui.button("Cancel").clicked() || cancel {
                             should_close = true;
-                            activity_form.clear();
                         }
-
-                        let can_save = activity_form.is_valid();
+                        let can_create = !entries.is_empty() && comment_error.is_none();
                         if ui
-                            .add_enabled(can_save, egui::Button::new("Create"))
+                            .add_enabled(
+                                can_create,
+                                egui::Button::new(format!("Create {} entries", entries.len())),
+                            )
                             .clicked()
+                            || (confirm && can_create)
                         {
-                            if let Some(pid) = activity_form.project_id {
-                                if let Err(e) =
-                                    db.create_activity_type(pid, activity_form.name.trim())
-                                {
-                                    eprintln!("Error creating activity: {}", e);
-                                } else {
-                                    cache.mark_dirty();
-                                    should_close = true;
-                                    activity_form.clear();
-                                }
-                            }
+                            cache.queue(AppAction::AddEntries {
+                                date: distribute.date,
+                                entries: entries.clone(),
+                                comment: distribute.comment.trim().to_string(),
+                            });
+                            should_close = true;
                         }
                     });
                 });
+
+            if matches!(dialog, DialogState::DistributeTime(_)) {
+                *dialog = DialogState::DistributeTime(distribute);
+            }
         }
 
-        DialogState::EditActivity(activity) => {
-            egui::Window::new("Edit Activity")
+        DialogState::CopyDay(mut copy) => {
+            let to_copy: Vec<_> = copy
+                .entries
+                .iter()
+                .filter(|e| e.kind != EntryKind::Adjustment)
+                .collect();
+            let minutes: i32 = to_copy.iter().map(|e| e.minutes).sum();
+
+            egui::Window::new("Copy Previous Day")
                 .collapsible(false)
                 .resizable(false)
                 .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
                 .show(ctx, |ui| {
-                    ui.horizontal(|ui| {
-                        ui.label("Name:");
-                        ui.text_edit_singleline(&mut activity_form.name);
-                    });
-
+                    ui.label(format!(
+                        "Copy {} entries ({}) from {} to {}?",
+                        to_copy.len(),
+                        cache.duration_style.format(minutes),
+                        copy.from.format("%A %Y-%m-%d"),
+                        copy.to.format("%A %Y-%m-%d")
+                    ));
+                    egui::ScrollArea::vertical()
+                        .max_height(240.0)
+                        .show(ui, |ui| {
+                            for entry in &to_copy {
+                                ui.horizontal(|ui| {
+                                    ui.label(
+                                        RichText::new(cache.duration_style.format(entry.minutes))
+                                            .monospace(),
+                                    );
+                                    ui.label(
+                                        activity_label(cache, entry.activity_type_id)
+                                            .unwrap_or_else(|| "?".to_string()),
+                                    );
+                                    if !copy.clear_comments {
+                                        ui.label(RichText::new(&entry.comment).italics());
+                                    }
+                                });
+                            }
+                        });
+                    ui.checkbox(&mut copy.clear_comments, "Leave the comments empty");
+                    ui.label(
+                        RichText::new("Work, travel (with its trip) and breaks are copied; adjustments are not.")
+                            .small()
+                            .color(Color32::GRAY),
+                    );
                     ui.add_space(10.0);
+
                     ui.horizontal(|ui| {
-                        if ui.button("Cancel").clicked() {
+                        if ui.button("Cancel").clicked() || cancel {
                             should_close = true;
-                            activity_form.clear();
                         }
-
-                        let can_save = !activity_form.name.trim().is_empty();
                         if ui
-                            .add_enabled(can_save, egui::Button::new("Save"))
+                            .add_enabled(!to_copy.is_empty(), egui::Button::new("Copy"))
                             .clicked()
+                            || (confirm && !to_copy.is_empty())
                         {
-                            if let Err(e) =
-                                db.update_activity_type(activity.id, activity_form.name.trim())
-                            {
-                                eprintln!("Error updating activity: {}", e);
-                            } else {
-                                cache.mark_dirty();
-                                should_close = true;
-                                activity_form.clear();
-                            }
+                            cache.queue(AppAction::CopyDay {
+                                from: copy.from,
+                                to: copy.to,
+                                clear_comments: copy.clear_comments,
+                            });
+                            should_close = true;
                         }
                     });
                 });
+
+            if matches!(dialog, DialogState::CopyDay(_)) {
+                *dialog = DialogState::CopyDay(copy);
+            }
         }
 
-        DialogState::EditTimeEntry(entry) => {
-            egui::Window::new("Edit Time Entry")
+        DialogState::MonthEnd(mut wizard) => {
+            let (start, end) = month_range(wizard.month);
+            let today = chrono::Local::now().date_naive();
+            if wizard.stale {
+                wizard.entries = db
+                    .get_entry_details_for_range(start, end)
+                    .unwrap_or_default();
+                wizard.activity_totals =
+                    db.get_activity_totals_for_month(start).unwrap_or_default();
+                wizard.locked = db.is_month_locked(start).unwrap_or(false);
+                wizard.stale = false;
+            }
+            let entries = std::mem::take(&mut wizard.entries);
+            let locked = wizard.locked;
+            let last_step = MonthEndWizard::STEPS.len() - 1;
+            let mut unlock = None;
+
+            egui::Window::new(format!("Close Month – {}", start.format("%B %Y")))
+                .id(egui::Id::new("month_end_wizard"))
                 .collapsible(false)
                 .resizable(false)
                 .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
-                .default_width(400.0)
+                .default_width(420.0)
                 .show(ctx, |ui| {
-                    // Show which activity this entry is for
-                    if let Some(activity) = cache.get_activity_by_id(entry.activity_type_id) {
-                        if let Some(project) = cache.get_project_by_id(activity.project_id) {
-                            ui.label(
-                                RichText::new(format!("{} - {}", project.name, activity.name))
-                                    .strong(),
+                    ui.label(
+                        RichText::new(format!(
+                            "Step {} of {}: {}",
+                            wizard.step + 1,
+                            MonthEndWizard::STEPS.len(),
+                            MonthEndWizard::STEPS[wizard.step]
+                        ))
+                        .strong(),
+                    );
+                    ui.separator();
+
+                    match wizard.step {
+                        0 => {
+                            // Working days with nothing logged
+                            let coverage = crate::reports::find_missing_days(
+                                &entries,
+                                start,
+                                end.min(today),
+                                &settings.calendar,
+                                |_| 1,
                             );
-                            ui.add_space(5.0);
+                            if coverage.missing.is_empty() {
+                                ui.label("✔ Time is logged on every working day.");
+                            } else {
+                                ui.label("Working days without any time logged:");
+                                draw_day_totals(ui, "month_end_missing", &coverage.missing, cache);
+                            }
                         }
-                    }
-
-                    ui.horizontal(|ui| {
-                        ui.label("Time (HH:MM):");
-                        ui.add(
-                            egui::TextEdit::singleline(&mut entry_form.time_str)
-                                .desired_width(80.0),
-                        );
-                    });
-
-                    ui.horizontal(|ui| {
-                        ui.label("Comment *:");
-                        ui.add(
-                            egui::TextEdit::singleline(&mut entry_form.comment)
-                                .desired_width(300.0)
-                                .hint_text("(required)"),
-                        );
-                    });
+                        1 => {
+                            let under: Vec<_> = crate::reports::find_missing_days(
+                                &entries,
+                                start,
+                                end.min(today),
+                                &settings.calendar,
+                                |date| settings.calendar.target_for(date),
+                            )
+                            .missing
+                            .into_iter()
+                            .filter(|day| day.total_minutes > 0)
+                            .collect();
+                            let over = crate::reports::days_over_target(
+                                &entries,
+                                settings.close_out.max_total_minutes,
+                            );
+                            if under.is_empty() && over.is_empty() {
+                                ui.label("✔ Every logged day is within the expected day total.");
+                            }
+                            if !under.is_empty() {
+                                ui.label("Below the day's target:");
+                                draw_day_totals(ui, "month_end_under", &under, cache);
+                            }
+                            if !over.is_empty() {
+                                ui.label(format!(
+                                    "Above {}:",
+                                    format_minutes_to_time(settings.close_out.max_total_minutes)
+                                ));
+                                draw_day_totals(ui, "month_end_over", &over, cache);
+                            }
 
-                    ui.add_space(10.0);
-                    ui.horizontal(|ui| {
-                        if ui.button("Cancel").clicked() {
-                            should_close = true;
-                            entry_form.clear();
+                            let totals = &wizard.activity_totals;
+                            let capped: Vec<_> = cache
+                                .all_activities
+                                .iter()
+                                .filter_map(|a| {
+                                    let total = totals.get(&a.id).copied().unwrap_or(0);
+                                    a.exceeds_cap(total).then_some((a, total))
+                                })
+                                .collect();
+                            if !capped.is_empty() {
+                                ui.add_space(6.0);
+                                ui.label("Over their monthly cap:");
+                                for (activity, total) in capped {
+                                    ui.label(format!(
+                                        "⚠ {}: {} of {}",
+                                        activity_label(cache, activity.id).unwrap_or_default(),
+                                        cache.duration_style.format_total(total),
+                                        cache.duration_style.format_total(
+                                            activity.monthly_cap_minutes.unwrap_or(0)
+                                        )
+                                    ));
+                                }
+                            }
                         }
-
-                        let can_save = entry_form.is_valid();
-                        if ui
-                            .add_enabled(can_save, egui::Button::new("Save"))
-                            .clicked()
-                        {
-                            if let Some(minutes) = entry_form.get_minutes() {
-                                if let Err(e) =
-                                    db.update_time_entry(entry.id, minutes, &entry_form.comment)
-                                {
-                                    eprintln!("Error updating entry: {}", e);
-                                } else {
-                                    cache.mark_dirty();
-                                    should_close = true;
-                                    entry_form.clear();
+                        2 => {
+                            let exported: Vec<_> = cache
+                                .export_log
+                                .iter()
+                                .filter(|r| r.kind == "tickets")
+                                .filter_map(|r| Some((r.start_date?, r.end_date?)))
+                                .collect();
+                            let weeks = crate::reports::unexported_weeks(start, end, &exported);
+                            if weeks.is_empty() {
+                                ui.label("✔ Every week of the month has been exported.");
+                            } else {
+                                ui.label("Weeks not covered by a ticket export yet:");
+                                for (from, to) in weeks {
+                                    ui.label(format!(
+                                        "v{}  {} – {}",
+                                        from.iso_week().week(),
+                                        from.format("%m-%d"),
+                                        to.format("%m-%d")
+                                    ));
                                 }
                             }
                         }
-                    });
-                });
-        }
-
-        DialogState::ConfirmDelete(target) => {
-            let (title, message) = match &target {
-                DeleteTarget::Project(_, name) => (
-                    "Delete Project?",
-                    format!(
-                        "Are you sure you want to permanently delete '{}'?\n\
-                         This will also delete all activities and time entries!",
-                        name
-                    ),
-                ),
-                DeleteTarget::Activity(_, name) => (
-                    "Delete Activity?",
-                    format!(
-                        "Are you sure you want to permanently delete '{}'?\n\
-                         This will also delete all time entries!",
-                        name
-                    ),
-                ),
-                DeleteTarget::TimeEntry(_) => (
-                    "Delete Entry?",
-                    "Are you sure you want to delete this time entry?".to_string(),
-                ),
-            };
+                        3 => {
+                            let profiles = &settings.export.profiles;
+                            if wizard.profile >= profiles.len() {
+                                wizard.profile = 0;
+                            }
+                            let profile = profiles.get(wizard.profile).cloned().unwrap_or_default();
+                            ui.horizontal(|ui| {
+                                ui.label("Export profile:");
+                                egui::ComboBox::from_id_salt("month_end_profile")
+                                    .selected_text(&profile.name)
+                                    .show_ui(ui, |ui| {
+                                        for (i, p) in profiles.iter().enumerate() {
+                                            ui.selectable_value(&mut wizard.profile, i, &p.name);
+                                        }
+                                    });
+                            });
+                            match regex::Regex::new(&settings.reports.ticket_pattern) {
+                                Ok(pattern) => {
+                                    let report =
+                                        crate::reports::aggregate_by_ticket(&entries, &pattern);
+                                    ui.label(format!(
+                                        "{} tickets for the whole month",
+                                        report.tickets.len()
+                                    ));
+                                    if ui
+                                        .add_enabled(
+                                            !report.tickets.is_empty(),
+                                            egui::Button::new("📄 Export tickets CSV…"),
+                                        )
+                                        .clicked()
+                                    {
+                                        wizard.export_result = export_ticket_report(
+                                            settings,
+                                            &report,
+                                            &profile,
+                                            (start, end),
+                                            messages,
+                                            db,
+                                        )
+                                        .map(|result| {
+                                            result
+                                                .map(|(path, rows)| {
+                                                    format!(
+                                                        "Exported {} tickets to {}",
+                                                        rows,
+                                                        path.display()
+                                                    )
+                                                })
+                                                .map_err(|e| format!("Export failed: {}", e))
+                                        });
+                                        cache.mark_dirty();
+                                    }
+                                }
+                                Err(_) => {
+                                    ui.colored_label(
+                                        Color32::RED,
+                                        "Invalid ticket pattern (see Reports)",
+                                    );
+                                }
+                            }
+                            match &wizard.export_result {
+                                Some(Ok(message)) => {
+                                    ui.colored_label(Color32::from_rgb(0, 150, 0), message);
+                                }
+                                Some(Err(message)) => {
+                                    ui.colored_label(Color32::RED, message);
+                                }
+                                None => {}
+                            }
+                        }
+                        _ => {
+                            if locked {
+                                ui.label(
+                                    RichText::new(format!(
+                                        "🔒 {} is locked",
+                                        start.format("%B %Y")
+                                    ))
+                                    .strong(),
+                                );
+                                if ui.button("Unlock…").clicked() {
+                                    unlock = Some(start);
+                                }
+                            } else {
+                                ui.label(
+                                    "Locking prevents adding, editing or deleting entries in \
+                                     this month. Use adjustment entries in an open month for \
+                                     later corrections.",
+                                );
+                                if ui.button("🔒 Lock month").clicked() {
+                                    cache.queue(AppAction::LockMonth(start));
+                                    wizard.stale = true;
+                                }
+                            }
+                        }
+                    }
 
-            egui::Window::new(title)
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        if ui.button("Close").clicked() || cancel {
+                            should_close = true;
+                        }
+                        if ui
+                            .add_enabled(wizard.step > 0, egui::Button::new("◀ Back"))
+                            .clicked()
+                        {
+                            wizard.step -= 1;
+                        }
+                        if wizard.step < last_step {
+                            if ui.button("Next ▶").clicked() || confirm {
+                                wizard.step += 1;
+                            }
+                        } else if ui.button("Done").clicked() || confirm {
+                            should_close = true;
+                        }
+                    });
+                });
+
+            wizard.entries = entries;
+            if let Some(month) = unlock {
+                *dialog = DialogState::ConfirmUnlock(UnlockTarget::Month(month));
+            } else if matches!(dialog, DialogState::MonthEnd(_)) {
+                *dialog = DialogState::MonthEnd(wizard);
+            }
+        }
+
+        DialogState::ExportEntries(mut export) => {
+            egui::Window::new(export.content.title())
                 .collapsible(false)
                 .resizable(false)
                 .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
                 .show(ctx, |ui| {
-                    ui.label(message);
+                    ui.label(export.content.description());
+                    ui.add_space(5.0);
+                    ui.horizontal(|ui| {
+                        ui.label("From:");
+                        ui.add(
+                            egui_extras::DatePickerButton::new(&mut export.start)
+                                .id_salt("entry_export_start"),
+                        );
+                        ui.label("To:");
+                        ui.add(
+                            egui_extras::DatePickerButton::new(&mut export.end)
+                                .id_salt("entry_export_end"),
+                        );
+                    });
+                    let valid = export.start <= export.end;
+                    if !valid {
+                        draw_field_error(
+                            ui,
+                            Some("The start date is after the end date".to_string()),
+                        );
+                    }
+                    if let Some((message, failed)) = &export.outcome {
+                        let color = if *failed {
+                            Color32::RED
+                        } else {
+                            Color32::from_rgb(0, 150, 0)
+                        };
+                        ui.colored_label(color, message);
+                    }
                     ui.add_space(10.0);
 
                     ui.horizontal(|ui| {
-                        if ui.button("Cancel").clicked() {
+                        if ui.button("Close").clicked() || cancel {
                             should_close = true;
                         }
-
                         if ui
-                            .button(RichText::new("Delete").color(Color32::RED))
+                            .add_enabled(valid, egui::Button::new("📄 Export…"))
                             .clicked()
+                            || (confirm && valid)
                         {
-                            let result = match &target {
-                                DeleteTarget::Project(id, _) => db.delete_project(*id),
-                                DeleteTarget::Activity(id, _) => db.delete_activity_type(*id),
-                                DeleteTarget::TimeEntry(id) => db.delete_time_entry(*id),
+                            let range = (export.start, export.end);
+                            let content = export.content;
+                            let write = |path: &std::path::Path| {
+                                let failed = |e: crate::database::DatabaseError| {
+                                    std::io::Error::other(e.to_string())
+                                };
+                                match content {
+                                    ExportContent::Entries => crate::export::write_entries_csv(
+                                        path,
+                                        &db.get_entry_details_for_range(range.0, range.1)
+                                            .map_err(failed)?,
+                                    ),
+                                    ExportContent::Travel => crate::export::write_travel_csv(
+                                        path,
+                                        &db.get_travel_between(range.0, range.1).map_err(failed)?,
+                                    ),
+                                    ExportContent::Expenses => crate::export::write_expenses_csv(
+                                        path,
+                                        &db.get_expenses_between(range.0, range.1)
+                                            .map_err(failed)?,
+                                    ),
+                                }
                             };
-
-                            match result {
-                                Ok(()) => {
+                            let outcome = export_report(
+                                settings,
+                                "entry_export",
+                                content.kind(),
+                                range,
+                                messages,
+                                db,
+                                write,
+                            )
+                            .transpose();
+                            export.outcome = match outcome {
+                                Ok(Some((path, rows))) => {
                                     cache.mark_dirty();
-                                    should_close = true;
-                                }
-                                Err(e) => {
-                                    *dialog = DialogState::ErrorMessage(e.to_string());
+                                    Some((
+                                        format!("Exported {} rows to {}", rows, path.display()),
+                                        false,
+                                    ))
                                 }
-                            }
+                                Ok(None) => export.outcome.take(),
+                                Err(e) => Some((format!("Export failed: {}", e), true)),
+                            };
                         }
                     });
                 });
+
+            if matches!(dialog, DialogState::ExportEntries(_)) {
+                *dialog = DialogState::ExportEntries(export);
+            }
         }
 
         DialogState::ErrorMessage(message) => {
@@ -1050,7 +5545,7 @@ pub fn draw_dialog(
                     ui.label(RichText::new(&message).color(Color32::RED));
                     ui.add_space(10.0);
 
-                    if ui.button("OK").clicked() {
+                    if ui.button("OK").clicked() || confirm || cancel {
                         should_close = true;
                     }
                 });
@@ -1061,3 +5556,745 @@ pub fn draw_dialog(
         *dialog = DialogState::None;
     }
 }
+
+/// Draw the settings view. Returns true when a setting was changed.
+pub fn draw_settings_view(
+    ui: &mut Ui,
+    settings: &mut Settings,
+    view: &mut SettingsViewState,
+    cache: &mut CachedData,
+    messages: &mut Vec<UserMessage>,
+) -> bool {
+    let mut changed = false;
+
+    ui.heading("Settings");
+    ui.add_space(10.0);
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        ui.group(|ui| {
+            ui.label(RichText::new("Day close-out checks").strong());
+            let close_out = &mut settings.close_out;
+
+            ui.horizontal(|ui| {
+                changed |= ui
+                    .checkbox(&mut close_out.check_total_range, "Day total between")
+                    .changed();
+                changed |= ui
+                    .add_enabled(
+                        close_out.check_total_range,
+                        minutes_drag_value(&mut close_out.min_total_minutes),
+                    )
+                    .changed();
+                ui.label("and");
+                changed |= ui
+                    .add_enabled(
+                        close_out.check_total_range,
+                        minutes_drag_value(&mut close_out.max_total_minutes),
+                    )
+                    .changed();
+            });
+            if close_out.max_total_minutes < close_out.min_total_minutes {
+                close_out.max_total_minutes = close_out.min_total_minutes;
+            }
+
+            changed |= ui
+                .checkbox(&mut close_out.check_comments, "All entries have comments")
+                .changed();
+            changed |= ui
+                .checkbox(
+                    &mut close_out.check_zero_entries,
+                    "No entries with zero duration",
+                )
+                .changed();
+
+            ui.add_space(5.0);
+            changed |= ui
+                .checkbox(
+                    &mut close_out.copy_summary,
+                    "Copy the daily summary to the clipboard when closing a day",
+                )
+                .changed();
+            if !settings.reports.saved.is_empty() {
+                ui.label("Write these saved reports when closing a day:");
+                ui.horizontal_wrapped(|ui| {
+                    for report in &settings.reports.saved {
+                        let position = close_out.run_reports.iter().position(|n| *n == report.name);
+                        let mut ticked = position.is_some();
+                        if ui.checkbox(&mut ticked, &report.name).changed() {
+                            match position {
+                                Some(i) => {
+                                    close_out.run_reports.remove(i);
+                                }
+                                None => close_out.run_reports.push(report.name.clone()),
+                            }
+                            changed = true;
+                        }
+                    }
+                });
+            }
+        });
+
+        ui.add_space(10.0);
+        ui.group(|ui| {
+            ui.label(RichText::new("Working days").strong());
+            ui.label("The day total check is skipped on other days.");
+            let calendar = &mut settings.calendar;
+            ui.horizontal(|ui| {
+                for (working, name) in calendar
+                    .working_weekdays
+                    .iter_mut()
+                    .zip(WEEKDAY_NAMES)
+                {
+                    changed |= ui.checkbox(working, name).changed();
+                }
+            });
+            ui.label("Daily target:");
+            ui.horizontal(|ui| {
+                for (target, name) in calendar.daily_targets.iter_mut().zip(WEEKDAY_NAMES) {
+                    ui.label(name);
+                    changed |= ui.add(minutes_drag_value(target)).changed();
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Week starts on:");
+                for option in WeekStart::ALL {
+                    changed |= ui
+                        .radio_value(&mut calendar.week_start, option, option.label())
+                        .changed();
+                }
+            });
+
+            ui.label(
+                RichText::new(
+                    "Mark holidays and absence days by right-clicking a day in the week strip or the 🗓 calendar.",
+                )
+                .small()
+                .color(Color32::GRAY),
+            );
+        });
+
+        ui.add_space(10.0);
+        ui.group(|ui| {
+            ui.label(RichText::new("Entries").strong());
+            ui.horizontal(|ui| {
+                ui.label("Longest single entry:");
+                changed |= ui
+                    .add(minutes_drag_value(&mut settings.entries.max_entry_minutes))
+                    .changed();
+                ui.label("New entries start at:");
+                changed |= ui
+                    .add(minutes_drag_value(&mut settings.entries.default_minutes))
+                    .changed();
+            });
+            ui.horizontal(|ui| {
+                ui.label("Lock entries older than:");
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut settings.entries.lock_after_days)
+                            .range(0..=365)
+                            .suffix(" days"),
+                    )
+                    .on_hover_text("0 allows editing entries of any age")
+                    .changed();
+            });
+            ui.horizontal(|ui| {
+                ui.label("After adding an entry, focus:");
+                let focus = &mut settings.entries.focus_after_add;
+                changed |= ui
+                    .radio_value(focus, EntryField::Activity, "Activity")
+                    .changed();
+                changed |= ui
+                    .radio_value(focus, EntryField::Comment, "Comment")
+                    .changed();
+            });
+            ui.horizontal(|ui| {
+                changed |= ui
+                    .checkbox(
+                        &mut settings.entries.show_duration_slider,
+                        "Show a duration slider, in steps of",
+                    )
+                    .changed();
+                changed |= ui
+                    .add_enabled(
+                        settings.entries.show_duration_slider,
+                        egui::DragValue::new(&mut settings.entries.slider_step_minutes)
+                            .range(1..=60)
+                            .suffix(" min"),
+                    )
+                    .changed();
+            });
+            ui.horizontal_wrapped(|ui| {
+                ui.label("Quick buttons (minutes):");
+                let increments = &mut settings.entries.quick_increments;
+                let mut remove = None;
+                for (i, minutes) in increments.iter_mut().enumerate() {
+                    changed |= ui
+                        .add(egui::DragValue::new(minutes).range(-240..=240).speed(1.0))
+                        .changed();
+                    if ui.small_button("✖").on_hover_text("Remove").clicked() {
+                        remove = Some(i);
+                    }
+                }
+                if let Some(i) = remove {
+                    increments.remove(i);
+                    changed = true;
+                }
+                if ui.button("➕").on_hover_text("Add a button").clicked() {
+                    increments.push(15);
+                    changed = true;
+                }
+            });
+        });
+
+        ui.add_space(10.0);
+        ui.group(|ui| {
+            ui.label(RichText::new("Weekday templates").strong());
+            ui.label(
+                "Entries suggested on the Time Tracking tab when an empty day falls on the weekday.",
+            );
+            let mut remove = None;
+            for template in &cache.entry_templates {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(WEEKDAY_NAMES[template.weekday as usize % 7]).strong(),
+                    );
+                    ui.label(
+                        RichText::new(format_minutes_to_time(template.minutes)).monospace(),
+                    );
+                    ui.label(
+                        activity_label(cache, template.activity_type_id)
+                            .unwrap_or_else(|| "?".to_string()),
+                    );
+                    ui.label(RichText::new(&template.comment).italics());
+                    if ui.small_button("🗑").clicked() {
+                        remove = Some(template.clone());
+                    }
+                });
+            }
+            if let Some(template) = remove {
+                cache.queue(AppAction::DeleteEntryTemplate(template));
+            }
+
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt("template_weekday")
+                    .selected_text(WEEKDAY_NAMES[view.template_weekday as usize % 7])
+                    .width(60.0)
+                    .show_ui(ui, |ui| {
+                        for (i, name) in WEEKDAY_NAMES.iter().enumerate() {
+                            ui.selectable_value(&mut view.template_weekday, i as u32, *name);
+                        }
+                    });
+                activity_picker(
+                    ui,
+                    "template_activity",
+                    cache,
+                    None,
+                    &mut view.template_activity_id,
+                    None,
+                );
+                ui.add(egui::TextEdit::singleline(&mut view.template_time).desired_width(50.0));
+                ui.add(
+                    egui::TextEdit::singleline(&mut view.template_comment)
+                        .desired_width(200.0)
+                        .hint_text("Comment"),
+                );
+                let minutes = crate::database::parse_time_to_minutes(&view.template_time)
+                    .ok()
+                    .filter(|m| *m > 0);
+                let can_add = view.template_activity_id.is_some()
+                    && minutes.is_some()
+                    && !view.template_comment.trim().is_empty();
+                if ui
+                    .add_enabled(can_add, egui::Button::new("➕ Add"))
+                    .clicked()
+                {
+                    if let (Some(activity_id), Some(minutes)) = (view.template_activity_id, minutes)
+                    {
+                        cache.queue(AppAction::CreateEntryTemplate {
+                            weekday: view.template_weekday,
+                            activity_type_id: activity_id,
+                            minutes,
+                            comment: view.template_comment.trim().to_string(),
+                        });
+                        view.template_comment.clear();
+                    }
+                }
+            });
+        });
+
+        ui.add_space(10.0);
+        ui.group(|ui| {
+            ui.label(RichText::new("Display").strong());
+            ui.horizontal(|ui| {
+                ui.label("Theme:");
+                for option in Theme::ALL {
+                    changed |= ui
+                        .radio_value(&mut settings.display.theme, option, option.label())
+                        .changed();
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Durations:");
+                let style = &mut settings.display.duration_style;
+                egui::ComboBox::from_id_salt("duration_style")
+                    .selected_text(style.label())
+                    .show_ui(ui, |ui| {
+                        for option in DurationStyle::ALL {
+                            changed |= ui
+                                .selectable_value(style, option, option.label())
+                                .changed();
+                        }
+                    });
+            });
+            ui.label(
+                RichText::new(
+                    "Used for times shown in the app, the Time column of exports and {time} in clipboard templates.",
+                )
+                .small(),
+            );
+            changed |= ui
+                .checkbox(
+                    &mut settings.display.show_recent_days,
+                    "Show recent days sidebar",
+                )
+                .on_hover_text("The last 14 days with their totals, next to Time Tracking and Daily Summary")
+                .changed();
+        });
+
+        ui.add_space(10.0);
+        ui.group(|ui| {
+            ui.label(RichText::new("Clipboard").strong());
+            ui.horizontal(|ui| {
+                ui.label("Summary line template:");
+                changed |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut settings.clipboard.summary_line_template)
+                            .desired_width(300.0),
+                    )
+                    .changed();
+            });
+            ui.label(
+                RichText::new(format!(
+                    "Placeholders: {} (\\t inserts a tab)",
+                    SUMMARY_PLACEHOLDERS
+                ))
+                .small(),
+            );
+            ui.horizontal(|ui| {
+                ui.label("Entry line template:");
+                changed |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut settings.clipboard.entry_line_template)
+                            .desired_width(300.0),
+                    )
+                    .changed();
+            });
+            ui.label(RichText::new(format!("Placeholders: {}", ENTRY_PLACEHOLDERS)).small());
+        });
+
+        ui.add_space(10.0);
+        ui.group(|ui| {
+            ui.label(RichText::new("Expenses").strong());
+            ui.horizontal(|ui| {
+                ui.label("Default currency:");
+                changed |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut settings.expenses.default_currency)
+                            .desired_width(50.0),
+                    )
+                    .changed();
+            });
+        });
+
+        ui.add_space(10.0);
+        ui.group(|ui| {
+            ui.label(RichText::new("Currencies").strong());
+            ui.horizontal(|ui| {
+                ui.label("Amounts:");
+                let style = &mut settings.currency.style;
+                egui::ComboBox::from_id_salt("money_style")
+                    .selected_text(style.label())
+                    .show_ui(ui, |ui| {
+                        for option in MoneyStyle::ALL {
+                            changed |= ui
+                                .selectable_value(style, option, option.label())
+                                .changed();
+                        }
+                    });
+            });
+            ui.horizontal(|ui| {
+                ui.label("Report currency:");
+                changed |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut settings.currency.report_currency)
+                            .desired_width(50.0),
+                    )
+                    .changed();
+            });
+            ui.label(
+                RichText::new(
+                    "Exchange rates: the value of one unit in the report currency. \
+                     Used for grand totals over several currencies.",
+                )
+                .small(),
+            );
+
+            let mut remove: Option<usize> = None;
+            egui::Grid::new("exchange_rates")
+                .num_columns(3)
+                .show(ui, |ui| {
+                    for (i, rate) in settings.currency.exchange_rates.iter_mut().enumerate() {
+                        changed |= ui
+                            .add(
+                                egui::TextEdit::singleline(&mut rate.currency)
+                                    .desired_width(50.0)
+                                    .hint_text("SEK"),
+                            )
+                            .changed();
+                        changed |= ui
+                            .add(
+                                egui::DragValue::new(&mut rate.rate)
+                                    .speed(0.001)
+                                    .range(0.0..=f64::MAX)
+                                    .max_decimals(6),
+                            )
+                            .changed();
+                        if ui.small_button("🗑").clicked() {
+                            remove = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+            if let Some(i) = remove {
+                settings.currency.exchange_rates.remove(i);
+                changed = true;
+            }
+            if ui.button("➕ Add exchange rate").clicked() {
+                settings
+                    .currency
+                    .exchange_rates
+                    .push(crate::settings::ExchangeRate::default());
+                changed = true;
+            }
+        });
+
+        ui.add_space(10.0);
+        ui.group(|ui| {
+            ui.label(RichText::new("Ticket links").strong());
+            ui.label(
+                RichText::new(
+                    "Comment text matching a pattern (regex) links to the URL; {ticket} is replaced by the match.",
+                )
+                .small(),
+            );
+
+            let mut remove: Option<usize> = None;
+            egui::Grid::new("ticket_link_rules")
+                .num_columns(3)
+                .show(ui, |ui| {
+                    for (i, rule) in settings.links.rules.iter_mut().enumerate() {
+                        changed |= ui
+                            .add(
+                                egui::TextEdit::singleline(&mut rule.pattern)
+                                    .desired_width(120.0)
+                                    .hint_text(r"PROJ-\d+")
+                                    .font(egui::TextStyle::Monospace),
+                            )
+                            .changed();
+                        changed |= ui
+                            .add(
+                                egui::TextEdit::singleline(&mut rule.url_template)
+                                    .desired_width(300.0)
+                                    .hint_text("https://jira.example.com/browse/{ticket}"),
+                            )
+                            .changed();
+                        if ui.small_button("🗑").clicked() {
+                            remove = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+            if let Some(i) = remove {
+                settings.links.rules.remove(i);
+                changed = true;
+            }
+            if ui.button("➕ Add link rule").clicked() {
+                settings.links.rules.push(TicketLinkRule::default());
+                changed = true;
+            }
+        });
+
+        ui.add_space(10.0);
+        ui.group(|ui| {
+            ui.label(RichText::new("Export profiles").strong());
+            ui.label("Rounding rules of the systems you export to. Applied to report totals.");
+            let mut remove = None;
+            egui::Grid::new("export_profiles")
+                .num_columns(4)
+                .show(ui, |ui| {
+                    for (i, profile) in settings.export.profiles.iter_mut().enumerate() {
+                        changed |= ui
+                            .add(
+                                egui::TextEdit::singleline(&mut profile.name)
+                                    .desired_width(150.0),
+                            )
+                            .changed();
+                        changed |= ui
+                            .add(
+                                egui::DragValue::new(&mut profile.step_minutes)
+                                    .range(0..=240)
+                                    .suffix(" min steps"),
+                            )
+                            .on_hover_text("0 keeps exact minutes")
+                            .changed();
+                        egui::ComboBox::from_id_salt(("rounding_mode", i))
+                            .selected_text(format!("{:?}", profile.mode))
+                            .show_ui(ui, |ui| {
+                                for mode in [RoundingMode::Nearest, RoundingMode::Up, RoundingMode::Down]
+                                {
+                                    changed |= ui
+                                        .selectable_value(&mut profile.mode, mode, format!("{:?}", mode))
+                                        .changed();
+                                }
+                            });
+                        if ui.small_button("🗑").clicked() {
+                            remove = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+            if let Some(i) = remove {
+                settings.export.profiles.remove(i);
+                changed = true;
+            }
+            if ui.button("➕ Add profile").clicked() {
+                settings.export.profiles.push(ExportProfile {
+                    name: "New profile".to_string(),
+                    ..Default::default()
+                });
+                changed = true;
+            }
+        });
+
+        ui.add_space(10.0);
+        ui.group(|ui| {
+            ui.label(RichText::new("Database").strong());
+            ui.horizontal(|ui| {
+                ui.label("File:");
+                ui.label(
+                    RichText::new(match &settings.storage.database_path {
+                        Some(path) => path.display().to_string(),
+                        None => "Default location".to_string(),
+                    })
+                    .monospace(),
+                );
+                if ui.button("📂 Choose…").clicked() {
+                    if let Some(picked) = file_dialog(settings, "database", "SQLite database", &["db"])
+                        .set_file_name("chronos_log.db")
+                        .save_file()
+                    {
+                        remember_dir(settings, "database", &picked, messages);
+                        settings.storage.database_path = Some(picked);
+                        changed = true;
+                    }
+                }
+                if ui
+                    .add_enabled(
+                        settings.storage.database_path.is_some(),
+                        egui::Button::new("Use default"),
+                    )
+                    .clicked()
+                {
+                    settings.storage.database_path = None;
+                    changed = true;
+                }
+            });
+            ui.label(
+                RichText::new(
+                    "Takes effect after a restart. A file that doesn't exist yet is created empty.",
+                )
+                .small(),
+            );
+        });
+
+        ui.add_space(10.0);
+        ui.group(|ui| {
+            ui.label(RichText::new("Backups").strong());
+            ui.label(
+                "A copy of the database is saved before deletions, catalog imports and \
+                 upgrades. Restoring one replaces all current data; the current state is \
+                 backed up first.",
+            );
+            ui.horizontal(|ui| {
+                ui.label("Back up on start:");
+                let on_startup = &mut settings.backups.on_startup;
+                changed |= ui
+                    .radio_value(on_startup, StartupBackup::Daily, "First start of the day")
+                    .changed();
+                changed |= ui
+                    .radio_value(on_startup, StartupBackup::EveryStart, "Every start")
+                    .changed();
+                changed |= ui
+                    .radio_value(on_startup, StartupBackup::Off, "Never")
+                    .changed();
+            });
+            ui.horizontal(|ui| {
+                ui.label("Keep the newest");
+                changed |= ui
+                    .add(egui::DragValue::new(&mut settings.backups.keep).range(1..=200))
+                    .changed();
+                ui.label("backups of each kind");
+            });
+            if cache.snapshots.is_empty() {
+                ui.label(RichText::new("No backups yet.").italics());
+            }
+            let mut restore = None;
+            egui::Grid::new("snapshots")
+                .striped(true)
+                .num_columns(4)
+                .show(ui, |ui| {
+                    for snapshot in &cache.snapshots {
+                        ui.label(
+                            RichText::new(snapshot.taken_at.format("%Y-%m-%d %H:%M:%S").to_string())
+                                .monospace(),
+                        )
+                        .on_hover_text(snapshot.path.display().to_string());
+                        ui.label(&snapshot.reason);
+                        let integrity = cache.snapshot_integrity.get(&snapshot.path).copied();
+                        draw_integrity(ui, integrity);
+                        if integrity == Some(crate::backup::Integrity::Corrupted) {
+                            ui.label("");
+                        } else if view.confirm_restore.as_ref() == Some(&snapshot.path) {
+                            ui.horizontal(|ui| {
+                                if ui
+                                    .button(RichText::new("Restore").color(Color32::RED))
+                                    .clicked()
+                                {
+                                    restore = Some(snapshot.path.clone());
+                                    view.confirm_restore = None;
+                                }
+                                if ui.button("Cancel").clicked() {
+                                    view.confirm_restore = None;
+                                }
+                            });
+                        } else if ui.small_button("↩ Restore…").clicked() {
+                            view.confirm_restore = Some(snapshot.path.clone());
+                        }
+                        ui.end_row();
+                    }
+                });
+            if let Some(path) = restore {
+                cache.queue(AppAction::RestoreBackup(path));
+            }
+        });
+
+        ui.add_space(10.0);
+        ui.group(|ui| {
+            ui.label(RichText::new("Transfer").strong());
+            ui.label("Move settings (checks, clipboard templates, export profiles, link rules) to another machine or share them with your team.");
+            ui.label(
+                RichText::new(
+                    "Weekday entry templates, day types and holidays are stored in the \
+                     database and are not part of the file.",
+                )
+                .small()
+                .color(Color32::GRAY),
+            );
+
+            ui.horizontal(|ui| {
+                if ui.button("📤 Export settings…").clicked() {
+                    if let Some(path) = file_dialog(settings, "settings_export", "TOML", &["toml"])
+                        .set_file_name("chronos-log-settings.toml")
+                        .save_file()
+                    {
+                        remember_dir(settings, "settings_export", &path, messages);
+                        match settings.export_to(&path) {
+                            Ok(()) => messages.push(UserMessage::info(format!(
+                                "Settings exported to {}",
+                                path.display()
+                            ))),
+                            Err(e) => {
+                                messages.push(UserMessage::error(format!("Export failed: {}", e)))
+                            }
+                        }
+                    }
+                }
+
+                if ui
+                    .button("📥 Import settings…")
+                    .on_hover_text(
+                        "Replaces all current settings except the database path, \
+                         dialog folders and collapsed groups",
+                    )
+                    .clicked()
+                {
+                    if let Some(path) =
+                        file_dialog(settings, "settings_import", "TOML", &["toml"]).pick_file()
+                    {
+                        match settings.import_from(&path) {
+                            Ok(imported) => {
+                                *settings = imported;
+                                remember_dir(settings, "settings_import", &path, messages);
+                                changed = true;
+                                messages.push(UserMessage::info("Settings imported"));
+                            }
+                            Err(e) => {
+                                messages.push(UserMessage::error(format!("Import failed: {}", e)))
+                            }
+                        }
+                    }
+                }
+            });
+        });
+    });
+
+    changed
+}
+
+/// Day total color against the daily target: yellow under, green at, red over
+fn target_color(total: i32, target: i32) -> Color32 {
+    if target == 0 {
+        Color32::GRAY // No target on days off
+    } else if total < target {
+        Color32::from_rgb(255, 200, 0)
+    } else if total == target {
+        Color32::from_rgb(0, 150, 0)
+    } else {
+        Color32::from_rgb(255, 0, 0)
+    }
+}
+
+/// Time remaining until the daily target, or the overtime with a warning
+fn draw_target_status(ui: &mut Ui, total: i32, target: i32, style: DurationStyle) {
+    if target == 0 {
+        return;
+    }
+    let color = target_color(total, target);
+    if total < target {
+        ui.label(
+            RichText::new(format!("{} remaining today", style.format(target - total))).color(color),
+        );
+    } else if total == target {
+        ui.label(RichText::new("✔ Target reached").color(color));
+    } else {
+        ui.label(RichText::new("⚠").size(18.0).color(color));
+        ui.label(
+            RichText::new(format!("{} over target!", style.format(total - target)))
+                .color(color)
+                .strong(),
+        );
+    }
+}
+
+/// Drag value editing a minute count, displayed as HH:MM
+fn minutes_drag_value(minutes: &mut i32) -> egui::DragValue<'_> {
+    egui::DragValue::new(minutes)
+        .range(0..=24 * 60)
+        .speed(5.0)
+        .custom_formatter(|n, _| format_minutes_to_time(n as i32))
+        .custom_parser(|s| {
+            crate::database::parse_time_to_minutes(s)
+                .ok()
+                .map(|m| m as f64)
+        })
+}
@@ -0,0 +1,356 @@
+// tests/database.rs
+// Database API against a database file on disk
+
+use chrono::NaiveDate;
+use chronos_log::catalog::{parse_csv, plan_import};
+use chronos_log::database::{parse_time_to_minutes, Database, DatabaseError};
+use chronos_log::formatting::format_minutes_to_time;
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+
+/// A database file in its own temporary folder, removed when dropped
+struct TempDb {
+    dir: PathBuf,
+}
+
+impl TempDb {
+    fn new(name: &str) -> Self {
+        let dir =
+            std::env::temp_dir().join(format!("chronos-log-it-{}-{}", name, std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::create_dir_all(&dir).unwrap();
+        Self { dir }
+    }
+
+    fn path(&self) -> PathBuf {
+        self.dir.join("chronos_log.db")
+    }
+
+    fn open(&self) -> Database {
+        Database::new(self.path()).unwrap()
+    }
+
+    /// A second connection to the same file, bypassing the API
+    fn raw(&self) -> Connection {
+        Connection::open(self.path()).unwrap()
+    }
+}
+
+impl Drop for TempDb {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.dir).ok();
+    }
+}
+
+fn date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+fn user_version(path: &Path) -> i64 {
+    Connection::open(path)
+        .unwrap()
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .unwrap()
+}
+
+fn snapshot_reasons(dir: &Path) -> Vec<String> {
+    chronos_log::backup::list_snapshots(&dir.join("backups"))
+        .into_iter()
+        .map(|s| s.reason)
+        .collect()
+}
+
+#[test]
+fn data_survives_reopening() {
+    let temp = TempDb::new("reopen");
+    {
+        let db = temp.open();
+        let project_id = db.create_project("Kept", "", "").unwrap();
+        let activity_id = db.create_activity_type(project_id, "Work", "").unwrap();
+        db.create_time_entry(activity_id, date("2024-10-01"), 90, "on disk")
+            .unwrap();
+    }
+
+    let db = temp.open();
+    let entries = db.get_time_entries_for_date(date("2024-10-01")).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].comment, "on disk");
+    // Up to date databases are not backed up on open
+    assert!(snapshot_reasons(&temp.dir).is_empty());
+}
+
+#[test]
+fn migrates_a_database_from_the_first_schema() {
+    let current = TempDb::new("migrate-current");
+    drop(current.open());
+    let latest = user_version(&current.path());
+    assert!(latest > 0);
+
+    // The schema before any migration, with some data
+    let temp = TempDb::new("migrate");
+    temp.raw()
+        .execute_batch(
+            r#"
+            CREATE TABLE projects (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE,
+                description TEXT DEFAULT '',
+                is_active INTEGER DEFAULT 1,
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP
+            );
+            CREATE TABLE activity_types (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                project_id INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                is_active INTEGER DEFAULT 1,
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+                UNIQUE(project_id, name)
+            );
+            CREATE TABLE time_entries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                activity_type_id INTEGER NOT NULL,
+                date TEXT NOT NULL,
+                minutes INTEGER NOT NULL,
+                comment TEXT DEFAULT '',
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (activity_type_id) REFERENCES activity_types(id) ON DELETE CASCADE
+            );
+            INSERT INTO projects (name, description) VALUES ('Legacy', 'from v0');
+            INSERT INTO activity_types (project_id, name) VALUES (1, 'Old work');
+            INSERT INTO time_entries (activity_type_id, date, minutes, comment)
+                VALUES (1, '2023-05-02', 45, 'before migrations');
+            "#,
+        )
+        .unwrap();
+
+    let db = temp.open();
+    assert_eq!(user_version(&temp.path()), latest);
+    assert_eq!(snapshot_reasons(&temp.dir), ["migration"]);
+
+    let project = &db.get_all_projects(false).unwrap()[0];
+    assert_eq!(project.name, "Legacy");
+    assert_eq!(project.code, "");
+    assert!(!project.archived);
+    let activity = &db.get_all_activity_types(false).unwrap()[0];
+    assert_eq!(activity.monthly_cap_minutes, None);
+    assert_eq!(activity.valid_from, None);
+    let entry = &db.get_time_entries_for_date(date("2023-05-02")).unwrap()[0];
+    assert_eq!(entry.minutes, 45);
+    assert_eq!(entry.reason, "");
+}
+
+#[test]
+fn deletes_are_consistent_with_foreign_keys_on() {
+    let temp = TempDb::new("foreign-keys");
+    let db = temp.open();
+    let project_id = db.create_project("Cascade", "", "").unwrap();
+    let used = db.create_activity_type(project_id, "Used", "").unwrap();
+    let unused = db.create_activity_type(project_id, "Unused", "").unwrap();
+    db.create_time_entry(used, date("2024-10-01"), 30, "x")
+        .unwrap();
+    db.set_project_links(project_id, &[("Wiki".into(), "https://wiki".into())])
+        .unwrap();
+    db.create_entry_template(0, unused, 60, "Planning").unwrap();
+
+    // The API refuses deletes that would orphan rows
+    assert!(matches!(
+        db.delete_project(project_id),
+        Err(DatabaseError::ProjectHasActivities)
+    ));
+    assert!(matches!(
+        db.delete_activity_type(used),
+        Err(DatabaseError::ActivityHasEntries)
+    ));
+    db.delete_activity_type(unused).unwrap();
+    assert!(db.get_entry_templates().unwrap().is_empty());
+
+    let raw = temp.raw();
+    raw.pragma_update(None, "foreign_keys", true).unwrap();
+    let violations: i64 = raw
+        .query_row("SELECT COUNT(*) FROM pragma_foreign_key_check", [], |row| {
+            row.get(0)
+        })
+        .unwrap();
+    assert_eq!(violations, 0);
+
+    // With enforcement on, the schema cascades from projects to entries
+    raw.execute("DELETE FROM projects WHERE id = ?1", [project_id])
+        .unwrap();
+    assert!(db.get_all_activity_types(false).unwrap().is_empty());
+    assert!(db
+        .get_time_entries_for_date(date("2024-10-01"))
+        .unwrap()
+        .is_empty());
+    assert!(db.get_all_project_links().unwrap().is_empty());
+}
+
+#[test]
+fn imports_a_catalog() {
+    let temp = TempDb::new("catalog");
+    let db = temp.open();
+    let csv = "project_code,project_name,activity_code,activity_name,valid_from,valid_to\n\
+               P1,Platform,,,,\n\
+               P1,Platform,A1,Development,,\n\
+               P1,Platform,A2,Retired,,2020-01-01\n\
+               P2,Support,S1,Tickets,,\n";
+    let rows = parse_csv(csv).unwrap();
+    let today = date("2024-10-01");
+
+    let changes = plan_import(&rows, &[], &[], today);
+    db.snapshot("catalog-import").unwrap();
+    db.apply_catalog_changes(&changes).unwrap();
+
+    let projects = db.get_all_projects(false).unwrap();
+    let mut codes: Vec<_> = projects.iter().map(|p| p.code.as_str()).collect();
+    codes.sort();
+    assert_eq!(codes, ["P1", "P2"]);
+    let activities = db.get_all_activity_types(false).unwrap();
+    let mut names: Vec<_> = activities.iter().map(|a| a.name.as_str()).collect();
+    names.sort();
+    // Codes past their end date are never added
+    assert_eq!(names, ["Development", "Tickets"]);
+    assert_eq!(snapshot_reasons(&temp.dir), ["catalog-import"]);
+
+    // Importing the same catalog again changes nothing
+    let again = plan_import(&rows, &projects, &activities, today);
+    assert!(again.is_empty(), "{:?}", again);
+}
+
+#[test]
+fn aggregates_match_the_entries() {
+    let temp = TempDb::new("aggregates");
+    let db = temp.open();
+    let alpha = db.create_project("Alpha", "", "").unwrap();
+    let beta = db.create_project("Beta", "", "").unwrap();
+    let a = db.create_activity_type(alpha, "A", "").unwrap();
+    let b = db.create_activity_type(beta, "B", "").unwrap();
+
+    // A deterministic spread of entries over October 2024
+    let start = date("2024-10-01");
+    let mut expected_total = 0;
+    for i in 0..60 {
+        let day = start + chrono::Duration::days((i * 7) % 31);
+        let minutes = 15 + (i * 37) % 240;
+        let activity = if i % 3 == 0 { b } else { a };
+        db.create_time_entry(activity, day, minutes as i32, "")
+            .unwrap();
+        expected_total += minutes as i32;
+    }
+    let end = date("2024-10-31");
+
+    let stats = db.statistics_between(start, end, true).unwrap();
+    assert_eq!(stats.total_minutes, expected_total);
+    assert_eq!(
+        db.total_minutes_between(start, end, true).unwrap(),
+        expected_total
+    );
+
+    let daily = db.get_daily_totals(start, end).unwrap();
+    assert_eq!(stats.logged_days as usize, daily.len());
+    assert_eq!(daily.values().sum::<i32>(), expected_total);
+    assert_eq!(
+        stats.per_project.iter().map(|p| p.minutes).sum::<i32>(),
+        expected_total
+    );
+    assert_eq!(
+        stats.per_weekday.iter().map(|w| w.minutes).sum::<i32>(),
+        expected_total
+    );
+    assert_eq!(
+        stats.per_weekday.iter().map(|w| w.days).sum::<i32>(),
+        stats.logged_days
+    );
+    let activities = db.minutes_per_activity_between(start, end).unwrap();
+    assert_eq!(
+        activities.iter().map(|a| a.minutes).sum::<i32>(),
+        expected_total
+    );
+    assert!(stats
+        .per_project
+        .windows(2)
+        .all(|pair| pair[0].minutes >= pair[1].minutes));
+}
+
+#[test]
+fn snapshot_restore_and_revert_day_on_disk() {
+    let temp = TempDb::new("restore");
+    let mut db = temp.open();
+    let project_id = db.create_project("P", "", "").unwrap();
+    let activity_id = db.create_activity_type(project_id, "A", "").unwrap();
+    db.create_time_entry(activity_id, date("2024-10-01"), 60, "original")
+        .unwrap();
+    let snapshot = db.snapshot("delete").unwrap().unwrap();
+
+    db.create_time_entry(activity_id, date("2024-10-01"), 600, "mangled")
+        .unwrap();
+    assert_eq!(db.revert_day(&snapshot, date("2024-10-01")).unwrap(), 1);
+    assert_eq!(db.get_total_time_for_date(date("2024-10-01")).unwrap(), 60);
+
+    db.create_project("Added later", "", "").unwrap();
+    db.restore(&snapshot).unwrap();
+    assert_eq!(db.get_all_projects(false).unwrap().len(), 1);
+}
+
+#[test]
+fn snapshot_limit_prunes_oldest() {
+    let temp = TempDb::new("rotation");
+    let mut db = temp.open();
+    db.set_snapshot_limit(2);
+    // Limits apply per reason: deletions don't push out the daily restore point
+    for reason in ["daily", "delete", "delete", "delete"] {
+        db.snapshot(reason).unwrap();
+        // Snapshot names have millisecond resolution
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    assert_eq!(snapshot_reasons(&temp.dir), ["delete", "delete", "daily"]);
+}
+
+// ==================== Duration parser properties ====================
+
+#[test]
+fn formatted_durations_parse_back() {
+    for minutes in 0..=24 * 60 * 3 {
+        let text = format_minutes_to_time(minutes);
+        assert_eq!(parse_time_to_minutes(&text).unwrap(), minutes, "{}", text);
+        assert_eq!(
+            parse_time_to_minutes(&format!("-{}", text)).unwrap(),
+            -minutes,
+            "-{}",
+            text
+        );
+        assert_eq!(
+            parse_time_to_minutes(&format!("  {} ", text)).unwrap(),
+            minutes
+        );
+    }
+}
+
+#[test]
+fn decimal_hours_round_to_whole_minutes() {
+    for hundredths in 0..=2400 {
+        let hours = hundredths as f64 / 100.0;
+        let expected = (hours * 60.0).round() as i32;
+        for text in [
+            format!("{}h", hours),
+            format!("{}H", hours),
+            format!("{}h", hours).replace('.', ","),
+        ] {
+            assert_eq!(parse_time_to_minutes(&text).unwrap(), expected, "{}", text);
+        }
+    }
+}
+
+#[test]
+fn malformed_durations_are_rejected() {
+    for minutes in 60..100 {
+        let text = format!("01:{}", minutes);
+        assert!(parse_time_to_minutes(&text).is_err(), "{}", text);
+    }
+    for text in [
+        "", ":", "1", "1:", ":30", "1:2:3", "a:30", "01:3x", "--1:00", "-h", "h", "1.5", "-1.5hh",
+        "1..5h", "nanh", "infh", "01 :30",
+    ] {
+        assert!(parse_time_to_minutes(text).is_err(), "{:?}", text);
+    }
+}